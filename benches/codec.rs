@@ -0,0 +1,76 @@
+//! Benchmarks of the serialization of the data sets of `bytevec::bench_support`.
+//!
+//! Run them with `cargo bench --features bench-support`. Criterion keeps the results of the
+//! previous run under `target/criterion`, and reports the regressions against them.
+
+#[macro_use]
+extern crate criterion;
+extern crate bytevec;
+
+use std::collections::HashMap;
+
+use bytevec::bench_support::{self, Order, SEED};
+use bytevec::{ByteEncodable, ByteDecodable, Encoder, PackedVec};
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+
+const SIZES: &[usize] = &[100, 10_000];
+
+fn bench_encode_decode<T>(c: &mut Criterion, group_name: &str, data: &dyn Fn(usize) -> T)
+    where T: ByteEncodable + ByteDecodable
+{
+    let mut group = c.benchmark_group(group_name);
+    for &len in SIZES {
+        let value = data(len);
+        let bytes = value.encode::<u32>().unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::new("encode", len), &value, |b, value| {
+            b.iter(|| black_box(value).encode::<u32>().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decode", len), &bytes, |b, bytes| {
+            b.iter(|| T::decode::<u32>(black_box(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn primitives(c: &mut Criterion) {
+    bench_encode_decode(c, "integers", &|len| bench_support::integers(len, SEED));
+    bench_encode_decode(c, "packed_integers",
+                        &|len| PackedVec(bench_support::integers(len, SEED)));
+    bench_encode_decode(c, "packed_floats", &|len| PackedVec(bench_support::floats(len, SEED)));
+}
+
+fn strings(c: &mut Criterion) {
+    bench_encode_decode(c, "strings", &|len| bench_support::strings(len, SEED));
+}
+
+fn nested_structs(c: &mut Criterion) {
+    bench_encode_decode(c, "orders", &|len| bench_support::orders(len, SEED));
+}
+
+fn collections(c: &mut Criterion) {
+    bench_encode_decode(c, "index", &|len| bench_support::index(len, SEED));
+}
+
+fn reuse(c: &mut Criterion) {
+    let orders = bench_support::orders(1000, SEED);
+    let bytes = orders.encode::<u32>().unwrap();
+    let mut group = c.benchmark_group("reuse");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    let mut encoder = Encoder::<u32>::new();
+    group.bench_function("encoder", |b| b.iter(|| encoder.encode(black_box(&orders)).map(|_| ())));
+    let mut decoded = Vec::<Order>::new();
+    group.bench_function("decode_into", |b| {
+        b.iter(|| decoded.decode_into::<u32>(black_box(&bytes)).unwrap())
+    });
+    let index = bench_support::index(1000, SEED);
+    let bytes = index.encode::<u32>().unwrap();
+    let mut decoded = HashMap::<u32, String>::new();
+    group.bench_function("decode_into_map", |b| {
+        b.iter(|| decoded.decode_into::<u32>(black_box(&bytes)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, primitives, strings, nested_structs, collections, reuse);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+//! Synthetic data sets for measuring the performance of the serialization.
+//!
+//! The benchmarks of this crate, under `benches/`, encode and decode the values built here,
+//! so other serialization libraries can be measured against the same data. Every generator
+//! takes the number of values and a seed, and returns the same values for the same seed on
+//! every platform, from a xorshift generator rather than the randomness of the system. This
+//! module is only available with the `bench-support` feature.
+//!
+//! ```rust
+//! # extern crate bytevec;
+//! # use bytevec::{bench_support, ByteEncodable, ByteDecodable};
+//! # fn main() {
+//! let orders = bench_support::orders(100, bench_support::SEED);
+//! assert_eq!(orders, bench_support::orders(100, bench_support::SEED));
+//! let bytes = orders.encode::<u32>().unwrap();
+//! assert_eq!(Vec::<bench_support::Order>::decode::<u32>(&bytes).unwrap(), orders);
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+/// The seed of the data sets of the benchmarks of this crate
+pub const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// A xorshift pseudorandom generator, which yields the same numbers on every platform
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Returns a generator started from `seed`, where a seed of 0 is taken as 1
+    pub fn new(seed: u64) -> Generator {
+        Generator { state: if seed == 0 { 1 } else { seed } }
+    }
+
+    /// Returns the next number of the sequence
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a number in the range `0..bound`, or 0 if `bound` is 0
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 { 0 } else { self.next_u64() % bound }
+    }
+
+    /// Returns a string of `len` ASCII letters and spaces
+    pub fn string(&mut self, len: usize) -> String {
+        const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        (0..len).map(|_| CHARS[self.below(CHARS.len() as u64) as usize] as char).collect()
+    }
+}
+
+bytevec_decl! {
+    /// A line of an `Order`
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct OrderLine {
+        pub sku: u32,
+        pub quantity: u16,
+        pub price: f64
+    }
+
+    /// The customer of an `Order`
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct Customer {
+        pub id: u64,
+        pub name: String,
+        pub email: String
+    }
+
+    /// A nested structure with primitive fields, strings and a collection
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct Order {
+        pub id: u64,
+        pub customer: Customer,
+        pub lines: Vec<OrderLine>,
+        pub note: String
+    }
+}
+
+/// Returns `len` integers spread over the whole range of `u64`
+pub fn integers(len: usize, seed: u64) -> Vec<u64> {
+    let mut generator = Generator::new(seed);
+    (0..len).map(|_| generator.next_u64()).collect()
+}
+
+/// Returns `len` floats between 0 and 1
+pub fn floats(len: usize, seed: u64) -> Vec<f64> {
+    let mut generator = Generator::new(seed);
+    (0..len).map(|_| (generator.next_u64() >> 11) as f64 / (1u64 << 53) as f64).collect()
+}
+
+/// Returns `len` strings of up to 64 characters
+pub fn strings(len: usize, seed: u64) -> Vec<String> {
+    let mut generator = Generator::new(seed);
+    (0..len)
+        .map(|_| {
+            let string_len = generator.below(65) as usize;
+            generator.string(string_len)
+        })
+        .collect()
+}
+
+/// Returns `len` orders of up to 8 lines each
+pub fn orders(len: usize, seed: u64) -> Vec<Order> {
+    let mut generator = Generator::new(seed);
+    (0..len as u64)
+        .map(|id| {
+            let line_count = generator.below(9);
+            let customer_id = generator.below(1000);
+            let name_len = 4 + generator.below(12) as usize;
+            let note_len = generator.below(33) as usize;
+            Order {
+                id,
+                customer: Customer {
+                    id: customer_id,
+                    name: generator.string(name_len),
+                    email: format!("customer{}@example.com", customer_id),
+                },
+                lines: (0..line_count)
+                    .map(|_| {
+                        OrderLine {
+                            sku: generator.below(100_000) as u32,
+                            quantity: 1 + generator.below(20) as u16,
+                            price: generator.below(100_000) as f64 / 100.0,
+                        }
+                    })
+                    .collect(),
+                note: generator.string(note_len),
+            }
+        })
+        .collect()
+}
+
+/// Returns a map of `len` distinct integer keys to strings of up to 16 characters
+pub fn index(len: usize, seed: u64) -> HashMap<u32, String> {
+    let mut generator = Generator::new(seed);
+    let mut map = HashMap::with_capacity(len);
+    let mut key = 0;
+    while map.len() < len {
+        key += 1 + generator.below(8) as u32;
+        let value_len = generator.below(17) as usize;
+        map.insert(key, generator.string(value_len));
+    }
+    map
+}
@@ -0,0 +1,194 @@
+//! Conversion of the byte representations to and from [CBOR][1].
+//!
+//! `encode` serializes a value with `u64` size indicators and reads it back through the
+//! [`Schema`](../enum.Schema.html) of its type, like the [`msgpack`](../msgpack/index.html)
+//! module, and writes the same structure with the shortest CBOR heads: a `struct` is a map
+//! from the names of its fields, an `enum` is the name of its variant if it has no fields,
+//! or a map from the name of the variant to the array of its fields otherwise, a collection
+//! is an array and a `char` is a one-character text string. `decode` reads the CBOR data
+//! item the other way around. The byte strings, the tags and the items of indefinite
+//! length have no counterpart in the schemas and are rejected, while the half precision
+//! floats are read as `f32`. This module is only available with the `cbor` feature.
+//!
+//! ```rust
+//! # #[macro_use] extern crate bytevec;
+//! # use bytevec::cbor;
+//! #[derive(Debug, PartialEq, Default)]
+//! struct Point {
+//!     x: u8,
+//!     y: i8
+//! }
+//!
+//! bytevec_impls! {
+//!     #[bytevec(schema)]
+//!     impl Point {
+//!         x: u8,
+//!         y: i8
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let point = Point { x: 1, y: -1 };
+//! let bytes = cbor::encode(&point).unwrap();
+//! assert_eq!(bytes, vec![0xa2, 0x61, b'x', 0x01, 0x61, b'y', 0x20]);
+//! assert_eq!(cbor::decode::<Point>(&bytes).unwrap(), point);
+//! # }
+//! ```
+//!
+//! [1]: https://www.rfc-editor.org/rfc/rfc8949
+
+use std::convert::TryFrom;
+
+use errors::{ByteVecError, BVExpectedSize};
+use value::{Value, MAX_DEPTH, take, take_uint};
+use {ByteEncodable, ByteDecodable, ByteSchema, BVEncodeResult, BVDecodeResult};
+
+/// Returns the CBOR data item of `value`
+pub fn encode<T>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: ByteEncodable + ByteSchema + ?Sized
+{
+    let value = Value::from_bytes(&value.encode::<u64>()?, &T::schema())?;
+    let mut bytes = Vec::new();
+    write_value(&value, &mut bytes);
+    Ok(bytes)
+}
+
+/// Reads a value of type `T` from its CBOR data item
+pub fn decode<T>(bytes: &[u8]) -> BVDecodeResult<T>
+    where T: ByteDecodable + ByteSchema
+{
+    let mut index = 0;
+    let value = read_value(bytes, &mut index, 0)?;
+    if index != bytes.len() {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(index),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    T::decode::<u64>(&value.to_bytes(&T::schema())?)
+}
+
+/// Writes the head of a data item, made of the major type and its argument
+fn write_head(major: u8, arg: u64, bytes: &mut Vec<u8>) {
+    let major = major << 5;
+    if arg < 24 {
+        bytes.push(major | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        bytes.push(major | 24);
+        bytes.push(arg as u8);
+    } else if arg <= u16::MAX as u64 {
+        bytes.push(major | 25);
+        bytes.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        bytes.push(major | 26);
+        bytes.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        bytes.push(major | 27);
+        bytes.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+fn write_value(value: &Value, bytes: &mut Vec<u8>) {
+    match *value {
+        Value::Nil => bytes.push(0xf6),
+        Value::Bool(value) => bytes.push(if value { 0xf5 } else { 0xf4 }),
+        Value::UInt(value) => write_head(0, value, bytes),
+        Value::Int(value) if value >= 0 => write_head(0, value as u64, bytes),
+        Value::Int(value) => write_head(1, !value as u64, bytes),
+        Value::F32(value) => {
+            bytes.push(0xfa);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        Value::F64(value) => {
+            bytes.push(0xfb);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        Value::String(ref string) => {
+            write_head(3, string.len() as u64, bytes);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+        Value::Array(ref values) => {
+            write_head(4, values.len() as u64, bytes);
+            for value in values {
+                write_value(value, bytes);
+            }
+        }
+        Value::Map(ref pairs) => {
+            write_head(5, pairs.len() as u64, bytes);
+            for (key, value) in pairs {
+                write_value(key, bytes);
+                write_value(value, bytes);
+            }
+        }
+    }
+}
+
+/// Returns the `f32` value of a half precision float
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (half >> 10) & 0x1f;
+    let mantissa = (half & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1024.0 + mantissa) * 2f32.powi(exponent as i32 - 25),
+    }
+}
+
+fn read_value(bytes: &[u8], index: &mut usize, depth: usize) -> BVDecodeResult<Value> {
+    if depth > MAX_DEPTH {
+        return Err(ByteVecError::InvalidValueError("a shallower nesting of arrays and maps"));
+    }
+    let initial = take(bytes, index, 1)?[0];
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    if major == 7 {
+        return match info {
+            20 => Ok(Value::Bool(false)),
+            21 => Ok(Value::Bool(true)),
+            22 => Ok(Value::Nil),
+            25 => Ok(Value::F32(half_to_f32(take_uint(bytes, index, 2)? as u16))),
+            26 => Ok(Value::F32(f32::from_bits(take_uint(bytes, index, 4)? as u32))),
+            27 => Ok(Value::F64(f64::from_bits(take_uint(bytes, index, 8)?))),
+            _ => Err(ByteVecError::InvalidValueError("a CBOR simple value of the schema")),
+        };
+    }
+    let arg = match info {
+        0..=23 => info as u64,
+        24 => take_uint(bytes, index, 1)?,
+        25 => take_uint(bytes, index, 2)?,
+        26 => take_uint(bytes, index, 4)?,
+        27 => take_uint(bytes, index, 8)?,
+        _ => return Err(ByteVecError::InvalidValueError("a CBOR item of definite length")),
+    };
+    // Every element takes at least one byte, which bounds the preallocated capacity
+    let capacity = (arg as usize).min(bytes.len() - *index);
+    Ok(match major {
+        0 => Value::UInt(arg),
+        1 if arg <= i64::MAX as u64 => Value::Int(!(arg as i64)),
+        1 => return Err(ByteVecError::InvalidValueError("an integer in the range of i64")),
+        3 => {
+            let len = usize::try_from(arg).map_err(|_| ByteVecError::SizeOverflowError)?;
+            let string = take(bytes, index, len)?;
+            Value::String(String::from_utf8(string.to_vec())
+                .map_err(|_| ByteVecError::InvalidValueError("a UTF-8 string"))?)
+        }
+        4 => {
+            let mut values = Vec::with_capacity(capacity);
+            for _ in 0..arg {
+                values.push(read_value(bytes, index, depth + 1)?);
+            }
+            Value::Array(values)
+        }
+        5 => {
+            let mut pairs = Vec::with_capacity(capacity);
+            for _ in 0..arg {
+                let key = read_value(bytes, index, depth + 1)?;
+                pairs.push((key, read_value(bytes, index, depth + 1)?));
+            }
+            Value::Map(pairs)
+        }
+        _ => return Err(ByteVecError::InvalidValueError("a CBOR item without bytes or tags")),
+    })
+}
@@ -0,0 +1,109 @@
+//! Framing of the byte representations for the [`tokio-util`][1] codec traits.
+//!
+//! [`BytevecCodec`](struct.BytevecCodec.html) writes each value as a frame made of the
+//! length of its byte representation, encoded as a `Size` value, followed by the byte
+//! representation itself. This module is only available with the `codec` feature.
+//!
+//! [1]: https://docs.rs/tokio-util
+
+use std::marker::PhantomData;
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use errors::{ByteVecError, BVExpectedSize};
+use frames::{frame_header, header_needed};
+use {ByteEncodable, ByteDecodable, BVSize};
+
+pub use frames::DEFAULT_MAX_FRAME_SIZE;
+
+/// A codec for the values of type `T`, framed with a length prefix of type `Size`.
+///
+/// The decoder waits until a whole frame is buffered before deserializing it, and
+/// rejects any frame whose payload is longer than the maximum frame size with a
+/// `BadSizeDecodeError`. The encoder rejects such values with an `OverflowError`.
+/// A `Framed<TcpStream, BytevecCodec<Message>>` then sends and receives `Message` values.
+pub struct BytevecCodec<T, Size = u32> {
+    max_frame_size: usize,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<T, Size> BytevecCodec<T, Size> {
+    /// Returns a codec with the default maximum frame size
+    pub fn new() -> BytevecCodec<T, Size> {
+        BytevecCodec::with_max_frame_size(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Returns a codec that accepts payloads of up to `max_frame_size` bytes
+    pub fn with_max_frame_size(max_frame_size: usize) -> BytevecCodec<T, Size> {
+        BytevecCodec {
+            max_frame_size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the maximum length of the payload of a frame
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+}
+
+impl<T, Size> Default for BytevecCodec<T, Size> {
+    fn default() -> BytevecCodec<T, Size> {
+        BytevecCodec::new()
+    }
+}
+
+impl<T, Size> Clone for BytevecCodec<T, Size> {
+    fn clone(&self) -> BytevecCodec<T, Size> {
+        BytevecCodec::with_max_frame_size(self.max_frame_size)
+    }
+}
+
+impl<T, Size> Decoder for BytevecCodec<T, Size>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    type Item = T;
+    type Error = ByteVecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, ByteVecError> {
+        let (payload_size, header_size) = match frame_header::<Size>(src)? {
+            Some(header) => header,
+            None => {
+                src.reserve(header_needed::<Size>(src));
+                return Ok(None);
+            }
+        };
+        if payload_size > self.max_frame_size {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::LessOrEqualThan(self.max_frame_size),
+                actual: payload_size,
+                offset: 0,
+            });
+        }
+        if src.len() < header_size + payload_size {
+            src.reserve(header_size + payload_size - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(header_size + payload_size);
+        T::decode::<Size>(&frame[header_size..]).map(Some)
+    }
+}
+
+impl<T, Size> Encoder<T> for BytevecCodec<T, Size>
+    where T: ByteEncodable,
+          Size: BVSize + ByteEncodable
+{
+    type Error = ByteVecError;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), ByteVecError> {
+        let payload_size = match item.get_size::<Size>() {
+            Some(size) if size.as_usize() <= self.max_frame_size => size,
+            _ => return Err(ByteVecError::OverflowError),
+        };
+        dst.reserve(payload_size.encoded_len() + payload_size.as_usize());
+        dst.extend_from_slice(&item.encode_framed::<Size>()?);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Compression of the byte representations, through [`flate2`][1] or [`zstd`][2].
+//!
+//! `ByteEncodable::encode_compressed` writes a small header made of the identifier of
+//! the [`Compression`](enum.Compression.html) algorithm and the length of the original
+//! byte representation, encoded as a `Size` value, followed by the compressed bytes.
+//! `ByteDecodable::decode_compressed` reads the algorithm from the header. This module is
+//! only available with the `flate2` or the `zstd` features, which enable the respective
+//! algorithms.
+//!
+//! [1]: https://docs.rs/flate2
+//! [2]: https://docs.rs/zstd
+
+use std::io::Read;
+#[cfg(feature = "flate2")]
+use std::io::Write;
+
+#[cfg(feature = "flate2")]
+use flate2::read::DeflateDecoder;
+#[cfg(feature = "flate2")]
+use flate2::write::DeflateEncoder;
+
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult};
+
+/// The compression algorithms, identified in the header of the compressed bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// The DEFLATE algorithm, with the `flate2` feature
+    #[cfg(feature = "flate2")]
+    Deflate,
+    /// The Zstandard algorithm, with the `zstd` feature
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the identifier of the algorithm written in the header
+    pub fn id(self) -> u8 {
+        match self {
+            #[cfg(feature = "flate2")]
+            Compression::Deflate => 1,
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => 2,
+        }
+    }
+
+    /// Returns the algorithm identified by `id`, if it's enabled
+    pub fn from_id(id: u8) -> Option<Compression> {
+        match id {
+            #[cfg(feature = "flate2")]
+            1 => Some(Compression::Deflate),
+            #[cfg(feature = "zstd")]
+            2 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn compress(compression: Compression, bytes: &[u8]) -> BVEncodeResult<Vec<u8>> {
+    Ok(match compression {
+        #[cfg(feature = "flate2")]
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), ::flate2::Compression::default());
+            encoder.write_all(bytes)?;
+            encoder.finish()?
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => ::zstd::stream::encode_all(bytes, ::zstd::DEFAULT_COMPRESSION_LEVEL)?,
+    })
+}
+
+// The decompressed bytes are read up to one byte past the length of the header, so a
+// corrupted header can't make the decoder allocate more than the actual contents.
+pub(crate) fn decompress(compression: Compression, bytes: &[u8], len: usize)
+    -> BVDecodeResult<Vec<u8>>
+{
+    let limit = (len as u64).saturating_add(1);
+    let mut decompressed = Vec::new();
+    match compression {
+        #[cfg(feature = "flate2")]
+        Compression::Deflate => {
+            DeflateDecoder::new(bytes).take(limit).read_to_end(&mut decompressed)?;
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            ::zstd::stream::read::Decoder::new(bytes)?.take(limit).read_to_end(&mut decompressed)?;
+        }
+    }
+    if decompressed.len() == len {
+        Ok(decompressed)
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: decompressed.len(),
+            offset: 0,
+        })
+    }
+}
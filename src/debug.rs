@@ -0,0 +1,444 @@
+//! Rendering of byte representations as JSON trees, for debugging.
+//!
+//! `to_json` walks a byte buffer along the [`Schema`](../enum.Schema.html) of a type and
+//! returns a JSON tree with the kind, the offset and the size of every value, along with
+//! the decoded primitive values, the numbers of elements, the versions and the variants.
+//! Fields are named after the `struct` fields, and the elements of collections and tuples
+//! after their index. Malformed buffers are rendered as far as they can be read: the value
+//! that can't be read gets an `"error"` entry with the decoding error, after the children
+//! read before the failure, so the offsets show where the sizes stop matching the buffer.
+//!
+//! ```rust
+//! # extern crate bytevec;
+//! # use bytevec::{debug, ByteEncodable};
+//! # fn main() {
+//! let bytes = (7u16, "ab".to_string()).encode::<u8>().unwrap();
+//! assert_eq!(debug::to_json::<(u16, String), u8>(&bytes),
+//!            r#"{"kind":"tuple","offset":0,"size":6,"children":[{"name":"0","kind":"primitive","type":"u16","offset":2,"size":2,"value":7},{"name":"1","kind":"string","offset":4,"size":2,"value":"ab"}]}"#);
+//!
+//! let json = debug::to_json::<(u16, String), u8>(&bytes[..5]);
+//! assert!(json.ends_with(r#""error":"The size expected for the structure at the offset 0 is more than 6, but the size of the given buffer is 5"}"#));
+//! # }
+//! ```
+
+use std::fmt::Write;
+
+use errors::{ByteVecError, BVExpectedSize};
+use impls::{index_width, string_bytes};
+use schema::{FieldSchema, Schema};
+use {ByteDecodable, ByteSchema, BVDecodeResult, BVSize, BitSet, BitVec, VarSize, ZigZag,
+     compact_table, read_size};
+
+/// Returns the JSON tree of the byte representation of a value of type `T`, written with
+/// `Size` as the size indicator
+pub fn to_json<T, Size>(bytes: &[u8]) -> String
+    where T: ByteSchema + ?Sized,
+          Size: BVSize + ByteDecodable
+{
+    schema_to_json::<Size>(&T::schema(), bytes)
+}
+
+/// Returns the JSON tree of a byte representation described by `schema`, written with
+/// `Size` as the size indicator
+pub fn schema_to_json<Size>(schema: &Schema, bytes: &[u8]) -> String
+    where Size: BVSize + ByteDecodable
+{
+    let mut json = String::new();
+    walk::<Size>(schema, bytes, 0).write_json(&mut json);
+    json
+}
+
+/// A value found in the buffer
+struct Node {
+    name: Option<String>,
+    kind: &'static str,
+    type_name: Option<&'static str>,
+    offset: usize,
+    size: usize,
+    /// Entries written before the children, with JSON values
+    entries: Vec<(&'static str, String)>,
+    children: Vec<Node>,
+    error: Option<String>,
+}
+
+impl Node {
+    fn write_json(&self, json: &mut String) {
+        json.push('{');
+        if let Some(ref name) = self.name {
+            json.push_str(r#""name":"#);
+            write_json_string(json, name);
+            json.push(',');
+        }
+        write!(json, r#""kind":"{}","#, self.kind).unwrap();
+        if let Some(type_name) = self.type_name {
+            write!(json, r#""type":"{}","#, type_name).unwrap();
+        }
+        write!(json, r#""offset":{},"size":{}"#, self.offset, self.size).unwrap();
+        for &(key, ref value) in &self.entries {
+            write!(json, r#","{}":{}"#, key, value).unwrap();
+        }
+        if !self.children.is_empty() {
+            json.push_str(r#","children":["#);
+            for (index, child) in self.children.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                child.write_json(json);
+            }
+            json.push(']');
+        }
+        if let Some(ref error) = self.error {
+            json.push_str(r#","error":"#);
+            write_json_string(json, error);
+        }
+        json.push('}');
+    }
+}
+
+fn write_json_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::new();
+    write_json_string(&mut json, string);
+    json
+}
+
+/// Returns the JSON number of a float, or a string for the values JSON can't represent
+fn json_float(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        json_string(&value.to_string())
+    }
+}
+
+fn kind_of(schema: &Schema) -> (&'static str, Option<&'static str>) {
+    match *schema {
+        Schema::Primitive { name, .. } => ("primitive", Some(name)),
+        Schema::String => ("string", None),
+        Schema::LossyString => ("lossy_string", None),
+        Schema::Unit => ("unit", None),
+        Schema::Seq(_) => ("seq", None),
+        Schema::Map(..) => ("map", None),
+        Schema::PackedSeq(_) => ("packed_seq", None),
+        Schema::OptionSeq(_) => ("option_seq", None),
+        Schema::InternedSeq(_) => ("interned_seq", None),
+        Schema::BitSeq => ("bit_seq", None),
+        Schema::BitSet => ("bit_set", None),
+        Schema::ZigZag(name) => ("zigzag", Some(name)),
+        Schema::Tuple(_) => ("tuple", None),
+        Schema::Struct { name, .. } => ("struct", Some(name)),
+        Schema::Enum { name, .. } => ("enum", Some(name)),
+    }
+}
+
+/// Reads the value described by `schema` from the whole of `bytes`, found at `offset`
+/// in the buffer
+fn walk<Size>(schema: &Schema, bytes: &[u8], offset: usize) -> Node
+    where Size: BVSize + ByteDecodable
+{
+    let (kind, type_name) = kind_of(schema);
+    let mut node = Node {
+        name: None,
+        kind,
+        type_name,
+        offset,
+        size: bytes.len(),
+        entries: Vec::new(),
+        children: Vec::new(),
+        error: None,
+    };
+    if let Err(error) = fill::<Size>(schema, bytes, &mut node) {
+        node.error = Some(error.at(offset).to_string());
+    }
+    node
+}
+
+fn expect_end(bytes: &[u8], end: usize) -> BVDecodeResult<()> {
+    if bytes.len() == end {
+        Ok(())
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(end),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+/// Reads a table of `entries` sizes, or of as many sizes as the count at `index` if
+/// `entries` is `None`, and returns the sizes along with the index that follows the table
+fn read_table<Size>(bytes: &[u8], mut index: usize, entries: Option<usize>)
+    -> BVDecodeResult<(Vec<usize>, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let entries = match entries {
+        Some(entries) => entries,
+        None => {
+            let (entries, count_len) = read_size::<Size>(&bytes[index..])
+                .map_err(|error| error.at(index))?;
+            index += count_len;
+            entries
+        }
+    };
+    let mut sizes = Vec::new();
+    for _ in 0..entries {
+        let (size, size_len) = read_size::<Size>(&bytes[index..])
+            .map_err(|error| error.at(index))?;
+        sizes.push(size);
+        index += size_len;
+    }
+    Ok((sizes, index))
+}
+
+/// Walks the entries of the given sizes from `index`, naming them after their position,
+/// and returns the index that follows them
+fn walk_entries<Size, F>(bytes: &[u8], mut index: usize, sizes: &[usize], node: &mut Node,
+                         schema_of: F)
+    -> BVDecodeResult<usize>
+    where Size: BVSize + ByteDecodable,
+          F: Fn(usize) -> Schema
+{
+    for (position, &size) in sizes.iter().enumerate() {
+        let end = index.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+        if bytes.len() < end {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(end),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let mut child = walk::<Size>(&schema_of(position), &bytes[index..end],
+                                     node.offset + index);
+        child.name = Some(position.to_string());
+        node.children.push(child);
+        index = end;
+    }
+    Ok(index)
+}
+
+fn fill<Size>(schema: &Schema, bytes: &[u8], node: &mut Node) -> BVDecodeResult<()>
+    where Size: BVSize + ByteDecodable
+{
+    match *schema {
+        Schema::Primitive { name, .. } => {
+            let value = match name {
+                "u8" => u8::decode::<Size>(bytes)?.to_string(),
+                "u16" => u16::decode::<Size>(bytes)?.to_string(),
+                "u32" => u32::decode::<Size>(bytes)?.to_string(),
+                "u64" => u64::decode::<Size>(bytes)?.to_string(),
+                "usize" => usize::decode::<Size>(bytes)?.to_string(),
+                "i8" => i8::decode::<Size>(bytes)?.to_string(),
+                "i16" => i16::decode::<Size>(bytes)?.to_string(),
+                "i32" => i32::decode::<Size>(bytes)?.to_string(),
+                "i64" => i64::decode::<Size>(bytes)?.to_string(),
+                #[cfg(feature = "half")]
+                "f16" => json_float(::half::f16::decode::<Size>(bytes)?.to_f64()),
+                "f32" => json_float(f32::decode::<Size>(bytes)? as f64),
+                "f64" => json_float(f64::decode::<Size>(bytes)?),
+                "char" => json_string(&char::decode::<Size>(bytes)?.to_string()),
+                _ => return Ok(()),
+            };
+            node.entries.push(("value", value));
+        }
+        Schema::String => {
+            node.entries.push(("value", json_string(&String::decode::<Size>(bytes)?)));
+        }
+        Schema::LossyString => {
+            let string = String::from_utf8_lossy(string_bytes::<Size>(bytes)?);
+            node.entries.push(("value", json_string(&string)));
+        }
+        Schema::Unit => {}
+        Schema::Seq(ref elem) => {
+            let (sizes, index) = read_table::<Size>(bytes, 0, None)?;
+            node.entries.push(("count", sizes.len().to_string()));
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |_| (**elem).clone())?;
+            expect_end(bytes, end)?;
+        }
+        Schema::Map(ref key, ref value) => {
+            let (sizes, index) = read_table::<Size>(bytes, 0, None)?;
+            node.entries.push(("count", sizes.len().to_string()));
+            let entry = Schema::Tuple(vec![(**key).clone(), (**value).clone()]);
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |_| entry.clone())?;
+            expect_end(bytes, end)?;
+        }
+        Schema::PackedSeq(ref elem) => {
+            let size = elem.fixed_size().unwrap_or(0);
+            let (count, index) = read_size::<Size>(bytes)?;
+            node.entries.push(("count", count.to_string()));
+            let sizes = vec![size; count.min(bytes.len())];
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |_| (**elem).clone())?;
+            expect_end(bytes, end)?;
+        }
+        Schema::OptionSeq(ref elem) => {
+            let (count, count_len) = read_size::<Size>(bytes)?;
+            node.entries.push(("count", count.to_string()));
+            let index = count_len + count.div_ceil(8);
+            if bytes.len() < index {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::MoreThan(index),
+                    actual: bytes.len(),
+                    offset: 0,
+                });
+            }
+            let present = (0..count)
+                .filter(|&position| bytes[count_len + position / 8] & (1 << (position % 8)) != 0)
+                .map(|position| position.to_string())
+                .collect::<Vec<_>>();
+            node.entries.push(("present", format!("[{}]", present.join(","))));
+            let (sizes, index) = read_table::<Size>(bytes, index, None)?;
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |_| (**elem).clone())?;
+            for (child, position) in node.children.iter_mut().zip(present) {
+                child.name = Some(position);
+            }
+            expect_end(bytes, end)?;
+        }
+        Schema::InternedSeq(ref elem) => {
+            let (sizes, index) = read_table::<Size>(bytes, 0, None)?;
+            let index = walk_entries::<Size, _>(bytes, index, &sizes, node, |_| (**elem).clone())?;
+            let (count, count_len) = read_size::<Size>(&bytes[index..])
+                .map_err(|error| error.at(index))?;
+            node.entries.push(("count", count.to_string()));
+            let width = index_width(sizes.len());
+            let end = count.checked_mul(width)
+                .and_then(|indexes_len| indexes_len.checked_add(index + count_len))
+                .ok_or(ByteVecError::SizeOverflowError)?;
+            expect_end(bytes, end)?;
+        }
+        Schema::BitSeq => {
+            let bits = BitVec::decode::<Size>(bytes)?
+                .0
+                .into_iter()
+                .map(|bit| bit.to_string())
+                .collect::<Vec<_>>();
+            node.entries.push(("value", format!("[{}]", bits.join(","))));
+        }
+        Schema::BitSet => {
+            let mut elems = BitSet::decode::<Size>(bytes)?.0.into_iter().collect::<Vec<_>>();
+            elems.sort();
+            let elems = elems.iter().map(|elem| elem.to_string()).collect::<Vec<_>>();
+            node.entries.push(("value", format!("[{}]", elems.join(","))));
+        }
+        Schema::ZigZag(name) => {
+            let value = match name {
+                "i8" => ZigZag::<i8>::decode::<Size>(bytes)?.0.to_string(),
+                "i16" => ZigZag::<i16>::decode::<Size>(bytes)?.0.to_string(),
+                "i32" => ZigZag::<i32>::decode::<Size>(bytes)?.0.to_string(),
+                "i64" => ZigZag::<i64>::decode::<Size>(bytes)?.0.to_string(),
+                _ => return Ok(()),
+            };
+            node.entries.push(("value", value));
+        }
+        Schema::Tuple(ref elems) => {
+            let (sizes, index) = read_table::<Size>(bytes, 0, Some(elems.len()))?;
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |position| {
+                elems[position].clone()
+            })?;
+            expect_end(bytes, end)?;
+        }
+        Schema::Struct { version, packed, extensible, compact, ref fields, .. } => {
+            let mut index = 0;
+            if version.is_some() {
+                let (version, version_len) = read_size::<Size>(bytes)?;
+                node.entries.push(("version", version.to_string()));
+                index += version_len;
+            }
+            let sizes = if packed {
+                fields.iter().map(|field| field.schema.fixed_size().unwrap_or(0)).collect()
+            } else if compact {
+                let (sizes, table_len) = compact_table::<Size>(&bytes[index..], fields.len())
+                    .map_err(|error| error.at(index))?;
+                index += table_len;
+                sizes
+            } else {
+                let count = if extensible { None } else { Some(fields.len()) };
+                let (sizes, table_end) = read_table::<Size>(bytes, index, count)?;
+                index = table_end;
+                sizes
+            };
+            for (position, &size) in sizes.iter().enumerate() {
+                let end = index.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+                if bytes.len() < end {
+                    return Err(ByteVecError::BadSizeDecodeError {
+                        expected: BVExpectedSize::MoreThan(end),
+                        actual: bytes.len(),
+                        offset: 0,
+                    });
+                }
+                let offset = node.offset + index;
+                let child = match fields.get(position) {
+                    Some(field) => walk_field::<Size>(field, &bytes[index..end], offset),
+                    None => Node {
+                        name: Some(position.to_string()),
+                        kind: "unknown",
+                        type_name: None,
+                        offset,
+                        size,
+                        entries: Vec::new(),
+                        children: Vec::new(),
+                        error: None,
+                    },
+                };
+                node.children.push(child);
+                index = end;
+            }
+            expect_end(bytes, index)?;
+        }
+        Schema::Enum { ref variants, .. } => {
+            let (tag, tag_len) = read_size::<Size>(bytes)?;
+            let variant = variants.iter()
+                .find(|variant| variant.tag == tag)
+                .ok_or(ByteVecError::UnknownVariantError(tag))?;
+            node.entries.push(("variant", json_string(variant.name)));
+            let (sizes, index) = read_table::<Size>(bytes, tag_len, Some(variant.fields.len()))?;
+            let end = walk_entries::<Size, _>(bytes, index, &sizes, node, |position| {
+                variant.fields[position].clone()
+            })?;
+            expect_end(bytes, end)?;
+        }
+    }
+    Ok(())
+}
+
+/// Walks a field with its byte order and the width of its size indicators
+fn walk_field<Size>(field: &FieldSchema, bytes: &[u8], offset: usize) -> Node
+    where Size: BVSize + ByteDecodable
+{
+    let mut reversed = Vec::new();
+    let field_bytes = if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+        reversed.extend(bytes.iter().rev());
+        &reversed[..]
+    } else {
+        bytes
+    };
+    let mut node = match field.size_width {
+        _ if field.skippable && bytes.is_empty() => {
+            let mut node = walk::<Size>(&Schema::Unit, bytes, offset);
+            node.kind = "skipped";
+            node
+        }
+        Some(0) => walk::<VarSize>(&field.schema, field_bytes, offset),
+        Some(1) => walk::<u8>(&field.schema, field_bytes, offset),
+        Some(2) => walk::<u16>(&field.schema, field_bytes, offset),
+        Some(4) => walk::<u32>(&field.schema, field_bytes, offset),
+        Some(8) => walk::<u64>(&field.schema, field_bytes, offset),
+        Some(16) => walk::<u128>(&field.schema, field_bytes, offset),
+        _ => walk::<Size>(&field.schema, field_bytes, offset),
+    };
+    node.name = Some(field.name.to_string());
+    node
+}
@@ -0,0 +1,181 @@
+use std::io::{self, Write};
+
+use errors::ByteVecError;
+use {ByteEncodable, ByteDecodable, BVEncodeResult, BVDecodeResult, BVSize};
+
+/// An object-safe companion of `ByteEncodable`, whose methods use the fixed size type `Size`.
+///
+/// The methods of `ByteEncodable` are generic over the size type, so its implementations
+/// can't be held as trait objects. Every sized `ByteEncodable` type implements this trait, so a
+/// queue of values of different types can be held as a `Vec<Box<dyn DynByteEncodable>>`,
+/// and `dyn DynByteEncodable<Size>` implements `ByteEncodable` in turn, so the whole queue
+/// is encoded as a collection. Encoding a trait object with a `Size` type whose layout is
+/// not the one of its own `Size` type, such as `u16` instead of `u32` or `VarSize`, fails
+/// with an `InvalidValueError`, while a `Deterministic<u32>` matches a `Deterministic<u32>`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, DynByteEncodable};
+/// # fn main() {
+/// let queue: Vec<Box<dyn DynByteEncodable>> = vec![Box::new(7u32), Box::new("ready")];
+/// assert_eq!((*queue[1]).dyn_encode().unwrap(), b"ready");
+/// let bytes = queue.encode::<u32>().unwrap();
+/// let (id, status) = <(u32, String)>::decode::<u32>(&bytes[4..]).unwrap();
+/// assert_eq!((id, status.as_str()), (7, "ready"));
+/// assert!(queue.encode::<u16>().is_err());
+/// # }
+/// ```
+pub trait DynByteEncodable<Size = u32> {
+    /// Returns the length of the byte representation, as `ByteEncodable::get_size`
+    fn dyn_get_size(&self) -> Option<Size>;
+    /// Returns the byte representation, as `ByteEncodable::encode`
+    fn dyn_encode(&self) -> BVEncodeResult<Vec<u8>>;
+    /// Appends the byte representation to `out`, as `ByteEncodable::encode_to_buf`
+    fn dyn_encode_to_buf(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>;
+    /// Writes the byte representation to `writer`, as `ByteEncodable::encode_to`
+    fn dyn_encode_to(&self, writer: &mut dyn Write) -> BVEncodeResult<()>;
+}
+
+impl<T, Size> DynByteEncodable<Size> for T
+    where T: ByteEncodable,
+          Size: BVSize + ByteEncodable
+{
+    fn dyn_get_size(&self) -> Option<Size> {
+        self.get_size::<Size>()
+    }
+
+    fn dyn_encode(&self) -> BVEncodeResult<Vec<u8>> {
+        self.encode::<Size>()
+    }
+
+    fn dyn_encode_to_buf(&self, out: &mut Vec<u8>) -> BVEncodeResult<()> {
+        self.encode_to_buf::<Size>(out)
+    }
+
+    fn dyn_encode_to(&self, writer: &mut dyn Write) -> BVEncodeResult<()> {
+        self.encode_to::<Size, dyn Write>(writer)
+    }
+}
+
+/// Returns `true` if the size types `A` and `B` write the same byte representations, as
+/// they are only told apart by their widths and their options
+fn same_layout<A, B>() -> bool
+    where A: BVSize,
+          B: BVSize
+{
+    A::from_usize(0).encoded_len() == B::from_usize(0).encoded_len() &&
+    A::max_value().encoded_len() == B::max_value().encoded_len() &&
+    A::max_value().checked_as_usize() == B::max_value().checked_as_usize() &&
+    A::deterministic() == B::deterministic() && A::bincode() == B::bincode() &&
+    A::strict() == B::strict() && A::big_endian() == B::big_endian()
+}
+
+fn check_layout<Size, S>() -> BVEncodeResult<()>
+    where Size: BVSize,
+          S: BVSize
+{
+    if same_layout::<Size, S>() {
+        Ok(())
+    } else {
+        Err(ByteVecError::InvalidValueError("the Size type of the trait object"))
+    }
+}
+
+/// Hands over a writer of any type as a `dyn Write`
+struct DynWriter<'a, W: ?Sized + 'a>(&'a mut W);
+
+impl<'a, W> Write for DynWriter<'a, W>
+    where W: Write + ?Sized
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.0.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<'a, S> ByteEncodable for dyn DynByteEncodable<S> + 'a
+    where S: BVSize
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        if !same_layout::<Size, S>() {
+            return None;
+        }
+        self.dyn_get_size().map(|size| Size::from_usize(size.as_usize()))
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        check_layout::<Size, S>()?;
+        self.dyn_encode()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        check_layout::<Size, S>()?;
+        self.dyn_encode_to(&mut DynWriter(writer))
+    }
+
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        check_layout::<Size, S>()?;
+        self.dyn_encode_to_buf(out)
+    }
+}
+
+impl<T> ByteEncodable for Box<T>
+    where T: ByteEncodable + ?Sized
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        (**self).encode_to::<Size, W>(writer)
+    }
+
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).encode_to_buf::<Size>(out)
+    }
+}
+
+impl<T> ByteDecodable for Box<T>
+    where T: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Box<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        T::decode::<Size>(bytes).map(Box::new)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Box<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        T::decode_with_len::<Size>(bytes).map(|(value, len)| (Box::new(value), len))
+    }
+}
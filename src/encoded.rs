@@ -0,0 +1,194 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use {ByteEncodable, ByteDecodable, BVEncodeResult, BVDecodeResult, BVSize};
+
+/// The byte representation of a value of type `T` serialized with the size type `Size`.
+///
+/// A `Vec<u8>` doesn't tell which type its bytes were serialized from, so nothing keeps a
+/// buffer written from one type from being deserialized as another. `Encoded` carries both
+/// types along with the bytes, so `decode` always deserializes the type the bytes were
+/// written from, and a mix up is a type error rather than a garbled value. The bytes can be
+/// taken out with `as_bytes` or `into_bytes` to be stored or sent elsewhere. `Display` writes
+/// the bytes as lowercase hexadecimal, and with the `serde` feature the wrapper is serialized
+/// as a byte string.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::Encoded;
+/// # fn main() {
+/// let encoded = Encoded::<(String, u16)>::new(&("Ada".to_string(), 1815)).unwrap();
+/// assert_eq!(encoded.len(), 4 + 4 + 3 + 2);
+/// assert_eq!(encoded.decode().unwrap(), ("Ada".to_string(), 1815));
+/// assert_eq!(Encoded::<u16, u8>::new(&0x0102).unwrap().to_string(), "0201");
+/// # }
+/// ```
+pub struct Encoded<T, Size = u32> {
+    bytes: Vec<u8>,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<T, Size> Encoded<T, Size>
+    where Size: BVSize
+{
+    /// Returns the byte representation of `value`
+    pub fn new(value: &T) -> BVEncodeResult<Encoded<T, Size>>
+        where T: ByteEncodable,
+              Size: ByteEncodable
+    {
+        value.encode::<Size>().map(Encoded::from_bytes)
+    }
+
+    /// Wraps `bytes` as the byte representation of a value of type `T`. The bytes are not
+    /// checked until they are deserialized with `decode`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Encoded<T, Size> {
+        Encoded {
+            bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Deserializes the value of type `T` from the bytes
+    pub fn decode(&self) -> BVDecodeResult<T>
+        where T: ByteDecodable,
+              Size: ByteDecodable
+    {
+        T::decode::<Size>(&self.bytes)
+    }
+
+    /// Returns the bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the bytes, dropping their type
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the length of the byte representation
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns `true` if the byte representation is empty, as the one of an empty `String`
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+// The impls are written by hand rather than derived, so they don't require `T` and `Size`
+// to implement the traits themselves.
+impl<T, Size> Clone for Encoded<T, Size> {
+    fn clone(&self) -> Encoded<T, Size> {
+        Encoded {
+            bytes: self.bytes.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Size> PartialEq for Encoded<T, Size> {
+    fn eq(&self, other: &Encoded<T, Size>) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<T, Size> Eq for Encoded<T, Size> {}
+
+impl<T, Size> Hash for Encoded<T, Size> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state)
+    }
+}
+
+impl<T, Size> AsRef<[u8]> for Encoded<T, Size> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<T, Size> From<Encoded<T, Size>> for Vec<u8> {
+    fn from(encoded: Encoded<T, Size>) -> Vec<u8> {
+        encoded.bytes
+    }
+}
+
+impl<T, Size> fmt::Debug for Encoded<T, Size> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Encoded").field(&self.bytes).finish()
+    }
+}
+
+impl<T, Size> fmt::Display for Encoded<T, Size> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::{self, Visitor, SeqAccess};
+
+    use super::Encoded;
+
+    impl<T, Size> Serialize for Encoded<T, Size> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            serializer.serialize_bytes(&self.bytes)
+        }
+    }
+
+    struct BytesVisitor<T, Size>(PhantomData<fn() -> (T, Size)>);
+
+    impl<'de, T, Size> Visitor<'de> for BytesVisitor<T, Size> {
+        type Value = Encoded<T, Size>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a byte string")
+        }
+
+        fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Encoded<T, Size>, E>
+            where E: de::Error
+        {
+            self.visit_byte_buf(bytes.to_vec())
+        }
+
+        fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Encoded<T, Size>, E>
+            where E: de::Error
+        {
+            Ok(Encoded {
+                bytes,
+                _marker: PhantomData,
+            })
+        }
+
+        // The formats without a byte string type, such as JSON, write the bytes as a sequence
+        fn visit_seq<A>(self, mut seq: A) -> Result<Encoded<T, Size>, A::Error>
+            where A: SeqAccess<'de>
+        {
+            let mut bytes = Vec::new();
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            self.visit_byte_buf(bytes)
+        }
+    }
+
+    impl<'de, T, Size> Deserialize<'de> for Encoded<T, Size> {
+        fn deserialize<D>(deserializer: D) -> Result<Encoded<T, Size>, D::Error>
+            where D: Deserializer<'de>
+        {
+            deserializer.deserialize_byte_buf(BytesVisitor(PhantomData))
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::marker::PhantomData;
+
+use errors::ByteVecError;
+use {ByteEncodable, BVEncodeResult, BVSize};
+
+/// An encoder that keeps its output buffer from one value to the next.
+///
+/// `ByteEncodable::encode` returns a new `Vec` for every value, while an `Encoder` writes
+/// each value into the same buffer, cleared before every call, and lends its bytes until
+/// the next one. Once the buffer has grown to fit the largest of the encoded values, the
+/// following calls don't allocate it again, which suits the producers of many small
+/// messages. If a value fails to serialize, the buffer is left empty.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, Encoder};
+/// # fn main() {
+/// let mut encoder = Encoder::<u16>::with_capacity(64);
+/// for tick in 0..3u32 {
+///     let message = (tick, format!("tick {}", tick));
+///     let bytes = encoder.encode(&message).unwrap();
+///     assert_eq!(bytes, &message.encode::<u16>().unwrap()[..]);
+/// }
+/// assert_eq!(encoder.capacity(), 64);
+/// # }
+/// ```
+pub struct Encoder<Size = u32> {
+    buffer: Vec<u8>,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<Size> Encoder<Size>
+    where Size: BVSize + ByteEncodable
+{
+    /// Returns an encoder with an empty buffer
+    pub fn new() -> Encoder<Size> {
+        Encoder::with_capacity(0)
+    }
+
+    /// Returns an encoder whose buffer holds `capacity` bytes without reallocating
+    pub fn with_capacity(capacity: usize) -> Encoder<Size> {
+        Encoder {
+            buffer: Vec::with_capacity(capacity),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes the buffer holds without reallocating
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Returns the byte representation of `value`, as written by `ByteEncodable::encode`
+    pub fn encode<T>(&mut self, value: &T) -> BVEncodeResult<&[u8]>
+        where T: ByteEncodable + ?Sized
+    {
+        self.buffer.clear();
+        let result = value.encode_to_buf::<Size>(&mut self.buffer);
+        self.finish(result)
+    }
+
+    /// Returns the frame of `value`, as written by `ByteEncodable::encode_framed`
+    pub fn encode_framed<T>(&mut self, value: &T) -> BVEncodeResult<&[u8]>
+        where T: ByteEncodable + ?Sized
+    {
+        self.buffer.clear();
+        let result = value.get_size::<Size>()
+            .ok_or(ByteVecError::OverflowError)
+            .and_then(|size| size.encode_to::<Size, _>(&mut self.buffer))
+            .and_then(|_| value.encode_to_buf::<Size>(&mut self.buffer));
+        self.finish(result)
+    }
+
+    fn finish(&mut self, result: BVEncodeResult<()>) -> BVEncodeResult<&[u8]> {
+        match result {
+            Ok(()) => Ok(&self.buffer),
+            Err(error) => {
+                self.buffer.clear();
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<Size> Default for Encoder<Size>
+    where Size: BVSize + ByteEncodable
+{
+    fn default() -> Encoder<Size> {
+        Encoder::new()
+    }
+}
@@ -1,36 +1,93 @@
+use std::io;
 use std::str::Utf8Error;
 use std::convert::From;
 use std::error::Error;
 use std::fmt::{self, Display};
 
+use header::FormatHeader;
+
 use self::ByteVecError::*;
 use self::BVExpectedSize::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BVExpectedSize {
     LessOrEqualThan(usize),
     MoreThan(usize),
     EqualTo(usize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum ByteVecError {
-    StringDecodeUtf8Error(Utf8Error),
+    StringDecodeUtf8Error {
+        error: Utf8Error,
+        offset: usize,
+    },
     BadSizeDecodeError {
         expected: BVExpectedSize,
         actual: usize,
+        offset: usize,
     },
     OverflowError,
+    UnknownVersionError(usize),
+    UnknownVariantError(usize),
+    IoError(io::Error),
+    BufferTooSmallError {
+        required: usize,
+        actual: usize,
+    },
+    LengthMismatchError {
+        expected: usize,
+        actual: usize,
+    },
+    BadMagicError,
+    HeaderMismatchError {
+        expected: FormatHeader,
+        actual: FormatHeader,
+    },
+    ChecksumMismatchError {
+        expected: u32,
+        actual: u32,
+    },
+    UnknownCompressionError(u8),
+    MissingFieldError(&'static str),
+    InvalidValueError(&'static str),
+    UnknownSizeWidthError(u8),
+    PathError {
+        path: String,
+        error: Box<ByteVecError>,
+    },
+    LimitExceededError {
+        limit: &'static str,
+        max: usize,
+        actual: usize,
+    },
+    TrailingBytesError {
+        consumed: usize,
+        remaining: usize,
+    },
+    SizeOverflowError,
+    BudgetExhaustedError {
+        budget: usize,
+    },
+    IndexOutOfBoundsError {
+        index: usize,
+        len: usize,
+    },
+    CustomError(String),
 }
 
 impl Display for ByteVecError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            StringDecodeUtf8Error(utf8_error) => write!(f, "StringDecodeUtf8Error: {}", utf8_error),
-            BadSizeDecodeError { expected, actual } => {
+            StringDecodeUtf8Error { ref error, offset } => {
+                write!(f, "StringDecodeUtf8Error: {} (at the offset {})", error, offset)
+            }
+            BadSizeDecodeError { expected, actual, offset } => {
                 write!(f,
-                       "The size expected for the structure is {}, but the size of the given \
-                        buffer is {}",
+                       "The size expected for the structure at the offset {} is {}, but the \
+                        size of the given buffer is {}",
+                       offset,
                        match expected {
                            LessOrEqualThan(expected) => format!("less or equal than {}", expected),
                            MoreThan(expected) => format!("more than {}", expected),
@@ -43,31 +100,269 @@ impl Display for ByteVecError {
                        "OverflowError: The size of the data structure surpasses the \
                        max value of the integral generic type")
             }
+            UnknownVersionError(version) => {
+                write!(f,
+                       "UnknownVersionError: The version {} of the data structure has no \
+                        migration path to the current version",
+                       version)
+            }
+            UnknownVariantError(tag) => {
+                write!(f, "UnknownVariantError: The enum has no variant with the tag {}", tag)
+            }
+            IoError(ref io_error) => write!(f, "IoError: {}", io_error),
+            BufferTooSmallError { required, actual } => {
+                write!(f,
+                       "BufferTooSmallError: The byte representation takes {} bytes, but the \
+                        size of the given buffer is {}",
+                       required,
+                       actual)
+            }
+            LengthMismatchError { expected, actual } => {
+                write!(f,
+                       "LengthMismatchError: The sequence was declared with {} elements, but {} \
+                        elements were given",
+                       expected,
+                       actual)
+            }
+            BadMagicError => {
+                write!(f, "BadMagicError: The buffer doesn't start with a format header")
+            }
+            HeaderMismatchError { expected, actual } => {
+                write!(f,
+                       "HeaderMismatchError: Expected the format version {} with a size width \
+                        of {}, but the buffer has the format version {} with a size width of {}",
+                       expected.version,
+                       expected.size_width,
+                       actual.version,
+                       actual.size_width)
+            }
+            ChecksumMismatchError { expected, actual } => {
+                write!(f,
+                       "ChecksumMismatchError: The checksum of the buffer is {:#010x}, but the \
+                        checksum of its contents is {:#010x}",
+                       expected,
+                       actual)
+            }
+            UnknownCompressionError(id) => {
+                write!(f,
+                       "UnknownCompressionError: The compression algorithm {} is unknown or \
+                        not enabled",
+                       id)
+            }
+            MissingFieldError(name) => {
+                write!(f,
+                       "MissingFieldError: The field {} has no compatible value in the old \
+                        layout and no default value",
+                       name)
+            }
+            InvalidValueError(expected) => {
+                write!(f, "InvalidValueError: Expected {} in the value", expected)
+            }
+            UnknownSizeWidthError(width) => {
+                write!(f,
+                       "UnknownSizeWidthError: The size indicators can't have a width of {} \
+                        bytes",
+                       width)
+            }
+            PathError { ref path, ref error } => {
+                write!(f, "PathError: Failed to decode {}: {}", path, error)
+            }
+            LimitExceededError { limit, max, actual } => {
+                write!(f,
+                       "LimitExceededError: The value {} exceeds the {} limit of {}",
+                       actual,
+                       limit,
+                       max)
+            }
+            TrailingBytesError { consumed, remaining } => {
+                write!(f,
+                       "TrailingBytesError: The value takes {} bytes, but {} bytes are left \
+                        after it",
+                       consumed,
+                       remaining)
+            }
+            SizeOverflowError => {
+                write!(f,
+                       "SizeOverflowError: The sizes read from the buffer add up to more than the \
+                        max value of usize")
+            }
+            BudgetExhaustedError { budget } => {
+                write!(f,
+                       "BudgetExhaustedError: The decoding takes more than the {} steps of its \
+                        budget",
+                       budget)
+            }
+            IndexOutOfBoundsError { index, len } => {
+                write!(f,
+                       "IndexOutOfBoundsError: The index {} is out of the bounds of a collection \
+                        of {} elements",
+                       index,
+                       len)
+            }
+            CustomError(ref message) => write!(f, "CustomError: {}", message),
         }
     }
 }
 
 impl Error for ByteVecError {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            StringDecodeUtf8Error { ref error, .. } => Some(error),
+            IoError(ref io_error) => Some(io_error),
+            PathError { ref error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+impl ByteVecError {
+    /// Returns the offset of the error in the buffer given to the deserialization, which is
+    /// the start of the structure of the wrong size for a `BadSizeDecodeError`, the first
+    /// invalid byte for a `StringDecodeUtf8Error`, and the first byte left after the value
+    /// for a `TrailingBytesError`
+    pub fn offset(&self) -> Option<usize> {
         match *self {
-            StringDecodeUtf8Error(ref utf8_error) => utf8_error.description(),
-            BadSizeDecodeError { .. } => {
-                "the size specified for the structure differs from the size of the given buffer"
+            StringDecodeUtf8Error { offset, .. } | BadSizeDecodeError { offset, .. } => {
+                Some(offset)
             }
-            OverflowError => "the size of the data structure surpasses max value of the size type",
+            TrailingBytesError { consumed, .. } => Some(consumed),
+            PathError { ref error, .. } => error.offset(),
+            _ => None,
         }
     }
 
-    fn cause(&self) -> Option<&Error> {
+    /// Returns the size or the number of elements that was expected, for the errors that
+    /// compare a length read from the buffer with the expected one
+    pub fn expected(&self) -> Option<BVExpectedSize> {
         match *self {
-            StringDecodeUtf8Error(ref utf8_error) => Some(utf8_error),
+            BadSizeDecodeError { expected, .. } => Some(expected),
+            BufferTooSmallError { required, .. } => Some(MoreThan(required)),
+            LengthMismatchError { expected, .. } => Some(EqualTo(expected)),
+            LimitExceededError { max, .. } => Some(LessOrEqualThan(max)),
+            TrailingBytesError { consumed, .. } => Some(EqualTo(consumed)),
+            PathError { ref error, .. } => error.expected(),
             _ => None,
         }
     }
+
+    /// Returns the size or the number of elements that was found, for the errors that
+    /// compare a length read from the buffer with the expected one
+    pub fn actual(&self) -> Option<usize> {
+        match *self {
+            BadSizeDecodeError { actual, .. } |
+            BufferTooSmallError { actual, .. } |
+            LengthMismatchError { actual, .. } |
+            LimitExceededError { actual, .. } => Some(actual),
+            TrailingBytesError { consumed, remaining } => Some(consumed.saturating_add(remaining)),
+            PathError { ref error, .. } => error.actual(),
+            _ => None,
+        }
+    }
+
+    /// Returns the path of the field or element that could not be decoded, such as
+    /// `employees[3].profile.name`, for a `PathError`
+    pub fn path(&self) -> Option<&str> {
+        match *self {
+            PathError { ref path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the path of the field or element where it occurred
+    ///
+    /// The errors of the fields and the elements are wrapped in a `PathError`, so the
+    /// variant of an error is matched on the error returned by this method.
+    pub fn inner(&self) -> &ByteVecError {
+        match *self {
+            PathError { ref error, .. } => error,
+            ref error => error,
+        }
+    }
+
+    /// Returns the error of the field `name` of a structure, with the field prepended to
+    /// its path.
+    #[doc(hidden)]
+    pub fn in_field(self, name: &str) -> ByteVecError {
+        self.prepend(name)
+    }
+
+    /// Returns the error of the element at `index` of a collection, with the index
+    /// prepended to its path.
+    #[doc(hidden)]
+    pub fn in_element(self, index: usize) -> ByteVecError {
+        self.prepend(&format!("[{}]", index))
+    }
+
+    fn prepend(self, segment: &str) -> ByteVecError {
+        match self {
+            PathError { path, error } => {
+                let separator = if path.starts_with('[') { "" } else { "." };
+                PathError {
+                    path: format!("{}{}{}", segment, separator, path),
+                    error,
+                }
+            }
+            error => {
+                PathError {
+                    path: segment.to_string(),
+                    error: Box::new(error),
+                }
+            }
+        }
+    }
+
+    /// Returns the error of a structure read from the buffer that starts at `start` in the
+    /// buffer of its container, with its offset relative to that buffer.
+    #[doc(hidden)]
+    pub fn at(self, start: usize) -> ByteVecError {
+        match self {
+            StringDecodeUtf8Error { error, offset } => {
+                StringDecodeUtf8Error {
+                    error,
+                    offset: offset.saturating_add(start),
+                }
+            }
+            BadSizeDecodeError { expected, actual, offset } => {
+                BadSizeDecodeError {
+                    expected,
+                    actual,
+                    offset: offset.saturating_add(start),
+                }
+            }
+            PathError { path, error } => {
+                PathError {
+                    path,
+                    error: Box::new(error.at(start)),
+                }
+            }
+            error => error,
+        }
+    }
 }
 
+/// The offset of the error is that of the first invalid byte in the string.
 impl From<Utf8Error> for ByteVecError {
     fn from(error: Utf8Error) -> ByteVecError {
-        StringDecodeUtf8Error(error)
+        StringDecodeUtf8Error {
+            offset: error.valid_up_to(),
+            error,
+        }
+    }
+}
+
+impl From<io::Error> for ByteVecError {
+    fn from(error: io::Error) -> ByteVecError {
+        IoError(error)
+    }
+}
+
+/// An `IoError` is converted back into the wrapped `io::Error`, and any other error
+/// into an `io::Error` of kind `InvalidData` with the `ByteVecError` as its source.
+impl From<ByteVecError> for io::Error {
+    fn from(error: ByteVecError) -> io::Error {
+        match error {
+            IoError(io_error) => io_error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
     }
 }
@@ -0,0 +1,198 @@
+use std::marker::PhantomData;
+
+use errors::{ByteVecError, BVExpectedSize};
+use {ByteDecodable, BVDecodeResult, BVSize, read_size};
+
+/// The default maximum length of the payload of a frame, 8 MiB
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// Returns the payload size and the header length of the frame at the start of
+/// `bytes`, or `None` if its header is not complete yet.
+pub(crate) fn frame_header<Size>(bytes: &[u8]) -> BVDecodeResult<Option<(usize, usize)>>
+    where Size: BVSize + ByteDecodable
+{
+    match read_size::<Size>(bytes) {
+        Ok(header) => Ok(Some(header)),
+        Err(ByteVecError::BadSizeDecodeError { .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the number of bytes still needed to complete the header at the start of `bytes`
+pub(crate) fn header_needed<Size>(bytes: &[u8]) -> usize
+    where Size: BVSize
+{
+    Size::get_size_of().as_usize().saturating_sub(bytes.len()).max(1)
+}
+
+/// An iterator over the values of a buffer of back-to-back frames.
+///
+/// Each frame is read as written by `ByteEncodable::encode_framed`, and deserialized
+/// as a value of type `T`. A frame that fails to deserialize is yielded as an error,
+/// and the iteration goes on with the next frame. The iteration stops at the end of
+/// the buffer, or at a trailing frame that is not complete yet, in which case
+/// `is_incomplete` returns `true` and `remaining` returns the bytes of that frame,
+/// so they can be prepended to the next chunk of the stream.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, FrameIter};
+/// # fn main() {
+/// let mut bytes = 1u16.encode_framed::<u32>().unwrap();
+/// bytes.extend(2u16.encode_framed::<u32>().unwrap());
+/// bytes.extend(&[2, 0, 0, 0, 3]);
+///
+/// let mut frames = FrameIter::<u16, u32>::new(&bytes);
+/// let values = frames.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(values, vec![1, 2]);
+/// assert!(frames.is_incomplete());
+/// assert_eq!(frames.remaining(), &[2, 0, 0, 0, 3]);
+/// # }
+/// ```
+pub struct FrameIter<'a, T, Size = u32> {
+    bytes: &'a [u8],
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<'a, T, Size> FrameIter<'a, T, Size>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    /// Returns an iterator over the frames of `bytes`
+    pub fn new(bytes: &'a [u8]) -> FrameIter<'a, T, Size> {
+        FrameIter {
+            bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the bytes that have not been read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns `true` if the remaining bytes are the start of a frame that is not complete
+    pub fn is_incomplete(&self) -> bool {
+        !self.bytes.is_empty() && self.frame_size().is_none()
+    }
+
+    fn frame_size(&self) -> Option<(usize, usize)> {
+        frame_header::<Size>(self.bytes)
+            .ok()
+            .and_then(|header| header)
+            .and_then(|(size, header_size)| {
+                let frame_size = header_size.checked_add(size)?;
+                if frame_size <= self.bytes.len() {
+                    Some((header_size, frame_size))
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+impl<'a, T, Size> Iterator for FrameIter<'a, T, Size>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    type Item = BVDecodeResult<T>;
+
+    fn next(&mut self) -> Option<BVDecodeResult<T>> {
+        self.frame_size().map(|(header_size, frame_size)| {
+            let value = T::decode::<Size>(&self.bytes[header_size..frame_size]);
+            self.bytes = &self.bytes[frame_size..];
+            value
+        })
+    }
+}
+
+/// The state of a [`StreamingDecoder`](struct.StreamingDecoder.html) after being fed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeProgress<T> {
+    /// The buffered frame is not complete, and at least this many more bytes are needed
+    NeedMore(usize),
+    /// A whole frame was buffered and deserialized into this value
+    Done(T),
+}
+
+/// An incremental decoder of framed values, for byte streams that arrive in chunks.
+///
+/// The chunks given to `feed` are accumulated until a whole frame, as written by
+/// `ByteEncodable::encode_framed`, is buffered. The frame is then deserialized and removed
+/// from the buffer, and any bytes after it are kept for the next value, which can be
+/// obtained by feeding an empty chunk. A frame whose payload is longer than the maximum
+/// frame size yields a `BadSizeDecodeError` and clears the buffer.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, StreamingDecoder, DecodeProgress};
+/// # fn main() {
+/// let bytes = "chunked".to_string().encode_framed::<u32>().unwrap();
+/// let mut decoder = StreamingDecoder::<String, u32>::new();
+/// assert_eq!(decoder.feed(&bytes[..2]).unwrap(), DecodeProgress::NeedMore(2));
+/// assert_eq!(decoder.feed(&bytes[2..6]).unwrap(), DecodeProgress::NeedMore(5));
+/// assert_eq!(decoder.feed(&bytes[6..]).unwrap(), DecodeProgress::Done("chunked".to_string()));
+/// # }
+/// ```
+pub struct StreamingDecoder<T, Size = u32> {
+    buffer: Vec<u8>,
+    max_frame_size: usize,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<T, Size> StreamingDecoder<T, Size>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    /// Returns a decoder with the default maximum frame size
+    pub fn new() -> StreamingDecoder<T, Size> {
+        StreamingDecoder::with_max_frame_size(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Returns a decoder that accepts payloads of up to `max_frame_size` bytes
+    pub fn with_max_frame_size(max_frame_size: usize) -> StreamingDecoder<T, Size> {
+        StreamingDecoder {
+            buffer: Vec::new(),
+            max_frame_size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes buffered so far
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Buffers `bytes`, and deserializes the first frame of the buffer if it is complete
+    pub fn feed(&mut self, bytes: &[u8]) -> BVDecodeResult<DecodeProgress<T>> {
+        self.buffer.extend_from_slice(bytes);
+        let (payload_size, header_size) = match frame_header::<Size>(&self.buffer)? {
+            Some(header) => header,
+            None => return Ok(DecodeProgress::NeedMore(header_needed::<Size>(&self.buffer))),
+        };
+        if payload_size > self.max_frame_size {
+            self.buffer.clear();
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::LessOrEqualThan(self.max_frame_size),
+                actual: payload_size,
+                offset: 0,
+            });
+        }
+        let frame_size = header_size + payload_size;
+        if self.buffer.len() < frame_size {
+            return Ok(DecodeProgress::NeedMore(frame_size - self.buffer.len()));
+        }
+        let value = T::decode::<Size>(&self.buffer[header_size..frame_size]);
+        self.buffer.drain(..frame_size);
+        value.map(DecodeProgress::Done)
+    }
+}
+
+impl<T, Size> Default for StreamingDecoder<T, Size>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    fn default() -> StreamingDecoder<T, Size> {
+        StreamingDecoder::new()
+    }
+}
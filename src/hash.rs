@@ -0,0 +1,62 @@
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+use {ByteEncodable, BVEncodeResult, Deterministic};
+
+/// Feeds the written bytes to a hasher
+struct HashWriter<'a, H: 'a>(&'a mut H);
+
+impl<'a, H> Write for HashWriter<'a, H>
+    where H: Hasher
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the hash of the byte representation of `value` with `Deterministic<u32>` size
+/// indicators, computed with a new `H` hasher.
+///
+/// The bytes are written to the hasher as they are serialized, without being held in a
+/// buffer. As the unordered collections are sorted, the equal values have the same hash
+/// whatever the iteration order of their collections, so the hash can be used as a content
+/// address or as a key to find the duplicate values. The hash is only as stable as the
+/// hasher: the `DefaultHasher` of the standard library may change between the releases of
+/// Rust, so the hashes that are stored should be computed with a hasher of a fixed
+/// algorithm. The bytes are fed in pieces, so the hash may differ from the one of the whole
+/// buffer of `value.encode::<Deterministic<u32>>()` with the hashers whose result depends
+/// on how their input is split.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::collections::HashSet;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # fn main() {
+/// let a = (0..100u32).collect::<HashSet<_>>();
+/// let b = (0..100u32).rev().collect::<HashSet<_>>();
+/// assert_eq!(bytevec::hash::<_, DefaultHasher>(&a).unwrap(),
+///            bytevec::hash::<_, DefaultHasher>(&b).unwrap());
+/// # }
+/// ```
+pub fn hash<T, H>(value: &T) -> BVEncodeResult<u64>
+    where T: ByteEncodable + ?Sized,
+          H: Hasher + Default
+{
+    let mut hasher = H::default();
+    hash_into(value, &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+/// Writes the byte representation of `value` with `Deterministic<u32>` size indicators to
+/// `hasher`, as `hash` does, so it can be combined with other data before being finished
+pub fn hash_into<T, H>(value: &T, hasher: &mut H) -> BVEncodeResult<()>
+    where T: ByteEncodable + ?Sized,
+          H: Hasher
+{
+    value.encode_to::<Deterministic<u32>, _>(&mut HashWriter(hasher))
+}
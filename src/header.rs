@@ -0,0 +1,81 @@
+use errors::{ByteVecError, BVExpectedSize};
+use {BVDecodeResult, BVSize};
+
+/// The magic number at the start of a format header
+pub const HEADER_MAGIC: [u8; 4] = *b"BVEC";
+
+/// The version of the byte representation written by this release of the library
+pub const FORMAT_VERSION: u8 = 1;
+
+/// The header written by `ByteEncodable::encode_with_header`.
+///
+/// The header is made of the `HEADER_MAGIC` bytes, the format version and the width of
+/// the size indicators, which is the length in bytes of the fixed size integral type used
+/// as `Size`, or 0 for `VarSize`. A buffer written with a different version or size width
+/// can be detected with `FormatHeader::read` before deserializing it.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, FormatHeader, FORMAT_VERSION};
+/// # fn main() {
+/// let bytes = "snapshot".encode_with_header::<u16>().unwrap();
+/// let (header, body) = FormatHeader::read(&bytes).unwrap();
+/// assert_eq!(header, FormatHeader { version: FORMAT_VERSION, size_width: 2 });
+/// assert_eq!(body, b"snapshot");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatHeader {
+    /// The version of the byte representation
+    pub version: u8,
+    /// The width of the size indicators, or 0 if they have a variable length
+    pub size_width: u8,
+}
+
+impl FormatHeader {
+    /// The length of the byte representation of a header
+    pub const LEN: usize = HEADER_MAGIC.len() + 2;
+
+    /// Returns the header of the current format version for the size type `Size`
+    pub fn of<Size>() -> FormatHeader
+        where Size: BVSize
+    {
+        let size_width = Size::max_value().encoded_len();
+        FormatHeader {
+            version: FORMAT_VERSION,
+            size_width: if size_width == Size::from_usize(0).encoded_len() {
+                size_width as u8
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Returns the header at the start of `bytes`, along with the bytes that follow it
+    pub fn read(bytes: &[u8]) -> BVDecodeResult<(FormatHeader, &[u8])> {
+        if bytes.len() < FormatHeader::LEN {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(FormatHeader::LEN),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        if bytes[..HEADER_MAGIC.len()] != HEADER_MAGIC {
+            return Err(ByteVecError::BadMagicError);
+        }
+        let header = FormatHeader {
+            version: bytes[HEADER_MAGIC.len()],
+            size_width: bytes[HEADER_MAGIC.len() + 1],
+        };
+        Ok((header, &bytes[FormatHeader::LEN..]))
+    }
+
+    /// Returns the byte representation of the header
+    pub fn to_bytes(&self) -> [u8; FormatHeader::LEN] {
+        let mut bytes = [0u8; FormatHeader::LEN];
+        bytes[..HEADER_MAGIC.len()].copy_from_slice(&HEADER_MAGIC);
+        bytes[HEADER_MAGIC.len()] = self.version;
+        bytes[HEADER_MAGIC.len() + 1] = self.size_width;
+        bytes
+    }
+}
@@ -0,0 +1,16 @@
+use bumpalo::Bump;
+
+use traits::DecodeArena;
+
+// The strings and slices decoded in a `Bump` are freed when it's dropped or reset.
+impl DecodeArena for Bump {
+    fn alloc_str(&self, string: &str) -> &str {
+        Bump::alloc_str(self, string)
+    }
+
+    fn alloc_slice_copy<T>(&self, values: &[T]) -> &[T]
+        where T: Copy
+    {
+        Bump::alloc_slice_copy(self, values)
+    }
+}
@@ -0,0 +1,145 @@
+use traits::{ByteEncodable, ByteDecodable, FixedByteSize};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::encode_buffered;
+use std::cmp;
+use std::io::Write;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+
+/// An array of elements with a fixed size, serialized without its length nor their sizes.
+///
+/// An array is serialized as a slice by default, with its length and a size table. The
+/// length of a `PackedArray<T, N>` and the sizes of its elements are part of its type, so it
+/// only stores the byte representations of the elements back to back. Its size is fixed as
+/// well, so it can be a field of a `#[bytevec(packed)]` structure or an element of a
+/// `PackedVec`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, PackedArray};
+/// # fn main() {
+/// let rgb = PackedArray([0x10u8, 0x20, 0x30]);
+/// assert_eq!(rgb.encode::<u32>().unwrap(), vec![0x10, 0x20, 0x30]);
+/// assert_eq!(PackedArray::<u8, 3>::decode::<u32>(&[0x10, 0x20, 0x30]).unwrap(), rgb);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct PackedArray<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> PackedArray<T, N> {
+    /// Returns the underlying array
+    pub fn into_inner(self) -> [T; N] {
+        self.0
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for PackedArray<T, N> {
+    fn from(array: [T; N]) -> PackedArray<T, N> {
+        PackedArray(array)
+    }
+}
+
+impl<T, const N: usize> Deref for PackedArray<T, N> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &[T; N] {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> DerefMut for PackedArray<T, N> {
+    fn deref_mut(&mut self) -> &mut [T; N] {
+        &mut self.0
+    }
+}
+
+impl<T, const N: usize> ByteEncodable for PackedArray<T, N>
+    where T: FixedByteSize
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        if Self::SIZE <= Size::max_value().as_usize() {
+            Some(Size::from_usize(Self::SIZE))
+        } else {
+            None
+        }
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            T::encode_slice_to::<Size, W>(&self.0, writer)
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl<T, const N: usize> ByteDecodable for PackedArray<T, N>
+    where T: FixedByteSize
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<PackedArray<T, N>>
+        where Size: BVSize + ByteDecodable
+    {
+        let mut array = MaybeUninit::uninit();
+        Self::decode_in_place::<Size>(&mut array, bytes)?;
+        // SAFETY: `decode_in_place` succeeded, so it initialized every element
+        Ok(unsafe { array.assume_init() })
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(PackedArray<T, N>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let len = cmp::min(bytes.len(), Self::SIZE);
+        Ok((Self::decode::<Size>(&bytes[..len])?, len))
+    }
+}
+
+impl<T, const N: usize> FixedByteSize for PackedArray<T, N>
+    where T: FixedByteSize
+{
+    const SIZE: usize = N * T::SIZE;
+    const ALIGN: usize = T::ALIGN;
+
+    fn decode_in_place<Size>(slot: &mut MaybeUninit<PackedArray<T, N>>, bytes: &[u8])
+        -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        if bytes.len() != Self::SIZE {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(Self::SIZE),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        // SAFETY: a `PackedArray` is transparent, an array of `MaybeUninit<T>` has the layout
+        // of an array of `T`, and it needs no initialization
+        let elems = unsafe { &mut *(slot.as_mut_ptr() as *mut [MaybeUninit<T>; N]) };
+        for index in 0..N {
+            let start = index * T::SIZE;
+            let result = T::decode_in_place::<Size>(&mut elems[index],
+                                                    &bytes[start..start + T::SIZE]);
+            if let Err(error) = result {
+                // The elements decoded before the failing one are dropped, so the slot is
+                // left uninitialized without leaking them
+                for elem in &mut elems[..index] {
+                    // SAFETY: the elements before `index` were initialized by `decode_in_place`
+                    unsafe { elem.assume_init_drop() };
+                }
+                return Err(error.at(start).in_element(index));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use traits::{ByteEncodable, ByteDecodable};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// A size indicator that selects the standard layout of bincode.
+///
+/// When `Bincode` is used as the `Size` type parameter, the values are serialized as with
+/// the default configuration of bincode 1, so the data persisted with it can be read
+/// through the traits of this crate. The strings and the collections are preceded by their
+/// length as a little endian `u64`, the tuples and the structures implemented through the
+/// macros of this crate are the concatenation of their fields, without size tables, and
+/// the variant of an `enum` is written as a little endian `u32`. The primitive types have
+/// the same layout in both formats, except for `char`, which bytevec writes as a `u32`
+/// rather than as its UTF-8 encoding. The field options that change the layout of a
+/// `struct`, such as `version`, `extensible` or `skip_if`, have no bincode equivalent.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, Bincode};
+/// # fn main() {
+/// let pair = (7u32, "ab".to_string());
+/// let bytes = pair.encode::<Bincode>().unwrap();
+/// assert_eq!(bytes, vec![7, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+/// assert_eq!(<(u32, String)>::decode::<Bincode>(&bytes).unwrap(), pair);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bincode(pub u64);
+
+impl BVSize for Bincode {
+    fn from_usize(val: usize) -> Bincode {
+        Bincode(val as u64)
+    }
+
+    fn as_usize(&self) -> usize {
+        self.0 as usize
+    }
+
+    fn checked_as_usize(&self) -> Option<usize> {
+        usize::try_from(self.0).ok()
+    }
+
+    fn max_value() -> Bincode {
+        Bincode(u64::MAX)
+    }
+
+    fn checked_add(self, rhs: Bincode) -> Option<Bincode> {
+        self.0.checked_add(rhs.0).map(Bincode)
+    }
+
+    fn get_size_of() -> Bincode {
+        Bincode(8)
+    }
+
+    fn bincode() -> bool {
+        true
+    }
+}
+
+impl ByteEncodable for Bincode {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        self.0.encode_to::<Size, W>(writer)
+    }
+}
+
+impl ByteDecodable for Bincode {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Bincode>
+        where Size: BVSize + ByteDecodable
+    {
+        u64::decode::<Size>(bytes).map(Bincode)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Bincode, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        u64::decode_with_len::<Size>(bytes).map(|(value, len)| (Bincode(value), len))
+    }
+}
@@ -0,0 +1,270 @@
+use traits::{ByteEncodable, ByteDecodable};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size, size_len, encode_buffered};
+use limits::check_elements;
+use std::collections::HashSet;
+use std::io::Write;
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+
+/// Returns the length of the number of bits as a `Size` value followed by the bitmap,
+/// if the number of bits fits in `Size`
+fn bitmap_size<Size>(bits: usize) -> Option<Size>
+    where Size: BVSize
+{
+    if bits <= Size::max_value().as_usize() {
+        size_len::<Size>(bits).checked_add(Size::from_usize(bits.div_ceil(8)))
+    } else {
+        None
+    }
+}
+
+/// Reads the number of bits and the bitmap at the start of `bytes`, and returns them
+/// along with their length
+fn read_bitmap<Size>(bytes: &[u8]) -> BVDecodeResult<(usize, &[u8], usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let (bits, header_len) = read_size::<Size>(bytes)?;
+    check_elements(bits)?;
+    let len = header_len + bits.div_ceil(8);
+    if bytes.len() < len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok((bits, &bytes[header_len..len], len))
+}
+
+fn is_set(bitmap: &[u8], index: usize) -> bool {
+    bitmap[index / 8] & (1 << (index % 8)) != 0
+}
+
+/// A `Vec<bool>` serialized with one bit per element.
+///
+/// The byte representation of a `BitVec` is the number of elements as a `Size` value,
+/// followed by the elements packed eight to a byte, starting from the least significant
+/// bit of the first byte.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, BitVec};
+/// # fn main() {
+/// let flags = BitVec(vec![true, false, true, true, false, false, false, false, true]);
+/// let bytes = flags.encode::<u8>().unwrap();
+/// assert_eq!(bytes, vec![9, 0b1101, 0b1]);
+/// assert_eq!(BitVec::decode::<u8>(&bytes).unwrap(), flags);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct BitVec(pub Vec<bool>);
+
+impl BitVec {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<bool> {
+        self.0
+    }
+}
+
+impl From<Vec<bool>> for BitVec {
+    fn from(vec: Vec<bool>) -> BitVec {
+        BitVec(vec)
+    }
+}
+
+impl Deref for BitVec {
+    type Target = Vec<bool>;
+
+    fn deref(&self) -> &Vec<bool> {
+        &self.0
+    }
+}
+
+impl DerefMut for BitVec {
+    fn deref_mut(&mut self) -> &mut Vec<bool> {
+        &mut self.0
+    }
+}
+
+impl ByteEncodable for BitVec {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        bitmap_size::<Size>(self.len())
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            let mut bitmap = vec![0u8; self.len().div_ceil(8)];
+            for (index, _) in self.iter().enumerate().filter(|&(_, bit)| *bit) {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+            Size::from_usize(self.len()).encode_to::<Size, W>(writer)?;
+            writer.write_all(&bitmap)?;
+            Ok(())
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl ByteDecodable for BitVec {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<BitVec>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = BitVec::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(BitVec, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (bits, bitmap, len) = read_bitmap::<Size>(bytes)?;
+        Ok((BitVec((0..bits).map(|index| is_set(bitmap, index)).collect()), len))
+    }
+}
+
+/// A `HashSet<u32>` serialized as a bitmap, for dense sets of integers.
+///
+/// The byte representation of a `BitSet` is its smallest element as a `u32`, followed by
+/// the number of integers from the smallest to the largest element as a `Size` value, and
+/// by a bitmap with one bit per integer of that range, set for the elements of the set.
+/// A set of `n` consecutive integers then takes `n / 8` bytes and a header, but a sparse
+/// set is better serialized as a `HashSet<u32>`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, BitSet};
+/// # fn main() {
+/// let ids = BitSet((1000..1800).collect());
+/// let bytes = ids.encode::<u32>().unwrap();
+/// assert_eq!(bytes.len(), 4 + 4 + 100);
+/// assert_eq!(BitSet::decode::<u32>(&bytes).unwrap(), ids);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BitSet(pub HashSet<u32>);
+
+impl BitSet {
+    /// Returns the underlying `HashSet`
+    pub fn into_inner(self) -> HashSet<u32> {
+        self.0
+    }
+
+    /// Returns the smallest element and the number of integers up to the largest one
+    fn range(&self) -> (u32, Option<usize>) {
+        match (self.iter().min(), self.iter().max()) {
+            (Some(&min), Some(&max)) => (min, ((max - min) as usize).checked_add(1)),
+            _ => (0, Some(0)),
+        }
+    }
+}
+
+impl From<HashSet<u32>> for BitSet {
+    fn from(set: HashSet<u32>) -> BitSet {
+        BitSet(set)
+    }
+}
+
+impl Deref for BitSet {
+    type Target = HashSet<u32>;
+
+    fn deref(&self) -> &HashSet<u32> {
+        &self.0
+    }
+}
+
+impl DerefMut for BitSet {
+    fn deref_mut(&mut self) -> &mut HashSet<u32> {
+        &mut self.0
+    }
+}
+
+impl ByteEncodable for BitSet {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.range()
+            .1
+            .and_then(bitmap_size::<Size>)
+            .and_then(|size| size.checked_add(Size::from_usize(size_of::<u32>())))
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        match self.range() {
+            (min, Some(bits)) if self.get_size::<Size>().is_some() => {
+                let mut bitmap = vec![0u8; bits.div_ceil(8)];
+                for elem in self.iter() {
+                    let index = (elem - min) as usize;
+                    bitmap[index / 8] |= 1 << (index % 8);
+                }
+                min.encode_to::<Size, W>(writer)?;
+                Size::from_usize(bits).encode_to::<Size, W>(writer)?;
+                writer.write_all(&bitmap)?;
+                Ok(())
+            }
+            _ => Err(ByteVecError::OverflowError),
+        }
+    }
+}
+
+impl ByteDecodable for BitSet {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<BitSet>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = BitSet::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(BitSet, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (min, min_len) = u32::decode_with_len::<Size>(bytes)?;
+        let (bits, bitmap, bitmap_len) = read_bitmap::<Size>(&bytes[min_len..])
+            .map_err(|error| error.at(min_len))?;
+        if bits > 0 && (bits - 1) as u64 > (u32::MAX - min) as u64 {
+            return Err(ByteVecError::OverflowError);
+        }
+        let set = (0..bits)
+            .filter(|&index| is_set(bitmap, index))
+            .map(|index| min + index as u32)
+            .collect();
+        Ok((BitSet(set), min_len + bitmap_len))
+    }
+}
@@ -0,0 +1,211 @@
+use traits::{ByteEncodable, ByteDecodable, ByteDecodeBorrowed};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size, size_len, encode_buffered};
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+// A byte blob is serialized as its length as a `Size` value followed by its bytes, which are
+// copied at once. This is the byte representation of a `PackedVec<u8>` as well.
+
+fn blob_size<Size>(blob: &[u8]) -> Option<Size>
+    where Size: BVSize + ByteEncodable
+{
+    blob.len()
+        .checked_add(size_len::<Size>(blob.len()).as_usize())
+        .and_then(|size| {
+            if size <= Size::max_value().as_usize() {
+                Some(Size::from_usize(size))
+            } else {
+                None
+            }
+        })
+}
+
+fn blob_encode_to<Size, W>(blob: &[u8], writer: &mut W) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable,
+          W: Write + ?Sized
+{
+    if blob_size::<Size>(blob).is_some() {
+        Size::from_usize(blob.len()).encode_to::<Size, W>(writer)?;
+        writer.write_all(blob)?;
+        Ok(())
+    } else {
+        Err(ByteVecError::OverflowError)
+    }
+}
+
+/// Returns the blob at the start of `bytes` along with the length of its byte representation
+fn blob_decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(&[u8], usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let (len, header_len) = read_size::<Size>(bytes)?;
+    let total_len = len.checked_add(header_len).ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < total_len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(total_len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok((&bytes[header_len..total_len], total_len))
+}
+
+fn blob_decode<Size>(bytes: &[u8]) -> BVDecodeResult<&[u8]>
+    where Size: BVSize + ByteDecodable
+{
+    let (blob, len) = blob_decode_with_len::<Size>(bytes)?;
+    if len == bytes.len() {
+        Ok(blob)
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+/// A byte blob, serialized with its length and copied at once.
+///
+/// A `Vec<u8>` is serialized as any other collection, with a size for each of its bytes, so a
+/// blob takes five times its length with `u32` sizes and is written one byte at a time. The
+/// bytes of a `ByteBuf` are written right after their length, with a single copy, and read
+/// back the same way. Its byte representation is the one of a `PackedVec<u8>`. The blobs
+/// borrowed from a buffer are serialized as [`Bytes`](struct.Bytes.html).
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, ByteBuf};
+/// # fn main() {
+/// let blob = ByteBuf(vec![7u8; 10_000]);
+/// let bytes = blob.encode::<u32>().unwrap();
+/// assert_eq!(bytes.len(), 4 + 10_000);
+/// assert_eq!(ByteBuf::decode::<u32>(&bytes).unwrap(), blob);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteBuf(pub Vec<u8>);
+
+impl ByteBuf {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteBuf {
+    fn from(vec: Vec<u8>) -> ByteBuf {
+        ByteBuf(vec)
+    }
+}
+
+impl Deref for ByteBuf {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for ByteBuf {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl ByteEncodable for ByteBuf {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        blob_size(self)
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        blob_encode_to::<Size, W>(self, writer)
+    }
+}
+
+impl ByteDecodable for ByteBuf {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<ByteBuf>
+        where Size: BVSize + ByteDecodable
+    {
+        blob_decode::<Size>(bytes).map(|blob| ByteBuf(blob.to_vec()))
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(ByteBuf, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        blob_decode_with_len::<Size>(bytes).map(|(blob, len)| (ByteBuf(blob.to_vec()), len))
+    }
+}
+
+/// A borrowed byte blob, serialized as a [`ByteBuf`](struct.ByteBuf.html).
+///
+/// A `&[u8]` is serialized as a `Vec<u8>`, with a size for each of its bytes. A `Bytes`
+/// writes its bytes at once after their length, and is deserialized with
+/// `ByteDecodeBorrowed` as a view into the byte buffer, without copying them.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodeBorrowed, Bytes};
+/// # fn main() {
+/// let bytes = Bytes(b"blob").encode::<u8>().unwrap();
+/// assert_eq!(bytes, b"\x04blob");
+/// assert_eq!(Bytes::decode_borrowed::<u8>(&bytes).unwrap(), Bytes(b"blob"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bytes<'a>(pub &'a [u8]);
+
+impl<'a> Deref for Bytes<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for Bytes<'a> {
+    fn from(bytes: &'a [u8]) -> Bytes<'a> {
+        Bytes(bytes)
+    }
+}
+
+impl<'a> ByteEncodable for Bytes<'a> {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        blob_size(self.0)
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        blob_encode_to::<Size, W>(self.0, writer)
+    }
+}
+
+impl<'a> ByteDecodeBorrowed<'a> for Bytes<'a> {
+    fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<Bytes<'a>>
+        where Size: BVSize + ByteDecodable
+    {
+        blob_decode::<Size>(bytes).map(Bytes)
+    }
+}
@@ -1,74 +1,132 @@
-use traits::{ByteEncodable, ByteDecodable};
+use traits::{ByteEncodable, ByteDecodable, ByteDecodeBorrowed, ByteDecodeIn, DecodeArena};
 use errors::{ByteVecError, BVExpectedSize};
 use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{table_len, read_size, read_size_table, read_size_array, size_len, decode_exact,
+            write_size, check_encoded_len, encode_buffered, SizeTable};
+use limits::{check_elements, enter_nested};
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::io::Write;
 
 macro_rules! validate_collection {
     ($byte_vec:ident, $index:ident, $len:ident, $size_vec:ident, $ret:expr) => {{
-        if $byte_vec.len() >= Size::get_size_of().as_usize() {
-            $len = try!(Size::decode::<Size>(
-                &$byte_vec[..Size::get_size_of().as_usize()])).as_usize();
-            $index = Size::get_size_of().as_usize();
-            let sizes_len = $len * Size::get_size_of().as_usize();
-            if $byte_vec[Size::get_size_of().as_usize()..].len() >= sizes_len {
-                $size_vec = Vec::new();
-                for _ in 0..$len {
-                    $size_vec.push(try!(Size::decode::<Size>(
-                        &$byte_vec[$index..$index + Size::get_size_of().as_usize()])));
-                    $index += Size::get_size_of().as_usize();
-                }
-                let body_size = $size_vec.iter().fold(0, |acc, ref size| acc + size.as_usize());
-                if body_size == $byte_vec[Size::get_size_of().as_usize() + sizes_len..].len() {
-                    $ret
-                } else {
-                    Err(ByteVecError::BadSizeDecodeError {
-                        expected: BVExpectedSize::EqualTo(
-                            Size::get_size_of().as_usize() + sizes_len + body_size),
-                        actual: $byte_vec.len()
-                    })
-                }
-            }
-            else {
-                Err(ByteVecError::BadSizeDecodeError {
-                    expected: BVExpectedSize::MoreThan(Size::get_size_of().as_usize() + sizes_len),
-                    actual: $byte_vec.len()
-                })
-            }
+        let (len, header_len) = read_size::<Size>($byte_vec)?;
+        check_elements(len)?;
+        let _nested = enter_nested()?;
+        $len = len;
+        let (sizes, sizes_len) = read_size_table::<Size>(&$byte_vec[header_len..], $len)
+            .map_err(|error| error.at(header_len))?;
+        $index = header_len + sizes_len;
+        $size_vec = sizes;
+        let body_size = $size_vec.iter()
+            .try_fold(0usize, |acc, size| acc.checked_add(*size))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if body_size == $byte_vec[$index..].len() {
+            $ret
         } else {
             Err(ByteVecError::BadSizeDecodeError {
-                expected: BVExpectedSize::MoreThan(Size::get_size_of().as_usize()),
-                actual: $byte_vec.len()
+                expected: BVExpectedSize::EqualTo($index.saturating_add(body_size)),
+                actual: $byte_vec.len(),
+                offset: 0
             })
         }
     }};
 }
 
+/// Reads the length of a string or of a byte slice serialized in the layout of bincode,
+/// and returns its bytes along with the length of the whole representation
+pub(crate) fn read_bincode_bytes<Size>(bytes: &[u8]) -> BVDecodeResult<(&[u8], usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let (len, header_len) = read_size::<Size>(bytes)?;
+    let total_len = header_len.checked_add(len).ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < total_len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(total_len),
+            actual: bytes.len(),
+            offset: 0
+        });
+    }
+    Ok((&bytes[header_len..total_len], total_len))
+}
+
+/// Fails in the strict mode if an unordered collection declared with `len` elements holds
+/// only `distinct` elements, because of duplicates
+fn check_distinct<Size: BVSize>(len: usize, distinct: usize) -> BVDecodeResult<()> {
+    if Size::strict() && distinct != len {
+        Err(ByteVecError::LengthMismatchError {
+            expected: len,
+            actual: distinct,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Decodes the elements of a collection serialized in the layout of bincode one at a
+/// time, handing each one over to `insert`, and checks that they take the whole buffer
+fn decode_bincode_elems<E, Size, F>(bytes: &[u8], mut insert: F) -> BVDecodeResult<usize>
+    where E: ByteDecodable,
+          Size: BVSize + ByteDecodable,
+          F: FnMut(E)
+{
+    let (entries, mut len) = read_size::<Size>(bytes)?;
+    check_elements(entries)?;
+    let _nested = enter_nested()?;
+    for position in 0..entries {
+        let (elem, elem_len) = E::decode_with_len::<Size>(&bytes[len..])
+            .map_err(|error| error.at(len).in_element(position))?;
+        insert(elem);
+        len += elem_len;
+    }
+    if len == bytes.len() {
+        Ok(entries)
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0
+        })
+    }
+}
+
+// In the layout of bincode, a string is preceded by its length
 impl ByteEncodable for str {
     fn get_size<Size>(&self) -> Option<Size>
         where Size: BVSize + ByteEncodable
     {
-        if self.len() <= Size::max_value().as_usize() {
-            Some(Size::from_usize(self.len()))
-        } else {
+        if self.len() > Size::max_value().as_usize() {
             None
+        } else if Size::bincode() {
+            Size::from_usize(self.len()).checked_add(size_len::<Size>(self.len()))
+        } else {
+            Some(Size::from_usize(self.len()))
         }
     }
 
     fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
         where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
     {
         if self.get_size::<Size>().is_some() {
-            let mut bytes = Vec::new();
-            bytes.extend_from_slice(&self.as_bytes().to_vec());
-            Ok(bytes)
+            if Size::bincode() {
+                Size::from_usize(self.len()).encode_to::<Size, W>(writer)?;
+            }
+            writer.write_all(self.as_bytes())?;
+            Ok(())
         } else {
             Err(ByteVecError::OverflowError)
         }
     }
 }
 
-impl<'a> ByteEncodable for &'a str {
+impl ByteEncodable for &str {
     fn get_size<Size>(&self) -> Option<Size>
         where Size: BVSize + ByteEncodable
     {
@@ -80,6 +138,13 @@ impl<'a> ByteEncodable for &'a str {
     {
         (**self).encode::<Size>()
     }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        (**self).encode_to::<Size, W>(writer)
+    }
 }
 
 impl ByteEncodable for String {
@@ -94,18 +159,253 @@ impl ByteEncodable for String {
     {
         (**self).encode::<Size>()
     }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        (**self).encode_to::<Size, W>(writer)
+    }
 }
 
 impl ByteDecodable for String {
     fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<String>
         where Size: BVSize + ByteDecodable
     {
-        Ok(try!(::std::str::from_utf8(bytes)).to_string())
+        if Size::bincode() {
+            decode_exact::<String, Size>(bytes)
+        } else {
+            Ok(::std::str::from_utf8(bytes)?.to_string())
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(String, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            let (string, len) = read_bincode_bytes::<Size>(bytes)?;
+            let string = ::std::str::from_utf8(string)
+                .map_err(|error| ByteVecError::from(error).at(len - string.len()))?;
+            Ok((string.to_string(), len))
+        } else {
+            Ok((String::decode::<Size>(bytes)?, bytes.len()))
+        }
+    }
+
+    fn decode_into<Size>(&mut self, bytes: &[u8]) -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        let (string, offset) = if Size::bincode() {
+            let (string, len) = read_bincode_bytes::<Size>(bytes)?;
+            if len != bytes.len() {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::EqualTo(len),
+                    actual: bytes.len(),
+                    offset: 0
+                });
+            }
+            (string, len - string.len())
+        } else {
+            (bytes, 0)
+        };
+        let string = ::std::str::from_utf8(string)
+            .map_err(|error| ByteVecError::from(error).at(offset))?;
+        self.clear();
+        self.push_str(string);
+        Ok(())
+    }
+}
+
+impl<'a> ByteDecodeBorrowed<'a> for &'a str {
+    fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<&'a str>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            let (string, len) = read_bincode_bytes::<Size>(bytes)?;
+            if len != bytes.len() {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::EqualTo(len),
+                    actual: bytes.len(),
+                    offset: 0
+                });
+            }
+            Ok(::std::str::from_utf8(string)
+                .map_err(|error| ByteVecError::from(error).at(len - string.len()))?)
+        } else {
+            Ok(::std::str::from_utf8(bytes)?)
+        }
     }
 }
 
+// A `&[u8]` is serialized as any other collection, with a size of 1 for each
+// element, so the elements are the last `len` bytes of the byte buffer. In the
+// layout of bincode, they follow the length.
+impl<'a> ByteDecodeBorrowed<'a> for &'a [u8] {
+    fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<&'a [u8]>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            let (elems, len) = read_bincode_bytes::<Size>(bytes)?;
+            if len != bytes.len() {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::EqualTo(len),
+                    actual: bytes.len(),
+                    offset: 0
+                });
+            }
+            return Ok(elems);
+        }
+        let (len, header_len) = read_size::<Size>(bytes)?;
+        let (sizes, sizes_len) = read_size_table::<Size>(&bytes[header_len..], len)
+            .map_err(|error| error.at(header_len))?;
+        if let Some(&size) = sizes.iter().find(|&&size| size != 1) {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(1),
+                actual: size,
+                offset: 0
+            });
+        }
+        let body_index = header_len + sizes_len;
+        if body_index.checked_add(len) != Some(bytes.len()) {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(body_index.saturating_add(len)),
+                actual: bytes.len(),
+                offset: 0
+            });
+        }
+        Ok(&bytes[body_index..])
+    }
+}
+
+// In the layout of bincode, the elements follow the number of elements without
+// a size table, so they are read one at a time.
+macro_rules! collection_decode_with_len_impl {
+    ($t:ty, $elem:ty) => {
+        fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<($t, usize)>
+            where Size: BVSize + ByteDecodable
+        {
+            let (entries, header_len) = read_size::<Size>(bytes)?;
+            if Size::bincode() {
+                check_elements(entries)?;
+                let _nested = enter_nested()?;
+                let mut len = header_len;
+                let collection = (0..entries)
+                    .map(|position| {
+                        let (elem, elem_len) = <$elem>::decode_with_len::<Size>(&bytes[len..])
+                            .map_err(|error| error.at(len).in_element(position))?;
+                        len += elem_len;
+                        Ok(elem)
+                    })
+                    .collect::<BVDecodeResult<$t>>()?;
+                check_distinct::<Size>(entries, collection.len())?;
+                return Ok((collection, len));
+            }
+            let len = header_len + table_len::<Size>(&bytes[header_len..], entries)
+                .map_err(|error| error.at(header_len))?;
+            Ok((<$t>::decode::<Size>(&bytes[..len])?, len))
+        }
+    }
+}
+
+// The unordered collections take the pattern and the expression of the key of each
+// element, which give the order of the elements in the deterministic mode.
 macro_rules! collection_encode_impl {
     () => {
+        collection_encode_impl!(@common);
+
+        fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable
+        {
+            collection_encode_impl!(@buf out self.len(), self.iter())
+        }
+
+        fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable,
+                  W: Write + ?Sized
+        {
+            collection_encode_impl!(@write writer self.len(), self.iter());
+            Ok(())
+        }
+    };
+    (sorted_by $elem:pat => $key:expr) => {
+        collection_encode_impl!(@common);
+
+        fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable
+        {
+            if Size::deterministic() {
+                let elems = collection_encode_impl!(@sorted self.len(), self.iter(), $elem => $key);
+                collection_encode_impl!(@buf out self.len(), elems.iter().map(|entry| entry.1))
+            } else {
+                collection_encode_impl!(@buf out self.len(), self.iter())
+            }
+        }
+
+        fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable,
+                  W: Write + ?Sized
+        {
+            if Size::deterministic() {
+                let elems = collection_encode_impl!(@sorted self.len(), self.iter(), $elem => $key);
+                collection_encode_impl!(@write writer self.len(),
+                                        elems.iter().map(|entry| entry.1));
+            } else {
+                collection_encode_impl!(@write writer self.len(), self.iter());
+            }
+            Ok(())
+        }
+    };
+    // Sorts the elements by the byte representation of their keys
+    (@sorted $len:expr, $elems:expr, $elem:pat => $key:expr) => {{
+        let mut elems = Vec::with_capacity($len);
+        for elem in $elems {
+            let $elem = elem;
+            elems.push(($key.encode::<Size>()?, elem));
+        }
+        elems.sort_by(|a, b| a.0.cmp(&b.0));
+        elems
+    }};
+    // Writes the elements into the buffer, and fills in their sizes afterwards
+    (@buf $out:ident $len:expr, $elems:expr) => {{
+        let start = $out.len();
+        write_size::<Size>($out, $len)?;
+        let mut table = SizeTable::new::<Size>($out, $len);
+        for elem in $elems {
+            let entry_start = $out.len();
+            (&elem).encode_to_buf::<Size>($out)?;
+            table.entry::<Size>($out, entry_start)?;
+        }
+        table.finish($out);
+        check_encoded_len::<Size>($out, start)
+    }};
+    (@write $writer:ident $len:expr, $elems:expr) => {
+        let mut sizes = Vec::with_capacity($len);
+        let mut total = Some(size_len::<Size>($len));
+        for elem in $elems {
+            let size = (&elem).get_size::<Size>().ok_or(ByteVecError::OverflowError)?.as_usize();
+            let header_len = if Size::bincode() {
+                Size::from_usize(0)
+            } else {
+                size_len::<Size>(size)
+            };
+            total = total.and_then(|total| total.checked_add(Size::from_usize(size)))
+                .and_then(|total| total.checked_add(header_len));
+            sizes.push(size);
+        }
+        if total.is_none() || $len > Size::max_value().as_usize() {
+            return Err(ByteVecError::OverflowError);
+        }
+        Size::from_usize($len).encode_to::<Size, W>($writer)?;
+        if !Size::bincode() {
+            for size in sizes {
+                Size::from_usize(size).encode_to::<Size, W>($writer)?;
+            }
+        }
+        for elem in $elems {
+            (&elem).encode_to::<Size, W>($writer)?;
+        }
+    };
+    (@common) => {
         fn get_size<Size>(&self) -> Option<Size> where Size: BVSize + ByteEncodable {
             self.iter()
                 .fold(Some(Size::from_usize(0)), |acc, elem| {
@@ -113,32 +413,24 @@ macro_rules! collection_encode_impl {
                         (&elem)
                             .get_size::<Size>()
                             .and_then(|size| {
+                                let header_len = if Size::bincode() {
+                                    Size::from_usize(0)
+                                } else {
+                                    size_len::<Size>(size.as_usize())
+                                };
                                 acc.checked_add(size).and_then(|acc_size|
-                                    acc_size.checked_add(Size::get_size_of())
+                                    acc_size.checked_add(header_len)
                                 )
                             })
                     })
                 })
-                .and_then(|total: Size| total.checked_add(Size::get_size_of()))
+                .and_then(|total: Size| total.checked_add(size_len::<Size>(self.len())))
         }
 
         fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
-            if self.get_size::<Size>().is_some() {
-                let mut bytes = Vec::new();
-                bytes.extend_from_slice(&try!((Size::from_usize(self.len())).encode::<Size>()));
-                for elem in self {
-                    bytes.extend_from_slice(&try!(
-                        (&elem).get_size::<Size>().unwrap().encode::<Size>()));
-                }
-                for elem in self {
-                    bytes.extend_from_slice(&try!((&elem).encode::<Size>()));
-                }
-                Ok(bytes)
-            } else {
-                Err(ByteVecError::OverflowError)
-            }
+            encode_buffered::<Self, Size>(self)
         }
-    }
+    };
 }
 
 impl<T> ByteEncodable for Vec<T>
@@ -150,21 +442,181 @@ impl<T> ByteEncodable for Vec<T>
 impl<T> ByteDecodable for Vec<T>
     where T: ByteDecodable
 {
+    collection_decode_with_len_impl!(Vec<T>, T);
+
     fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Vec<T>>
         where Size: BVSize + ByteDecodable
     {
+        if Size::bincode() {
+            return decode_exact::<Vec<T>, Size>(bytes);
+        }
         let len;
         let mut index;
-        let mut sizes;
+        let sizes;
         validate_collection!(bytes, index, len, sizes, {
             let mut vec = Vec::with_capacity(len);
-            for size in sizes.into_iter() {
-                vec.push(try!(T::decode::<Size>(&bytes[index..index + size.as_usize()])));
-                index += size.as_usize();
+            for (position, size) in sizes.into_iter().enumerate() {
+                vec.push(T::decode::<Size>(&bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?);
+                index += size;
             }
             Ok(vec)
         })
     }
+
+    fn decode_into<Size>(&mut self, bytes: &[u8]) -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            self.clear();
+            return decode_bincode_elems::<T, Size, _>(bytes, |elem| self.push(elem)).map(|_| ());
+        }
+        let len;
+        let mut index;
+        let sizes;
+        validate_collection!(bytes, index, len, sizes, {
+            self.truncate(len);
+            self.reserve(len - self.len());
+            for (position, size) in sizes.into_iter().enumerate() {
+                let elem_bytes = &bytes[index..index + size];
+                let result = match self.get_mut(position) {
+                    Some(elem) => elem.decode_into::<Size>(elem_bytes),
+                    None => T::decode::<Size>(elem_bytes).map(|elem| self.push(elem)),
+                };
+                result.map_err(|error| error.at(index).in_element(position))?;
+                index += size;
+            }
+            Ok(())
+        })
+    }
+}
+
+macro_rules! borrowed_vec_impls {
+    ($($t:ty),*) => {
+        $(
+            impl<'a> ByteDecodeBorrowed<'a> for Vec<$t> {
+                fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<Vec<$t>>
+                    where Size: BVSize + ByteDecodable
+                {
+                    if Size::bincode() {
+                        let (len, mut index) = read_size::<Size>(bytes)?;
+                        check_elements(len)?;
+                        let mut vec = Vec::new();
+                        for position in 0..len {
+                            let elem_len = read_bincode_bytes::<Size>(&bytes[index..])
+                                .map_err(|error| error.at(index).in_element(position))?.1;
+                            vec.push(<$t>::decode_borrowed::<Size>(&bytes[index..index + elem_len])
+                                .map_err(|error| error.at(index).in_element(position))?);
+                            index += elem_len;
+                        }
+                        if index != bytes.len() {
+                            return Err(ByteVecError::BadSizeDecodeError {
+                                expected: BVExpectedSize::EqualTo(index),
+                                actual: bytes.len(),
+                                offset: 0
+                            });
+                        }
+                        return Ok(vec);
+                    }
+                    let len;
+                    let mut index;
+                    let sizes;
+                    validate_collection!(bytes, index, len, sizes, {
+                        let mut vec = Vec::with_capacity(len);
+                        for (position, size) in sizes.into_iter().enumerate() {
+                            vec.push(<$t>::decode_borrowed::<Size>(&bytes[index..index + size])
+                                .map_err(|error| error.at(index).in_element(position))?);
+                            index += size;
+                        }
+                        Ok(vec)
+                    })
+                }
+            }
+        )*
+    }
+}
+
+borrowed_vec_impls!(&'a str, &'a [u8]);
+
+// A `&'a str` decoded in an arena is read as a `&str` borrowed from the buffer, and then
+// copied into the arena.
+impl<'a> ByteDecodeIn<'a> for &'a str {
+    fn decode_in<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<&'a str>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        Ok(arena.alloc_str(<&str>::decode_borrowed::<Size>(bytes)?))
+    }
+
+    fn decode_in_with_len<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<(&'a str, usize)>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        if Size::bincode() {
+            let len = read_bincode_bytes::<Size>(bytes)?.1;
+            Ok((<&str>::decode_in::<Size, A>(&bytes[..len], arena)?, len))
+        } else {
+            Ok((<&str>::decode_in::<Size, A>(bytes, arena)?, bytes.len()))
+        }
+    }
+}
+
+// The elements of a `&'a [T]` decoded in an arena are gathered in a `Vec` before they're
+// copied into the arena, and are decoded in the arena themselves.
+impl<'a, T> ByteDecodeIn<'a> for &'a [T]
+    where T: ByteDecodeIn<'a> + Copy
+{
+    fn decode_in<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<&'a [T]>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        if Size::bincode() {
+            let (slice, len) = <&[T]>::decode_in_with_len::<Size, A>(bytes, arena)?;
+            if len != bytes.len() {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::EqualTo(len),
+                    actual: bytes.len(),
+                    offset: 0
+                });
+            }
+            return Ok(slice);
+        }
+        let len;
+        let mut index;
+        let sizes;
+        validate_collection!(bytes, index, len, sizes, {
+            let mut vec = Vec::with_capacity(len);
+            for (position, size) in sizes.into_iter().enumerate() {
+                vec.push(T::decode_in::<Size, A>(&bytes[index..index + size], arena)
+                    .map_err(|error| error.at(index).in_element(position))?);
+                index += size;
+            }
+            Ok(arena.alloc_slice_copy(&vec))
+        })
+    }
+
+    fn decode_in_with_len<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<(&'a [T], usize)>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        let (entries, header_len) = read_size::<Size>(bytes)?;
+        if Size::bincode() {
+            check_elements(entries)?;
+            let _nested = enter_nested()?;
+            let mut len = header_len;
+            let mut vec = Vec::new();
+            for position in 0..entries {
+                let (elem, elem_len) = T::decode_in_with_len::<Size, A>(&bytes[len..], arena)
+                    .map_err(|error| error.at(len).in_element(position))?;
+                vec.push(elem);
+                len += elem_len;
+            }
+            return Ok((arena.alloc_slice_copy(&vec), len));
+        }
+        let len = header_len + table_len::<Size>(&bytes[header_len..], entries)
+            .map_err(|error| error.at(header_len))?;
+        Ok((<&[T]>::decode_in::<Size, A>(&bytes[..len], arena)?, len))
+    }
 }
 
 impl<T> ByteEncodable for [T]
@@ -176,57 +628,119 @@ impl<T> ByteEncodable for [T]
 impl<T> ByteEncodable for HashSet<T>
     where T: ByteEncodable + Eq + Hash
 {
-    collection_encode_impl!();
+    collection_encode_impl!(sorted_by elem => elem);
 }
 
 impl<T> ByteDecodable for HashSet<T>
     where T: ByteDecodable + Eq + Hash
 {
+    collection_decode_with_len_impl!(HashSet<T>, T);
+
     fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<HashSet<T>>
         where Size: BVSize + ByteDecodable
     {
+        if Size::bincode() {
+            return decode_exact::<HashSet<T>, Size>(bytes);
+        }
         let len;
         let mut index;
-        let mut sizes;
+        let sizes;
         validate_collection!(bytes, index, len, sizes, {
             let mut set = HashSet::with_capacity(len);
-            for size in sizes.into_iter() {
-                set.insert(try!(T::decode::<Size>(&bytes[index..index + size.as_usize()])));
-                index += size.as_usize();
+            for (position, size) in sizes.into_iter().enumerate() {
+                set.insert(T::decode::<Size>(&bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?);
+                index += size;
             }
+            check_distinct::<Size>(len, set.len())?;
             Ok(set)
         })
     }
+
+    fn decode_into<Size>(&mut self, bytes: &[u8]) -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        self.clear();
+        if Size::bincode() {
+            let len = decode_bincode_elems::<T, Size, _>(bytes, |elem| {
+                self.insert(elem);
+            })?;
+            return check_distinct::<Size>(len, self.len());
+        }
+        let len;
+        let mut index;
+        let sizes;
+        validate_collection!(bytes, index, len, sizes, {
+            self.reserve(len);
+            for (position, size) in sizes.into_iter().enumerate() {
+                self.insert(T::decode::<Size>(&bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?);
+                index += size;
+            }
+            check_distinct::<Size>(len, self.len())
+        })
+    }
 }
 
 impl<K, V> ByteEncodable for HashMap<K, V>
     where K: ByteEncodable + Hash + Eq,
           V: ByteEncodable
 {
-    collection_encode_impl!();
+    collection_encode_impl!(sorted_by (key, _) => key);
 }
 
 impl<K, V> ByteDecodable for HashMap<K, V>
     where K: ByteDecodable + Hash + Eq,
           V: ByteDecodable
 {
+    collection_decode_with_len_impl!(HashMap<K, V>, (K, V));
+
     fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<HashMap<K, V>>
         where Size: BVSize + ByteDecodable
     {
+        if Size::bincode() {
+            return decode_exact::<HashMap<K, V>, Size>(bytes);
+        }
         let len;
         let mut index;
-        let mut sizes;
+        let sizes;
         validate_collection!(bytes, index, len, sizes, {
             let mut map = HashMap::with_capacity(len);
-            for size in sizes.into_iter() {
-                let (key, value) = try!(<(K, V)>::decode::<Size>(&bytes[index..index +
-                                                                               size.as_usize()]));
+            for (position, size) in sizes.into_iter().enumerate() {
+                let (key, value) = <(K, V)>::decode::<Size>(&bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?;
                 map.insert(key, value);
-                index += size.as_usize();
+                index += size;
             }
+            check_distinct::<Size>(len, map.len())?;
             Ok(map)
         })
     }
+
+    fn decode_into<Size>(&mut self, bytes: &[u8]) -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        self.clear();
+        if Size::bincode() {
+            let len = decode_bincode_elems::<(K, V), Size, _>(bytes, |(key, value)| {
+                self.insert(key, value);
+            })?;
+            return check_distinct::<Size>(len, self.len());
+        }
+        let len;
+        let mut index;
+        let sizes;
+        validate_collection!(bytes, index, len, sizes, {
+            self.reserve(len);
+            for (position, size) in sizes.into_iter().enumerate() {
+                let (key, value) = <(K, V)>::decode::<Size>(&bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?;
+                self.insert(key, value);
+                index += size;
+            }
+            check_distinct::<Size>(len, self.len())
+        })
+    }
 }
 
 macro_rules! tuple_impls {
@@ -241,26 +755,41 @@ macro_rules! tuple_impls {
             fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
                 (&(&self.0)).encode::<Size>()
             }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+                where Size: BVSize + ByteEncodable
+            {
+                (&(&self.0)).encode_to_buf::<Size>(out)
+            }
         }
 
         impl<'a, $t,> ByteEncodable for &'a (&'a $t,)
             where $t: ByteEncodable
         {
             fn get_size<Size>(&self) -> Option<Size> where Size: BVSize + ByteEncodable {
-                self.0.get_size::<Size>().and_then(|elem_size|
-                    elem_size.checked_add(Size::get_size_of()))
+                self.0.get_size::<Size>().and_then(|elem_size| {
+                    if Size::bincode() {
+                        return Some(elem_size);
+                    }
+                    let header_len = size_len::<Size>(elem_size.as_usize());
+                    elem_size.checked_add(header_len)
+                })
             }
 
             fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
-                if self.get_size::<Size>().is_some() {
-                    let mut bytes = Vec::new();
-                    bytes.extend_from_slice(&try!(
-                        self.0.get_size::<Size>().unwrap().encode::<Size>()));
-                    bytes.extend_from_slice(&try!(self.0.encode::<Size>()));
-                    Ok(bytes)
-                } else {
-                    Err(ByteVecError::OverflowError)
-                }
+                encode_buffered::<Self, Size>(self)
+            }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+                where Size: BVSize + ByteEncodable
+            {
+                let start = out.len();
+                let mut table = SizeTable::new::<Size>(out, 1);
+                let entry_start = out.len();
+                self.0.encode_to_buf::<Size>(out)?;
+                table.entry::<Size>(out, entry_start)?;
+                table.finish(out);
+                check_encoded_len::<Size>(out, start)
             }
         }
 
@@ -270,27 +799,31 @@ macro_rules! tuple_impls {
             fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<($t,)>
                 where Size: BVSize + ByteDecodable
             {
-                let size;
-
-                if bytes.len() >= Size::get_size_of().as_usize() {
-                    size = try!(Size::decode::<Size>(&bytes[..Size::get_size_of().as_usize()]));
+                if Size::bincode() {
+                    return decode_exact::<($t,), Size>(bytes);
                 }
-                else {
-                    return Err(ByteVecError::BadSizeDecodeError {
-                        expected: BVExpectedSize::MoreThan(Size::get_size_of().as_usize()),
-                        actual: bytes.len()
-                    });
-                }
-                if size.as_usize() == bytes[Size::get_size_of().as_usize()..].len() {
-                    Ok((try!($t::decode::<Size>(&bytes[Size::get_size_of().as_usize()..])),))
+                let (size, header_len) = read_size::<Size>(bytes)?;
+                if size == bytes[header_len..].len() {
+                    Ok(($t::decode::<Size>(&bytes[header_len..])
+                        .map_err(|error| error.at(header_len))?,))
                 } else {
                     Err(ByteVecError::BadSizeDecodeError {
-                        expected: BVExpectedSize::EqualTo(
-                            Size::get_size_of().as_usize() + size.as_usize()),
-                        actual: bytes.len()
+                        expected: BVExpectedSize::EqualTo(header_len.saturating_add(size)),
+                        actual: bytes.len(),
+                        offset: 0
                     })
                 }
             }
+
+            fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(($t,), usize)>
+                where Size: BVSize + ByteDecodable
+            {
+                if Size::bincode() {
+                    return $t::decode_with_len::<Size>(bytes).map(|(elem, len)| ((elem,), len));
+                }
+                let len = table_len::<Size>(bytes, 1)?;
+                Ok((<($t,)>::decode::<Size>(&bytes[..len])?, len))
+            }
         }
     };
 
@@ -309,6 +842,13 @@ macro_rules! tuple_impls {
                 let &(ref $elem, $(ref $_elem),*) = self;
                 (&($elem, $($_elem),*)).encode::<Size>()
             }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+                where Size: BVSize + ByteEncodable
+            {
+                let &(ref $elem, $(ref $_elem),*) = self;
+                (&($elem, $($_elem),*)).encode_to_buf::<Size>(out)
+            }
         }
 
         impl<'a, $t, $($_t,)*> ByteEncodable for &'a (&'a $t, $(&'a $_t),*)
@@ -319,42 +859,55 @@ macro_rules! tuple_impls {
                 let mut size = Some(Size::from_usize(0));
 
                 size = size.and_then(|size: Size|
-                    $elem.get_size::<Size>().and_then(|elem_size|
+                    $elem.get_size::<Size>().and_then(|elem_size| {
+                        let header_len = if Size::bincode() {
+                            Size::from_usize(0)
+                        } else {
+                            size_len::<Size>(elem_size.as_usize())
+                        };
                         size.checked_add(elem_size).and_then(
-                            |acc_size| acc_size.checked_add(Size::get_size_of())
+                            |acc_size| acc_size.checked_add(header_len)
                         )
-                    )
+                    })
                 );
                 $(
                     size = size.and_then(|size: Size|
-                        $_elem.get_size::<Size>().and_then(|elem_size|
+                        $_elem.get_size::<Size>().and_then(|elem_size| {
+                            let header_len = if Size::bincode() {
+                                Size::from_usize(0)
+                            } else {
+                                size_len::<Size>(elem_size.as_usize())
+                            };
                             size.checked_add(elem_size).and_then(
-                                |acc_size| acc_size.checked_add(Size::get_size_of())
+                                |acc_size| acc_size.checked_add(header_len)
                             )
-                        )
+                        })
                     );
                 )*
                 size
             }
 
             fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
-                if self.get_size::<Size>().is_some() {
-                    let &&($elem, $($_elem),*) = self;
-                    let mut bytes = Vec::new();
-                    bytes.extend_from_slice(&try!(
-                        $elem.get_size::<Size>().unwrap().encode::<Size>()));
-                    $(
-                        bytes.extend_from_slice(&try!(
-                            $_elem.get_size::<Size>().unwrap().encode::<Size>()));
-                    )*
-                    bytes.extend_from_slice(&try!($elem.encode::<Size>()));
-                    $(
-                        bytes.extend_from_slice(&try!($_elem.encode::<Size>()));
-                    )*
-                    Ok(bytes)
-                } else {
-                    Err(ByteVecError::OverflowError)
-                }
+                encode_buffered::<Self, Size>(self)
+            }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+                where Size: BVSize + ByteEncodable
+            {
+                let &&($elem, $($_elem),*) = self;
+                let start = out.len();
+                let entries = <[&str]>::len(&[stringify!($elem), $(stringify!($_elem)),*]);
+                let mut table = SizeTable::new::<Size>(out, entries);
+                let entry_start = out.len();
+                $elem.encode_to_buf::<Size>(out)?;
+                table.entry::<Size>(out, entry_start)?;
+                $(
+                    let entry_start = out.len();
+                    $_elem.encode_to_buf::<Size>(out)?;
+                    table.entry::<Size>(out, entry_start)?;
+                )*
+                table.finish(out);
+                check_encoded_len::<Size>(out, start)
             }
         }
 
@@ -365,59 +918,60 @@ macro_rules! tuple_impls {
             fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<($t, $($_t),*)>
                 where Size: BVSize + ByteDecodable
             {
-                let mut index = 0;
-                let mut sizes = ::std::collections::HashMap::new();
-
-                if bytes.len() >= Size::get_size_of().as_usize() {
-                    sizes.insert(stringify!($elem),
-                        try!(Size::decode::<Size>(&bytes[..Size::get_size_of().as_usize()])));
-                    index += Size::get_size_of().as_usize();
+                if Size::bincode() {
+                    return decode_exact::<($t, $($_t),*), Size>(bytes);
                 }
-                else {
-                    return Err(ByteVecError::BadSizeDecodeError {
-                        expected: BVExpectedSize::MoreThan(Size::get_size_of().as_usize() + index),
-                        actual: bytes.len()
-                    });
-                }
-                $(
-                    if bytes[index..].len() >= Size::get_size_of().as_usize() {
-                        sizes.insert(stringify!($_elem),
-                            try!(Size::decode::<Size>(
-                                &bytes[index..index + Size::get_size_of().as_usize()])));
-                        index += Size::get_size_of().as_usize();
-                    }
-                    else {
-                        return Err(ByteVecError::BadSizeDecodeError {
-                            expected: BVExpectedSize::MoreThan(Size::get_size_of().as_usize() + index),
-                            actual: bytes.len()
-                        });
-                    }
-                )*
+                const ENTRIES: usize = <[&str]>::len(&[stringify!($elem), $(stringify!($_elem)),*]);
+                let (sizes, mut index) = read_size_array::<Size, ENTRIES>(bytes)?;
 
-                let body_size = sizes.values().fold(0, |acc, ref size| acc + size.as_usize());
+                let body_size = sizes.iter()
+                    .try_fold(0usize, |acc, size| acc.checked_add(*size))
+                    .ok_or(ByteVecError::SizeOverflowError)?;
                 if body_size == bytes[index..].len() {
+                    let mut sizes = sizes.iter().copied();
                     Ok((
                         {
-                            let elem = try!($t::decode::<Size>(
-                                &bytes[index..index + sizes[stringify!($elem)].as_usize()]));
-                            index += sizes[stringify!($elem)].as_usize();
+                            let size = sizes.next().unwrap();
+                            let elem = $t::decode::<Size>(&bytes[index..index + size])
+                                .map_err(|error| error.at(index))?;
+                            index += size;
                             elem
                         },
                         $({
-                            let elem = try!($_t::decode::<Size>(
-                                &bytes[index..index + sizes[stringify!($_elem)].as_usize()]));
-                            index += sizes[stringify!($_elem)].as_usize();
+                            let size = sizes.next().unwrap();
+                            let elem = $_t::decode::<Size>(&bytes[index..index + size])
+                                .map_err(|error| error.at(index))?;
+                            index += size;
                             elem
                         }),*
                     ))
                 } else {
                     Err(ByteVecError::BadSizeDecodeError {
-                        expected: BVExpectedSize::EqualTo(
-                            Size::get_size_of().as_usize() * sizes.len() + body_size),
-                        actual: bytes.len()
+                        expected: BVExpectedSize::EqualTo(index.saturating_add(body_size)),
+                        actual: bytes.len(),
+                        offset: 0
                     })
                 }
             }
+
+            fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(($t, $($_t),*), usize)>
+                where Size: BVSize + ByteDecodable
+            {
+                if Size::bincode() {
+                    let mut len = 0;
+                    let ($elem, elem_len) = $t::decode_with_len::<Size>(bytes)?;
+                    len += elem_len;
+                    $(
+                        let ($_elem, elem_len) = $_t::decode_with_len::<Size>(&bytes[len..])
+                            .map_err(|error| error.at(len))?;
+                        len += elem_len;
+                    )*
+                    return Ok((($elem, $($_elem),*), len));
+                }
+                let entries = <[&str]>::len(&[stringify!($elem), $(stringify!($_elem)),*]);
+                let len = table_len::<Size>(bytes, entries)?;
+                Ok((<($t, $($_t),*)>::decode::<Size>(&bytes[..len])?, len))
+            }
         }
 
         tuple_impls!($($_t: $_elem),*);
@@ -449,6 +1003,9 @@ impl ByteEncodable for () {
     fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
         where Size: BVSize + ByteEncodable
     {
+        if Size::bincode() {
+            return Ok(Vec::new());
+        }
         // Send only size of 0
         Size::from_usize(0).encode::<Size>()
     }
@@ -460,4 +1017,10 @@ impl ByteDecodable for () {
     {
         Ok(())
     }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<((), usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        Ok(((), if Size::bincode() { 0 } else { bytes.len() }))
+    }
 }
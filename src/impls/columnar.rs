@@ -0,0 +1,263 @@
+use traits::{ByteEncodable, ByteDecodable, ByteColumns};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size, read_size_table, size_len};
+use limits::check_elements;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+/// A `Vec` of structures, serialized column by column.
+///
+/// A `Vec<T>` writes its elements one after another, each with the size table of its
+/// fields. A `ColumnVec<T>` writes the first field of every element, then the second
+/// field of every element, and so on, which puts similar bytes next to each other for
+/// the compression algorithms. The byte representation is the number of elements and the
+/// length of each column as `Size` values, followed by the columns, each made of the size
+/// of each entry and the entries. The length of the columns lets
+/// [`decode_column`](#method.decode_column) read the values of a single field without
+/// decoding the others. The element type must implement `ByteColumns`, as the structures
+/// implemented with `#[bytevec(columnar)]` do.
+///
+/// ```rust
+/// # #[macro_use] extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, ColumnVec};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct Sample {
+///     sensor: u16,
+///     value: f32
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(columnar)]
+///     impl Sample {
+///         sensor: u16,
+///         value: f32
+///     }
+/// }
+///
+/// # fn main() {
+/// let samples = ColumnVec(vec![Sample {sensor: 1, value: 0.5},
+///                              Sample {sensor: 1, value: 0.25}]);
+/// let bytes = samples.encode::<u8>().unwrap();
+/// // 2 elements, columns of 6 and 10 bytes, then the sizes and values of `sensor`
+/// assert_eq!(&bytes[..9], &[2, 6, 10, 2, 2, 1, 0, 1, 0]);
+/// assert_eq!(ColumnVec::<Sample>::decode::<u8>(&bytes).unwrap(), samples);
+/// assert_eq!(ColumnVec::<Sample>::decode_column::<f32, u8>(&bytes, 1).unwrap(),
+///            vec![0.5, 0.25]);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ColumnVec<T>(pub Vec<T>);
+
+impl<T> ColumnVec<T> {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for ColumnVec<T> {
+    fn from(vec: Vec<T>) -> ColumnVec<T> {
+        ColumnVec(vec)
+    }
+}
+
+impl<T> Deref for ColumnVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ColumnVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> ColumnVec<T>
+    where T: ByteColumns
+{
+    /// Returns the byte representation of each column
+    fn columns<Size>(&self) -> BVEncodeResult<Vec<Vec<u8>>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut columns = vec![Vec::new(); T::COLUMNS];
+        let mut bodies = vec![Vec::new(); T::COLUMNS];
+        for elem in self.iter() {
+            let entries = elem.encode_columns::<Size>()?;
+            if entries.len() != T::COLUMNS {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: T::COLUMNS,
+                    actual: entries.len(),
+                });
+            }
+            for ((sizes, body), entry) in columns.iter_mut().zip(&mut bodies).zip(entries) {
+                if entry.len() > Size::max_value().as_usize() {
+                    return Err(ByteVecError::OverflowError);
+                }
+                Size::from_usize(entry.len()).encode_to::<Size, _>(sizes)?;
+                body.extend_from_slice(&entry);
+            }
+        }
+        for (column, body) in columns.iter_mut().zip(bodies) {
+            column.extend_from_slice(&body);
+            if column.len() > Size::max_value().as_usize() {
+                return Err(ByteVecError::OverflowError);
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Reads the values of a single field from the byte representation of a
+    /// `ColumnVec<T>`, without decoding the other columns. `column` is the position of the
+    /// field among the fields with an entry, and the entries are decoded as `U` values.
+    pub fn decode_column<U, Size>(bytes: &[u8], column: usize) -> BVDecodeResult<Vec<U>>
+        where U: ByteDecodable,
+              Size: BVSize + ByteDecodable
+    {
+        let (len, column_lens, mut index) = read_header::<T, Size>(bytes)?;
+        let column_len = *column_lens.get(column).ok_or(ByteVecError::LengthMismatchError {
+            expected: column + 1,
+            actual: column_lens.len(),
+        })?;
+        index += column_lens[..column].iter().sum::<usize>();
+        read_column::<Size>(&bytes[index..index + column_len], len)
+            .map_err(|error| error.at(index))?
+            .into_iter()
+            .map(|entry| U::decode::<Size>(entry))
+            .collect()
+    }
+}
+
+/// Reads the number of elements and the length of each column, checks that the columns
+/// fit in `bytes`, and returns them with the length of the header
+fn read_header<T, Size>(bytes: &[u8]) -> BVDecodeResult<(usize, Vec<usize>, usize)>
+    where T: ByteColumns,
+          Size: BVSize + ByteDecodable
+{
+    let (len, len_len) = read_size::<Size>(bytes)?;
+    check_elements(len)?;
+    let (column_lens, table_len) = read_size_table::<Size>(&bytes[len_len..], T::COLUMNS)
+        .map_err(|error| error.at(len_len))?;
+    let header_len = len_len + table_len;
+    let total_len = column_lens.iter()
+        .try_fold(header_len, |acc, column_len| acc.checked_add(*column_len))
+        .ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < total_len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(total_len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok((len, column_lens, header_len))
+}
+
+/// Returns the `len` entries of a column
+fn read_column<Size>(bytes: &[u8], len: usize) -> BVDecodeResult<Vec<&[u8]>>
+    where Size: BVSize + ByteDecodable
+{
+    let (sizes, mut index) = read_size_table::<Size>(bytes, len)?;
+    let body_len = sizes.iter()
+        .try_fold(index, |acc, size| acc.checked_add(*size))
+        .ok_or(ByteVecError::SizeOverflowError)?;
+    if body_len != bytes.len() {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(body_len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok(sizes.into_iter()
+        .map(|size| {
+            index += size;
+            &bytes[index - size..index]
+        })
+        .collect())
+}
+
+impl<T> ByteEncodable for ColumnVec<T>
+    where T: ByteColumns
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        if self.len() > Size::max_value().as_usize() {
+            return None;
+        }
+        let columns = self.columns::<Size>().ok()?;
+        let mut size = Some(size_len::<Size>(self.len()));
+        for column in columns {
+            size = size.and_then(|size: Size| {
+                size.checked_add(size_len::<Size>(column.len()))
+                    .and_then(|size| size.checked_add(Size::from_usize(column.len())))
+            });
+        }
+        size
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::new();
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.len() > Size::max_value().as_usize() {
+            return Err(ByteVecError::OverflowError);
+        }
+        let columns = self.columns::<Size>()?;
+        Size::from_usize(self.len()).encode_to::<Size, W>(writer)?;
+        for column in &columns {
+            Size::from_usize(column.len()).encode_to::<Size, W>(writer)?;
+        }
+        for column in &columns {
+            writer.write_all(column)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> ByteDecodable for ColumnVec<T>
+    where T: ByteColumns
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<ColumnVec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = ColumnVec::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(ColumnVec<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (len, column_lens, mut index) = read_header::<T, Size>(bytes)?;
+        let mut columns = Vec::with_capacity(column_lens.len());
+        for column_len in column_lens {
+            columns.push(read_column::<Size>(&bytes[index..index + column_len], len)
+                .map_err(|error| error.at(index))?);
+            index += column_len;
+        }
+        let mut vec = Vec::with_capacity(len.min(bytes.len()));
+        for row in 0..len {
+            let entries = columns.iter().map(|column| column[row]).collect::<Vec<_>>();
+            vec.push(T::decode_columns::<Size>(&entries)?);
+        }
+        Ok((ColumnVec(vec), index))
+    }
+}
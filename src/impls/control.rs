@@ -0,0 +1,230 @@
+use traits::{ByteEncodable, ByteDecodable};
+use errors::{ByteVecError, BVExpectedSize};
+use impls::{size_len, table_len, write_size, check_encoded_len, encode_buffered, read_size,
+            decode_exact};
+use limits::enter_nested;
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::ops::ControlFlow;
+use std::task::Poll;
+
+// `ControlFlow` and `Poll` are serialized as the enums of `bytevec_impls`, which can't be
+// used for generic types: the index of the variant, in the order of declaration, followed by
+// its field as a tuple of a single element, or by nothing for `Poll::Pending`. In the layout
+// of bincode, the index is a `u32` and the field follows it without a size table.
+
+fn variant_size<Size>(tag: usize, field_size: Option<Size>) -> Option<Size>
+    where Size: BVSize + ByteEncodable
+{
+    if Size::bincode() {
+        field_size.and_then(|size| size.checked_add(Size::from_usize(4)))
+    } else {
+        field_size.and_then(|size| size.checked_add(size_len::<Size>(tag)))
+    }
+}
+
+/// Appends the index of a variant to `out`
+pub(crate) fn write_tag<Size>(out: &mut Vec<u8>, tag: usize) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    if Size::bincode() {
+        (tag as u32).encode_to::<Size, _>(out)
+    } else {
+        write_size::<Size>(out, tag)
+    }
+}
+
+/// Returns the variant index at the start of `bytes` along with its length
+pub(crate) fn read_tag<Size>(bytes: &[u8]) -> BVDecodeResult<(usize, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    if Size::bincode() {
+        u32::decode_with_len::<Size>(bytes).map(|(tag, len)| (tag as usize, len))
+    } else {
+        read_size::<Size>(bytes)
+    }
+}
+
+/// Deserializes the field of the variant `name` that follows its index
+fn decode_field<T, Size>(bytes: &[u8], index: usize, name: &'static str) -> BVDecodeResult<T>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    <(T,)>::decode::<Size>(&bytes[index..])
+        .map(|(value,)| value)
+        .map_err(|error| error.at(index).in_field(name))
+}
+
+/// Returns the end of the field that follows the index of a variant at `index`
+fn field_end<Size>(bytes: &[u8], index: usize) -> BVDecodeResult<usize>
+    where Size: BVSize + ByteDecodable
+{
+    Ok(index + table_len::<Size>(&bytes[index..], 1).map_err(|error| error.at(index))?)
+}
+
+/// Deserializes the field of the variant `name` that follows its index in the layout of
+/// bincode, along with the length of the whole value
+fn decode_field_with_len<T, Size>(bytes: &[u8], index: usize, name: &'static str)
+    -> BVDecodeResult<(T, usize)>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    <(T,)>::decode_with_len::<Size>(&bytes[index..])
+        .map(|((value,), len)| (value, index + len))
+        .map_err(|error| error.at(index).in_field(name))
+}
+
+impl<B, C> ByteEncodable for ControlFlow<B, C>
+    where B: ByteEncodable,
+          C: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        match *self {
+            ControlFlow::Continue(ref value) => variant_size(0, (&(value,)).get_size::<Size>()),
+            ControlFlow::Break(ref value) => variant_size(1, (&(value,)).get_size::<Size>()),
+        }
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        let start = out.len();
+        match *self {
+            ControlFlow::Continue(ref value) => {
+                write_tag::<Size>(out, 0)?;
+                (&(value,)).encode_to_buf::<Size>(out)?;
+            }
+            ControlFlow::Break(ref value) => {
+                write_tag::<Size>(out, 1)?;
+                (&(value,)).encode_to_buf::<Size>(out)?;
+            }
+        }
+        check_encoded_len::<Size>(out, start)
+    }
+}
+
+impl<B, C> ByteDecodable for ControlFlow<B, C>
+    where B: ByteDecodable,
+          C: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<ControlFlow<B, C>>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            return decode_exact::<ControlFlow<B, C>, Size>(bytes);
+        }
+        let _nested = enter_nested()?;
+        let (tag, index) = read_size::<Size>(bytes)?;
+        match tag {
+            0 => decode_field::<C, Size>(bytes, index, "Continue").map(ControlFlow::Continue),
+            1 => decode_field::<B, Size>(bytes, index, "Break").map(ControlFlow::Break),
+            _ => Err(ByteVecError::UnknownVariantError(tag)),
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(ControlFlow<B, C>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let _nested = enter_nested()?;
+        let (tag, index) = read_tag::<Size>(bytes)?;
+        match tag {
+            0 if Size::bincode() => decode_field_with_len::<C, Size>(bytes, index, "Continue")
+                .map(|(value, len)| (ControlFlow::Continue(value), len)),
+            1 if Size::bincode() => decode_field_with_len::<B, Size>(bytes, index, "Break")
+                .map(|(value, len)| (ControlFlow::Break(value), len)),
+            0 => {
+                let len = field_end::<Size>(bytes, index)?;
+                decode_field::<C, Size>(&bytes[..len], index, "Continue")
+                    .map(|value| (ControlFlow::Continue(value), len))
+            }
+            1 => {
+                let len = field_end::<Size>(bytes, index)?;
+                decode_field::<B, Size>(&bytes[..len], index, "Break")
+                    .map(|value| (ControlFlow::Break(value), len))
+            }
+            _ => Err(ByteVecError::UnknownVariantError(tag)),
+        }
+    }
+}
+
+impl<T> ByteEncodable for Poll<T>
+    where T: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        match *self {
+            Poll::Ready(ref value) => variant_size(0, (&(value,)).get_size::<Size>()),
+            Poll::Pending => variant_size(1, Some(Size::from_usize(0))),
+        }
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        let start = out.len();
+        match *self {
+            Poll::Ready(ref value) => {
+                write_tag::<Size>(out, 0)?;
+                (&(value,)).encode_to_buf::<Size>(out)?;
+            }
+            Poll::Pending => write_tag::<Size>(out, 1)?,
+        }
+        check_encoded_len::<Size>(out, start)
+    }
+}
+
+impl<T> ByteDecodable for Poll<T>
+    where T: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Poll<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            return decode_exact::<Poll<T>, Size>(bytes);
+        }
+        let _nested = enter_nested()?;
+        let (tag, index) = read_size::<Size>(bytes)?;
+        match tag {
+            0 => decode_field::<T, Size>(bytes, index, "Ready").map(Poll::Ready),
+            1 if bytes.len() == index => Ok(Poll::Pending),
+            1 => Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(index),
+                actual: bytes.len(),
+                offset: 0,
+            }),
+            _ => Err(ByteVecError::UnknownVariantError(tag)),
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Poll<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let _nested = enter_nested()?;
+        let (tag, index) = read_tag::<Size>(bytes)?;
+        match tag {
+            0 if Size::bincode() => decode_field_with_len::<T, Size>(bytes, index, "Ready")
+                .map(|(value, len)| (Poll::Ready(value), len)),
+            0 => {
+                let len = field_end::<Size>(bytes, index)?;
+                decode_field::<T, Size>(&bytes[..len], index, "Ready")
+                    .map(|value| (Poll::Ready(value), len))
+            }
+            1 => Ok((Poll::Pending, index)),
+            _ => Err(ByteVecError::UnknownVariantError(tag)),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use traits::{ByteEncodable, ByteDecodable};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::io::Write;
+
+/// A size indicator that selects the deterministic serialization of unordered collections.
+///
+/// `Deterministic<S>` serializes its values exactly as `S`, but when it's used as the
+/// `Size` type parameter, the entries of the `HashMap`s and the elements of the `HashSet`s
+/// are sorted by the byte representation of their keys before being written, instead of
+/// following the iteration order. The same value then always has the same byte
+/// representation, which can be hashed or compared. The bytes can be deserialized with
+/// either `S` or `Deterministic<S>`, which keeps the other options of `S`, such as the
+/// strict deserialization of `Strict<S>` or the layout of `Bincode`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::collections::HashSet;
+/// # use bytevec::{ByteEncodable, Deterministic};
+/// # fn main() {
+/// let a = (0..100u32).collect::<HashSet<_>>();
+/// let b = (0..100u32).rev().collect::<HashSet<_>>();
+/// assert_eq!(a.encode::<Deterministic<u32>>().unwrap(),
+///            b.encode::<Deterministic<u32>>().unwrap());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Deterministic<S>(pub S);
+
+impl<S> BVSize for Deterministic<S>
+    where S: BVSize
+{
+    fn from_usize(val: usize) -> Deterministic<S> {
+        Deterministic(S::from_usize(val))
+    }
+
+    fn as_usize(&self) -> usize {
+        self.0.as_usize()
+    }
+
+    fn checked_as_usize(&self) -> Option<usize> {
+        self.0.checked_as_usize()
+    }
+
+    fn max_value() -> Deterministic<S> {
+        Deterministic(S::max_value())
+    }
+
+    fn checked_add(self, rhs: Deterministic<S>) -> Option<Deterministic<S>> {
+        self.0.checked_add(rhs.0).map(Deterministic)
+    }
+
+    fn get_size_of() -> Deterministic<S> {
+        Deterministic(S::get_size_of())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len()
+    }
+
+    fn deterministic() -> bool {
+        true
+    }
+
+    fn bincode() -> bool {
+        S::bincode()
+    }
+
+    fn strict() -> bool {
+        S::strict()
+    }
+
+    fn big_endian() -> bool {
+        S::big_endian()
+    }
+}
+
+impl<S> ByteEncodable for Deterministic<S>
+    where S: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        self.0.encode_to::<Size, W>(writer)
+    }
+}
+
+impl<S> ByteDecodable for Deterministic<S>
+    where S: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Deterministic<S>>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode::<Size>(bytes).map(Deterministic)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Deterministic<S>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode_with_len::<Size>(bytes).map(|(value, len)| (Deterministic(value), len))
+    }
+}
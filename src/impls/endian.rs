@@ -0,0 +1,113 @@
+use traits::{ByteEncodable, ByteDecodable};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::io::Write;
+
+/// A size indicator that selects the big endian serialization of the primitive values.
+///
+/// `BigEndian<S>` serializes its values as `S` in big endian, and keeps the other options of
+/// `S`, such as the deterministic order of `Deterministic<S>`. When it's used as the `Size`
+/// type parameter, the integral types, the floating point types and `char` are written in
+/// big endian, and so are the size indicators, for compatibility with the network formats.
+/// The fields annotated with `#[bytevec(big_endian)]` are written in big endian as well,
+/// while the fields with their own `size` type follow the byte order of that type.
+/// `VarSize` has no byte order, so `BigEndian<VarSize>` only changes the primitive values.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, BigEndian};
+/// # fn main() {
+/// let pair = (0x0102u16, vec![0x0a0bu16]);
+/// let bytes = pair.encode::<BigEndian<u16>>().unwrap();
+/// assert_eq!(bytes, vec![0, 2, 0, 6, 1, 2, 0, 1, 0, 2, 0x0a, 0x0b]);
+/// assert_eq!(<(u16, Vec<u16>)>::decode::<BigEndian<u16>>(&bytes).unwrap(), pair);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct BigEndian<S>(pub S);
+
+impl<S> BVSize for BigEndian<S>
+    where S: BVSize
+{
+    fn from_usize(val: usize) -> BigEndian<S> {
+        BigEndian(S::from_usize(val))
+    }
+
+    fn as_usize(&self) -> usize {
+        self.0.as_usize()
+    }
+
+    fn checked_as_usize(&self) -> Option<usize> {
+        self.0.checked_as_usize()
+    }
+
+    fn max_value() -> BigEndian<S> {
+        BigEndian(S::max_value())
+    }
+
+    fn checked_add(self, rhs: BigEndian<S>) -> Option<BigEndian<S>> {
+        self.0.checked_add(rhs.0).map(BigEndian)
+    }
+
+    fn get_size_of() -> BigEndian<S> {
+        BigEndian(S::get_size_of())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len()
+    }
+
+    fn deterministic() -> bool {
+        S::deterministic()
+    }
+
+    fn bincode() -> bool {
+        S::bincode()
+    }
+
+    fn strict() -> bool {
+        S::strict()
+    }
+
+    fn big_endian() -> bool {
+        true
+    }
+}
+
+impl<S> ByteEncodable for BigEndian<S>
+    where S: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        self.0.encode_to::<Size, W>(writer)
+    }
+}
+
+impl<S> ByteDecodable for BigEndian<S>
+    where S: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<BigEndian<S>>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode::<Size>(bytes).map(BigEndian)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(BigEndian<S>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode_with_len::<Size>(bytes).map(|(value, len)| (BigEndian(value), len))
+    }
+}
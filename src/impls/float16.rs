@@ -0,0 +1,59 @@
+use half::f16;
+
+use traits::{ByteEncodable, ByteDecodable, ByteRemote};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+
+/// A proxy that serializes `f32` values as half precision floats.
+///
+/// Used with the `#[bytevec(with = LossyF16)]` field attribute, an `f32` field is
+/// converted to the nearest [`f16`][1] and written with 2 bytes instead of 4, and is read
+/// back as the `f32` value of that `f16`. The conversion keeps about 3 significant decimal
+/// digits and turns the values beyond ±65504 into infinities, which is enough for data
+/// such as the embeddings of machine learning models. This type is only available with the
+/// `half` feature.
+///
+/// ```rust
+/// # #[macro_use] extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, LossyF16};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct Embedding {
+///     weight: f32
+/// }
+///
+/// bytevec_impls! {
+///     impl Embedding {
+///         #[bytevec(with = LossyF16)]
+///         weight: f32
+///     }
+/// }
+///
+/// # fn main() {
+/// let bytes = Embedding {weight: 0.1}.encode::<u8>().unwrap();
+/// assert_eq!(bytes.len(), 1 + 2);
+/// assert_eq!(Embedding::decode::<u8>(&bytes).unwrap().weight, 0.099975586);
+/// # }
+/// ```
+///
+/// [1]: https://docs.rs/half/2/half/struct.f16.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LossyF16;
+
+impl ByteRemote<f32> for LossyF16 {
+    fn get_size<Size>(value: &f32) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        f16::from_f32(*value).get_size::<Size>()
+    }
+
+    fn encode<Size>(value: &f32) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        f16::from_f32(*value).encode::<Size>()
+    }
+
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<f32>
+        where Size: BVSize + ByteDecodable
+    {
+        f16::decode::<Size>(bytes).map(f16::to_f32)
+    }
+}
@@ -0,0 +1,267 @@
+use traits::{ByteEncodable, ByteDecodable, ByteRemote};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size, size_len};
+use limits::check_elements;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+/// A `Vec` serialized with a dictionary of its distinct elements.
+///
+/// Each distinct element of an `InternedVec<T>` is written once, and every element is
+/// written as its index in that dictionary, which keeps collections of repeated values,
+/// such as the keys of log records or enum-like string columns, compact. The byte
+/// representation is made of the distinct elements, serialized as a `Vec<T>` in the order
+/// of their first appearance, followed by the number of elements as a `Size` value and the
+/// index of each of them. The indexes are written in little endian with 1, 2, 4 or 8 bytes,
+/// the fewest that fit every index of the dictionary.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, InternedVec};
+/// # fn main() {
+/// let keys = InternedVec(vec!["level".to_string(); 100]);
+/// let bytes = keys.encode::<u32>().unwrap();
+/// assert_eq!(bytes.len(), 4 + 4 + 5 + 4 + 100);
+/// assert_eq!(InternedVec::<String>::decode::<u32>(&bytes).unwrap(), keys);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct InternedVec<T>(pub Vec<T>);
+
+impl<T> InternedVec<T> {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+/// Returns the distinct elements and the index of each element among them
+fn intern<T: Eq + Hash>(elems: &[T]) -> (Vec<&T>, Vec<usize>) {
+    let mut table = Vec::new();
+    let mut positions = HashMap::new();
+    let indexes = elems.iter()
+        .map(|elem| {
+            *positions.entry(elem).or_insert_with(|| {
+                table.push(elem);
+                table.len() - 1
+            })
+        })
+        .collect();
+    (table, indexes)
+}
+
+/// Returns the number of bytes of the indexes into a dictionary of `len` elements
+pub(crate) fn index_width(len: usize) -> usize {
+    match len as u64 {
+        0..=0x100 => 1,
+        0x101..=0x1_0000 => 2,
+        0x1_0001..=0x1_0000_0000 => 4,
+        _ => 8,
+    }
+}
+
+impl<T> From<Vec<T>> for InternedVec<T> {
+    fn from(vec: Vec<T>) -> InternedVec<T> {
+        InternedVec(vec)
+    }
+}
+
+impl<T> Deref for InternedVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for InternedVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+
+fn interned_size<T, Size>(elems: &[T]) -> Option<Size>
+    where T: ByteEncodable + Eq + Hash,
+          Size: BVSize + ByteEncodable
+{
+    let (table, _) = intern(elems);
+    let max_value = Size::max_value().as_usize();
+    if table.len() > max_value || elems.len() > max_value {
+        return None;
+    }
+    let mut size = Some(size_len::<Size>(table.len()));
+    for elem in &table {
+        size = size.and_then(|size: Size| {
+            elem.get_size::<Size>().and_then(|elem_size| {
+                let header_len = size_len::<Size>(elem_size.as_usize());
+                size.checked_add(elem_size)
+                    .and_then(|acc_size| acc_size.checked_add(header_len))
+            })
+        });
+    }
+    size.and_then(|size| size.checked_add(size_len::<Size>(elems.len())))
+        .and_then(|size| {
+            elems.len()
+                .checked_mul(index_width(table.len()))
+                .filter(|&indexes_len| indexes_len <= max_value)
+                .and_then(|indexes_len| size.checked_add(Size::from_usize(indexes_len)))
+        })
+}
+
+fn interned_encode_to<T, Size, W>(elems: &[T], writer: &mut W) -> BVEncodeResult<()>
+    where T: ByteEncodable + Eq + Hash,
+          Size: BVSize + ByteEncodable,
+          W: Write + ?Sized
+{
+    if interned_size::<T, Size>(elems).is_some() {
+        let (table, indexes) = intern(elems);
+        Size::from_usize(table.len()).encode_to::<Size, W>(writer)?;
+        for elem in &table {
+            elem.get_size::<Size>().unwrap().encode_to::<Size, W>(writer)?;
+        }
+        for elem in &table {
+            elem.encode_to::<Size, W>(writer)?;
+        }
+        Size::from_usize(elems.len()).encode_to::<Size, W>(writer)?;
+        let width = index_width(table.len());
+        for index in indexes {
+            writer.write_all(&(index as u64).to_le_bytes()[..width])?;
+        }
+        Ok(())
+    } else {
+        Err(ByteVecError::OverflowError)
+    }
+}
+
+fn interned_decode_with_len<T, Size>(bytes: &[u8]) -> BVDecodeResult<(Vec<T>, usize)>
+    where T: ByteDecodable + Clone,
+          Size: BVSize + ByteDecodable
+{
+    let (table, mut index) = Vec::<T>::decode_with_len::<Size>(bytes)?;
+    let (len, len_len) = read_size::<Size>(&bytes[index..]).map_err(|error| error.at(index))?;
+    check_elements(len)?;
+    index += len_len;
+    let width = index_width(table.len());
+    let total_len = len.checked_mul(width)
+        .and_then(|indexes_len| indexes_len.checked_add(index))
+        .ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < total_len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(total_len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    let mut vec = Vec::with_capacity(len);
+    for entry in bytes[index..total_len].chunks(width) {
+        let mut le_bytes = [0u8; 8];
+        le_bytes[..width].copy_from_slice(entry);
+        let entry = u64::from_le_bytes(le_bytes);
+        match table.get(entry as usize) {
+            Some(elem) if entry <= usize::MAX as u64 => vec.push(elem.clone()),
+            _ => {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: table.len(),
+                    actual: entry.saturating_add(1) as usize,
+                })
+            }
+        }
+    }
+    Ok((vec, total_len))
+}
+
+fn interned_decode<T, Size>(bytes: &[u8]) -> BVDecodeResult<Vec<T>>
+    where T: ByteDecodable + Clone,
+          Size: BVSize + ByteDecodable
+{
+    let (vec, len) = interned_decode_with_len::<T, Size>(bytes)?;
+    if len == bytes.len() {
+        Ok(vec)
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+impl<T> ByteEncodable for InternedVec<T>
+    where T: ByteEncodable + Eq + Hash
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        interned_size(self)
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::new();
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        interned_encode_to::<T, Size, W>(self, writer)
+    }
+}
+
+impl<T> ByteDecodable for InternedVec<T>
+    where T: ByteDecodable + Clone
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<InternedVec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        interned_decode::<T, Size>(bytes).map(InternedVec)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(InternedVec<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        interned_decode_with_len::<T, Size>(bytes).map(|(vec, len)| (InternedVec(vec), len))
+    }
+}
+
+/// The dictionary mode of a `Vec<T>` field, serialized as an [`InternedVec<T>`].
+///
+/// A `Vec<T>` field is opted into the dictionary encoding by annotating it with
+/// `#[bytevec(dictionary)]` in [`bytevec_impls`](macro.bytevec_impls.html) or
+/// `bytevec_decl`, which is a shorthand for `#[bytevec(with = Dictionary)]`, so the type of
+/// the field doesn't change. The byte representation of the field is the one of an
+/// `InternedVec<T>` with the same elements.
+///
+/// [`InternedVec<T>`]: struct.InternedVec.html
+pub struct Dictionary;
+
+impl<T> ByteRemote<Vec<T>> for Dictionary
+    where T: ByteEncodable + ByteDecodable + Eq + Hash + Clone
+{
+    fn get_size<Size>(value: &Vec<T>) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        interned_size(value)
+    }
+
+    fn encode<Size>(value: &Vec<T>) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::new();
+        interned_encode_to::<T, Size, _>(value, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Vec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        interned_decode::<T, Size>(bytes)
+    }
+}
@@ -1,12 +1,75 @@
+use traits::{ByteEncodable, ByteDecodable};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult};
+use std::convert::TryFrom;
+
+#[cfg(feature = "bumpalo")]
+mod arena;
+mod arrays;
+mod bincode;
+mod bits;
+mod bytes;
 mod collections;
+mod columnar;
+mod control;
+mod deterministic;
+mod endian;
+#[cfg(feature = "half")]
+mod float16;
+mod interned;
+mod packed;
 mod primitives;
+mod runs;
+#[cfg(feature = "zeroize")]
+mod secret;
+mod strict;
+mod text;
+mod unknown;
+mod varint;
+
+pub use self::arrays::PackedArray;
+pub use self::bincode::Bincode;
+pub use self::bits::{BitVec, BitSet};
+pub use self::bytes::{ByteBuf, Bytes};
+pub use self::columnar::ColumnVec;
+pub use self::deterministic::Deterministic;
+pub use self::endian::BigEndian;
+#[cfg(feature = "half")]
+pub use self::float16::LossyF16;
+pub use self::interned::{InternedVec, Dictionary};
+pub(crate) use self::collections::read_bincode_bytes;
+#[cfg(feature = "serde")]
+pub(crate) use self::control::{write_tag, read_tag};
+pub(crate) use self::interned::index_width;
+pub use self::packed::{PackedVec, OptionVec};
+pub use self::runs::{RunVec, RUN_VEC_MAX_LEN};
+#[cfg(feature = "zeroize")]
+pub(crate) use self::secret::read_to_end_zeroizing;
+pub use self::strict::Strict;
+pub use self::text::{LossyUtf8, MaybeUtf8};
+pub(crate) use self::text::string_bytes;
+pub use self::unknown::UnknownFields;
+pub use self::varint::{VarSize, ZigZag};
 
 /// Represents the generic integral type of the structure size indicators
+///
+/// `usize` can be used as `Size` for the byte representations that never leave the process,
+/// as it takes the natural width of the platform. Its width is not portable, so a buffer
+/// written with `usize` size indicators on a 64 bit target can't be read on a 32 bit target,
+/// and neither can a buffer written on a little endian target be read on a big endian
+/// target, as with every other `Size` type. Use a fixed width type such as `u32` or `u64`
+/// for the byte representations that are stored or sent elsewhere. `u128` can also be used,
+/// though every size indicator takes 16 bytes.
 pub trait BVSize: Sized {
     /// Returns a `Self` value casted from an `usize` value
     fn from_usize(val: usize) -> Self;
     /// Returns an `usize` value casted from a `Self` value
     fn as_usize(&self) -> usize;
+    /// Returns `self` as an `usize` value, or `None` if it doesn't fit in an `usize`, as a
+    /// `u64` value on the 32 bit targets
+    fn checked_as_usize(&self) -> Option<usize> {
+        Some(self.as_usize())
+    }
     /// Returns the max value for `Self`
     fn max_value() -> Self;
     /// Calls the `checked_add` method of `self` passing `rhs`
@@ -14,6 +77,31 @@ pub trait BVSize: Sized {
     /// Returns the returned value of [`std::mem::size_of`][1] for `Self`
     /// [1]: http://doc.rust-lang.org/stable/std/mem/fn.size_of.html
     fn get_size_of() -> Self;
+    /// Returns the length of the byte representation of `self` as a size indicator,
+    /// which is the same for every value of the fixed size integral types
+    fn encoded_len(&self) -> usize {
+        Self::get_size_of().as_usize()
+    }
+    /// Returns `true` if the unordered collections are serialized in a deterministic order,
+    /// as with [`Deterministic`](struct.Deterministic.html)
+    fn deterministic() -> bool {
+        false
+    }
+    /// Returns `true` if the values are serialized in the standard layout of bincode, as
+    /// with [`Bincode`](struct.Bincode.html)
+    fn bincode() -> bool {
+        false
+    }
+    /// Returns `true` if the duplicate elements of the unordered collections are rejected
+    /// on deserialization, as with [`Strict`](struct.Strict.html)
+    fn strict() -> bool {
+        false
+    }
+    /// Returns `true` if the primitive values and the size indicators are serialized in big
+    /// endian, as with [`BigEndian`](struct.BigEndian.html)
+    fn big_endian() -> bool {
+        false
+    }
 }
 
 macro_rules! def_BVSize {
@@ -28,6 +116,10 @@ macro_rules! def_BVSize {
                     *self as usize
                 }
                 
+                fn checked_as_usize(&self) -> Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+                
                 fn max_value() -> Self {
                     <$t>::max_value()
                 }
@@ -44,4 +136,274 @@ macro_rules! def_BVSize {
     }
 }
 
-def_BVSize!(u8, u16, u32, u64);
\ No newline at end of file
+def_BVSize!(u8, u16, u32, u64, u128, usize);
+
+/// Returns the value of the size indicator at the start of `bytes`, along with
+/// the length of its byte representation.
+#[doc(hidden)]
+pub fn read_size<Size>(bytes: &[u8]) -> BVDecodeResult<(usize, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let (size, len) = Size::decode_with_len::<Size>(bytes)?;
+    Ok((size.checked_as_usize().ok_or(ByteVecError::SizeOverflowError)?, len))
+}
+
+/// Returns the length of the byte representation of `value` as a size indicator.
+#[doc(hidden)]
+pub fn size_len<Size>(value: usize) -> Size
+    where Size: BVSize
+{
+    Size::from_usize(Size::from_usize(value).encoded_len())
+}
+
+/// Deserializes a value that takes the whole buffer through its `decode_with_len`
+/// implementation, as the values serialized in the layout of bincode.
+#[doc(hidden)]
+pub fn decode_exact<T, Size>(bytes: &[u8]) -> BVDecodeResult<T>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    let (value, len) = T::decode_with_len::<Size>(bytes)?;
+    if len == bytes.len() {
+        Ok(value)
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+/// Reads a table of `entries` size indicators from the start of `bytes`, and
+/// returns the sizes along with the length of the table.
+#[doc(hidden)]
+pub fn read_size_table<Size>(bytes: &[u8], entries: usize) -> BVDecodeResult<(Vec<usize>, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let mut sizes = Vec::new();
+    let mut index = 0;
+    for _ in 0..entries {
+        let (size, len) = read_size::<Size>(&bytes[index..]).map_err(|error| error.at(index))?;
+        sizes.push(size);
+        index += len;
+    }
+    Ok((sizes, index))
+}
+
+/// Reads a table of `N` size indicators from the start of `bytes` into an array, for the
+/// tuples, whose number of entries is known beforehand, and returns the sizes along with
+/// the length of the table.
+pub(crate) fn read_size_array<Size, const N: usize>(bytes: &[u8])
+    -> BVDecodeResult<([usize; N], usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let mut sizes = [0; N];
+    let mut index = 0;
+    for size in sizes.iter_mut() {
+        let (entry_size, len) = read_size::<Size>(&bytes[index..])
+            .map_err(|error| error.at(index))?;
+        *size = entry_size;
+        index += len;
+    }
+    Ok((sizes, index))
+}
+
+/// Reads the size table of a compact `struct` with `entries` entries, which leaves out
+/// the size of the last entry, from the start of `bytes`. The last size is the length of
+/// the rest of `bytes`, so the whole buffer must be given. Returns the sizes of every
+/// entry along with the length of the table.
+#[doc(hidden)]
+pub fn compact_table<Size>(bytes: &[u8], entries: usize) -> BVDecodeResult<(Vec<usize>, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    if entries == 0 {
+        return Ok((Vec::new(), 0));
+    }
+    let (mut sizes, table_len) = read_size_table::<Size>(bytes, entries - 1)?;
+    let len = sizes.iter()
+        .try_fold(table_len, |len, size| len.checked_add(*size))
+        .ok_or(ByteVecError::SizeOverflowError)?;
+    match bytes.len().checked_sub(len) {
+        Some(last_size) => sizes.push(last_size),
+        None => {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+    Ok((sizes, table_len))
+}
+
+/// Returns the length of the start of `bytes` made of a table of `entries` sizes
+/// followed by the bodies they measure, as in the byte representation of tuples.
+#[doc(hidden)]
+pub fn table_len<Size>(bytes: &[u8], entries: usize) -> BVDecodeResult<usize>
+    where Size: BVSize + ByteDecodable
+{
+    let (sizes, table_len) = read_size_table::<Size>(bytes, entries)?;
+    let len = sizes.iter()
+        .try_fold(table_len, |len, size| len.checked_add(*size))
+        .ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < len {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(len),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok(len)
+}
+
+/// Appends `size` to `out` as a `Size` value, and fails if it overflows the `Size` type.
+#[doc(hidden)]
+pub fn write_size<Size>(out: &mut Vec<u8>, size: usize) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    if size > Size::max_value().as_usize() {
+        return Err(ByteVecError::OverflowError);
+    }
+    Size::from_usize(size).encode_to::<Size, _>(out)
+}
+
+/// Fails if a byte representation that starts at `start` in `out` overflows the `Size`
+/// type.
+#[doc(hidden)]
+pub fn check_encoded_len<Size>(out: &[u8], start: usize) -> BVEncodeResult<()>
+    where Size: BVSize
+{
+    if out.len() - start > Size::max_value().as_usize() {
+        Err(ByteVecError::OverflowError)
+    } else {
+        Ok(())
+    }
+}
+
+/// The size table of a value written by `ByteEncodable::encode_to_buf`, whose entries are
+/// written after it into the same buffer.
+///
+/// If every `Size` value takes the same number of bytes, the room of the table is reserved
+/// in the buffer before the entries are written, and each size is filled in once its entry
+/// is written. Otherwise, as with `VarSize`, the sizes are collected and inserted in front
+/// of the entries at the end. In the layout of bincode, there's no size table.
+#[doc(hidden)]
+pub struct SizeTable {
+    start: usize,
+    width: Option<usize>,
+    slot: usize,
+    sizes: Vec<u8>,
+}
+
+impl SizeTable {
+    /// Starts a table of `entries` sizes at the end of `out`
+    pub fn new<Size>(out: &mut Vec<u8>, entries: usize) -> SizeTable
+        where Size: BVSize
+    {
+        let width = Size::from_usize(0).encoded_len();
+        let width = if width == Size::max_value().encoded_len() { Some(width) } else { None };
+        let start = out.len();
+        if let Some(width) = width {
+            if !Size::bincode() {
+                out.resize(start + entries * width, 0);
+            }
+        }
+        SizeTable {
+            start,
+            width,
+            slot: 0,
+            sizes: Vec::new(),
+        }
+    }
+
+    /// Records the size of the next entry, which was written at the end of `out` from
+    /// `entry_start`
+    pub fn entry<Size>(&mut self, out: &mut [u8], entry_start: usize) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = out.len() - entry_start;
+        if size > Size::max_value().as_usize() {
+            return Err(ByteVecError::OverflowError);
+        }
+        if Size::bincode() {
+            return Ok(());
+        }
+        match self.width {
+            Some(width) => {
+                let at = self.start + self.slot * width;
+                Size::from_usize(size).encode_to::<Size, _>(&mut &mut out[at..at + width])?;
+            }
+            None => Size::from_usize(size).encode_to::<Size, _>(&mut self.sizes)?,
+        }
+        self.slot += 1;
+        Ok(())
+    }
+
+    /// Inserts the collected sizes in front of the entries, if they weren't filled in
+    pub fn finish(self, out: &mut Vec<u8>) {
+        if !self.sizes.is_empty() {
+            out.splice(self.start..self.start, self.sizes);
+        }
+    }
+}
+
+/// Returns the byte representation of `value` written by its `encode_to_buf`
+/// implementation into a buffer allocated with the length given by `get_size`.
+#[doc(hidden)]
+pub fn encode_buffered<T, Size>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: ByteEncodable + ?Sized,
+          Size: BVSize + ByteEncodable
+{
+    let size = value.get_size::<Size>().ok_or(ByteVecError::OverflowError)?;
+    let mut bytes = Vec::with_capacity(size.as_usize());
+    value.encode_to_buf::<Size>(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Returns `offset` rounded up to a multiple of `align`.
+#[doc(hidden)]
+pub const fn align_up(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// Returns the size and the alignment of a `#[repr(C)]` struct made of fields with
+/// the given sizes and alignments.
+#[doc(hidden)]
+pub const fn aligned_layout(fields: &[(usize, usize)]) -> (usize, usize) {
+    let mut offset = 0;
+    let mut align = 1;
+    let mut index = 0;
+    while index < fields.len() {
+        let (field_size, field_align) = fields[index];
+        offset = align_up(offset, field_align) + field_size;
+        if field_align > align {
+            align = field_align;
+        }
+        index += 1;
+    }
+    (align_up(offset, align), align)
+}
+
+/// Returns `true` if the fields at `offsets`, with the given sizes and alignments, make
+/// up the `#[repr(C)]` layout of a struct of length `size` and alignment `align`.
+#[doc(hidden)]
+pub const fn is_aligned_layout(offsets: &[usize],
+                               fields: &[(usize, usize)],
+                               size: usize,
+                               align: usize)
+                               -> bool {
+    let mut offset = 0;
+    let mut index = 0;
+    while index < fields.len() {
+        let (field_size, field_align) = fields[index];
+        offset = align_up(offset, field_align);
+        if offsets[index] != offset {
+            return false;
+        }
+        offset += field_size;
+        index += 1;
+    }
+    let layout = aligned_layout(fields);
+    layout.0 == size && layout.1 == align
+}
@@ -0,0 +1,302 @@
+use traits::{ByteEncodable, ByteDecodable, FixedByteSize};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size, size_len, encode_buffered};
+use limits::check_elements;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+/// A `Vec` of elements with a fixed size, serialized without a size table.
+///
+/// A `Vec<T>` stores the size of each of its elements, which is redundant when every
+/// element has the same size. A `PackedVec<T>` only stores the number of elements as a
+/// `Size` value, followed by the byte representations of the elements back to back.
+/// The element type must implement `FixedByteSize`, as the primitive types and the
+/// `#[bytevec(packed)]` structures do.
+///
+/// The bytes of a `PackedVec<u8>` are copied at once and take a byte each, as the ones of a
+/// [`ByteBuf`](struct.ByteBuf.html), which is the type to use for byte blobs.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, PackedVec};
+/// # fn main() {
+/// let samples = PackedVec(vec![0.5f64; 1000]);
+/// let bytes = samples.encode::<u32>().unwrap();
+/// assert_eq!(bytes.len(), 4 + 8 * 1000);
+/// assert_eq!(PackedVec::<f64>::decode::<u32>(&bytes).unwrap(), samples);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PackedVec<T>(pub Vec<T>);
+
+impl<T> PackedVec<T> {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for PackedVec<T> {
+    fn from(vec: Vec<T>) -> PackedVec<T> {
+        PackedVec(vec)
+    }
+}
+
+impl<T> Deref for PackedVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for PackedVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> ByteEncodable for PackedVec<T>
+    where T: FixedByteSize
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.len()
+            .checked_mul(T::SIZE)
+            .and_then(|body_size| body_size.checked_add(size_len::<Size>(self.len()).as_usize()))
+            .and_then(|size| {
+                if size <= Size::max_value().as_usize() && self.len() <= Size::max_value().as_usize() {
+                    Some(Size::from_usize(size))
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            Size::from_usize(self.len()).encode_to::<Size, W>(writer)?;
+            T::encode_slice_to::<Size, W>(self, writer)
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl<T> ByteDecodable for PackedVec<T>
+    where T: FixedByteSize
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<PackedVec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = PackedVec::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(PackedVec<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (len, header_len) = read_size::<Size>(bytes)?;
+        check_elements(len)?;
+        let total_len = len.checked_mul(T::SIZE)
+            .and_then(|body_size| body_size.checked_add(header_len))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if bytes.len() < total_len {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(total_len),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let vec = T::decode_slice::<Size>(&bytes[header_len..total_len], len)
+            .map_err(|error| error.at(header_len))?;
+        Ok((PackedVec(vec), total_len))
+    }
+}
+
+/// A `Vec` of optional elements, serialized with a presence bitmap.
+///
+/// The byte representation of an `OptionVec<T>` is the number of elements as a `Size` value,
+/// followed by a bitmap with one bit per element, set for the elements that are present,
+/// and by the present elements serialized as a `Vec<T>`. The missing elements take a single
+/// bit, which keeps sparse columns of data compact.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, OptionVec};
+/// # fn main() {
+/// let mut column = vec![None; 64];
+/// column[3] = Some(7u32);
+/// let column = OptionVec(column);
+/// let bytes = column.encode::<u32>().unwrap();
+/// assert_eq!(bytes.len(), 4 + 8 + 4 + 4 + 4);
+/// assert_eq!(OptionVec::<u32>::decode::<u32>(&bytes).unwrap(), column);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct OptionVec<T>(pub Vec<Option<T>>);
+
+impl<T> OptionVec<T> {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<Option<T>> {
+        self.0
+    }
+
+    fn bitmap(&self) -> Vec<u8> {
+        let mut bitmap = vec![0u8; self.len().div_ceil(8)];
+        for (index, elem) in self.iter().enumerate() {
+            if elem.is_some() {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+        }
+        bitmap
+    }
+}
+
+impl<T> From<Vec<Option<T>>> for OptionVec<T> {
+    fn from(vec: Vec<Option<T>>) -> OptionVec<T> {
+        OptionVec(vec)
+    }
+}
+
+impl<T> Deref for OptionVec<T> {
+    type Target = Vec<Option<T>>;
+
+    fn deref(&self) -> &Vec<Option<T>> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for OptionVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<Option<T>> {
+        &mut self.0
+    }
+}
+
+impl<T> ByteEncodable for OptionVec<T>
+    where T: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        let present = self.iter().flatten().count();
+        let header_len = size_len::<Size>(self.len())
+            .checked_add(Size::from_usize(self.len().div_ceil(8)))
+            .and_then(|size| size.checked_add(size_len::<Size>(present)));
+        if self.len() > Size::max_value().as_usize() {
+            return None;
+        }
+        self.iter()
+            .flatten()
+            .fold(header_len, |acc, elem| {
+                acc.and_then(|acc: Size| {
+                    elem.get_size::<Size>().and_then(|size| {
+                        let elem_header_len = size_len::<Size>(size.as_usize());
+                        acc.checked_add(size).and_then(|acc_size|
+                            acc_size.checked_add(elem_header_len)
+                        )
+                    })
+                })
+            })
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            Size::from_usize(self.len()).encode_to::<Size, W>(writer)?;
+            writer.write_all(&self.bitmap())?;
+            Size::from_usize(self.iter().flatten().count()).encode_to::<Size, W>(writer)?;
+            for elem in self.iter().flatten() {
+                elem.get_size::<Size>().unwrap().encode_to::<Size, W>(writer)?;
+            }
+            for elem in self.iter().flatten() {
+                elem.encode_to::<Size, W>(writer)?;
+            }
+            Ok(())
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl<T> ByteDecodable for OptionVec<T>
+    where T: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<OptionVec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = OptionVec::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(OptionVec<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (len, header_len) = read_size::<Size>(bytes)?;
+        check_elements(len)?;
+        let values_index = header_len + len.div_ceil(8);
+        if bytes.len() < values_index {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(values_index),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let bitmap = &bytes[header_len..values_index];
+        let (values, values_len) = Vec::<T>::decode_with_len::<Size>(&bytes[values_index..])
+            .map_err(|error| error.at(values_index))?;
+        let present = (0..len).filter(|index| bitmap[index / 8] & (1 << (index % 8)) != 0).count();
+        if present != values.len() {
+            return Err(ByteVecError::LengthMismatchError {
+                expected: present,
+                actual: values.len(),
+            });
+        }
+        let mut values = values.into_iter();
+        let vec = (0..len)
+            .map(|index| {
+                if bitmap[index / 8] & (1 << (index % 8)) != 0 {
+                    values.next()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Ok((OptionVec(vec), values_index + values_len))
+    }
+}
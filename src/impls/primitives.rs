@@ -1,11 +1,69 @@
-use traits::{ByteEncodable, ByteDecodable};
+use traits::{ByteEncodable, ByteDecodable, FixedByteSize};
 use errors::{ByteVecError, BVExpectedSize};
-use std::mem::transmute;
 use {BVEncodeResult, BVDecodeResult, BVSize};
-use std::mem::size_of;
+use std::cmp;
+use std::io::Write;
+use std::mem::{align_of, size_of};
+
+/// The length of the stack buffer through which the slices of primitive values are converted
+/// to and from their byte order
+const SWAP_BUFFER_LEN: usize = 4096;
+
+// Converts the slices of values through a stack buffer, so the byte swaps of the big endian
+// targets, or of the `BigEndian` size types, are done in bulk, and the bytes are written a
+// buffer at a time rather than with a write for each value.
+macro_rules! bulk_slice_impl {
+    ($t:ty, $unsizd:ty, $to_unsizd:expr, $from_unsizd:expr) => {
+        fn encode_slice_to<Size, W>(values: &[$t], writer: &mut W) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable,
+                  W: Write + ?Sized
+        {
+            const SIZE: usize = size_of::<$unsizd>();
+            let mut buffer = [0u8; SWAP_BUFFER_LEN];
+            for chunk in values.chunks(SWAP_BUFFER_LEN / SIZE) {
+                let bytes = &mut buffer[..chunk.len() * SIZE];
+                for (value, bytes) in chunk.iter().zip(bytes.chunks_exact_mut(SIZE)) {
+                    let unsigned: $unsizd = $to_unsizd(*value);
+                    if Size::big_endian() {
+                        bytes.copy_from_slice(&unsigned.to_be_bytes());
+                    } else {
+                        bytes.copy_from_slice(&unsigned.to_le_bytes());
+                    }
+                }
+                writer.write_all(bytes)?;
+            }
+            Ok(())
+        }
+
+        fn decode_slice<Size>(bytes: &[u8], len: usize) -> BVDecodeResult<Vec<$t>>
+            where Size: BVSize + ByteDecodable
+        {
+            const SIZE: usize = size_of::<$unsizd>();
+            let mut values = Vec::with_capacity(len);
+            for (index, bytes) in bytes.chunks_exact(SIZE).enumerate() {
+                let mut t_bytes = [0u8; SIZE];
+                t_bytes.copy_from_slice(bytes);
+                let unsigned = if Size::big_endian() {
+                    <$unsizd>::from_be_bytes(t_bytes)
+                } else {
+                    <$unsizd>::from_le_bytes(t_bytes)
+                };
+                let value: BVDecodeResult<$t> = $from_unsizd(unsigned);
+                values.push(value.map_err(|error| error.at(index * SIZE).in_element(index))?);
+            }
+            Ok(values)
+        }
+    }
+}
 
 macro_rules! impl_integrals {
-    {$($t:ty : $size:expr),*} => {
+    (@slices $t:ty, {}) => {
+        bulk_slice_impl!($t, $t, |u| u, |u| Ok(u));
+    };
+    (@slices $t:ty, {$($fixed:tt)+}) => {
+        $($fixed)+
+    };
+    {$($t:ty : $size:tt $({$($fixed:tt)*})?),*} => {
         $(
             impl ByteEncodable for $t {
                 fn get_size<Size>(&self) -> Option<Size> where Size: BVSize + ByteEncodable {
@@ -15,11 +73,24 @@ macro_rules! impl_integrals {
                 fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
                     where Size: BVSize + ByteEncodable
                 {
-                    unsafe {
-                        let bytes: [u8; $size] = transmute(self.to_le());
-                        Ok(bytes.to_vec())
+                    if Size::big_endian() {
+                        Ok(self.to_be_bytes().to_vec())
+                    } else {
+                        Ok(self.to_le_bytes().to_vec())
                     }
                 }
+
+                fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+                    where Size: BVSize + ByteEncodable,
+                          W: Write + ?Sized
+                {
+                    if Size::big_endian() {
+                        writer.write_all(&self.to_be_bytes())?;
+                    } else {
+                        writer.write_all(&self.to_le_bytes())?;
+                    }
+                    Ok(())
+                }
             }
 
             impl ByteDecodable for $t {
@@ -28,35 +99,69 @@ macro_rules! impl_integrals {
                 {
                     if bytes.len() == $size {
                         let mut t_bytes = [0u8; $size];
-                        for (b, s) in (&mut t_bytes).into_iter().zip(bytes) {
-                            *b = *s;
+                        t_bytes.copy_from_slice(bytes);
+                        if Size::big_endian() {
+                            Ok(<$t>::from_be_bytes(t_bytes))
+                        } else {
+                            Ok(<$t>::from_le_bytes(t_bytes))
                         }
-                        unsafe { Ok(<$t>::from_le(transmute(t_bytes))) }
                     } else {
                         Err(ByteVecError::BadSizeDecodeError {
                             expected: BVExpectedSize::EqualTo($size as usize),
-                            actual: bytes.len()
+                            actual: bytes.len(),
+                            offset: 0
                         })
                     }
                 }
+
+                fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<($t, usize)>
+                    where Size: BVSize + ByteDecodable
+                {
+                    let len = cmp::min(bytes.len(), $size);
+                    Ok((<$t>::decode::<Size>(&bytes[..len])?, len))
+                }
+            }
+
+            impl FixedByteSize for $t {
+                const SIZE: usize = $size;
+                const ALIGN: usize = align_of::<$t>();
+                impl_integrals!(@slices $t, {$($($fixed)*)?});
             }
         )*
     }
 }
 
+// The byte representation of a `u8` slice is the slice itself, so the byte blobs held in a
+// `PackedVec<u8>` are copied at once.
 impl_integrals! {
-    u8: 1,
+    u8: 1 {
+        fn encode_slice_to<Size, W>(values: &[u8], writer: &mut W) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable,
+                  W: Write + ?Sized
+        {
+            writer.write_all(values)?;
+            Ok(())
+        }
+
+        fn decode_slice<Size>(bytes: &[u8], _len: usize) -> BVDecodeResult<Vec<u8>>
+            where Size: BVSize + ByteDecodable
+        {
+            Ok(bytes.to_vec())
+        }
+    },
     u16: 2,
     u32: 4,
     u64: 8,
+    u128: 16,
     i8: 1,
     i16: 2,
     i32: 4,
-    i64: 8
+    i64: 8,
+    i128: 16
 }
 
 macro_rules! as_unsized_impl {
-    {$($t:ty : $unsizd:ty),*} => {
+    {$($t:ty : $unsizd:ty => $to_unsizd:expr, $from_unsizd:expr),*} => {
         $(
             impl ByteEncodable for $t {
                 fn get_size<Size>(&self) -> Option<Size>
@@ -68,10 +173,16 @@ macro_rules! as_unsized_impl {
                 fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
                     where Size: BVSize + ByteEncodable
                 {
-                    unsafe {
-                        let unsigned: $unsizd = transmute(*self);
-                        unsigned.encode::<Size>()
-                    }
+                    let unsigned: $unsizd = $to_unsizd(*self);
+                    unsigned.encode::<Size>()
+                }
+
+                fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+                    where Size: BVSize + ByteEncodable,
+                          W: Write + ?Sized
+                {
+                    let unsigned: $unsizd = $to_unsizd(*self);
+                    unsigned.encode_to::<Size, W>(writer)
                 }
             }
 
@@ -79,18 +190,41 @@ macro_rules! as_unsized_impl {
                 fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<$t>
                     where Size: BVSize + ByteDecodable
                 {
-                    let unsigned = try!(<$unsizd>::decode::<Size>(bytes));
-                    unsafe { Ok(transmute(unsigned)) }
+                    let unsigned = <$unsizd>::decode::<Size>(bytes)?;
+                    $from_unsizd(unsigned)
                 }
+
+                fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<($t, usize)>
+                    where Size: BVSize + ByteDecodable
+                {
+                    let (unsigned, len) = <$unsizd>::decode_with_len::<Size>(bytes)?;
+                    let value: BVDecodeResult<$t> = $from_unsizd(unsigned);
+                    Ok((value?, len))
+                }
+            }
+
+            impl FixedByteSize for $t {
+                const SIZE: usize = <$unsizd as FixedByteSize>::SIZE;
+                const ALIGN: usize = align_of::<$t>();
+                bulk_slice_impl!($t, $unsizd, $to_unsizd, $from_unsizd);
             }
         )*
     }
 }
 
+// The conversions from the unsigned type return a `BVDecodeResult`, as not every
+// `u32` value is a `char`.
+as_unsized_impl! {
+    f32: u32 => f32::to_bits, |u| Ok(f32::from_bits(u)),
+    f64: u64 => f64::to_bits, |u| Ok(f64::from_bits(u)),
+    char: u32 => |c| c as u32, |u| {
+        ::std::char::from_u32(u).ok_or(ByteVecError::InvalidValueError("a Unicode scalar value"))
+    }
+}
+
+#[cfg(feature = "half")]
 as_unsized_impl! {
-    f32: u32,
-    f64: u64,
-    char: u32
+    ::half::f16: u16 => ::half::f16::to_bits, |u| Ok(::half::f16::from_bits(u))
 }
 
 impl ByteEncodable for usize {
@@ -110,6 +244,18 @@ impl ByteEncodable for usize {
             _ => panic!("unknown size for usize"),
         }
     }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        match size_of::<usize>() {
+            2 => (*self as u16).encode_to::<Size, W>(writer),
+            4 => (*self as u32).encode_to::<Size, W>(writer),
+            8 => (*self as u64).encode_to::<Size, W>(writer),
+            _ => panic!("unknown size for usize"),
+        }
+    }
 }
 
 impl ByteDecodable for usize {
@@ -117,10 +263,22 @@ impl ByteDecodable for usize {
         where Size: BVSize + ByteDecodable
     {
         Ok(match size_of::<usize>() {
-            2 => try!(u16::decode::<Size>(bytes)).as_usize(),
-            4 => try!(u32::decode::<Size>(bytes)).as_usize(),
-            8 => try!(u64::decode::<Size>(bytes)).as_usize(),
+            2 => u16::decode::<Size>(bytes)?.as_usize(),
+            4 => u32::decode::<Size>(bytes)?.as_usize(),
+            8 => u64::decode::<Size>(bytes)?.as_usize(),
             _ => panic!("unknown size for usize"),
         })
     }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(usize, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let len = cmp::min(bytes.len(), size_of::<usize>());
+        Ok((usize::decode::<Size>(&bytes[..len])?, len))
+    }
+}
+
+impl FixedByteSize for usize {
+    const SIZE: usize = size_of::<usize>();
+    const ALIGN: usize = align_of::<usize>();
 }
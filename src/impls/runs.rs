@@ -0,0 +1,177 @@
+use traits::{ByteEncodable, ByteDecodable};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_size_table, size_len};
+use limits::{check_elements, check_expansion};
+use std::io::Write;
+use std::iter;
+use std::ops::{Deref, DerefMut};
+
+/// A `Vec` serialized as runs of equal elements.
+///
+/// Each run of consecutive equal elements of a `RunVec<T>` is written once, along with the
+/// number of elements in it, which keeps sequences with long constant stretches, such as
+/// sampled sensor readings or status flags, compact. The byte representation is made of
+/// the first element of each run, serialized as a `Vec<T>`, followed by the length of each
+/// run as a `Size` value. A buffer with a run of no elements is rejected.
+///
+/// A run of a few bytes can declare billions of elements, so without limits a `RunVec` of
+/// more than [`RUN_VEC_MAX_LEN`](constant.RUN_VEC_MAX_LEN.html) elements yields a
+/// `LimitExceededError`. The buffers from untrusted sources should be read with
+/// `decode_limited`, whose `max_elements` limit bounds the expanded runs instead.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, RunVec};
+/// # fn main() {
+/// let mut readings = vec![20u16; 1000];
+/// readings.extend(vec![21u16; 500]);
+/// let readings = RunVec(readings);
+/// let bytes = readings.encode::<u16>().unwrap();
+/// // 2 runs, the sizes and values of the runs, then the length of each run
+/// assert_eq!(bytes, vec![2, 0, 2, 0, 2, 0, 20, 0, 21, 0, 0xe8, 0x03, 0xf4, 0x01]);
+/// assert_eq!(RunVec::<u16>::decode::<u16>(&bytes).unwrap(), readings);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RunVec<T>(pub Vec<T>);
+
+/// The maximum number of elements of a `RunVec` decoded without limits
+pub const RUN_VEC_MAX_LEN: usize = 1 << 24;
+
+impl<T> RunVec<T> {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T: PartialEq> RunVec<T> {
+    /// Returns the first element of each run and the number of elements in it
+    fn runs(&self) -> Vec<(&T, usize)> {
+        let mut runs: Vec<(&T, usize)> = Vec::new();
+        for elem in self.iter() {
+            match runs.last_mut() {
+                Some(&mut (value, ref mut len)) if value == elem => *len += 1,
+                _ => runs.push((elem, 1)),
+            }
+        }
+        runs
+    }
+}
+
+impl<T> From<Vec<T>> for RunVec<T> {
+    fn from(vec: Vec<T>) -> RunVec<T> {
+        RunVec(vec)
+    }
+}
+
+impl<T> Deref for RunVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for RunVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> ByteEncodable for RunVec<T>
+    where T: ByteEncodable + PartialEq
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        let runs = self.runs();
+        let max_value = Size::max_value().as_usize();
+        if runs.len() > max_value || runs.iter().any(|&(_, len)| len > max_value) {
+            return None;
+        }
+        let mut size = Some(size_len::<Size>(runs.len()));
+        for &(elem, len) in &runs {
+            size = size.and_then(|size: Size| {
+                elem.get_size::<Size>().and_then(|elem_size| {
+                    let header_len = size_len::<Size>(elem_size.as_usize());
+                    size.checked_add(elem_size)
+                        .and_then(|acc_size| acc_size.checked_add(header_len))
+                        .and_then(|acc_size| acc_size.checked_add(size_len::<Size>(len)))
+                })
+            });
+        }
+        size
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::new();
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            let runs = self.runs();
+            Size::from_usize(runs.len()).encode_to::<Size, W>(writer)?;
+            for &(elem, _) in &runs {
+                elem.get_size::<Size>().unwrap().encode_to::<Size, W>(writer)?;
+            }
+            for &(elem, _) in &runs {
+                elem.encode_to::<Size, W>(writer)?;
+            }
+            for &(_, len) in &runs {
+                Size::from_usize(len).encode_to::<Size, W>(writer)?;
+            }
+            Ok(())
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl<T> ByteDecodable for RunVec<T>
+    where T: ByteDecodable + Clone
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<RunVec<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = RunVec::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(RunVec<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let (values, index) = Vec::<T>::decode_with_len::<Size>(bytes)?;
+        let (lens, lens_len) = read_size_table::<Size>(&bytes[index..], values.len())
+            .map_err(|error| error.at(index))?;
+        if lens.contains(&0) {
+            return Err(ByteVecError::InvalidValueError("a run of one or more elements"));
+        }
+        let len = lens.iter()
+            .try_fold(0usize, |acc, len| acc.checked_add(*len))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        check_elements(len)?;
+        check_expansion(len, RUN_VEC_MAX_LEN)?;
+        let mut vec = Vec::new();
+        for (value, run_len) in values.into_iter().zip(lens) {
+            vec.extend(iter::repeat_n(value, run_len));
+        }
+        Ok((RunVec(vec), index + lens_len))
+    }
+}
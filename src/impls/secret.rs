@@ -0,0 +1,78 @@
+use std::io::{self, Read, Write};
+
+use zeroize::{Zeroize, Zeroizing};
+
+use traits::{ByteEncodable, ByteDecodable};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+
+// A `Zeroizing<T>` is serialized exactly as `T`, and its value is wiped from the memory
+// when it's dropped, so the key material can be held in the structures serialized with
+// this crate.
+impl<T> ByteEncodable for Zeroizing<T>
+    where T: ByteEncodable + Zeroize
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        (**self).encode_to::<Size, W>(writer)
+    }
+
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        (**self).encode_to_buf::<Size>(out)
+    }
+}
+
+impl<T> ByteDecodable for Zeroizing<T>
+    where T: ByteDecodable + Zeroize
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Zeroizing<T>>
+        where Size: BVSize + ByteDecodable
+    {
+        T::decode::<Size>(bytes).map(Zeroizing::new)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Zeroizing<T>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        T::decode_with_len::<Size>(bytes).map(|(value, len)| (Zeroizing::new(value), len))
+    }
+}
+
+/// Reads `reader` until the end of the stream into a buffer that is wiped when it's
+/// dropped. The buffer is grown by hand, so that the memory left by each reallocation is
+/// wiped as well.
+pub(crate) fn read_to_end_zeroizing<R>(reader: &mut R) -> io::Result<Zeroizing<Vec<u8>>>
+    where R: Read + ?Sized
+{
+    let mut buf = Zeroizing::new(Vec::with_capacity(1024));
+    let mut chunk = Zeroizing::new([0u8; 1024]);
+    loop {
+        let len = match reader.read(&mut chunk[..]) {
+            Ok(0) => return Ok(buf),
+            Ok(len) => len,
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        if buf.capacity() - buf.len() < len {
+            let mut grown = Zeroizing::new(Vec::with_capacity(buf.capacity() * 2 + len));
+            grown.extend_from_slice(&buf);
+            buf = grown;
+        }
+        buf.extend_from_slice(&chunk[..len]);
+    }
+}
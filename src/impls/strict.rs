@@ -0,0 +1,114 @@
+use traits::{ByteEncodable, ByteDecodable};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::io::Write;
+
+/// A size indicator that selects the strict deserialization of unordered collections.
+///
+/// `Strict<S>` serializes its values exactly as `S`, and keeps the other options of `S`,
+/// such as the deterministic order of `Deterministic<S>`, the byte order of `BigEndian<S>`
+/// or the layout of `Bincode`. When
+/// it's used as the `Size` type parameter, a `HashSet` or a `HashMap` whose byte
+/// representation holds the same element or key more than once yields a
+/// `LengthMismatchError` with the declared number of elements and the number of distinct
+/// ones, instead of silently keeping only one of them. Such buffers are never written by
+/// this crate, so they're a sign of corrupted or forged data.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::collections::HashSet;
+/// # use bytevec::{ByteEncodable, ByteDecodable, Strict};
+/// # fn main() {
+/// let bytes = vec![1u8, 2, 1].encode::<u8>().unwrap();
+/// assert_eq!(HashSet::<u8>::decode::<u8>(&bytes).unwrap().len(), 2);
+/// assert!(HashSet::<u8>::decode::<Strict<u8>>(&bytes).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Strict<S>(pub S);
+
+impl<S> BVSize for Strict<S>
+    where S: BVSize
+{
+    fn from_usize(val: usize) -> Strict<S> {
+        Strict(S::from_usize(val))
+    }
+
+    fn as_usize(&self) -> usize {
+        self.0.as_usize()
+    }
+
+    fn checked_as_usize(&self) -> Option<usize> {
+        self.0.checked_as_usize()
+    }
+
+    fn max_value() -> Strict<S> {
+        Strict(S::max_value())
+    }
+
+    fn checked_add(self, rhs: Strict<S>) -> Option<Strict<S>> {
+        self.0.checked_add(rhs.0).map(Strict)
+    }
+
+    fn get_size_of() -> Strict<S> {
+        Strict(S::get_size_of())
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len()
+    }
+
+    fn deterministic() -> bool {
+        S::deterministic()
+    }
+
+    fn bincode() -> bool {
+        S::bincode()
+    }
+
+    fn strict() -> bool {
+        true
+    }
+
+    fn big_endian() -> bool {
+        S::big_endian()
+    }
+}
+
+impl<S> ByteEncodable for Strict<S>
+    where S: ByteEncodable
+{
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        self.0.encode::<Size>()
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        self.0.encode_to::<Size, W>(writer)
+    }
+}
+
+impl<S> ByteDecodable for Strict<S>
+    where S: ByteDecodable
+{
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Strict<S>>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode::<Size>(bytes).map(Strict)
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Strict<S>, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        S::decode_with_len::<Size>(bytes).map(|(value, len)| (Strict(value), len))
+    }
+}
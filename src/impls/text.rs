@@ -0,0 +1,203 @@
+use traits::{ByteEncodable, ByteDecodable, ByteRemote};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use impls::{read_bincode_bytes, size_len, encode_buffered};
+use std::io::Write;
+
+/// Returns the bytes of a string that takes the whole byte buffer
+pub(crate) fn string_bytes<Size>(bytes: &[u8]) -> BVDecodeResult<&[u8]>
+    where Size: BVSize + ByteDecodable
+{
+    if Size::bincode() {
+        let (string, len) = read_bincode_bytes::<Size>(bytes)?;
+        if len != bytes.len() {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        Ok(string)
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// A proxy that decodes `String` values without failing on invalid UTF-8.
+///
+/// Used with the `#[bytevec(with = LossyUtf8)]` field attribute, a `String` field is
+/// serialized as usual, but the invalid UTF-8 sequences of its byte representation are
+/// replaced with `U+FFFD REPLACEMENT CHARACTER` on deserialization, as with
+/// [`String::from_utf8_lossy`][1], instead of yielding a `StringDecodeUtf8Error`. Use
+/// [`MaybeUtf8`](enum.MaybeUtf8.html) to keep the original bytes instead.
+///
+/// ```rust
+/// # #[macro_use] extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, LossyUtf8};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct LogLine {
+///     level: u8,
+///     message: String
+/// }
+///
+/// bytevec_impls! {
+///     impl LogLine {
+///         level: u8,
+///         #[bytevec(with = LossyUtf8)]
+///         message: String
+///     }
+/// }
+///
+/// # fn main() {
+/// let bytes = vec![1, 3, 4, b'o', b'k', 0xff];
+/// assert_eq!(LogLine::decode::<u8>(&bytes).unwrap().message, "ok\u{fffd}");
+/// # }
+/// ```
+///
+/// [1]: https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LossyUtf8;
+
+impl ByteRemote<String> for LossyUtf8 {
+    fn get_size<Size>(value: &String) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        value.get_size::<Size>()
+    }
+
+    fn encode<Size>(value: &String) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        value.encode::<Size>()
+    }
+
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<String>
+        where Size: BVSize + ByteDecodable
+    {
+        Ok(String::from_utf8_lossy(string_bytes::<Size>(bytes)?).into_owned())
+    }
+}
+
+/// A string that keeps its raw bytes if they aren't valid UTF-8.
+///
+/// A `MaybeUtf8` is serialized exactly as a `String`, so it can replace a `String` field
+/// or element to read buffers that may hold invalid UTF-8. The valid strings are decoded
+/// as `Utf8` values, and the others as `Raw` values with the bytes of the string.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, MaybeUtf8};
+/// # fn main() {
+/// let bytes = vec!["ok".to_string()].encode::<u8>().unwrap();
+/// assert_eq!(Vec::<MaybeUtf8>::decode::<u8>(&bytes).unwrap(),
+///            vec![MaybeUtf8::Utf8("ok".to_string())]);
+/// assert_eq!(MaybeUtf8::decode::<u8>(&[b'o', b'k', 0xff]).unwrap(),
+///            MaybeUtf8::Raw(vec![b'o', b'k', 0xff]));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MaybeUtf8 {
+    /// A valid UTF-8 string
+    Utf8(String),
+    /// The bytes of a string that isn't valid UTF-8
+    Raw(Vec<u8>),
+}
+
+impl MaybeUtf8 {
+    /// Returns the bytes of the string
+    pub fn as_bytes(&self) -> &[u8] {
+        match *self {
+            MaybeUtf8::Utf8(ref string) => string.as_bytes(),
+            MaybeUtf8::Raw(ref bytes) => bytes,
+        }
+    }
+
+    /// Returns the string, with the invalid UTF-8 sequences of a `Raw` value replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self.as_bytes()).into_owned()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> MaybeUtf8 {
+        match ::std::str::from_utf8(bytes) {
+            Ok(string) => MaybeUtf8::Utf8(string.to_string()),
+            Err(_) => MaybeUtf8::Raw(bytes.to_vec()),
+        }
+    }
+}
+
+impl Default for MaybeUtf8 {
+    fn default() -> MaybeUtf8 {
+        MaybeUtf8::Utf8(String::new())
+    }
+}
+
+impl From<String> for MaybeUtf8 {
+    fn from(string: String) -> MaybeUtf8 {
+        MaybeUtf8::Utf8(string)
+    }
+}
+
+impl From<Vec<u8>> for MaybeUtf8 {
+    fn from(bytes: Vec<u8>) -> MaybeUtf8 {
+        match String::from_utf8(bytes) {
+            Ok(string) => MaybeUtf8::Utf8(string),
+            Err(error) => MaybeUtf8::Raw(error.into_bytes()),
+        }
+    }
+}
+
+impl ByteEncodable for MaybeUtf8 {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        let len = self.as_bytes().len();
+        if len > Size::max_value().as_usize() {
+            None
+        } else if Size::bincode() {
+            Size::from_usize(len).checked_add(size_len::<Size>(len))
+        } else {
+            Some(Size::from_usize(len))
+        }
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        encode_buffered::<Self, Size>(self)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        if self.get_size::<Size>().is_some() {
+            if Size::bincode() {
+                Size::from_usize(self.as_bytes().len()).encode_to::<Size, W>(writer)?;
+            }
+            writer.write_all(self.as_bytes())?;
+            Ok(())
+        } else {
+            Err(ByteVecError::OverflowError)
+        }
+    }
+}
+
+impl ByteDecodable for MaybeUtf8 {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<MaybeUtf8>
+        where Size: BVSize + ByteDecodable
+    {
+        Ok(MaybeUtf8::from_bytes(string_bytes::<Size>(bytes)?))
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(MaybeUtf8, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        if Size::bincode() {
+            let (string, len) = read_bincode_bytes::<Size>(bytes)?;
+            Ok((MaybeUtf8::from_bytes(string), len))
+        } else {
+            Ok((MaybeUtf8::from_bytes(bytes), bytes.len()))
+        }
+    }
+}
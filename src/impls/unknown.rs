@@ -0,0 +1,84 @@
+use traits::ByteEncodable;
+use {BVEncodeResult, BVSize};
+use impls::size_len;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+/// The entries of an extensible `struct` that follow the fields it knows about.
+///
+/// A byte representation written by a newer version of an extensible `struct` may
+/// have more entries than the fields of the current version. The field marked with
+/// `#[bytevec(unknown)]` collects the byte representation of each of them, in order,
+/// and they are written back after the known fields when the value is serialized again.
+/// See the `extensible` option of [`bytevec_impls!`](macro.bytevec_impls.html).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct UnknownFields(pub Vec<Vec<u8>>);
+
+impl UnknownFields {
+    /// Returns the underlying `Vec`
+    pub fn into_inner(self) -> Vec<Vec<u8>> {
+        self.0
+    }
+
+    /// Returns the length of the entries along with their sizes
+    #[doc(hidden)]
+    pub fn table_size<Size>(&self) -> Option<Size>
+        where Size: BVSize
+    {
+        let mut size = Some(Size::from_usize(0));
+        for entry in self.iter() {
+            size = size.and_then(|size: Size| {
+                if entry.len() <= Size::max_value().as_usize() {
+                    size.checked_add(Size::from_usize(entry.len()))
+                        .and_then(|size| size.checked_add(size_len::<Size>(entry.len())))
+                } else {
+                    None
+                }
+            });
+        }
+        size
+    }
+
+    /// Writes the size of each entry
+    #[doc(hidden)]
+    pub fn encode_sizes_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        for entry in self.iter() {
+            Size::from_usize(entry.len()).encode_to::<Size, W>(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the entries
+    #[doc(hidden)]
+    pub fn encode_entries_to<W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where W: Write + ?Sized
+    {
+        for entry in self.iter() {
+            writer.write_all(entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Vec<u8>>> for UnknownFields {
+    fn from(entries: Vec<Vec<u8>>) -> UnknownFields {
+        UnknownFields(entries)
+    }
+}
+
+impl Deref for UnknownFields {
+    type Target = Vec<Vec<u8>>;
+
+    fn deref(&self) -> &Vec<Vec<u8>> {
+        &self.0
+    }
+}
+
+impl DerefMut for UnknownFields {
+    fn deref_mut(&mut self) -> &mut Vec<Vec<u8>> {
+        &mut self.0
+    }
+}
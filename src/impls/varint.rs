@@ -0,0 +1,224 @@
+use traits::{ByteEncodable, ByteDecodable};
+use errors::{ByteVecError, BVExpectedSize};
+use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::cmp;
+use std::convert::TryFrom;
+use std::io::Write;
+
+/// A size indicator serialized as a variable length integer.
+///
+/// `VarSize` can be used as the `Size` type parameter of the serialization operations
+/// in place of the fixed size integral types. Each size indicator is then written in the
+/// [LEB128][1] format, 7 bits per byte, so sizes lower than 128 take a single byte and the
+/// largest `u64` value takes 10 bytes.
+///
+/// [1]: https://en.wikipedia.org/wiki/LEB128
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VarSize(pub u64);
+
+/// The maximum length of the byte representation of a `VarSize`
+const MAX_LEN: usize = 10;
+
+impl BVSize for VarSize {
+    fn from_usize(val: usize) -> VarSize {
+        VarSize(val as u64)
+    }
+
+    fn as_usize(&self) -> usize {
+        self.0 as usize
+    }
+
+    fn checked_as_usize(&self) -> Option<usize> {
+        usize::try_from(self.0).ok()
+    }
+
+    fn max_value() -> VarSize {
+        VarSize(u64::MAX)
+    }
+
+    fn checked_add(self, rhs: VarSize) -> Option<VarSize> {
+        self.0.checked_add(rhs.0).map(VarSize)
+    }
+
+    // The shortest byte representation of a `VarSize`
+    fn get_size_of() -> VarSize {
+        VarSize(1)
+    }
+
+    fn encoded_len(&self) -> usize {
+        cmp::max(1, (64 - self.0.leading_zeros() as usize).div_ceil(7))
+    }
+}
+
+impl ByteEncodable for VarSize {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        Some(Size::from_usize(self.encoded_len()))
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        let mut buffer = [0u8; MAX_LEN];
+        let mut value = self.0;
+        let mut len = 0;
+        loop {
+            buffer[len] = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                break;
+            }
+            buffer[len] |= 0x80;
+            len += 1;
+        }
+        writer.write_all(&buffer[..len + 1])?;
+        Ok(())
+    }
+}
+
+impl ByteDecodable for VarSize {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<VarSize>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = VarSize::decode_with_len::<Size>(bytes)?;
+        if len == bytes.len() {
+            Ok(value)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(VarSize, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        let mut value = 0u64;
+        for (index, byte) in bytes.iter().take(MAX_LEN).enumerate() {
+            let bits = u64::from(byte & 0x7f);
+            if index == MAX_LEN - 1 && bits > 1 {
+                return Err(ByteVecError::OverflowError);
+            }
+            value |= bits << (7 * index);
+            if byte & 0x80 == 0 {
+                return Ok((VarSize(value), index + 1));
+            }
+        }
+        if bytes.len() >= MAX_LEN {
+            Err(ByteVecError::OverflowError)
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(bytes.len()),
+                actual: bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+}
+
+/// A signed integer serialized with the zig-zag encoding.
+///
+/// The integer is mapped to an unsigned integer that interleaves the positive and the
+/// negative values, so 0, -1, 1, -2 become 0, 1, 2, 3, and the result is written in the
+/// LEB128 format, as a `VarSize` is. Integers close to 0 then take a single byte whatever
+/// their sign, as the `sint32` and `sint64` types of Protocol Buffers do.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, ZigZag};
+/// # fn main() {
+/// assert_eq!(ZigZag(-1i64).encode::<u32>().unwrap(), vec![1]);
+/// assert_eq!(ZigZag(300i32).encode::<u32>().unwrap(), vec![0xd8, 0x04]);
+/// assert_eq!(ZigZag::<i32>::decode::<u32>(&[3]).unwrap(), ZigZag(-2));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ZigZag<T>(pub T);
+
+impl<T> ZigZag<T> {
+    /// Returns the underlying integer
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for ZigZag<T> {
+    fn from(value: T) -> ZigZag<T> {
+        ZigZag(value)
+    }
+}
+
+macro_rules! zigzag_impls {
+    ($($t:ident $u:ident),*) => {
+        $(
+            impl ZigZag<$t> {
+                fn zigzag(&self) -> VarSize {
+                    VarSize(((self.0 << 1) ^ (self.0 >> ($t::BITS - 1))) as $u as u64)
+                }
+            }
+
+            impl ByteEncodable for ZigZag<$t> {
+                fn get_size<Size>(&self) -> Option<Size>
+                    where Size: BVSize + ByteEncodable
+                {
+                    self.zigzag().get_size::<Size>()
+                }
+
+                fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+                    where Size: BVSize + ByteEncodable
+                {
+                    self.zigzag().encode::<Size>()
+                }
+
+                fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+                    where Size: BVSize + ByteEncodable,
+                          W: Write + ?Sized
+                {
+                    self.zigzag().encode_to::<Size, W>(writer)
+                }
+            }
+
+            impl ByteDecodable for ZigZag<$t> {
+                fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<ZigZag<$t>>
+                    where Size: BVSize + ByteDecodable
+                {
+                    let (value, len) = ZigZag::<$t>::decode_with_len::<Size>(bytes)?;
+                    if len == bytes.len() {
+                        Ok(value)
+                    } else {
+                        Err(ByteVecError::BadSizeDecodeError {
+                            expected: BVExpectedSize::EqualTo(len),
+                            actual: bytes.len(),
+                            offset: 0,
+                        })
+                    }
+                }
+
+                fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(ZigZag<$t>, usize)>
+                    where Size: BVSize + ByteDecodable
+                {
+                    let (VarSize(value), len) = VarSize::decode_with_len::<Size>(bytes)?;
+                    if value > u64::from($u::MAX) {
+                        return Err(ByteVecError::OverflowError);
+                    }
+                    let value = value as $u;
+                    Ok((ZigZag((value >> 1) as $t ^ -((value & 1) as $t)), len))
+                }
+            }
+        )*
+    }
+}
+
+zigzag_impls!(i8 u8, i16 u16, i32 u32, i64 u64);
@@ -34,7 +34,7 @@
 //! 
 //! bytevec implements `ByteEncodable` out of the box for the following types:
 //! 
-//! - The integral types: `u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`
+//! - The integral types: `u8`, `u16`, `u32`, `u64`, `u128`, `i8`, `i16`, `i32`, `i64`, `i128`
 //! 
 //! - The floating point types: `f32` and `f64`
 //! 
@@ -50,6 +50,9 @@
 //! 
 //! - Tuples with up to 12 elements
 //! 
+//! - [`ControlFlow`](http://doc.rust-lang.org/stable/std/ops/enum.ControlFlow.html) and
+//!   [`Poll`](http://doc.rust-lang.org/stable/std/task/enum.Poll.html)
+//! 
 //! - Custom `struct`s
 //! 
 //! For collections and other structures, automatic implementation of bytevec
@@ -61,38 +64,42 @@
 //! rules when translating some type value to bytes:
 //! 
 //! - For a primitive type such as the integral types, floating points
-//! or char that have fixed size, it will just grab the bytes and put them 
-//! on a `u8` buffer of the same length as the size of the type through 
-//! [`std::mem::transmute`][1]. These types are converted to and from little endian on
-//! serialization and deserialization respectively.
+//!   or char that have fixed size, it will just grab the bytes and put them 
+//!   on a `u8` buffer of the same length as the size of the type through 
+//!   [`std::mem::transmute`][1]. These types are converted to and from little endian on
+//!   serialization and deserialization respectively.
 //! 
 //! - String and str don't store their byte count, it's up to their container (if any)
-//! to store the size of the byte buffer of the string.
+//!   to store the size of the byte buffer of the string.
 //! 
 //! - Complex data structures such as `struct`s, tuples and collections need to store
-//! the sizes of their underlying data fields. These sizes are stored as values of a generic
-//! integral type parameter `Size` that should be provided in every call of the methods of the
-//! `ByteEncodable` and `ByteDecodable` traits. This type parameter is propagated to the
-//! serialization and deserialization operations of the contained data fields. The type parameter
-//! `Size` is constrained by the `BVSize` trait. Currently the types that implement this trait
-//! are `u8`, `u16`, `u32` and `u64`. Users should select the type for the `Size` type parameter
-//! according to the expected size of the byte buffer. If the expected size exceeds the 
-//! 2<sup>32</sup> byte length limit of `u32`, use `u64` instead.
+//!   the sizes of their underlying data fields. These sizes are stored as values of a generic
+//!   integral type parameter `Size` that should be provided in every call of the methods of the
+//!   `ByteEncodable` and `ByteDecodable` traits. This type parameter is propagated to the
+//!   serialization and deserialization operations of the contained data fields. The type parameter
+//!   `Size` is constrained by the `BVSize` trait. Currently the types that implement this trait
+//!   are `u8`, `u16`, `u32`, `u64`, `u128` and `usize`. Users should select the type for the
+//!   `Size` type parameter according to the expected size of the byte buffer. If the expected
+//!   size exceeds the 2<sup>32</sup> byte length limit of `u32`, use `u64` instead. `usize`
+//!   takes the natural width of the platform, so it only suits the buffers that never leave
+//!   the process. The `VarSize` type can
+//!   also be used as `Size` to write each size as a variable length integer, which takes a
+//!   single byte for sizes lower than 128 and grows as needed up to the `u64` limit.
 //! 
 //! - For structures with defined fields such as a custom `struct` or a tuple,
-//! it will store the size of each field on a sequence of `Size` values at the start
-//! of the slice segment for the structure, followed by the actual bytes of 
-//! the values of the fields.
+//!   it will store the size of each field on a sequence of `Size` values at the start
+//!   of the slice segment for the structure, followed by the actual bytes of 
+//!   the values of the fields.
 //! 
 //! - For any collection with variable length, it will first store the length
-//! (in elements, not byte count) on a `Size` value, followed by the byte count
-//! (yes, of `Size`) of each element, and then the actual values of the elements.
-//! All of this done in order, order is important, the same order of serialization
-//! is the order of deserialization.
+//!   (in elements, not byte count) on a `Size` value, followed by the byte count
+//!   (yes, of `Size`) of each element, and then the actual values of the elements.
+//!   All of this done in order, order is important, the same order of serialization
+//!   is the order of deserialization.
 //! 
 //! - All serializable values can be nested, so any structure that implements 
-//! `ByteEncodable` containing a `Vec`, `String`, or another structure that also implements
-//! `ByteEncodable` will be serialized along all its fields.
+//!   `ByteEncodable` containing a `Vec`, `String`, or another structure that also implements
+//!   `ByteEncodable` will be serialized along all its fields.
 //! 
 //! ###The `ByteDecodable` trait
 //! Given a byte vector retrieved from memory, a file, or maybe a TCP connection,
@@ -117,6 +124,9 @@
 //! a `limit` argument. This parameter is compared to the length of the `u8` buffer and
 //! if the buffer length is greater than it, it will return a `BadSizeDecodeError`,
 //! otherwise it will return the result of `decode` on the byte buffer.
+//! The `decode_limited` method goes further for the buffers from untrusted sources, with
+//! a `DecodeLimits` value that also bounds the number of elements of the collections, the
+//! nesting of the values read from the buffer and the number of steps of the decoding.
 //! 
 //! ###Example: Serialization and deserialization of a slice
 //! 
@@ -132,15 +142,148 @@
 //! assert_eq!(vec, slice);
 //! # }
 //! ```
+//!
+//! The `encode` and `decode` functions of the crate, like the `to_bytes` and `from_bytes`
+//! methods of the traits, take `u32` as the `Size` type, so the same example can be written
+//! without the type parameter:
+//!
+//! ```rust
+//! # extern crate bytevec;
+//! # use bytevec::{ByteEncodable, ByteDecodable};
+//! # fn main() {
+//! let slice: &[&str] = &["Rust", "Is", "Awesome!"];
+//! let bytes = bytevec::encode(slice).unwrap();
+//! let vec: Vec<String> = bytevec::decode(&bytes).unwrap();
+//! assert_eq!(vec, slice);
+//!
+//! let bytes = slice.to_bytes().unwrap();
+//! assert_eq!(<Vec<String>>::from_bytes(&bytes).unwrap(), slice);
+//! # }
+//! ```
 //! [1]: http://doc.rust-lang.org/stable/std/mem/fn.transmute.html
 
+#[cfg(feature = "codec")]
+extern crate bytes;
+#[cfg(feature = "codec")]
+extern crate tokio_util;
+#[cfg(feature = "flate2")]
+extern crate flate2;
+#[cfg(feature = "zstd")]
+extern crate zstd;
+#[cfg(feature = "half")]
+extern crate half;
+#[cfg(feature = "testing")]
+extern crate arbitrary;
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "bumpalo")]
+extern crate bumpalo;
+#[cfg(feature = "serde")]
+extern crate serde;
+
 #[macro_use]
 mod macros;
 mod traits;
 pub mod errors;
 mod impls;
+mod frames;
+mod seq;
+mod encoder;
+mod dynamic;
+mod registry;
+mod encoded;
+mod hash;
+mod options;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod reader;
+mod header;
+mod checksum;
+mod schema;
+mod migrate;
+mod view;
+mod patch;
+mod limits;
+mod validate;
+pub mod debug;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+pub mod compress;
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "protobuf"))]
+#[cfg_attr(not(any(feature = "msgpack", feature = "cbor")), allow(dead_code))]
+mod value;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "bench-support")]
+pub mod bench_support;
+#[cfg(feature = "serde")]
+pub mod serde_bridge;
 
-pub use traits::{ByteEncodable, ByteDecodable};
+pub use traits::{ByteEncodable, ByteDecodable, BytesDecodeExt, EncodeExt, ByteDecodeBorrowed,
+                 ByteDecodeIn, DecodeArena, ByteRemote, FixedByteSize, PayloadTransform,
+                 ByteColumns};
+pub use frames::{FrameIter, StreamingDecoder, DecodeProgress, DEFAULT_MAX_FRAME_SIZE};
+pub use seq::{SeqEncoder, SeqDecoder, encode_iter, decode_n, collection_index,
+              merge_encoded_vecs, peek_len, peek_sizes};
+pub use encoder::Encoder;
+pub use dynamic::DynByteEncodable;
+pub use registry::TypeRegistry;
+pub use encoded::Encoded;
+pub use hash::{hash, hash_into};
+pub use options::{Options, options};
+pub use reader::EncodedReader;
+pub use header::{FormatHeader, HEADER_MAGIC, FORMAT_VERSION};
+pub use schema::{ByteSchema, Schema, FieldSchema, VariantSchema};
+pub use migrate::Migrator;
+pub use view::LazyStruct;
+pub use patch::{Patch, splice_field};
+pub use limits::DecodeLimits;
+pub use validate::{validate, validate_schema, validate_limited};
 pub type BVEncodeResult<T> = Result<T, errors::ByteVecError>;
 pub type BVDecodeResult<T> = Result<T, errors::ByteVecError>;
-pub use impls::BVSize;
\ No newline at end of file
+pub use impls::{BVSize, VarSize, ZigZag, Deterministic, Strict, BigEndian, Bincode, PackedVec,
+                PackedArray, ByteBuf, Bytes, OptionVec, InternedVec, Dictionary, BitVec, BitSet,
+                UnknownFields, ColumnVec, RunVec, RUN_VEC_MAX_LEN, LossyUtf8, MaybeUtf8};
+#[cfg(feature = "half")]
+pub use impls::LossyF16;
+#[cfg(feature = "rayon")]
+pub use parallel::{ParallelEncode, PARALLEL_MIN_LEN};
+#[doc(hidden)]
+pub use limits::{enter_nested, check_elements};
+#[doc(hidden)]
+pub use impls::{table_len, read_size, read_size_table, compact_table, size_len, decode_exact,
+                write_size, check_encoded_len, SizeTable, encode_buffered, align_up, aligned_layout,
+                is_aligned_layout};
+/// Returns the byte representation of `value` with `u32` size indicators, the same as
+/// `value.encode::<u32>()`
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # fn main() {
+/// let bytes = bytevec::encode(&("Ada", 1815u16)).unwrap();
+/// let (name, year): (String, u16) = bytevec::decode(&bytes).unwrap();
+/// assert_eq!((name.as_str(), year), ("Ada", 1815));
+/// # }
+/// ```
+pub fn encode<T>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: ByteEncodable + ?Sized
+{
+    value.encode::<u32>()
+}
+
+/// Returns the instance of `T` deserialized from a byte buffer with `u32` size indicators,
+/// the same as `T::decode::<u32>(bytes)`
+pub fn decode<T>(bytes: &[u8]) -> BVDecodeResult<T>
+    where T: ByteDecodable
+{
+    T::decode::<u32>(bytes)
+}
@@ -0,0 +1,184 @@
+use std::cell::Cell;
+
+use errors::ByteVecError;
+use BVDecodeResult;
+
+/// The limits enforced by `ByteDecodable::decode_limited`.
+///
+/// A buffer of a few bytes can declare a collection of billions of elements, or nest
+/// collections deep enough to exhaust the stack, so the buffers from untrusted sources
+/// should be read with limits. `max_bytes` bounds the length of the buffer,
+/// `max_elements` the number of elements of the collections, counting the expanded runs
+/// of a `RunVec` and the bits of a `BitVec`, and `max_depth` the nesting of the
+/// structures, enums and collections. The elements are counted across the whole buffer,
+/// so the budget is shared by the nested collections rather than granted to each of
+/// them. A value that exceeds a limit yields a `LimitExceededError`. `max_steps` is a
+/// budget for the work of the decoding, which takes a step for each structure, enum and
+/// collection, and for each element of the collections, and yields a
+/// `BudgetExhaustedError` once it's spent. The default limits are unbounded, so only the
+/// ones that are set apply.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, DecodeLimits};
+/// # fn main() {
+/// let limits = DecodeLimits {max_elements: 4, ..DecodeLimits::default()};
+/// let bytes = vec![vec![1u8, 2]].encode::<u8>().unwrap();
+/// assert!(Vec::<Vec<u8>>::decode_limited::<u8>(&bytes, &limits).is_ok());
+/// // 2 lists of 2 elements make 6 elements
+/// let bytes = vec![vec![1u8, 2], vec![3, 4]].encode::<u8>().unwrap();
+/// assert!(Vec::<Vec<u8>>::decode_limited::<u8>(&bytes, &limits).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeLimits {
+    /// The maximum number of elements of all the collections
+    pub max_elements: usize,
+    /// The maximum length of the buffer
+    pub max_bytes: usize,
+    /// The maximum nesting of the structures, enums and collections
+    pub max_depth: usize,
+    /// The maximum number of steps of the decoding
+    pub max_steps: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_elements: usize::MAX,
+            max_bytes: usize::MAX,
+            max_depth: usize::MAX,
+            max_steps: usize::MAX,
+        }
+    }
+}
+
+/// The state of a decoding with limits
+#[derive(Clone, Copy)]
+struct Budget {
+    limits: DecodeLimits,
+    depth: usize,
+    elements: usize,
+    steps: usize,
+}
+
+impl Budget {
+    /// Takes `steps` steps of the decoding, and fails if the `max_steps` budget is spent
+    fn step(self, steps: usize) -> BVDecodeResult<Budget> {
+        let total = self.steps.saturating_add(steps);
+        if total > self.limits.max_steps {
+            Err(ByteVecError::BudgetExhaustedError { budget: self.limits.max_steps })
+        } else {
+            Ok(Budget { steps: total, ..self })
+        }
+    }
+}
+
+// The limits of the decoding in progress on the current thread, along with the depth
+// of the value being read and the number of elements and of steps read so far, as the
+// decoding functions don't take them as arguments.
+thread_local! {
+    static ACTIVE: Cell<Option<Budget>> = const { Cell::new(None) };
+}
+
+/// Sets the limits of the decodings on the current thread until it's dropped, and then
+/// restores the previous ones.
+pub(crate) struct LimitsGuard(Option<Budget>);
+
+impl LimitsGuard {
+    pub(crate) fn new(limits: &DecodeLimits) -> LimitsGuard {
+        let budget = Budget {
+            limits: *limits,
+            depth: 0,
+            elements: 0,
+            steps: 0,
+        };
+        LimitsGuard(ACTIVE.with(|active| active.replace(Some(budget))))
+    }
+}
+
+impl Drop for LimitsGuard {
+    fn drop(&mut self) {
+        ACTIVE.with(|active| active.set(self.0));
+    }
+}
+
+/// Leaves a nested value when it's dropped.
+#[doc(hidden)]
+pub struct NestingGuard(bool);
+
+impl Drop for NestingGuard {
+    fn drop(&mut self) {
+        if self.0 {
+            ACTIVE.with(|active| {
+                active.set(active.get().map(|budget| Budget { depth: budget.depth - 1, ..budget }));
+            });
+        }
+    }
+}
+
+fn exceeded(limit: &'static str, max: usize, actual: usize) -> ByteVecError {
+    ByteVecError::LimitExceededError {
+        limit,
+        max,
+        actual,
+    }
+}
+
+/// Enters a nested structure, enum or collection, and returns a guard that leaves it
+/// when it's dropped. Fails if the nesting exceeds the `max_depth` limit, or if the
+/// `max_steps` budget is spent.
+#[doc(hidden)]
+pub fn enter_nested() -> BVDecodeResult<NestingGuard> {
+    ACTIVE.with(|active| {
+        match active.get() {
+            Some(budget) => {
+                if budget.depth >= budget.limits.max_depth {
+                    return Err(exceeded("max_depth", budget.limits.max_depth, budget.depth + 1));
+                }
+                let budget = budget.step(1)?;
+                active.set(Some(Budget { depth: budget.depth + 1, ..budget }));
+                Ok(NestingGuard(true))
+            }
+            None => Ok(NestingGuard(false)),
+        }
+    })
+}
+
+/// Counts the `count` elements of a collection, and fails if the elements read so far
+/// exceed the `max_elements` limit, or if the `max_steps` budget is spent.
+#[doc(hidden)]
+pub fn check_elements(count: usize) -> BVDecodeResult<()> {
+    ACTIVE.with(|active| {
+        if let Some(budget) = active.get() {
+            let elements = budget.elements.saturating_add(count);
+            if elements > budget.limits.max_elements {
+                return Err(exceeded("max_elements", budget.limits.max_elements, elements));
+            }
+            active.set(Some(Budget { elements, ..budget.step(count)? }));
+        }
+        Ok(())
+    })
+}
+
+/// Fails if the `count` elements expanded from a few bytes, as the runs of a `RunVec`,
+/// exceed `max` while no limits are set. The decodings with limits are bounded by
+/// `max_elements` instead.
+pub(crate) fn check_expansion(count: usize, max: usize) -> BVDecodeResult<()> {
+    ACTIVE.with(|active| {
+        if active.get().is_none() && count > max {
+            Err(exceeded("max_elements", max, count))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Fails if a buffer of `len` bytes exceeds the `max_bytes` limit.
+pub(crate) fn check_bytes(limits: &DecodeLimits, len: usize) -> BVDecodeResult<()> {
+    if len > limits.max_bytes {
+        Err(exceeded("max_bytes", limits.max_bytes, len))
+    } else {
+        Ok(())
+    }
+}
@@ -12,7 +12,9 @@
 /// will be serialized and deserialized. In the deserialization process, the
 /// rest of the fields of the `struct` will be initialized using the value
 /// returned from the [`Default::default()`][1] method, so the `struct` must 
-/// implement [`Default`][2].
+/// implement [`Default`][2]. When the listed fields are all of the fields of the
+/// `struct`, the `#[bytevec(complete)]` attribute can be given to the `impl` instead,
+/// and the `struct` is then built without requiring `Default`.
 ///
 /// # Examples
 ///
@@ -43,99 +45,1852 @@
 ///     assert_eq!(p1, p2);
 /// }
 /// ```
+///
+/// # Tuple structs
+///
+/// Tuple structs and newtypes are implemented by listing the types of their fields
+/// between parentheses, as in `impl Meters(f64)`. The fields are serialized in the
+/// same way as the fields of a regular `struct`, so `struct Pair(u32, String)` has
+/// the same byte representation as the tuple `(u32, String)`. Alternatively, the
+/// fields can be listed by index between braces, as in `impl Pair {0: u32}`, which
+/// allows a partial implementation of a tuple struct.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct Meters(f64);
+///
+/// bytevec_impls! {
+///     impl Meters(f64)
+/// }
+///
+/// fn main() {
+///     let m1 = Meters(3.5);
+///     let bytes = m1.encode::<u32>().unwrap();
+///     let m2 = Meters::decode::<u32>(&bytes).unwrap();
+///     assert_eq!(m1, m2);
+/// }
+/// ```
+///
+/// An `impl` of a `struct` with a single field can be annotated with
+/// `#[bytevec(transparent)]`, in which case the `struct` has exactly the same byte
+/// representation as its field, without any size indicator, so a `UserId(u64)`
+/// can be decoded as a bare `u64` and vice versa.
+///
+/// # Field defaults
+///
+/// A field can be listed with a `#[bytevec(default = expr)]` attribute. Such a field
+/// is not serialized, and on deserialization it is initialized with the value of
+/// `expr` instead. Unlike serde, the expression is written as is rather than as a
+/// string literal. If any of the listed fields is left out this way, the macro
+/// takes the field list as complete and the `struct` is not required to implement
+/// `Default`, which is useful for fields of types like [`NonZeroU32`][3].
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// use std::num::NonZeroU32;
+///
+/// #[derive(PartialEq, Eq, Debug)]
+/// struct Counter {
+///     value: u32,
+///     step: NonZeroU32
+/// }
+///
+/// bytevec_impls! {
+///     impl Counter {
+///         value: u32,
+///         #[bytevec(default = NonZeroU32::new(1).unwrap())]
+///         step: NonZeroU32
+///     }
+/// }
+///
+/// fn main() {
+///     let c1 = Counter {value: 10, step: NonZeroU32::new(5).unwrap()};
+///     let bytes = c1.encode::<u32>().unwrap();
+///     let c2 = Counter::decode::<u32>(&bytes).unwrap();
+///     assert_eq!(c2, Counter {value: 10, step: NonZeroU32::new(1).unwrap()});
+/// }
+/// ```
+///
+/// # Versioning
+///
+/// An `impl` can be annotated with `#[bytevec(version = N)]`, in which case the
+/// version number is stored as a `Size` value before the rest of the structure.
+/// On deserialization, the version is read back and compared against `N`. Older
+/// versions can be upgraded with `migrate(M => from_vM, ...)`, where `from_vM` is an
+/// associated function of the `struct` that takes the bytes that follow the version
+/// and returns a `BVDecodeResult<Self>`. A version without a migration function
+/// yields an `UnknownVersionError`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable, BVDecodeResult, BVSize};
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct PointV1 {
+///     x: u32
+/// }
+///
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct Point {
+///     x: u32,
+///     y: u32
+/// }
+///
+/// impl Point {
+///     fn from_v1<Size>(bytes: &[u8]) -> BVDecodeResult<Point>
+///         where Size: BVSize + ByteDecodable
+///     {
+///         let old = PointV1::decode::<Size>(bytes)?;
+///         Ok(Point {x: old.x, y: 0})
+///     }
+/// }
+///
+/// bytevec_impls! {
+///     impl PointV1 {
+///         x: u32
+///     }
+///
+///     #[bytevec(version = 2, migrate(1 => from_v1))]
+///     impl Point {
+///         x: u32,
+///         y: u32
+///     }
+/// }
+///
+/// fn main() {
+///     let p1 = Point {x: 32, y: 436};
+///     let bytes = p1.encode::<u32>().unwrap();
+///     let p2 = Point::decode::<u32>(&bytes).unwrap();
+///     assert_eq!(p1, p2);
+/// }
+/// ```
+///
+/// # Foreign field types
+///
+/// A field annotated with `#[bytevec(with = Proxy)]` is serialized through the
+/// [`ByteRemote`](trait.ByteRemote.html) implementation of `Proxy` instead of its own
+/// implementation of the serialization traits. See [`bytevec_remote`](macro.bytevec_remote.html)
+/// for a way to declare such proxies for types from other crates.
+///
+/// A `Vec<T>` field annotated with `#[bytevec(dictionary)]` is serialized through the
+/// [`Dictionary`](struct.Dictionary.html) proxy, as an [`InternedVec<T>`](struct.InternedVec.html)
+/// that writes each distinct element once and every element as its index among them, which
+/// keeps the fields of repeated values compact.
+///
+/// # Field encoding
+///
+/// A field annotated with `#[bytevec(size = T)]` uses the `BVSize` type `T` instead of
+/// `Size` for its own size indicators, so a `struct` serialized with `u64` sizes can still
+/// use `u8` sizes for a collection of small strings. The size of the field itself is
+/// still stored as a `Size` value in the size table of the `struct`.
+///
+/// A field of a primitive type annotated with `#[bytevec(big_endian)]` is serialized in
+/// big endian rather than in little endian, for compatibility with existing binary formats,
+/// whether or not the `Size` type is a [`BigEndian`](struct.BigEndian.html) one.
+/// The bytes of any other type would simply be reversed, so this attribute should only be
+/// used with the integral types, the floating point types and `char`.
+///
+/// # Conditional fields
+///
+/// A field annotated with `#[bytevec(skip_if = predicate)]` is skipped on serialization
+/// whenever `predicate`, called with a reference to the whole `struct`, returns `true`.
+/// A skipped field still has an entry of size 0 in the byte representation, and it's
+/// deserialized as its `default` expression, or as `Default::default()` if it has none.
+/// Note that a field that happens to serialize to zero bytes, like an empty `String`,
+/// is deserialized as the default value as well.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct Snapshot {
+///     id: u32,
+///     cache: Vec<u32>
+/// }
+///
+/// bytevec_impls! {
+///     impl Snapshot {
+///         id: u32,
+///         #[bytevec(skip_if = |s: &Snapshot| s.cache.len() > 2)]
+///         cache: Vec<u32>
+///     }
+/// }
+///
+/// fn main() {
+///     let s1 = Snapshot {id: 1, cache: vec![1, 2, 3]};
+///     let bytes = s1.encode::<u32>().unwrap();
+///     let s2 = Snapshot::decode::<u32>(&bytes).unwrap();
+///     assert_eq!(s2, Snapshot {id: 1, cache: vec![]});
+/// }
+/// ```
+///
+/// A field annotated with `#[bytevec(redact)]` is always skipped, so sensitive values such
+/// as passwords or tokens never reach the byte representation, while the other fields are
+/// serialized as usual. The field is deserialized as its `default` expression or as
+/// `Default::default()`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct Login {
+///     user: String,
+///     password: String
+/// }
+///
+/// bytevec_impls! {
+///     impl Login {
+///         user: String,
+///         #[bytevec(redact)]
+///         password: String
+///     }
+/// }
+///
+/// fn main() {
+///     let login = Login {user: "ann".to_string(), password: "hunter2".to_string()};
+///     let bytes = login.encode::<u8>().unwrap();
+///     assert_eq!(bytes, vec![3, 0, b'a', b'n', b'n']);
+///     assert_eq!(Login::decode::<u8>(&bytes).unwrap().password, "");
+/// }
+/// ```
+///
+/// # Extensible structs
+///
+/// The `#[bytevec(extensible)]` attribute stores the number of entries as a `Size` value
+/// before the size table, so fields can be appended to a `struct` without a new version.
+/// An older buffer with fewer entries deserializes the missing fields as their default
+/// value, as for the skipped fields of the previous section, and the entries that follow
+/// the known fields in a newer buffer are skipped. A field of type
+/// [`UnknownFields`](struct.UnknownFields.html) annotated with `#[bytevec(unknown)]`
+/// collects those entries instead, and writes them back on serialization, so an older
+/// program can pass the data of a newer one through unharmed.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable, UnknownFields};
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct ConfigV1 {
+///     name: String,
+///     rest: UnknownFields
+/// }
+///
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct Config {
+///     name: String,
+///     retries: u8
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(extensible)]
+///     impl ConfigV1 {
+///         name: String,
+///         #[bytevec(unknown)]
+///         rest: UnknownFields
+///     }
+///
+///     #[bytevec(extensible)]
+///     impl Config {
+///         name: String,
+///         retries: u8
+///     }
+/// }
+///
+/// fn main() {
+///     let config = Config {name: "db".to_string(), retries: 3};
+///     let old = ConfigV1::decode::<u32>(&config.encode::<u32>().unwrap()).unwrap();
+///     assert_eq!(old.rest, UnknownFields(vec![vec![3]]));
+///     let bytes = old.encode::<u32>().unwrap();
+///     assert_eq!(Config::decode::<u32>(&bytes).unwrap(), config);
+/// }
+/// ```
+///
+/// # Compact structs
+///
+/// The size of the last entry of the size table can be recovered from the length of the
+/// buffer, so the `#[bytevec(compact)]` attribute leaves it out, which saves one `Size`
+/// value per `struct`. A compact `struct` then takes the whole buffer given to
+/// `decode_with_len`, as a versioned one does, but it can be an element of a collection
+/// or a field of another `struct`, since those give each value its own buffer. This
+/// option can't be combined with `transparent`, `packed` or `extensible`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct Reading {
+///     sensor: u16,
+///     label: String
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(compact)]
+///     impl Reading {
+///         sensor: u16,
+///         label: String
+///     }
+/// }
+///
+/// fn main() {
+///     let reading = Reading {sensor: 7, label: "temp".to_string()};
+///     let bytes = reading.encode::<u32>().unwrap();
+///     // The size of `sensor`, then the values, without the size of `label`
+///     assert_eq!(bytes, vec![2, 0, 0, 0, 7, 0, b't', b'e', b'm', b'p']);
+///     assert_eq!(Reading::decode::<u32>(&bytes).unwrap(), reading);
+/// }
+/// ```
+///
+/// # Packed structs
+///
+/// If every field of a `struct` has a fixed size, as the primitive types do, the
+/// `#[bytevec(packed)]` attribute serializes its fields one after another, without the
+/// size table. The generated `ENCODED_SIZE` associated constant holds the length of the
+/// byte representation, and the `struct` implements
+/// [`FixedByteSize`](trait.FixedByteSize.html), so it can be used as a field of another
+/// packed `struct`. The `big_endian` field attribute is supported in this mode.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Debug, Default)]
+/// struct Vertex {
+///     x: f32,
+///     y: f32,
+///     color: u32
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(packed)]
+///     impl Vertex {
+///         x: f32,
+///         y: f32,
+///         color: u32
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(Vertex::ENCODED_SIZE, 12);
+///     let v1 = Vertex {x: 0.5, y: 1.5, color: 0xff00ff};
+///     let bytes = v1.encode::<u8>().unwrap();
+///     assert_eq!(bytes.len(), Vertex::ENCODED_SIZE);
+///     assert_eq!(Vertex::decode::<u8>(&bytes).unwrap(), v1);
+/// }
+/// ```
+///
+/// Adding the `aligned` option pads each field to its natural alignment and the whole
+/// `struct` to the largest alignment of its fields, as `#[repr(C)]` does. The declaration
+/// of the `struct` must then have the `#[repr(C)]` attribute and list every field, which is
+/// checked at compile time, so on little endian targets the byte representation has the
+/// memory layout that C code expects. The padding bytes are written as zeros.
+///
+/// # Columnar structs
+///
+/// The `#[bytevec(columnar)]` attribute also implements
+/// [`ByteColumns`](trait.ByteColumns.html) for the `struct`, so a
+/// [`ColumnVec`](struct.ColumnVec.html) of it is serialized field by field rather than
+/// element by element. Each column holds the entries that the field has in the size table
+/// of the `struct`, with its options, while the version and the number of entries of an
+/// extensible `struct` are left out.
+///
+/// # Enums
+///
+/// The `impl enum Name {...}` form implements the serialization traits for an `enum`,
+/// listing its variants as they are declared. Each value is serialized as the index of
+/// its variant, written as a `Size` value, followed by the fields of the variant
+/// serialized like a tuple. Unit variants only take the index. An index that doesn't
+/// match any variant yields an `UnknownVariantError` on deserialization. Up to 64
+/// variants of up to 12 fields each are supported, and `#[bytevec(schema)]` is the only
+/// option available for enums.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// #[derive(PartialEq, Debug)]
+/// enum Command {
+///     Quit,
+///     Move(i32, i32),
+///     Say {text: String}
+/// }
+///
+/// bytevec_impls! {
+///     impl enum Command {
+///         Quit,
+///         Move(i32, i32),
+///         Say {text: String}
+///     }
+/// }
+///
+/// fn main() {
+///     let c1 = Command::Say {text: "hello".to_string()};
+///     let bytes = c1.encode::<u8>().unwrap();
+///     assert_eq!(Command::decode::<u8>(&bytes).unwrap(), c1);
+/// }
+/// ```
+///
+/// # Schemas
+///
+/// The `#[bytevec(schema)]` attribute also implements [`ByteSchema`](trait.ByteSchema.html)
+/// for the `struct` or `enum`, describing the fields that are part of its byte
+/// representation along with their options. Every field type must implement `ByteSchema`
+/// as well, or the `with` proxy type for the fields that have one.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteSchema, Schema};
+/// #[derive(Default)]
+/// struct Point {
+///     x: u32,
+///     y: u32
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(schema)]
+///     impl Point {
+///         x: u32,
+///         y: u32
+///     }
+/// }
+///
+/// fn main() {
+///     match Point::schema() {
+///         Schema::Struct {name, fields, ..} => {
+///             assert_eq!(name, "Point");
+///             assert_eq!(fields[1].name, "y");
+///         }
+///         _ => unreachable!()
+///     }
+/// }
+/// ```
 /// [1]: http://doc.rust-lang.org/stable/std/default/trait.Default.html#tymethod.default
 /// [2]: http://doc.rust-lang.org/stable/std/default/trait.Default.html
+/// [3]: http://doc.rust-lang.org/stable/std/num/struct.NonZeroU32.html
 #[macro_export]
 macro_rules! bytevec_impls {
-    {$(impl $name:ident {$($field:ident : $t:ty),*})*} => {
+    // Struct attribute muncher. Collects the `#[bytevec(...)]` options given
+    // to the `impl` itself and ignores any other attribute.
+    (@struct $name:ident [$($sopts:tt)*] #[bytevec($($args:tt)*)] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)*] [$($args)*] $($rest)*);
+    };
+    (@struct $name:ident [$($sopts:tt)*] #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct $name [$($sopts)*] $($rest)*);
+    };
+    (@struct $name:ident [$($sopts:tt)*] {$($body:tt)*}) => {
+        $crate::bytevec_impls!(@fields {$name [$($sopts)*]} [] [] $($body)*);
+    };
+    (@struct $name:ident $sopts:tt ($($body:tt)*)) => {
+        $crate::bytevec_impls!(@tuple_fields $name $sopts []
+            [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15] $($body)*);
+    };
+
+    // Tuple struct field muncher. Names each field after the next index
+    // and hands the result over to the regular field muncher.
+    (@tuple_fields $name:ident $sopts:tt [$($fields:tt)*] $idxs:tt #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@tuple_fields $name $sopts [$($fields)* #[$($attr)*]] $idxs
+            $($rest)*);
+    };
+    (@tuple_fields $name:ident $sopts:tt [$($fields:tt)*] [$idx:tt $($idxs:tt)*]
+        $t:ty $(, $($rest:tt)*)?) => {
+        $crate::bytevec_impls!(@tuple_fields $name $sopts [$($fields)* $idx: $t,] [$($idxs)*]
+            $($($rest)*)?);
+    };
+    (@tuple_fields $name:ident $sopts:tt [$($fields:tt)*] $idxs:tt) => {
+        $crate::bytevec_impls!(@fields {$name $sopts} [] [] $($fields)*);
+    };
+
+    (@struct_opts $name:ident [$($sopts:tt)*] [version = $version:expr $(, $($args:tt)*)?]
+        $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (version [$version])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*]
+        [migrate($($old:expr => $from:ident),* $(,)?) $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (migrate [$(($old) $from)*])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [transparent $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (transparent [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [complete $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (complete [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [packed $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (packed [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [aligned $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (aligned [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [extensible $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (extensible [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [compact $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (compact [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [schema $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (schema [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident [$($sopts:tt)*] [columnar $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct_opts $name [$($sopts)* (columnar [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@struct_opts $name:ident $sopts:tt [] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct $name $sopts $($rest)*);
+    };
+    (@struct_opts $name:ident $sopts:tt [$($args:tt)*] $($rest:tt)*) => {
+        compile_error!(concat!("unknown bytevec option: ", stringify!($($args)*)));
+    };
+
+    // Field list muncher. Collects the `#[bytevec(...)]` options of each field
+    // into `(key [value])` pairs and ignores any other attribute.
+    (@fields $head:tt [$($fields:tt)*] [$($opts:tt)*]
+        #[bytevec($($args:tt)*)] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head [$($fields)*] [$($opts)*] [$($args)*] $($rest)*);
+    };
+    (@fields $head:tt [$($fields:tt)*] [$($opts:tt)*] #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@fields $head [$($fields)*] [$($opts)*] $($rest)*);
+    };
+    (@fields $head:tt [$($fields:tt)*] [$($opts:tt)*] $field:tt : $t:ty, $($rest:tt)*) => {
+        $crate::bytevec_impls!(@fields $head [$($fields)* {$field : $t; $($opts)*}] [] $($rest)*);
+    };
+    (@fields $head:tt [$($fields:tt)*] [$($opts:tt)*] $field:tt : $t:ty) => {
+        $crate::bytevec_impls!(@fields $head [$($fields)* {$field : $t; $($opts)*}] []);
+    };
+    (@fields $head:tt [$($fields:tt)*] []) => {
+        $crate::bytevec_impls!(@impl $head $($fields)*);
+    };
+
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [default = $default:expr $(, $($args:tt)*)?]
+        $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (default [$default])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [skip_if = $pred:expr $(, $($args:tt)*)?]
+        $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (skip_if [$pred])]
+            [$($($args)*)?] $($rest)*);
+    };
+    // A redacted field is a field that is always skipped.
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [redact $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (skip_if [|_| true])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [with = $with:ty $(, $($args:tt)*)?]
+        $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (with [$with])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [dictionary $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields
+            [$($opts)* (with [$crate::Dictionary]) (dictionary [])] [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [size = $size:ty $(, $($args:tt)*)?]
+        $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (size [$size])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [big_endian $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (big_endian [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt [$($opts:tt)*] [unknown $(, $($args:tt)*)?] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@opts $head $fields [$($opts)* (unknown [])]
+            [$($($args)*)?] $($rest)*);
+    };
+    (@opts $head:tt $fields:tt $opts:tt [] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@fields $head $fields $opts $($rest)*);
+    };
+    (@opts $head:tt $fields:tt $opts:tt [$($args:tt)*] $($rest:tt)*) => {
+        compile_error!(concat!("unknown bytevec option: ", stringify!($($args)*)));
+    };
+
+    // `@if key [opts] {then} {else}` expands to `then` if the option `key` is
+    // present in `opts`, and to `else` otherwise.
+    (@if default [(default $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if version [(version $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if transparent [(transparent $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if complete [(complete $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if packed [(packed $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if aligned [(aligned $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if schema [(schema $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if columnar [(columnar $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if extensible [(extensible $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if compact [(compact $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if unknown [(unknown $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if skip_if [(skip_if $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if big_endian [(big_endian $value:tt) $($opts:tt)*] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if $key:ident [$opt:tt $($opts:tt)*] $then:tt $_else:tt) => {
+        $crate::bytevec_impls! { @if $key [$($opts)*] $then $_else }
+    };
+    (@if $key:ident [] $_then:tt {$($_else:tt)*}) => {
+        $($_else)*
+    };
+
+    // `@get key [opts] expr` expands to the value of the option `key`, or to
+    // `expr` if the option is not present in `opts`.
+    (@get default [(default [$value:expr]) $($opts:tt)*] $($_else:tt)*) => {
+        $value
+    };
+    (@get version [(version [$value:expr]) $($opts:tt)*] $($_else:tt)*) => {
+        $value
+    };
+    (@get size [(size [$value:ty]) $($opts:tt)*] $($_else:tt)*) => {
+        $value
+    };
+    (@get $key:ident [$opt:tt $($opts:tt)*] $($_else:tt)*) => {
+        $crate::bytevec_impls!(@get $key [$($opts)*] $($_else)*)
+    };
+    (@get $key:ident [] $($_else:tt)*) => {
+        $($_else)*
+    };
+
+    // The `@field_*` rules expand to the serialization operations of a field,
+    // which use the `Size` type given with `size`, and the byte order given
+    // with `big_endian`, if any.
+    (@field_get_size $opts:tt $t:ty, $value:expr) => {
+        $crate::bytevec_impls!(@with_get_size $opts $t, $value,
+                               $crate::bytevec_impls!(@get size $opts Size))
+            .and_then(|field_size| {
+                let field_size = $crate::BVSize::as_usize(&field_size);
+                if field_size <= Size::max_value().as_usize() {
+                    Some(Size::from_usize(field_size))
+                } else {
+                    None
+                }
+            })
+    };
+
+    (@field_encode $opts:tt $t:ty, $value:expr) => {
+        $crate::bytevec_impls!(@with_encode $opts $t, $value,
+                               $crate::bytevec_impls!(@get size $opts Size))
+            .map(|mut bytes| {
+                if $crate::bytevec_impls!(@if big_endian $opts {true} {false}) &&
+                   !<$crate::bytevec_impls!(@get size $opts Size) as $crate::BVSize>::big_endian() {
+                    bytes.reverse();
+                }
+                bytes
+            })
+    };
+
+    // Fields without a `with` proxy or the `big_endian` option are written
+    // straight to the writer instead of being encoded into a buffer first.
+    (@field_encode_to $opts:tt $t:ty, $value:expr, $writer:ident) => {
+        $crate::bytevec_impls! { @if big_endian $opts {
+            ::std::io::Write::write_all($writer,
+                &$crate::bytevec_impls!(@field_encode $opts $t, $value)?)?
+        } {
+            $crate::bytevec_impls!(@with_encode_to $opts $t, $value,
+                                   $crate::bytevec_impls!(@get size $opts Size), $writer)
+        }}
+    };
+
+    (@field_encode_to_buf $opts:tt $t:ty, $value:expr, $out:ident) => {
+        $crate::bytevec_impls! { @if big_endian $opts {
+            $out.extend_from_slice(&$crate::bytevec_impls!(@field_encode $opts $t, $value)?)
+        } {
+            $crate::bytevec_impls!(@with_encode_to_buf $opts $t, $value,
+                                   $crate::bytevec_impls!(@get size $opts Size), $out)
+        }}
+    };
+
+    (@field_decode $opts:tt $t:ty, $bytes:expr) => {
+        $crate::bytevec_impls! { @if big_endian $opts {{
+            let reversed;
+            let bytes: &[u8] = if <$crate::bytevec_impls!(@get size $opts Size)
+                                   as $crate::BVSize>::big_endian() {
+                $bytes
+            } else {
+                reversed = $bytes.iter().rev().cloned().collect::<Vec<u8>>();
+                &reversed
+            };
+            $crate::bytevec_impls!(@with_decode $opts $t, bytes,
+                                   $crate::bytevec_impls!(@get size $opts Size))
+        }} {
+            $crate::bytevec_impls!(@with_decode $opts $t, $bytes,
+                                   $crate::bytevec_impls!(@get size $opts Size))
+        }}
+    };
+
+    // Decodes a field of a packed struct into its slot, in place if it has no options
+    (@field_decode_in_place [] $t:ty, $slot:ident, $bytes:expr) => {
+        <$t as $crate::FixedByteSize>::decode_in_place::<Size>($slot, $bytes)
+    };
+    (@field_decode_in_place $opts:tt $t:ty, $slot:ident, $bytes:expr) => {
+        $crate::bytevec_impls!(@field_decode $opts $t, $bytes).map(|field| {
+            $slot.write(field);
+        })
+    };
+
+    // The `@with_*` rules go through the `ByteRemote` proxy given with `with`,
+    // if any.
+    (@with_get_size [(with [$with:ty]) $($opts:tt)*] $t:ty, $value:expr, $size:ty) => {
+        <$with as $crate::ByteRemote<$t>>::get_size::<$size>(&$value)
+    };
+    (@with_get_size [$opt:tt $($opts:tt)*] $t:ty, $value:expr, $size:ty) => {
+        $crate::bytevec_impls!(@with_get_size [$($opts)*] $t, $value, $size)
+    };
+    (@with_get_size [] $t:ty, $value:expr, $size:ty) => {
+        <$t as $crate::ByteEncodable>::get_size::<$size>(&$value)
+    };
+
+    (@with_encode_to [(with [$with:ty]) $($opts:tt)*] $t:ty, $value:expr, $size:ty,
+        $writer:ident) => {
+        ::std::io::Write::write_all($writer,
+            &<$with as $crate::ByteRemote<$t>>::encode::<$size>(&$value)?)?
+    };
+    (@with_encode_to [$opt:tt $($opts:tt)*] $t:ty, $value:expr, $size:ty, $writer:ident) => {
+        $crate::bytevec_impls!(@with_encode_to [$($opts)*] $t, $value, $size, $writer)
+    };
+    (@with_encode_to [] $t:ty, $value:expr, $size:ty, $writer:ident) => {
+        <$t as $crate::ByteEncodable>::encode_to::<$size, W>(&$value, $writer)?
+    };
+
+    (@with_encode_to_buf [(with [$with:ty]) $($opts:tt)*] $t:ty, $value:expr, $size:ty,
+        $out:ident) => {
+        $out.extend_from_slice(&<$with as $crate::ByteRemote<$t>>::encode::<$size>(&$value)?)
+    };
+    (@with_encode_to_buf [$opt:tt $($opts:tt)*] $t:ty, $value:expr, $size:ty, $out:ident) => {
+        $crate::bytevec_impls!(@with_encode_to_buf [$($opts)*] $t, $value, $size, $out)
+    };
+    (@with_encode_to_buf [] $t:ty, $value:expr, $size:ty, $out:ident) => {
+        <$t as $crate::ByteEncodable>::encode_to_buf::<$size>(&$value, $out)?
+    };
+
+    (@with_encode [(with [$with:ty]) $($opts:tt)*] $t:ty, $value:expr, $size:ty) => {
+        <$with as $crate::ByteRemote<$t>>::encode::<$size>(&$value)
+    };
+    (@with_encode [$opt:tt $($opts:tt)*] $t:ty, $value:expr, $size:ty) => {
+        $crate::bytevec_impls!(@with_encode [$($opts)*] $t, $value, $size)
+    };
+    (@with_encode [] $t:ty, $value:expr, $size:ty) => {
+        <$t as $crate::ByteEncodable>::encode::<$size>(&$value)
+    };
+
+    (@with_decode [(with [$with:ty]) $($opts:tt)*] $t:ty, $bytes:expr, $size:ty) => {
+        <$with as $crate::ByteRemote<$t>>::decode::<$size>($bytes)
+    };
+    (@with_decode [$opt:tt $($opts:tt)*] $t:ty, $bytes:expr, $size:ty) => {
+        $crate::bytevec_impls!(@with_decode [$($opts)*] $t, $bytes, $size)
+    };
+    (@with_decode [] $t:ty, $bytes:expr, $size:ty) => {
+        <$t as $crate::ByteDecodable>::decode::<$size>($bytes)
+    };
+
+    (@with_decode_with_len [(with [$with:ty]) $($opts:tt)*] $t:ty, $bytes:expr, $size:ty) => {
+        <$with as $crate::ByteRemote<$t>>::decode::<$size>($bytes)
+            .map(|value| (value, $bytes.len()))
+    };
+    (@with_decode_with_len [$opt:tt $($opts:tt)*] $t:ty, $bytes:expr, $size:ty) => {
+        $crate::bytevec_impls!(@with_decode_with_len [$($opts)*] $t, $bytes, $size)
+    };
+    (@with_decode_with_len [] $t:ty, $bytes:expr, $size:ty) => {
+        <$t as $crate::ByteDecodable>::decode_with_len::<$size>($bytes)
+    };
+
+    // `@if_serialized [opts] {then} {else}` expands to `then` if the field has
+    // an entry in the byte representation. Only the fields with a default
+    // expression and without a `skip_if` predicate, and the `unknown` field,
+    // are left out.
+    (@if_serialized $opts:tt {$($then:tt)*} {$($else:tt)*}) => {
+        $crate::bytevec_impls! { @if unknown $opts {$($else)*} {
+            $crate::bytevec_impls! { @if skip_if $opts {$($then)*} {
+                $crate::bytevec_impls! { @if default $opts {$($else)*} {$($then)*} }
+            }}
+        }}
+    };
+
+    // `@if_any_omitted [[opts]...] {then} {else}` expands to `then` if any of
+    // the fields is left out of the byte representation.
+    (@if_any_omitted [$opts:tt $($rest:tt)*] {$($then:tt)*} $else:tt) => {
+        $crate::bytevec_impls! { @if_serialized $opts {
+            $crate::bytevec_impls! { @if_any_omitted [$($rest)*] {$($then)*} $else }
+        } {$($then)*} }
+    };
+    (@if_any_omitted [] $_then:tt {$($else:tt)*}) => {
+        $($else)*
+    };
+
+    // `@struct_lit [sopts] [[opts]...] Name {fields}` builds the deserialized struct.
+    // The unlisted fields are initialized with `Default::default()`, unless the
+    // field list is complete because of the `complete` option or of a field left
+    // out with a default expression, so that the struct needn't implement `Default`.
+    (@struct_lit $sopts:tt $fopts:tt $name:ident {$($fields:tt)*}) => {
+        $crate::bytevec_impls! { @if complete $sopts {$name {$($fields)*}} {
+            $crate::bytevec_impls! { @if_any_omitted $fopts {$name {$($fields)*}} {
+                $name {$($fields)* ..Default::default()}
+            }}
+        }}
+    };
+
+    // `@skipped [opts] self` expands to the result of the `skip_if` predicate of
+    // the field, or to `false` if there is none.
+    (@skipped [(skip_if [$pred:expr]) $($opts:tt)*] $this:ident) => {
+        ($pred)($this)
+    };
+    (@skipped [$opt:tt $($opts:tt)*] $this:ident) => {
+        $crate::bytevec_impls!(@skipped [$($opts)*] $this)
+    };
+    (@skipped [] $this:ident) => {
+        false
+    };
+
+    // Decodes the value of a field from the next entry of the size table, whose
+    // position is `slot`. Empty entries of fields with a `skip_if` predicate yield
+    // the default value, and so do the fields of an extensible struct missing from
+    // an older buffer. The `unknown` field takes the entries left after the known
+    // fields.
+    (@decode_field $sopts:tt $opts:tt $field:tt : $t:ty,
+        $bytes:ident $index:ident $sizes:ident $slot:ident $unknown:ident) => {
+        $crate::bytevec_impls! { @if_serialized $opts {
+            $crate::bytevec_impls! { @if extensible $sopts {{
+                $slot += 1;
+                match $sizes.get($slot - 1) {
+                    Some(&size) => $crate::bytevec_impls!(@decode_entry $opts $field: $t,
+                        $bytes $index size),
+                    None => $crate::bytevec_impls!(@get default $opts Default::default()),
+                }
+            }} {{
+                let size = $sizes[$slot];
+                $slot += 1;
+                $crate::bytevec_impls!(@decode_entry $opts $field: $t, $bytes $index size)
+            }}}
+        } {
+            $crate::bytevec_impls! { @if unknown $opts {
+                $crate::UnknownFields($unknown)
+            } {
+                $crate::bytevec_impls!(@get default $opts)
+            }}
+        }}
+    };
+    (@decode_entry $opts:tt $field:tt : $t:ty, $bytes:ident $index:ident $size:ident) => {{
+        let field = $crate::bytevec_impls! { @if skip_if $opts {
+            if $size == 0 {
+                $crate::bytevec_impls!(@get default $opts Default::default())
+            } else {
+                $crate::bytevec_impls!(@field_decode $opts $t, &$bytes[$index..$index + $size])
+                    .map_err(|error| error.at($index).in_field(stringify!($field)))?
+            }
+        } {
+            $crate::bytevec_impls!(@field_decode $opts $t, &$bytes[$index..$index + $size])
+                .map_err(|error| error.at($index).in_field(stringify!($field)))?
+        }};
+        $index += $size;
+        field
+    }};
+
+    // Decodes the value of a field in the layout of bincode from the start of
+    // `&bytes[index..]`, and moves `index` past it.
+    (@bincode_field $opts:tt $field:tt : $t:ty, $bytes:ident $index:ident) => {
+        $crate::bytevec_impls! { @if_serialized $opts {{
+            let (field, len) = $crate::bytevec_impls! { @if big_endian $opts {{
+                let len = <$t as $crate::ByteDecodable>::decode_with_len::<
+                    $crate::bytevec_impls!(@get size $opts Size)>(&$bytes[$index..])
+                        .map_err(|error| error.at($index).in_field(stringify!($field)))?.1;
+                ($crate::bytevec_impls!(@field_decode $opts $t, &$bytes[$index..$index + len])
+                    .map_err(|error| error.at($index).in_field(stringify!($field)))?, len)
+            }} {
+                $crate::bytevec_impls!(@with_decode_with_len $opts $t, &$bytes[$index..],
+                                       $crate::bytevec_impls!(@get size $opts Size))
+                    .map_err(|error| error.at($index).in_field(stringify!($field)))?
+            }};
+            $index += len;
+            field
+        }} {
+            $crate::bytevec_impls! { @if unknown $opts {
+                $crate::UnknownFields::default()
+            } {
+                $crate::bytevec_impls!(@get default $opts)
+            }}
+        }}
+    };
+
+    // Returns early from `decode` with the result of the migration function
+    // registered for `version`, if any.
+    (@migrate [(migrate [$(($old:expr) $from:ident)*]) $($opts:tt)*]
+        $name:ident $version:ident $bytes:expr) => {
         $(
-            impl $crate::ByteEncodable for $name
+            if $version == ($old) as usize {
+                return $name::$from::<Size>($bytes);
+            }
+        )*
+    };
+    (@migrate [$opt:tt $($opts:tt)*] $name:ident $version:ident $bytes:expr) => {
+        $crate::bytevec_impls!(@migrate [$($opts)*] $name $version $bytes)
+    };
+    (@migrate [] $name:ident $version:ident $bytes:expr) => {};
+
+    (@impl {$name:ident [$($sopts:tt)*]} $($fields:tt)*) => {
+        $crate::bytevec_impls!(@check_unknown [$($sopts)*] $($fields)*);
+        $crate::bytevec_impls!(@check_compact $name [$($sopts)*]);
+        $crate::bytevec_impls!(@if transparent [$($sopts)*] {
+            $crate::bytevec_impls!(@impl_transparent $name $($fields)*);
+        } {
+            $crate::bytevec_impls! { @if packed [$($sopts)*] {
+                $crate::bytevec_impls!(@impl_packed {$name [$($sopts)*]} $($fields)*);
+            } {
+                $crate::bytevec_impls!(@impl_struct {$name [$($sopts)*]} $($fields)*);
+            }}
+        });
+        $crate::bytevec_impls!(@if schema [$($sopts)*] {
+            $crate::bytevec_impls!(@impl_schema {$name [$($sopts)*]} $($fields)*);
+        } {});
+        $crate::bytevec_impls!(@if columnar [$($sopts)*] {
+            $crate::bytevec_impls!(@impl_columns {$name [$($sopts)*]} $($fields)*);
+        } {});
+    };
+
+    // `@elided_entry [sopts] [opts]...` expands to the position of the entry without
+    // a size in the size table, which is the last one of a compact struct.
+    (@elided_entry $sopts:tt $($fopts:tt)*) => {
+        $crate::bytevec_impls!(@if compact $sopts {
+            (0usize $(+ $crate::bytevec_impls!(@if_serialized $fopts {1} {0}))*).checked_sub(1)
+        } {
+            None::<usize>
+        })
+    };
+
+    // `@if_whole_buffer [sopts] {then} {else}` expands to `then` if a struct takes the
+    // whole buffer given to `decode_with_len`.
+    (@if_whole_buffer $sopts:tt {$($then:tt)*} {$($else:tt)*}) => {
+        $crate::bytevec_impls! { @if version $sopts {$($then)*} {
+            $crate::bytevec_impls! { @if compact $sopts {$($then)*} {$($else)*} }
+        }}
+    };
+
+    // `@unknown_fields self {field; opts}...` expands to a reference to the
+    // `unknown` field, if any.
+    (@unknown_fields $this:ident $({$field:tt; $($opts:tt)*})*) => {
+        None::<&$crate::UnknownFields> $(.or($crate::bytevec_impls! { @if unknown [$($opts)*] {
+            Some(&$this.$field)
+        } {
+            None
+        }}))*
+    };
+
+    (@check_unknown $sopts:tt $({$field:tt : $t:ty; $($opts:tt)*})*) => {
+        $crate::bytevec_impls! { @if extensible $sopts {} {
+            $(
+                $crate::bytevec_impls! { @if unknown [$($opts)*] {
+                    compile_error!(concat!("the unknown field `", stringify!($field),
+                                           "` needs the `extensible` option"));
+                } {} }
+            )*
+        }}
+    };
+
+    // Only the structs with a size table can leave out the size of their last entry,
+    // and the entries of an extensible one may be followed by unknown entries.
+    (@check_compact $name:ident $sopts:tt) => {
+        $crate::bytevec_impls! { @if compact $sopts {
+            $crate::bytevec_impls! { @if transparent $sopts {
+                compile_error!(concat!("a transparent struct can't be compact: ",
+                                       stringify!($name)));
+            } {} }
+            $crate::bytevec_impls! { @if packed $sopts {
+                compile_error!(concat!("a packed struct can't be compact: ", stringify!($name)));
+            } {} }
+            $crate::bytevec_impls! { @if extensible $sopts {
+                compile_error!(concat!("an extensible struct can't be compact: ",
+                                       stringify!($name)));
+            } {} }
+        } {} }
+    };
+
+    // A transparent struct has the schema of its field, and any other struct lists
+    // the fields that have an entry in the byte representation.
+    (@impl_schema {$name:ident [$($sopts:tt)*]} $({$field:tt : $t:ty; $($opts:tt)*})*) => {
+        impl $crate::ByteSchema for $name {
+            fn schema() -> $crate::Schema {
+                $crate::bytevec_impls! { @if transparent [$($sopts)*] {
+                    $(<$t as $crate::ByteSchema>::schema())*
+                } {{
+                    let mut fields = Vec::new();
+                    $(
+                        $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                            fields.push($crate::FieldSchema {
+                                name: stringify!($field),
+                                schema: $crate::bytevec_impls!(@with_schema [$($opts)*] $t),
+                                big_endian: $crate::bytevec_impls!(@if big_endian [$($opts)*]
+                                    {true} {false}),
+                                skippable: $crate::bytevec_impls!(@if skip_if [$($opts)*]
+                                    {true} {false}),
+                                size_width: $crate::bytevec_impls!(@size_width [$($opts)*]),
+                            });
+                        } {});
+                    )*
+                    $crate::Schema::Struct {
+                        name: stringify!($name),
+                        version: $crate::bytevec_impls!(@if version [$($sopts)*] {
+                            Some($crate::bytevec_impls!(@get version [$($sopts)*]) as usize)
+                        } {
+                            None
+                        }),
+                        packed: $crate::bytevec_impls!(@if packed [$($sopts)*] {true} {false}),
+                        extensible: $crate::bytevec_impls!(@if extensible [$($sopts)*]
+                            {true} {false}),
+                        compact: $crate::bytevec_impls!(@if compact [$($sopts)*] {true} {false}),
+                        fields,
+                    }
+                }}}
+            }
+        }
+    };
+    (@with_schema [(with $with:tt) (dictionary []) $($opts:tt)*] $t:ty) => {
+        $crate::Schema::InternedSeq(Box::new(
+            <<$t as ::std::iter::IntoIterator>::Item as $crate::ByteSchema>::schema()))
+    };
+    (@with_schema [(with [$with:ty]) $($opts:tt)*] $t:ty) => {
+        <$with as $crate::ByteSchema>::schema()
+    };
+    (@with_schema [$opt:tt $($opts:tt)*] $t:ty) => {
+        $crate::bytevec_impls!(@with_schema [$($opts)*] $t)
+    };
+    (@with_schema [] $t:ty) => {
+        <$t as $crate::ByteSchema>::schema()
+    };
+    (@size_width [(size [$size:ty]) $($opts:tt)*]) => {
+        Some($crate::FormatHeader::of::<$size>().size_width)
+    };
+    (@size_width [$opt:tt $($opts:tt)*]) => {
+        $crate::bytevec_impls!(@size_width [$($opts)*])
+    };
+    (@size_width []) => {
+        None
+    };
+
+    // The entries of a columnar struct are those of its size table. They're decoded
+    // from a buffer made of the entries back to back, as the fields of a struct are.
+    (@impl_columns {$name:ident $sopts:tt} $({$field:tt : $t:ty; $($opts:tt)*})*) => {
+        #[allow(unused_assignments, unused_mut, unused_variables, clippy::needless_update)]
+        impl $crate::ByteColumns for $name {
+            const COLUMNS: usize = 0 $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*]
+                {1} {0}))*;
+
+            fn encode_columns<Size>(&self) -> $crate::BVEncodeResult<Vec<Vec<u8>>>
+                where Size: $crate::BVSize + $crate::ByteEncodable
             {
-                fn get_size<Size>(&self) -> Option<Size>
-                    where Size: $crate::BVSize + $crate::ByteEncodable
+                let mut entries = Vec::with_capacity(Self::COLUMNS);
+                $(
+                    $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                        entries.push(if $crate::bytevec_impls!(@skipped [$($opts)*] self) {
+                            Vec::new()
+                        } else {
+                            $crate::bytevec_impls!(@field_encode [$($opts)*] $t, self.$field)?
+                        });
+                    } {});
+                )*
+                Ok(entries)
+            }
+
+            fn decode_columns<Size>(entries: &[&[u8]]) -> $crate::BVDecodeResult<$name>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if entries.len() != Self::COLUMNS {
+                    return Err($crate::errors::ByteVecError::LengthMismatchError {
+                        expected: Self::COLUMNS,
+                        actual: entries.len(),
+                    });
+                }
+                let bytes = entries.concat();
+                let bytes = &bytes[..];
+                let mut index = 0;
+                let mut slot = 0;
+                let mut sizes = [0usize; Self::COLUMNS];
+                for (size, entry) in sizes.iter_mut().zip(entries) {
+                    *size = entry.len();
+                }
+                let unknown: Vec<Vec<u8>> = Vec::new();
+                Ok($crate::bytevec_impls!(@struct_lit $sopts [$([$($opts)*])*] $name {
+                    $($field: $crate::bytevec_impls!(@decode_field $sopts [$($opts)*]
+                        $field: $t, bytes index sizes slot unknown),)*
+                }))
+            }
+        }
+    };
+
+    // A packed struct only has fields of fixed size, so it doesn't need a size table.
+    // An aligned one pads its fields as a `#[repr(C)]` struct does, and checks that
+    // its declaration has that layout.
+    (@impl_packed {$name:ident $sopts:tt} $({$field:tt : $t:ty; $($opts:tt)*})*) => {
+        impl $name {
+            /// The length of the byte representation of the `struct`
+            pub const ENCODED_SIZE: usize = $crate::bytevec_impls!(@if aligned $sopts {
+                $crate::aligned_layout(&[$((<$t as $crate::FixedByteSize>::SIZE,
+                                            <$t as $crate::FixedByteSize>::ALIGN)),*]).0
+            } {
+                0 $(+ <$t as $crate::FixedByteSize>::SIZE)*
+            });
+        }
+
+        impl $crate::FixedByteSize for $name {
+            const SIZE: usize = $name::ENCODED_SIZE;
+            const ALIGN: usize = $crate::bytevec_impls!(@if aligned $sopts {
+                $crate::aligned_layout(&[$((<$t as $crate::FixedByteSize>::SIZE,
+                                            <$t as $crate::FixedByteSize>::ALIGN)),*]).1
+            } {
+                1
+            });
+
+            // The fields are decoded into their place in the slot, which requires every
+            // field of the `struct` to be listed
+            $crate::bytevec_impls! { @if complete $sopts {
+                #[allow(unused_assignments)]
+                fn decode_in_place<Size>(slot: &mut ::std::mem::MaybeUninit<$name>,
+                                         bytes: &[u8]) -> $crate::BVDecodeResult<()>
+                    where Size: $crate::BVSize + $crate::ByteDecodable
                 {
-                    let mut size = Some(Size::from_usize(0));
+                    if bytes.len() != $name::ENCODED_SIZE {
+                        return Err($crate::errors::ByteVecError::BadSizeDecodeError {
+                            expected: $crate::errors::BVExpectedSize::EqualTo(
+                                $name::ENCODED_SIZE),
+                            actual: bytes.len(),
+                            offset: 0
+                        });
+                    }
+                    let value = slot.as_mut_ptr();
+                    let mut index = 0;
+                    $(
+                        $crate::bytevec_impls!(@if aligned $sopts {
+                            index = $crate::align_up(index,
+                                                     <$t as $crate::FixedByteSize>::ALIGN);
+                        } {});
+                        let size = <$t as $crate::FixedByteSize>::SIZE;
+                        // SAFETY: the field lies within the `struct` of the slot, and a
+                        // `MaybeUninit` of its type has the same layout
+                        let field = unsafe {
+                            &mut *(::std::ptr::addr_of_mut!((*value).$field)
+                                   as *mut ::std::mem::MaybeUninit<$t>)
+                        };
+                        $crate::bytevec_impls!(@field_decode_in_place [$($opts)*] $t, field,
+                            &bytes[index..index + size])
+                            .map_err(|error| error.at(index).in_field(stringify!($field)))?;
+                        index += size;
+                    )*
+                    Ok(())
+                }
+            } {} }
+        }
+
+        $crate::bytevec_impls!(@if aligned $sopts {
+            const _: () = assert!(
+                $crate::is_aligned_layout(
+                    &[$(::std::mem::offset_of!($name, $field)),*],
+                    &[$((<$t as $crate::FixedByteSize>::SIZE,
+                         <$t as $crate::FixedByteSize>::ALIGN)),*],
+                    ::std::mem::size_of::<$name>(),
+                    ::std::mem::align_of::<$name>()),
+                concat!("the fields of ", stringify!($name),
+                        " don't make up its #[repr(C)] layout"));
+        } {});
+
+        impl $crate::ByteEncodable for $name
+        {
+            fn get_size<Size>(&self) -> Option<Size>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                if $name::ENCODED_SIZE <= Size::max_value().as_usize() {
+                    Some(Size::from_usize($name::ENCODED_SIZE))
+                } else {
+                    None
+                }
+            }
+
+            fn encode<Size>(&self) -> $crate::BVEncodeResult<Vec<u8>>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                if self.get_size::<Size>().is_some() {
+                    let mut bytes = Vec::with_capacity($name::ENCODED_SIZE);
                     $(
+                        $crate::bytevec_impls!(@if aligned $sopts {
+                            let align = <$t as $crate::FixedByteSize>::ALIGN;
+                            bytes.resize($crate::align_up(bytes.len(), align), 0);
+                        } {});
+                        bytes.extend_from_slice(&$crate::bytevec_impls!(
+                            @field_encode [$($opts)*] $t, self.$field)?);
+                    )*
+                    bytes.resize($name::ENCODED_SIZE, 0);
+                    Ok(bytes)
+                } else {
+                    Err($crate::errors::ByteVecError::OverflowError)
+                }
+            }
+        }
+
+        #[allow(unused_assignments, clippy::needless_update)]
+        impl $crate::ByteDecodable for $name {
+            fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$name>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if bytes.len() == $name::ENCODED_SIZE {
+                    let mut index = 0;
+                    Ok($crate::bytevec_impls!(@struct_lit $sopts [$([$($opts)*])*] $name {
+                        $($field: {
+                            $crate::bytevec_impls!(@if aligned $sopts {
+                                index = $crate::align_up(index,
+                                                         <$t as $crate::FixedByteSize>::ALIGN);
+                            } {});
+                            let size = <$t as $crate::FixedByteSize>::SIZE;
+                            let field = $crate::bytevec_impls!(@field_decode [$($opts)*] $t,
+                                &bytes[index..index + size])
+                                .map_err(|error| error.at(index).in_field(stringify!($field)))?;
+                            index += size;
+                            field
+                        },)*
+                    }))
+                } else {
+                    Err($crate::errors::ByteVecError::BadSizeDecodeError {
+                        expected: $crate::errors::BVExpectedSize::EqualTo($name::ENCODED_SIZE),
+                        actual: bytes.len(),
+                        offset: 0
+                    })
+                }
+            }
+
+            fn decode_with_len<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<($name, usize)>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                let len = ::std::cmp::min(bytes.len(), $name::ENCODED_SIZE);
+                Ok(($name::decode::<Size>(&bytes[..len])?, len))
+            }
+        }
+    };
+
+    // A transparent newtype has the same byte representation as its only field.
+    (@impl_transparent $name:ident {$field:tt : $t:ty;}) => {
+        impl $crate::ByteEncodable for $name
+        {
+            fn get_size<Size>(&self) -> Option<Size>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                self.$field.get_size::<Size>()
+            }
+
+            fn encode<Size>(&self) -> $crate::BVEncodeResult<Vec<u8>>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                self.$field.encode::<Size>()
+            }
+
+            fn encode_to<Size, W>(&self, writer: &mut W) -> $crate::BVEncodeResult<()>
+                where Size: $crate::BVSize + $crate::ByteEncodable,
+                      W: ::std::io::Write + ?Sized
+            {
+                self.$field.encode_to::<Size, W>(writer)
+            }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> $crate::BVEncodeResult<()>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                self.$field.encode_to_buf::<Size>(out)
+            }
+        }
+
+        impl $crate::ByteDecodable for $name {
+            fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$name>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                Ok($name {
+                    $field: <$t as $crate::ByteDecodable>::decode::<Size>(bytes)?
+                })
+            }
+
+            fn decode_with_len<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<($name, usize)>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                let (value, len) = <$t as $crate::ByteDecodable>::decode_with_len::<Size>(bytes)?;
+                Ok(($name { $field: value }, len))
+            }
+        }
+    };
+    (@impl_transparent $name:ident $($fields:tt)*) => {
+        compile_error!(concat!("a transparent impl must have exactly one field without options: ",
+                               stringify!($name)));
+    };
+
+    (@impl_struct {$name:ident $sopts:tt} $({$field:tt : $t:ty; $($opts:tt)*})*) => {
+        #[allow(unused_assignments)]
+        impl $crate::ByteEncodable for $name
+        {
+            fn get_size<Size>(&self) -> Option<Size>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                let mut size = $crate::bytevec_impls!(@if version $sopts {
+                    if ($crate::bytevec_impls!(@get version $sopts) as usize) <=
+                        Size::max_value().as_usize() {
+                        Some($crate::size_len::<Size>(
+                            $crate::bytevec_impls!(@get version $sopts) as usize))
+                    } else {
+                        None
+                    }
+                } {
+                    Some(Size::from_usize(0))
+                });
+                let elided = $crate::bytevec_impls!(@elided_entry $sopts $([$($opts)*])*);
+                let mut entry = 0;
+                $(
+                    $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                        let field_size: Option<Size> =
+                            if $crate::bytevec_impls!(@skipped [$($opts)*] self) {
+                                Some(Size::from_usize(0))
+                            } else {
+                                $crate::bytevec_impls!(@field_get_size [$($opts)*] $t,
+                                    self.$field)
+                            };
                         size = size.and_then(|size: Size|
-                            self.$field.get_size::<Size>().and_then(|field_size|
+                            field_size.and_then(|field_size| {
+                                let header_len = if Size::bincode() || elided == Some(entry) {
+                                    Size::from_usize(0)
+                                } else {
+                                    $crate::size_len::<Size>(field_size.as_usize())
+                                };
                                 size.checked_add(field_size).and_then(
-                                    |acc_size| acc_size.checked_add(
-                                        Size::get_size_of())
+                                    |acc_size| acc_size.checked_add(header_len)
                                 )
-                            )
+                            })
                         );
-                    )*
-                    size
+                        entry += 1;
+                    } {});
+                )*
+                $crate::bytevec_impls!(@if extensible $sopts {
+                    let known = 0usize $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*]
+                        {1} {0}))*;
+                    let unknown = $crate::bytevec_impls!(@unknown_fields self
+                        $({$field; $($opts)*})*);
+                    let count = known + unknown.map_or(0, |unknown| unknown.len());
+                    size = size.and_then(|size: Size| if count <= Size::max_value().as_usize() {
+                        size.checked_add($crate::size_len::<Size>(count))
+                    } else {
+                        None
+                    });
+                    if let Some(unknown) = unknown {
+                        size = size.and_then(|size: Size| {
+                            unknown.table_size::<Size>().and_then(|table_size| {
+                                size.checked_add(table_size)
+                            })
+                        });
+                    }
+                } {});
+                size
+            }
+
+            fn encode<Size>(&self) -> $crate::BVEncodeResult<Vec<u8>>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                $crate::encode_buffered::<Self, Size>(self)
+            }
+
+            // The fields are written into the buffer, and their sizes are filled in afterwards
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> $crate::BVEncodeResult<()>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                let start = out.len();
+                $crate::bytevec_impls!(@if version $sopts {
+                    $crate::write_size::<Size>(out,
+                        $crate::bytevec_impls!(@get version $sopts) as usize)?;
+                } {});
+                const KNOWN: usize = 0 $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*]
+                    {1} {0}))*;
+                let known = KNOWN;
+                let unknown = $crate::bytevec_impls!(@unknown_fields self
+                    $({$field; $($opts)*})*);
+                let count = known + unknown.map_or(0, |unknown| unknown.len());
+                $crate::bytevec_impls!(@if extensible $sopts {
+                    $crate::write_size::<Size>(out, count)?;
+                } {});
+                let elided = $crate::bytevec_impls!(@elided_entry $sopts $([$($opts)*])*);
+                let mut table = $crate::SizeTable::new::<Size>(out,
+                    count - elided.map_or(0, |_| 1));
+                let mut entry = 0;
+                $(
+                    $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                        let entry_start = out.len();
+                        if !$crate::bytevec_impls!(@skipped [$($opts)*] self) {
+                            $crate::bytevec_impls!(@field_encode_to_buf [$($opts)*] $t,
+                                self.$field, out);
+                        }
+                        if elided != Some(entry) {
+                            table.entry::<Size>(out, entry_start)?;
+                        }
+                        entry += 1;
+                    } {});
+                )*
+                if let Some(unknown) = unknown {
+                    for unknown_entry in unknown.iter() {
+                        let entry_start = out.len();
+                        out.extend_from_slice(unknown_entry);
+                        table.entry::<Size>(out, entry_start)?;
+                    }
                 }
+                table.finish(out);
+                $crate::check_encoded_len::<Size>(out, start)
+            }
 
-                fn encode<Size>(&self) -> $crate::BVEncodeResult<Vec<u8>>
-                    where Size: $crate::BVSize + $crate::ByteEncodable
-                {
-                    if self.get_size::<Size>().is_some() {
-                        let mut bytes = Vec::new();
-                        $(
-                            let field_size: Option<Size> = self.$field.get_size::<Size>();
-                            bytes.extend_from_slice(&try!(
-                                field_size.unwrap().encode::<Size>()));
-                        )*
+            fn encode_to<Size, W>(&self, writer: &mut W) -> $crate::BVEncodeResult<()>
+                where Size: $crate::BVSize + $crate::ByteEncodable,
+                      W: ::std::io::Write + ?Sized
+            {
+                if self.get_size::<Size>().is_some() {
+                    $crate::bytevec_impls!(@if version $sopts {
+                        let version = $crate::bytevec_impls!(@get version $sopts) as usize;
+                        Size::from_usize(version).encode_to::<Size, W>(writer)?;
+                    } {});
+                    let unknown = $crate::bytevec_impls!(@unknown_fields self
+                        $({$field; $($opts)*})*);
+                    $crate::bytevec_impls!(@if extensible $sopts {
+                        let known = 0usize $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*]
+                            {1} {0}))*;
+                        let count = known + unknown.map_or(0, |unknown| unknown.len());
+                        Size::from_usize(count).encode_to::<Size, W>(writer)?;
+                    } {});
+                    if !Size::bincode() {
+                        let elided = $crate::bytevec_impls!(@elided_entry $sopts
+                            $([$($opts)*])*);
+                        let mut entry = 0;
                         $(
-                            bytes.extend_from_slice(&try!(self.$field.encode::<Size>()));
+                            $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                                let field_size: Option<Size> =
+                                    if $crate::bytevec_impls!(@skipped [$($opts)*] self) {
+                                        Some(Size::from_usize(0))
+                                    } else {
+                                        $crate::bytevec_impls!(@field_get_size [$($opts)*] $t,
+                                            self.$field)
+                                    };
+                                if elided != Some(entry) {
+                                    field_size.unwrap().encode_to::<Size, W>(writer)?;
+                                }
+                                entry += 1;
+                            } {});
                         )*
-                        Ok(bytes)
-                    } else {
-                        Err($crate::errors::ByteVecError::OverflowError)
+                        if let Some(unknown) = unknown {
+                            unknown.encode_sizes_to::<Size, W>(writer)?;
+                        }
                     }
+                    $(
+                        $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                            if !$crate::bytevec_impls!(@skipped [$($opts)*] self) {
+                                $crate::bytevec_impls!(@field_encode_to [$($opts)*] $t,
+                                    self.$field, writer);
+                            }
+                        } {});
+                    )*
+                    if let Some(unknown) = unknown {
+                        unknown.encode_entries_to(writer)?;
+                    }
+                    Ok(())
+                } else {
+                    Err($crate::errors::ByteVecError::OverflowError)
                 }
             }
+        }
 
-            #[allow(dead_code, unused_assignments)]
-            impl $crate::ByteDecodable for $name {
-                fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$name>
-                    where Size: $crate::BVSize + $crate::ByteDecodable
-                {
-                    let mut index = 0;
-                    let mut sizes = ::std::collections::HashMap::new();
-                    $(
-                        if bytes[index..].len() >= Size::get_size_of().as_usize() {
-                            sizes.insert(stringify!($field),
-                                try!(Size::decode::<Size>(
-                                    &bytes[index..index + Size::get_size_of().as_usize()])));
-                            index += Size::get_size_of().as_usize();
-                        }
-                        else {
-                            return Err($crate::errors::ByteVecError::BadSizeDecodeError {
-                                expected: $crate::errors::BVExpectedSize::MoreThan(
-                                    Size::get_size_of().as_usize() + index),
-                                actual: bytes.len()
+        #[allow(dead_code, unused_assignments, unused_mut, unused_variables,
+                clippy::needless_update)]
+        impl $crate::ByteDecodable for $name {
+            fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$name>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if Size::bincode() {
+                    return $crate::decode_exact::<$name, Size>(bytes);
+                }
+                let _nested = $crate::enter_nested()?;
+                let mut index = 0;
+                $crate::bytevec_impls!(@if version $sopts {
+                    let (version, version_len) = $crate::read_size::<Size>(bytes)?;
+                    index += version_len;
+                    if version != $crate::bytevec_impls!(@get version $sopts) as usize {
+                        $crate::bytevec_impls!(@migrate $sopts $name version &bytes[index..]);
+                        return Err($crate::errors::ByteVecError::UnknownVersionError(version));
+                    }
+                } {});
+                // An extensible struct has the number of entries, which can be more or
+                // fewer than the known fields
+                const KNOWN: usize = 0 $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*]
+                    {1} {0}))*;
+                let known = KNOWN;
+                let count = $crate::bytevec_impls!(@if extensible $sopts {{
+                    let (count, count_len) = $crate::read_size::<Size>(&bytes[index..])
+                        .map_err(|error| error.at(index))?;
+                    index += count_len;
+                    count
+                }} {
+                    known
+                });
+                // The sizes of the known fields are held on the stack, in the order of
+                // the fields. A compact struct gets the size of its last entry from the
+                // length of the buffer.
+                let mut known_sizes = [0usize; KNOWN];
+                let mut filled = 0;
+                $crate::bytevec_impls!(@if compact $sopts {
+                    let (compact_sizes, table_len) =
+                        $crate::compact_table::<Size>(&bytes[index..], count)
+                            .map_err(|error| error.at(index))?;
+                    index += table_len;
+                } {});
+                $(
+                    $crate::bytevec_impls!(@if_serialized [$($opts)*] {
+                        if filled < count {
+                            $crate::bytevec_impls!(@if compact $sopts {
+                                known_sizes[filled] = compact_sizes[filled];
+                            } {
+                                let (size, size_len) =
+                                    $crate::read_size::<Size>(&bytes[index..])
+                                        .map_err(|error| error.at(index))?;
+                                known_sizes[filled] = size;
+                                index += size_len;
                             });
+                            filled += 1;
                         }
-                    )*
+                    } {});
+                )*
+                let sizes = &known_sizes[..filled];
+                let mut slot = 0;
+                let (unknown_sizes, table_len) = $crate::read_size_table::<Size>(&bytes[index..],
+                    count.saturating_sub(known)).map_err(|error| error.at(index))?;
+                index += table_len;
 
-                    let body_size = sizes.values().fold(0, |acc, ref size| acc + size.as_usize());
-                    if body_size == bytes[index..].len() {
-                        Ok($name {
-                            $(
-                                $field: {
-                                    let size = sizes[stringify!($field)].as_usize();
-                                    let field = try!(<$t as $crate::ByteDecodable>::decode::<Size>(
-                                        &bytes[index..index + size]));
-                                    index += size;
-                                    field
-                                },
-                            )*
-                            ..Default::default()
-                        })
-                    } else {
-                        Err($crate::errors::ByteVecError::BadSizeDecodeError {
-                            expected: $crate::errors::BVExpectedSize::EqualTo(
-                                Size::get_size_of().as_usize() * sizes.len() + body_size),
-                            actual: bytes.len()
+                let known_size = sizes.iter()
+                    .try_fold(0usize, |acc, size| acc.checked_add(*size))
+                    .ok_or($crate::errors::ByteVecError::SizeOverflowError)?;
+                let body_size = unknown_sizes.iter()
+                    .try_fold(known_size, |acc, size| acc.checked_add(*size))
+                    .ok_or($crate::errors::ByteVecError::SizeOverflowError)?;
+                if body_size == bytes[index..].len() {
+                    let mut unknown_index = index + known_size;
+                    let unknown = unknown_sizes.iter()
+                        .map(|size| {
+                            unknown_index += size;
+                            bytes[unknown_index - size..unknown_index].to_vec()
                         })
+                        .collect::<Vec<_>>();
+                    Ok($crate::bytevec_impls!(@struct_lit $sopts [$([$($opts)*])*] $name {
+                        $($field: $crate::bytevec_impls!(@decode_field $sopts [$($opts)*]
+                            $field: $t, bytes index sizes slot unknown),)*
+                    }))
+                } else {
+                    Err($crate::errors::ByteVecError::BadSizeDecodeError {
+                        expected: $crate::errors::BVExpectedSize::EqualTo(
+                            index.saturating_add(body_size)),
+                        actual: bytes.len(),
+                        offset: 0
+                    })
+                }
+            }
+
+            // The layout of an older version may differ, so a versioned struct
+            // reads the whole buffer, and so does a compact one, which needs it to
+            // find the size of its last entry. In the layout of bincode, the fields
+            // are read one after another.
+            fn decode_with_len<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<($name, usize)>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if Size::bincode() {
+                    let _nested = $crate::enter_nested()?;
+                    let mut index = 0;
+                    $crate::bytevec_impls!(@if version $sopts {
+                        let (version, version_len) = $crate::read_size::<Size>(bytes)?;
+                        index += version_len;
+                        if version != $crate::bytevec_impls!(@get version $sopts) as usize {
+                            return Err($crate::errors::ByteVecError::UnknownVersionError(version));
+                        }
+                    } {});
+                    let value = $crate::bytevec_impls!(@struct_lit $sopts [$([$($opts)*])*] $name {
+                        $($field: $crate::bytevec_impls!(@bincode_field [$($opts)*]
+                            $field: $t, bytes index),)*
+                    });
+                    return Ok((value, index));
+                }
+                $crate::bytevec_impls!(@if_whole_buffer $sopts {
+                    $name::decode::<Size>(bytes).map(|value| (value, bytes.len()))
+                } {{
+                    let entries = 0 $(+ $crate::bytevec_impls!(@if_serialized [$($opts)*] {1} {0}))*;
+                    let len = $crate::bytevec_impls!(@if extensible $sopts {{
+                        let (entries, count_len) = $crate::read_size::<Size>(bytes)?;
+                        $crate::table_len::<Size>(&bytes[count_len..], entries)
+                            .map_err(|error| error.at(count_len))?
+                            .checked_add(count_len)
+                            .ok_or($crate::errors::ByteVecError::SizeOverflowError)?
+                    }} {
+                        $crate::table_len::<Size>(bytes, entries)?
+                    });
+                    Ok(($name::decode::<Size>(&bytes[..len])?, len))
+                }})
+            }
+        }
+    };
+
+    // Enum variant muncher. Normalizes each variant into its tag, its name, its match
+    // pattern, its bindings with their types and its constructor expression.
+    (@enum_variants $name:ident $sopts:tt $done:tt $tags:tt #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@enum_variants $name $sopts $done $tags $($rest)*);
+    };
+    (@enum_variants $name:ident $sopts:tt $done:tt $tags:tt
+        $variant:ident ($($t:ty),* $(,)?) $(, $($rest:tt)*)?) => {
+        $crate::bytevec_impls!(@enum_tuple $name $sopts $done $tags $variant []
+            [f0 f1 f2 f3 f4 f5 f6 f7 f8 f9 f10 f11] [$($t,)*] [$($($rest)*)?]);
+    };
+    (@enum_variants $name:ident $sopts:tt [$($done:tt)*] [$tag:tt $($tags:tt)*]
+        $variant:ident {$($(#[$($attr:tt)*])* $field:ident : $t:ty),* $(,)?}
+        $(, $($rest:tt)*)?) => {
+        $crate::bytevec_impls!(@enum_variants $name $sopts
+            [$($done)* {$tag $variant [$name::$variant {$(ref $field),*}] [$($field: $t),*]
+                [$name::$variant {$($field),*}]}]
+            [$($tags)*] $($($rest)*)?);
+    };
+    (@enum_variants $name:ident $sopts:tt [$($done:tt)*] [$tag:tt $($tags:tt)*]
+        $variant:ident $(, $($rest:tt)*)?) => {
+        $crate::bytevec_impls!(@enum_variants $name $sopts
+            [$($done)* {$tag $variant [$name::$variant] [] [$name::$variant]}]
+            [$($tags)*] $($($rest)*)?);
+    };
+    (@enum_variants $name:ident $sopts:tt [$($done:tt)*] $tags:tt) => {
+        $crate::bytevec_impls!(@impl_enum $name $sopts $($done)*);
+    };
+    (@enum_variants $name:ident $sopts:tt $done:tt $tags:tt $($rest:tt)*) => {
+        compile_error!(concat!("unsupported enum variant: ", stringify!($($rest)*)));
+    };
+
+    // Names the fields of a tuple variant after the next unused binding
+    (@enum_tuple $name:ident $sopts:tt $done:tt $tags:tt $variant:ident
+        [$($b:ident: $bt:ty,)*] [$next:ident $($pool:ident)*] [$t:ty, $($ts:tt)*] $rest:tt) => {
+        $crate::bytevec_impls!(@enum_tuple $name $sopts $done $tags $variant
+            [$($b: $bt,)* $next: $t,] [$($pool)*] [$($ts)*] $rest);
+    };
+    (@enum_tuple $name:ident $sopts:tt [$($done:tt)*] [$tag:tt $($tags:tt)*] $variant:ident
+        [$($b:ident: $bt:ty,)*] $pool:tt [] [$($rest:tt)*]) => {
+        $crate::bytevec_impls!(@enum_variants $name $sopts
+            [$($done)* {$tag $variant [$name::$variant($(ref $b),*)] [$($b: $bt),*]
+                [$name::$variant($($b),*)]}]
+            [$($tags)*] $($rest)*);
+    };
+
+    // The fields of a variant are serialized as a tuple, unless there are none.
+    (@enum_payload get_size []) => {
+        Some(Size::from_usize(0))
+    };
+    (@enum_payload get_size [$($b:ident)+]) => {
+        (&($($b,)+)).get_size::<Size>()
+    };
+    (@enum_payload encode_to_buf [] $out:ident) => {};
+    (@enum_payload encode_to_buf [$($b:ident)+] $out:ident) => {
+        (&($($b,)+)).encode_to_buf::<Size>($out)?
+    };
+
+    (@impl_enum $name:ident $sopts:tt
+        $({$tag:tt $variant:ident [$($pat:tt)*] [$($b:ident: $t:ty),*] [$($ctor:tt)*]})*) => {
+        #[allow(clippy::absurd_extreme_comparisons)]
+        impl $crate::ByteEncodable for $name
+        {
+            fn get_size<Size>(&self) -> Option<Size>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                match *self {
+                    $($($pat)* => {
+                        if Size::bincode() {
+                            $crate::bytevec_impls!(@enum_payload get_size [$($b)*])
+                                .and_then(|size: Size| size.checked_add(Size::from_usize(4)))
+                                .filter(|_| $tag <= u32::MAX as usize)
+                        } else if $tag <= Size::max_value().as_usize() {
+                            $crate::bytevec_impls!(@enum_payload get_size [$($b)*])
+                                .and_then(|size: Size|
+                                    size.checked_add($crate::size_len::<Size>($tag)))
+                        } else {
+                            None
+                        }
+                    })*
+                }
+            }
+
+            fn encode<Size>(&self) -> $crate::BVEncodeResult<Vec<u8>>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                $crate::encode_buffered::<Self, Size>(self)
+            }
+
+            fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> $crate::BVEncodeResult<()>
+                where Size: $crate::BVSize + $crate::ByteEncodable
+            {
+                let start = out.len();
+                match *self {
+                    $($($pat)* => {
+                        if !Size::bincode() {
+                            $crate::write_size::<Size>(out, $tag)?;
+                        } else if $tag <= u32::MAX as usize {
+                            ($tag as u32).encode_to::<Size, _>(out)?;
+                        } else {
+                            return Err($crate::errors::ByteVecError::OverflowError);
+                        }
+                        $crate::bytevec_impls!(@enum_payload encode_to_buf [$($b)*] out);
+                    })*
+                }
+                $crate::check_encoded_len::<Size>(out, start)
+            }
+        }
+
+        impl $crate::ByteDecodable for $name {
+            fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$name>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if Size::bincode() {
+                    return $crate::decode_exact::<$name, Size>(bytes);
+                }
+                let _nested = $crate::enter_nested()?;
+                let (tag, index) = $crate::read_size::<Size>(bytes)?;
+                match tag {
+                    $($tag => {
+                        $crate::bytevec_impls!(@if_empty [$($b)*] {
+                            if bytes.len() != index {
+                                return Err($crate::errors::ByteVecError::BadSizeDecodeError {
+                                    expected: $crate::errors::BVExpectedSize::EqualTo(index),
+                                    actual: bytes.len(),
+                                    offset: 0
+                                });
+                            }
+                        } {
+                            let ($($b,)*) = <($($t,)*)>::decode::<Size>(&bytes[index..])
+                                .map_err(|error| error.at(index).in_field(stringify!($variant)))?;
+                        });
+                        Ok($($ctor)*)
+                    })*
+                    _ => Err($crate::errors::ByteVecError::UnknownVariantError(tag))
+                }
+            }
+
+            // In the layout of bincode, the tag is a `u32` and the fields of the
+            // variant follow it without a size table.
+            fn decode_with_len<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<($name, usize)>
+                where Size: $crate::BVSize + $crate::ByteDecodable
+            {
+                if Size::bincode() {
+                    let _nested = $crate::enter_nested()?;
+                    let (tag, index) = <u32 as $crate::ByteDecodable>::decode_with_len::<Size>(
+                        bytes)?;
+                    return match tag as usize {
+                        $($tag => {
+                            let (($($b,)*), len) =
+                                <($($t,)*)>::decode_with_len::<Size>(&bytes[index..])
+                                    .map_err(|error| {
+                                        error.at(index).in_field(stringify!($variant))
+                                    })?;
+                            Ok(($($ctor)*, index + len))
+                        })*
+                        tag => Err($crate::errors::ByteVecError::UnknownVariantError(tag))
+                    };
+                }
+                let (tag, index) = $crate::read_size::<Size>(bytes)?;
+                let len = match tag {
+                    $($tag => index + $crate::table_len::<Size>(&bytes[index..],
+                        <[&str]>::len(&[$(stringify!($b)),*])).map_err(|error| error.at(index))?,)*
+                    _ => return Err($crate::errors::ByteVecError::UnknownVariantError(tag))
+                };
+                Ok(($name::decode::<Size>(&bytes[..len])?, len))
+            }
+        }
+
+        $crate::bytevec_impls! { @if schema $sopts {
+            impl $crate::ByteSchema for $name {
+                fn schema() -> $crate::Schema {
+                    $crate::Schema::Enum {
+                        name: stringify!($name),
+                        variants: vec![$(
+                            $crate::VariantSchema {
+                                name: stringify!($variant),
+                                tag: $tag,
+                                fields: vec![$(<$t as $crate::ByteSchema>::schema()),*],
+                            }
+                        ),*],
                     }
                 }
             }
-        )*
+        } {}}
+    };
+
+    (@if_empty [] {$($then:tt)*} $_else:tt) => {
+        $($then)*
+    };
+    (@if_empty [$($item:tt)+] $_then:tt {$($else:tt)*}) => {
+        $($else)*
+    };
+
+    // Enum impls, which only take the `schema` option
+    ($(#[$($attr:tt)*])* impl enum $name:ident {$($body:tt)*} $($rest:tt)*) => {
+        $crate::bytevec_impls!(@enum [] $(#[$($attr)*])* $name {$($body)*});
+        $crate::bytevec_impls!($($rest)*);
+    };
+    (@enum [$($sopts:tt)*] #[bytevec(schema)] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@enum [$($sopts)* (schema [])] $($rest)*);
     };
+    (@enum $sopts:tt #[bytevec $($args:tt)*] $($rest:tt)*) => {
+        compile_error!(concat!("unsupported bytevec option for an enum: ",
+            stringify!($($args)*)));
+    };
+    (@enum $sopts:tt #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_impls!(@enum $sopts $($rest)*);
+    };
+    (@enum $sopts:tt $name:ident {$($body:tt)*}) => {
+        $crate::bytevec_impls!(@enum_variants $name $sopts []
+            [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30
+             31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58
+             59 60 61 62 63] $($body)*);
+    };
+
+    ($(#[$($attr:tt)*])* impl $name:ident $body:tt $($rest:tt)*) => {
+        $crate::bytevec_impls!(@struct $name [] $(#[$($attr)*])* $body);
+        $crate::bytevec_impls!($($rest)*);
+    };
+
+    () => {};
 }
 
 
@@ -145,6 +1900,17 @@ macro_rules! bytevec_impls {
 /// automatically implement both the `ByteEncodable` and `ByteDecodable` traits,
 /// as long as all of the fields also implement both traits.
 ///
+/// The declared field list is always complete, so the `struct` doesn't implement nor
+/// require [`Default`](http://doc.rust-lang.org/stable/std/default/trait.Default.html),
+/// and its fields can be of any type that implements the serialization traits.
+///
+/// Both the `struct` and each of its fields can have any visibility, such as `pub`
+/// or `pub(crate)`, and any attribute or doc comment. The `#[bytevec(...)]` attributes
+/// are handed over to [`bytevec_impls`](macro.bytevec_impls.html), and every other
+/// attribute is kept on the `struct` declaration. Tuple structs, like
+/// `pub struct Meters(pub f64);`, can be declared as well, and so can enums, which are
+/// serialized as described in the documentation of `bytevec_impls`.
+///
 /// # Examples
 ///
 /// ```rust
@@ -154,10 +1920,11 @@ macro_rules! bytevec_impls {
 /// # use bytevec::{ByteEncodable, ByteDecodable};
 /// #
 /// bytevec_decl! {
+///     /// A point in a 2D plane
 ///     #[derive(PartialEq, Eq, Debug)]
 ///     pub struct Point {
-///         x: u32,
-///         y: u32
+///         pub x: u32,
+///         pub(crate) y: u32
 ///     }
 /// }
 ///
@@ -170,36 +1937,200 @@ macro_rules! bytevec_impls {
 /// ```
 #[macro_export]
 macro_rules! bytevec_decl {
-    {$($(#[$attr:meta])* struct $name:ident {$($field:ident : $t:ty),*})*} => {
-        $(
-            $(#[$attr])*
-            #[derive(Default)]
-            struct $name {
-                $($field: $t),*
+    // Struct declaration muncher. The `#[bytevec(...)]` attributes are forwarded
+    // to `bytevec_impls`, while the rest are kept on the declared `struct`.
+    (@struct [$($attrs:tt)*] [$($bv_attrs:tt)*] #[bytevec $($args:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@struct [$($attrs)*] [$($bv_attrs)* #[bytevec $($args)*]]
+            $($rest)*);
+    };
+    (@struct [$($attrs:tt)*] $bv_attrs:tt #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@struct [$($attrs)* #[$($attr)*]] $bv_attrs $($rest)*);
+    };
+    (@struct $attrs:tt $bv_attrs:tt $vis:vis struct $name:ident {$($body:tt)*} $($rest:tt)*) => {
+        $crate::bytevec_decl!(@fields {$attrs $bv_attrs [$vis] $name} [] [] [] []
+            $($body)*);
+        $crate::bytevec_decl!($($rest)*);
+    };
+    (@struct [$($attrs:tt)*] [$($bv_attrs:tt)*] $vis:vis enum $name:ident {$($body:tt)*}
+        $($rest:tt)*) => {
+        $($attrs)*
+        $vis enum $name {
+            $($body)*
+        }
+        $crate::bytevec_impls! {
+            $($bv_attrs)*
+            impl enum $name {
+                $($body)*
             }
-            bytevec_impls!(impl $name {$($field:$t),*});
-        )*
+        }
+        $crate::bytevec_decl!($($rest)*);
+    };
+    (@struct $attrs:tt $bv_attrs:tt $vis:vis struct $name:ident ($($body:tt)*); $($rest:tt)*) => {
+        $crate::bytevec_decl!(@tuple_fields {$attrs $bv_attrs [$vis] $name} [] [] [] []
+            $($body)*);
+        $crate::bytevec_decl!($($rest)*);
     };
 
-    {$($(#[$attr:meta])* pub struct $name:ident {$(pub $field:ident : $t:ty),*})*} => {
-        $(
-            $(#[$attr])*
-            #[derive(Default)]
-            pub struct $name {
-                $(pub $field: $t),*
+    // Field list muncher, splitting the field attributes the same way.
+    (@fields $head:tt $fields:tt $bv_fields:tt [$($attrs:tt)*] [$($bv_attrs:tt)*]
+        #[bytevec $($args:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@fields $head $fields $bv_fields [$($attrs)*]
+            [$($bv_attrs)* #[bytevec $($args)*]] $($rest)*);
+    };
+    (@fields $head:tt $fields:tt $bv_fields:tt [$($attrs:tt)*] $bv_attrs:tt
+        #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@fields $head $fields $bv_fields [$($attrs)* #[$($attr)*]]
+            $bv_attrs $($rest)*);
+    };
+    (@fields $head:tt [$($fields:tt)*] [$($bv_fields:tt)*] [$($attrs:tt)*] [$($bv_attrs:tt)*]
+        $vis:vis $field:ident : $t:ty $(, $($rest:tt)*)?) => {
+        $crate::bytevec_decl!(@fields $head
+            [$($fields)* $($attrs)* $vis $field: $t,]
+            [$($bv_fields)* $($bv_attrs)* $field: $t,] [] [] $($($rest)*)?);
+    };
+    (@fields {[$($attrs:tt)*] [$($bv_attrs:tt)*] [$vis:vis] $name:ident}
+        [$($fields:tt)*] [$($bv_fields:tt)*] [] []) => {
+        $($attrs)*
+        $vis struct $name {
+            $($fields)*
+        }
+        $crate::bytevec_impls! {
+            #[bytevec(complete)]
+            $($bv_attrs)*
+            impl $name {
+                $($bv_fields)*
             }
-            bytevec_impls!(impl $name {$($field:$t),*});
-        )*
+        }
+    };
+
+    (@tuple_fields $head:tt $fields:tt $bv_fields:tt [$($attrs:tt)*] [$($bv_attrs:tt)*]
+        #[bytevec $($args:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@tuple_fields $head $fields $bv_fields [$($attrs)*]
+            [$($bv_attrs)* #[bytevec $($args)*]] $($rest)*);
+    };
+    (@tuple_fields $head:tt $fields:tt $bv_fields:tt [$($attrs:tt)*] $bv_attrs:tt
+        #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::bytevec_decl!(@tuple_fields $head $fields $bv_fields [$($attrs)* #[$($attr)*]]
+            $bv_attrs $($rest)*);
+    };
+    (@tuple_fields $head:tt [$($fields:tt)*] [$($bv_fields:tt)*] [$($attrs:tt)*]
+        [$($bv_attrs:tt)*] $vis:vis $t:ty $(, $($rest:tt)*)?) => {
+        $crate::bytevec_decl!(@tuple_fields $head [$($fields)* $($attrs)* $vis $t,]
+            [$($bv_fields)* $($bv_attrs)* $t,] [] [] $($($rest)*)?);
+    };
+    (@tuple_fields {[$($attrs:tt)*] [$($bv_attrs:tt)*] [$vis:vis] $name:ident}
+        [$($fields:tt)*] [$($bv_fields:tt)*] [] []) => {
+        $($attrs)*
+        $vis struct $name($($fields)*);
+        $crate::bytevec_impls! {
+            #[bytevec(complete)]
+            $($bv_attrs)*
+            impl $name($($bv_fields)*)
+        }
+    };
+
+    (@$($rest:tt)*) => {
+        compile_error!(concat!("unexpected bytevec_decl input: ", stringify!($($rest)*)));
+    };
+
+    () => {};
+
+    ($($rest:tt)+) => {
+        $crate::bytevec_decl!(@struct [] [] $($rest)+);
+    };
+}
+
+/// Implements the byte serialization operations for foreign types through a local proxy.
+///
+/// The traits of this crate can't be implemented for types declared in other crates,
+/// so this macro declares a proxy type that implements [`ByteRemote`](trait.ByteRemote.html)
+/// for the foreign type instead. The proxy is then used with the `#[bytevec(with = Proxy)]`
+/// field attribute of [`bytevec_impls`](macro.bytevec_impls.html) and `bytevec_decl`.
+///
+/// The fields of the foreign type are listed the same way as in `bytevec_impls`, and a field
+/// followed by parentheses, like `len(): usize`, is read through a getter method instead.
+/// On deserialization, the value is built with a `struct` literal unless a constructor
+/// function is given after the fields, as in `=> Range::new;`, which is then called with
+/// the fields in order. The byte representation of the foreign type is the same as the one
+/// of a tuple of its fields, so up to 12 fields are supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// mod geometry {
+///     #[derive(PartialEq, Debug, Default)]
+///     pub struct Vec2 {
+///         pub x: f32,
+///         pub y: f32
+///     }
+/// }
+///
+/// bytevec_remote! {
+///     struct Vec2Def for geometry::Vec2 {
+///         x: f32,
+///         y: f32
+///     }
+/// }
+///
+/// bytevec_decl! {
+///     #[derive(PartialEq, Debug)]
+///     struct Sprite {
+///         #[bytevec(with = Vec2Def)]
+///         position: geometry::Vec2
+///     }
+/// }
+///
+/// fn main() {
+///     let s1 = Sprite {position: geometry::Vec2 {x: 1.0, y: 2.5}};
+///     let bytes = s1.encode::<u32>().unwrap();
+///     let s2 = Sprite::decode::<u32>(&bytes).unwrap();
+///     assert_eq!(s1, s2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! bytevec_remote {
+    (@construct $remote:ty, [$($field:ident),*]) => {{
+        type Remote = $remote;
+        Remote { $($field),* }
+    }};
+    (@construct $remote:ty, [$($field:ident),*] $ctor:expr) => {
+        ($ctor)($($field),*)
     };
 
-    {$($(#[$attr:meta])* pub struct $name:ident {$($field:ident : $t:ty),*})*} => {
+    {$($(#[$attr:meta])* $vis:vis struct $proxy:ident for $remote:ty {
+        $($field:ident $(($($args:tt)*))? : $t:ty),* $(,)?
+    } $(=> $ctor:expr;)?)*} => {
         $(
             $(#[$attr])*
-            #[derive(Default)]
-            pub struct $name {
-                $($field: $t),*
+            $vis struct $proxy;
+
+            impl $crate::ByteRemote<$remote> for $proxy {
+                fn get_size<Size>(value: &$remote) -> Option<Size>
+                    where Size: $crate::BVSize + $crate::ByteEncodable
+                {
+                    let fields: ($(&$t,)*) = ($(&value.$field $(($($args)*))?,)*);
+                    (&fields).get_size::<Size>()
+                }
+
+                fn encode<Size>(value: &$remote) -> $crate::BVEncodeResult<Vec<u8>>
+                    where Size: $crate::BVSize + $crate::ByteEncodable
+                {
+                    let fields: ($(&$t,)*) = ($(&value.$field $(($($args)*))?,)*);
+                    (&fields).encode::<Size>()
+                }
+
+                fn decode<Size>(bytes: &[u8]) -> $crate::BVDecodeResult<$remote>
+                    where Size: $crate::BVSize + $crate::ByteDecodable
+                {
+                    let ($($field,)*) =
+                        <($($t,)*) as $crate::ByteDecodable>::decode::<Size>(bytes)?;
+                    Ok($crate::bytevec_remote!(@construct $remote, [$($field),*] $($ctor)?))
+                }
             }
-            bytevec_impls!(impl $name {$($field:$t),*});
         )*
     };
 }
@@ -0,0 +1,251 @@
+use std::marker::PhantomData;
+
+use errors::{ByteVecError, BVExpectedSize};
+use schema::{FieldSchema, Schema};
+use {ByteEncodable, ByteDecodable, ByteSchema, BVDecodeResult, BVEncodeResult, BVSize,
+     compact_table, read_size, read_size_table};
+
+/// A reader of the byte representations written with an older layout of a `struct`.
+///
+/// The migrator is built from the [`Schema`](enum.Schema.html) of the old layout, and maps
+/// its fields by name onto the fields of the current layout of `T`, as given by its
+/// `ByteSchema` implementation. The fields that were removed are ignored, and the renamed
+/// ones are declared with `rename`. A field of the current layout that is missing from the
+/// old one, or whose description changed, takes the value given with `with_default`, or a
+/// size of 0 if it's skippable, and yields a `MissingFieldError` otherwise.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteSchema, Migrator};
+/// #[derive(Default)]
+/// struct UserV1 {
+///     id: u32,
+///     name: String,
+///     age: u8
+/// }
+///
+/// #[derive(PartialEq, Eq, Debug, Default)]
+/// struct User {
+///     id: u32,
+///     full_name: String,
+///     email: String
+/// }
+///
+/// bytevec_impls! {
+///     #[bytevec(schema)]
+///     impl UserV1 {
+///         id: u32,
+///         name: String,
+///         age: u8
+///     }
+///
+///     #[bytevec(schema)]
+///     impl User {
+///         id: u32,
+///         full_name: String,
+///         email: String
+///     }
+/// }
+///
+/// fn main() {
+///     let old = UserV1 {id: 1, name: "Ada".to_string(), age: 36};
+///     let bytes = old.encode::<u32>().unwrap();
+///     let migrator = Migrator::<User, u32>::new(UserV1::schema())
+///         .rename("name", "full_name")
+///         .with_default("email", "unknown").unwrap();
+///     assert_eq!(migrator.migrate(&bytes).unwrap(), User {
+///         id: 1,
+///         full_name: "Ada".to_string(),
+///         email: "unknown".to_string()
+///     });
+/// }
+/// ```
+pub struct Migrator<T, Size = u32> {
+    from: Layout,
+    to: Layout,
+    renames: Vec<(&'static str, &'static str)>,
+    defaults: Vec<(&'static str, Vec<u8>)>,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<T, Size> Migrator<T, Size>
+    where T: ByteDecodable + ByteSchema,
+          Size: BVSize + ByteEncodable + ByteDecodable
+{
+    /// Returns a migrator from the layout described by `from`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or the schema of `T` is not a `Schema::Struct`.
+    pub fn new(from: Schema) -> Migrator<T, Size> {
+        Migrator {
+            from: Layout::of(from),
+            to: Layout::of(T::schema()),
+            renames: Vec::new(),
+            defaults: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the field `old` of the old layout as the field `new` of the current one
+    pub fn rename(mut self, old: &'static str, new: &'static str) -> Migrator<T, Size> {
+        self.renames.push((old, new));
+        self
+    }
+
+    /// Sets the value of the field `name` of the current layout for the buffers that
+    /// don't have a compatible value for it. The value is serialized with `Size`, so its
+    /// byte representation must match the options of the field.
+    pub fn with_default<V>(mut self, name: &'static str, value: &V)
+        -> BVEncodeResult<Migrator<T, Size>>
+        where V: ByteEncodable + ?Sized
+    {
+        self.defaults.push((name, value.encode::<Size>()?));
+        Ok(self)
+    }
+
+    /// Deserializes a value of type `T` from a byte buffer written with the old layout
+    pub fn migrate(&self, bytes: &[u8]) -> BVDecodeResult<T> {
+        let old_values = self.from.read_fields::<Size>(bytes)?;
+        let mut values = Vec::with_capacity(self.to.fields.len());
+        for field in &self.to.fields {
+            let old_name = self.renames
+                .iter()
+                .find(|&&(_, new)| new == field.name)
+                .map_or(field.name, |&(old, _)| old);
+            let old_value = self.from
+                .fields
+                .iter()
+                .zip(&old_values)
+                .find(|&(old, _)| old.name == old_name && compatible(old, field))
+                .map(|(_, value)| *value);
+            let default = self.defaults
+                .iter()
+                .find(|&&(name, _)| name == field.name)
+                .map(|(_, value)| &value[..]);
+            values.push(match old_value.or(default) {
+                Some(value) => value,
+                None if field.skippable => &[],
+                None => return Err(ByteVecError::MissingFieldError(field.name)),
+            });
+        }
+        T::decode::<Size>(&self.to.write_fields::<Size>(&values)?)
+    }
+}
+
+/// Two fields are compatible if they have the same byte representation.
+fn compatible(old: &FieldSchema, new: &FieldSchema) -> bool {
+    old.schema == new.schema && old.big_endian == new.big_endian &&
+    old.size_width == new.size_width
+}
+
+pub(crate) struct Layout {
+    version: Option<usize>,
+    packed: bool,
+    extensible: bool,
+    compact: bool,
+    pub(crate) fields: Vec<FieldSchema>,
+}
+
+impl Layout {
+    pub(crate) fn of(schema: Schema) -> Layout {
+        match schema {
+            Schema::Struct { version, packed, extensible, compact, fields, .. } => {
+                Layout {
+                    version,
+                    packed,
+                    extensible,
+                    compact,
+                    fields,
+                }
+            }
+            schema => panic!("the layout is not a struct: {:?}", schema),
+        }
+    }
+
+    /// Splits the byte representation of a `struct` with this layout into its fields.
+    /// The fields of an extensible `struct` that are missing from the buffer are left
+    /// out, and so are its unknown entries.
+    pub(crate) fn read_fields<'a, Size>(&self, bytes: &'a [u8]) -> BVDecodeResult<Vec<&'a [u8]>>
+        where Size: BVSize + ByteDecodable
+    {
+        let mut index = 0;
+        if let Some(version) = self.version {
+            let (actual, version_len) = read_size::<Size>(bytes)?;
+            if actual != version {
+                return Err(ByteVecError::UnknownVersionError(actual));
+            }
+            index += version_len;
+        }
+        let sizes = if self.packed {
+            self.fields
+                .iter()
+                .map(|field| {
+                    field.schema.fixed_size().ok_or(ByteVecError::MissingFieldError(field.name))
+                })
+                .collect::<BVDecodeResult<Vec<_>>>()?
+        } else if self.compact {
+            let (sizes, table_len) = compact_table::<Size>(&bytes[index..], self.fields.len())
+                .map_err(|error| error.at(index))?;
+            index += table_len;
+            sizes
+        } else {
+            let mut entries = self.fields.len();
+            if self.extensible {
+                let (count, count_len) = read_size::<Size>(&bytes[index..])
+                    .map_err(|error| error.at(index))?;
+                entries = count;
+                index += count_len;
+            }
+            let (sizes, table_len) = read_size_table::<Size>(&bytes[index..], entries)
+                .map_err(|error| error.at(index))?;
+            index += table_len;
+            sizes
+        };
+        let body_size = sizes.iter()
+            .try_fold(0usize, |acc, size| acc.checked_add(*size))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if body_size != bytes[index..].len() {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(index.saturating_add(body_size)),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let mut fields = Vec::with_capacity(sizes.len());
+        for size in sizes {
+            fields.push(&bytes[index..index + size]);
+            index += size;
+        }
+        fields.truncate(self.fields.len());
+        Ok(fields)
+    }
+
+    /// Joins the byte representations of the fields into a `struct` with this layout
+    fn write_fields<Size>(&self, fields: &[&[u8]]) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = Vec::new();
+        let count = if self.extensible { Some(fields.len()) } else { None };
+        let sizes = self.version.iter().cloned().chain(count).chain(if self.packed {
+            Vec::new()
+        } else if self.compact {
+            let elided = fields.len().saturating_sub(1);
+            fields[..elided].iter().map(|field| field.len()).collect()
+        } else {
+            fields.iter().map(|field| field.len()).collect()
+        });
+        for size in sizes {
+            if size > Size::max_value().as_usize() {
+                return Err(ByteVecError::OverflowError);
+            }
+            Size::from_usize(size).encode_to::<Size, _>(&mut bytes)?;
+        }
+        for field in fields {
+            bytes.extend_from_slice(field);
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,217 @@
+//! Conversion of the byte representations to and from [MessagePack][1].
+//!
+//! `encode` serializes a value with `u64` size indicators and reads it back through the
+//! [`Schema`](../enum.Schema.html) of its type, which gives the names of the fields of the
+//! structures and the variants of the enums. A `struct` is written as a map from the names
+//! of its fields, an `enum` as the name of its variant if it has no fields, or as a map
+//! from the name of the variant to the array of its fields otherwise, a collection as an
+//! array, a map as a map and a `char` as a one-character string. `decode` reads the
+//! MessagePack document the other way around. The binary and extension types of
+//! MessagePack have no counterpart in the schemas and are rejected. This module is only
+//! available with the `msgpack` feature.
+//!
+//! ```rust
+//! # #[macro_use] extern crate bytevec;
+//! # use bytevec::msgpack;
+//! #[derive(Debug, PartialEq, Default)]
+//! struct Point {
+//!     x: u8,
+//!     y: i8
+//! }
+//!
+//! bytevec_impls! {
+//!     #[bytevec(schema)]
+//!     impl Point {
+//!         x: u8,
+//!         y: i8
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let point = Point { x: 1, y: -1 };
+//! let bytes = msgpack::encode(&point).unwrap();
+//! assert_eq!(bytes, vec![0x82, 0xa1, b'x', 0x01, 0xa1, b'y', 0xff]);
+//! assert_eq!(msgpack::decode::<Point>(&bytes).unwrap(), point);
+//! # }
+//! ```
+//!
+//! [1]: https://msgpack.org
+
+use errors::{ByteVecError, BVExpectedSize};
+use value::{Value, MAX_DEPTH, take, take_uint};
+use {ByteEncodable, ByteDecodable, ByteSchema, BVEncodeResult, BVDecodeResult};
+
+/// Returns the MessagePack document of `value`
+pub fn encode<T>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: ByteEncodable + ByteSchema + ?Sized
+{
+    let value = Value::from_bytes(&value.encode::<u64>()?, &T::schema())?;
+    let mut bytes = Vec::new();
+    write_value(&value, &mut bytes);
+    Ok(bytes)
+}
+
+/// Reads a value of type `T` from its MessagePack document
+pub fn decode<T>(bytes: &[u8]) -> BVDecodeResult<T>
+    where T: ByteDecodable + ByteSchema
+{
+    let mut index = 0;
+    let value = read_value(bytes, &mut index, 0)?;
+    if index != bytes.len() {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(index),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    T::decode::<u64>(&value.to_bytes(&T::schema())?)
+}
+
+fn write_len(len: usize, fix: u8, fix_max: usize, markers: [u8; 3], bytes: &mut Vec<u8>) {
+    if len <= fix_max {
+        bytes.push(fix | len as u8);
+    } else if markers[0] != 0 && len <= u8::MAX as usize {
+        bytes.push(markers[0]);
+        bytes.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        bytes.push(markers[1]);
+        bytes.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        bytes.push(markers[2]);
+        bytes.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_value(value: &Value, bytes: &mut Vec<u8>) {
+    match *value {
+        Value::Nil => bytes.push(0xc0),
+        Value::Bool(value) => bytes.push(if value { 0xc3 } else { 0xc2 }),
+        Value::UInt(value) => {
+            if value < 0x80 {
+                bytes.push(value as u8);
+            } else if value <= u8::MAX as u64 {
+                bytes.push(0xcc);
+                bytes.push(value as u8);
+            } else if value <= u16::MAX as u64 {
+                bytes.push(0xcd);
+                bytes.extend_from_slice(&(value as u16).to_be_bytes());
+            } else if value <= u32::MAX as u64 {
+                bytes.push(0xce);
+                bytes.extend_from_slice(&(value as u32).to_be_bytes());
+            } else {
+                bytes.push(0xcf);
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        Value::Int(value) if value >= 0 => write_value(&Value::UInt(value as u64), bytes),
+        Value::Int(value) => {
+            if value >= -32 {
+                bytes.push(value as u8);
+            } else if value >= i8::MIN as i64 {
+                bytes.push(0xd0);
+                bytes.push(value as u8);
+            } else if value >= i16::MIN as i64 {
+                bytes.push(0xd1);
+                bytes.extend_from_slice(&(value as i16).to_be_bytes());
+            } else if value >= i32::MIN as i64 {
+                bytes.push(0xd2);
+                bytes.extend_from_slice(&(value as i32).to_be_bytes());
+            } else {
+                bytes.push(0xd3);
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        Value::F32(value) => {
+            bytes.push(0xca);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        Value::F64(value) => {
+            bytes.push(0xcb);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        Value::String(ref string) => {
+            write_len(string.len(), 0xa0, 31, [0xd9, 0xda, 0xdb], bytes);
+            bytes.extend_from_slice(string.as_bytes());
+        }
+        Value::Array(ref values) => {
+            write_len(values.len(), 0x90, 15, [0, 0xdc, 0xdd], bytes);
+            for value in values {
+                write_value(value, bytes);
+            }
+        }
+        Value::Map(ref pairs) => {
+            write_len(pairs.len(), 0x80, 15, [0, 0xde, 0xdf], bytes);
+            for (key, value) in pairs {
+                write_value(key, bytes);
+                write_value(value, bytes);
+            }
+        }
+    }
+}
+
+fn take_int(bytes: &[u8], index: &mut usize, len: usize) -> BVDecodeResult<i64> {
+    let shift = 64 - 8 * len as u32;
+    Ok(((take_uint(bytes, index, len)? << shift) as i64) >> shift)
+}
+
+fn read_value(bytes: &[u8], index: &mut usize, depth: usize) -> BVDecodeResult<Value> {
+    if depth > MAX_DEPTH {
+        return Err(ByteVecError::InvalidValueError("a shallower nesting of arrays and maps"));
+    }
+    let marker = take(bytes, index, 1)?[0];
+    let (kind, len) = match marker {
+        0x00..=0x7f => return Ok(Value::UInt(marker as u64)),
+        0xe0..=0xff => return Ok(Value::Int(marker as i8 as i64)),
+        0xc0 => return Ok(Value::Nil),
+        0xc2 => return Ok(Value::Bool(false)),
+        0xc3 => return Ok(Value::Bool(true)),
+        0xcc => return Ok(Value::UInt(take_uint(bytes, index, 1)?)),
+        0xcd => return Ok(Value::UInt(take_uint(bytes, index, 2)?)),
+        0xce => return Ok(Value::UInt(take_uint(bytes, index, 4)?)),
+        0xcf => return Ok(Value::UInt(take_uint(bytes, index, 8)?)),
+        0xd0 => return Ok(Value::Int(take_int(bytes, index, 1)?)),
+        0xd1 => return Ok(Value::Int(take_int(bytes, index, 2)?)),
+        0xd2 => return Ok(Value::Int(take_int(bytes, index, 4)?)),
+        0xd3 => return Ok(Value::Int(take_int(bytes, index, 8)?)),
+        0xca => {
+            return Ok(Value::F32(f32::from_bits(take_uint(bytes, index, 4)? as u32)));
+        }
+        0xcb => return Ok(Value::F64(f64::from_bits(take_uint(bytes, index, 8)?))),
+        0xa0..=0xbf => (0xa0, (marker & 0x1f) as u64),
+        0x90..=0x9f => (0x90, (marker & 0x0f) as u64),
+        0x80..=0x8f => (0x80, (marker & 0x0f) as u64),
+        0xd9 => (0xa0, take_uint(bytes, index, 1)?),
+        0xda => (0xa0, take_uint(bytes, index, 2)?),
+        0xdb => (0xa0, take_uint(bytes, index, 4)?),
+        0xdc => (0x90, take_uint(bytes, index, 2)?),
+        0xdd => (0x90, take_uint(bytes, index, 4)?),
+        0xde => (0x80, take_uint(bytes, index, 2)?),
+        0xdf => (0x80, take_uint(bytes, index, 4)?),
+        _ => return Err(ByteVecError::InvalidValueError("a MessagePack type without binary data")),
+    };
+    let len = len as usize;
+    // Every element takes at least one byte, which bounds the preallocated capacity
+    let capacity = len.min(bytes.len() - *index);
+    Ok(match kind {
+        0xa0 => {
+            let string = take(bytes, index, len)?;
+            Value::String(String::from_utf8(string.to_vec())
+                .map_err(|_| ByteVecError::InvalidValueError("a UTF-8 string"))?)
+        }
+        0x90 => {
+            let mut values = Vec::with_capacity(capacity);
+            for _ in 0..len {
+                values.push(read_value(bytes, index, depth + 1)?);
+            }
+            Value::Array(values)
+        }
+        _ => {
+            let mut pairs = Vec::with_capacity(capacity);
+            for _ in 0..len {
+                let key = read_value(bytes, index, depth + 1)?;
+                pairs.push((key, read_value(bytes, index, depth + 1)?));
+            }
+            Value::Map(pairs)
+        }
+    })
+}
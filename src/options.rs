@@ -0,0 +1,145 @@
+use std::marker::PhantomData;
+
+use limits::{check_bytes, DecodeLimits, LimitsGuard};
+use {ByteEncodable, ByteDecodable, BVEncodeResult, BVDecodeResult, BVSize, BigEndian,
+     Deterministic, Encoder, Strict, VarSize};
+
+/// The configuration of the serialization, gathered in a builder.
+///
+/// The options that change the byte representation are carried by the `Size` type of the
+/// builder, which wraps the size type in `BigEndian`, `Deterministic` or `Strict` as they're
+/// enabled, and is given to the methods of the traits. The other options only change how a
+/// buffer is read: the `DecodeLimits` of `decode_limited`, and whether the bytes that follow
+/// the value are ignored, as with `decode_lenient`, or rejected. `with_size` and
+/// `varint_sizes` replace the size type along with the options carried by it, so they're
+/// meant to be called first.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::collections::HashMap;
+/// # use bytevec::DecodeLimits;
+/// # fn main() {
+/// let options = bytevec::options()
+///     .with_size::<u16>()
+///     .big_endian()
+///     .deterministic()
+///     .limits(DecodeLimits { max_elements: 100, ..DecodeLimits::default() });
+/// let map = (0..10u32).map(|key| (key, key * 2)).collect::<HashMap<_, _>>();
+/// let bytes = options.encode(&map).unwrap();
+/// assert_eq!(options.decode::<HashMap<u32, u32>>(&bytes).unwrap(), map);
+///
+/// let lenient = options.allow_trailing_bytes();
+/// assert_eq!(lenient.decode::<u32>(&[0, 0, 0, 7, 0xff]).unwrap(), 7);
+/// assert!(options.decode::<u32>(&[0, 0, 0, 7, 0xff]).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Options<Size = u32> {
+    limits: Option<DecodeLimits>,
+    trailing_bytes: bool,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+/// Returns the default options, with `u32` size indicators
+pub fn options() -> Options<u32> {
+    Options::new()
+}
+
+impl<Size> Options<Size>
+    where Size: BVSize + ByteEncodable + ByteDecodable
+{
+    /// Returns the options with the size type `Size`, without limits and rejecting the
+    /// trailing bytes
+    pub fn new() -> Options<Size> {
+        Options {
+            limits: None,
+            trailing_bytes: false,
+            _marker: PhantomData,
+        }
+    }
+
+    fn with_marker<S>(self) -> Options<S> {
+        Options {
+            limits: self.limits,
+            trailing_bytes: self.trailing_bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Uses `S` as the size type, such as `u8`, `u64` or `Bincode`
+    pub fn with_size<S>(self) -> Options<S>
+        where S: BVSize + ByteEncodable + ByteDecodable
+    {
+        self.with_marker()
+    }
+
+    /// Uses `VarSize` as the size type, whose size indicators have a variable length
+    pub fn varint_sizes(self) -> Options<VarSize> {
+        self.with_marker()
+    }
+
+    /// Serializes the primitive values and the size indicators in big endian
+    pub fn big_endian(self) -> Options<BigEndian<Size>> {
+        self.with_marker()
+    }
+
+    /// Serializes the unordered collections in a deterministic order
+    pub fn deterministic(self) -> Options<Deterministic<Size>> {
+        self.with_marker()
+    }
+
+    /// Rejects the duplicate elements of the unordered collections on deserialization
+    pub fn strict(self) -> Options<Strict<Size>> {
+        self.with_marker()
+    }
+
+    /// Bounds the buffers that are deserialized, as with `decode_limited`
+    pub fn limits(mut self, limits: DecodeLimits) -> Options<Size> {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Ignores the bytes that follow the value on deserialization, as with `decode_lenient`
+    pub fn allow_trailing_bytes(mut self) -> Options<Size> {
+        self.trailing_bytes = true;
+        self
+    }
+
+    /// Returns the byte representation of `value`
+    pub fn encode<T>(&self, value: &T) -> BVEncodeResult<Vec<u8>>
+        where T: ByteEncodable + ?Sized
+    {
+        value.encode::<Size>()
+    }
+
+    /// Returns an `Encoder` that writes the values with these options into a reused buffer
+    pub fn encoder(&self) -> Encoder<Size> {
+        Encoder::new()
+    }
+
+    /// Returns the instance of `T` deserialized from `bytes`
+    pub fn decode<T>(&self, bytes: &[u8]) -> BVDecodeResult<T>
+        where T: ByteDecodable
+    {
+        let _limits = match self.limits {
+            Some(ref limits) => {
+                check_bytes(limits, bytes.len())?;
+                Some(LimitsGuard::new(limits))
+            }
+            None => None,
+        };
+        if self.trailing_bytes {
+            T::decode_with_len::<Size>(bytes).map(|(value, _)| value)
+        } else {
+            T::decode::<Size>(bytes)
+        }
+    }
+}
+
+impl<Size> Default for Options<Size>
+    where Size: BVSize + ByteEncodable + ByteDecodable
+{
+    fn default() -> Options<Size> {
+        Options::new()
+    }
+}
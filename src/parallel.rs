@@ -0,0 +1,162 @@
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use rayon::prelude::*;
+
+use errors::ByteVecError;
+use {ByteEncodable, BVEncodeResult, BVSize, write_size};
+
+/// The number of elements below which a collection is encoded on the current thread
+pub const PARALLEL_MIN_LEN: usize = 4096;
+
+/// Parallel serialization of the large collections, through [`rayon`][1].
+///
+/// `par_encode` returns the same byte representation as `ByteEncodable::encode`, but the
+/// elements are split in chunks that are encoded on the threads of the rayon pool, each
+/// one into its own buffer along with the sizes of its elements. The size table is then
+/// written from those sizes, followed by the buffers of the chunks in order. Collections
+/// with fewer than `PARALLEL_MIN_LEN` elements are encoded on the current thread.
+///
+/// This trait is only available with the `rayon` feature.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ParallelEncode};
+/// # fn main() {
+/// let rows = (0..10_000u32).map(|row| format!("row {}", row)).collect::<Vec<_>>();
+/// assert_eq!(rows.par_encode::<u32>().unwrap(), rows.encode::<u32>().unwrap());
+/// # }
+/// ```
+///
+/// [1]: https://docs.rs/rayon
+pub trait ParallelEncode {
+    /// Returns the byte representation of the collection, encoding its elements in parallel
+    fn par_encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable;
+}
+
+/// Encodes `elems` in chunks on the rayon pool and writes the collection of them
+fn encode_chunks<E, Size, F>(elems: &[E], encode_elem: F) -> BVEncodeResult<Vec<u8>>
+    where E: Sync,
+          Size: BVSize + ByteEncodable,
+          F: Fn(&E, &mut Vec<u8>) -> BVEncodeResult<()> + Sync
+{
+    let chunk_len = cmp::max(elems.len() / (rayon::current_num_threads() * 4), 1);
+    let chunks = elems.par_chunks(chunk_len)
+        .map(|chunk| {
+            let mut bytes = Vec::new();
+            let mut sizes = Vec::with_capacity(chunk.len());
+            for elem in chunk {
+                let start = bytes.len();
+                encode_elem(elem, &mut bytes)?;
+                sizes.push(bytes.len() - start);
+            }
+            Ok((bytes, sizes))
+        })
+        .collect::<BVEncodeResult<Vec<_>>>()?;
+
+    let max = Size::max_value().as_usize();
+    if elems.len() > max {
+        return Err(ByteVecError::OverflowError);
+    }
+    let mut total = Size::from_usize(elems.len()).encoded_len();
+    for (bytes, sizes) in &chunks {
+        total += bytes.len();
+        if !Size::bincode() {
+            for &size in sizes {
+                if size > max {
+                    return Err(ByteVecError::OverflowError);
+                }
+                total += Size::from_usize(size).encoded_len();
+            }
+        }
+    }
+    if total > max {
+        return Err(ByteVecError::OverflowError);
+    }
+
+    let mut out = Vec::with_capacity(total);
+    write_size::<Size>(&mut out, elems.len())?;
+    if !Size::bincode() {
+        for (_, sizes) in &chunks {
+            for &size in sizes {
+                write_size::<Size>(&mut out, size)?;
+            }
+        }
+    }
+    for (bytes, _) in chunks {
+        out.extend_from_slice(&bytes);
+    }
+    Ok(out)
+}
+
+/// Orders the elements of an unordered collection by the byte representation of their
+/// keys, in the deterministic mode
+fn sort_by_key<E, K, Size, F>(elems: Vec<E>, key: F) -> BVEncodeResult<Vec<E>>
+    where E: Send + Sync,
+          K: ByteEncodable + ?Sized,
+          Size: BVSize + ByteEncodable,
+          F: Fn(&E) -> &K + Sync
+{
+    if !Size::deterministic() {
+        return Ok(elems);
+    }
+    let mut keyed = elems.into_par_iter()
+        .map(|elem| Ok((key(&elem).encode::<Size>()?, elem)))
+        .collect::<BVEncodeResult<Vec<_>>>()?;
+    keyed.par_sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(keyed.into_iter().map(|entry| entry.1).collect())
+}
+
+impl<T> ParallelEncode for [T]
+    where T: ByteEncodable + Sync
+{
+    fn par_encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        if self.len() < PARALLEL_MIN_LEN {
+            return self.encode::<Size>();
+        }
+        encode_chunks::<T, Size, _>(self, |elem, out| elem.encode_to_buf::<Size>(out))
+    }
+}
+
+impl<T> ParallelEncode for Vec<T>
+    where T: ByteEncodable + Sync
+{
+    fn par_encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        self[..].par_encode::<Size>()
+    }
+}
+
+impl<T> ParallelEncode for HashSet<T>
+    where T: ByteEncodable + Eq + Hash + Sync
+{
+    fn par_encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        if self.len() < PARALLEL_MIN_LEN {
+            return self.encode::<Size>();
+        }
+        let elems = sort_by_key::<_, T, Size, _>(self.iter().collect(), |elem| *elem)?;
+        encode_chunks::<_, Size, _>(&elems, |elem, out| elem.encode_to_buf::<Size>(out))
+    }
+}
+
+impl<K, V> ParallelEncode for HashMap<K, V>
+    where K: ByteEncodable + Eq + Hash + Sync,
+          V: ByteEncodable + Sync
+{
+    fn par_encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        if self.len() < PARALLEL_MIN_LEN {
+            return self.encode::<Size>();
+        }
+        let elems = sort_by_key::<_, K, Size, _>(self.iter().collect(), |elem| elem.0)?;
+        encode_chunks::<_, Size, _>(&elems, |elem, out| elem.encode_to_buf::<Size>(out))
+    }
+}
@@ -0,0 +1,373 @@
+use errors::{ByteVecError, BVExpectedSize};
+use schema::Schema;
+use {ByteEncodable, ByteDecodable, ByteSchema, BVDecodeResult, BVEncodeResult, BVSize, VarSize,
+     compact_table, read_size, read_size_table};
+
+/// The changes between two byte representations of a value.
+///
+/// A byte representation is split into entries by its size table, which are the fields of
+/// a `struct`, the elements of a tuple or collection, or the fields of a packed `struct`
+/// and the elements of a `PackedVec`, found through the [`Schema`](enum.Schema.html) of the
+/// type. A patch holds the number of entries of the new representation and the entries that
+/// differ from the old one, so replicas can exchange only the changed fields and elements.
+/// The values of the other types are a single entry. A patch can be serialized itself.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable, Patch};
+/// # fn main() {
+/// let old = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+/// let new = vec!["a".to_string(), "x".to_string(), "c".to_string(), "d".to_string()];
+/// let old_bytes = old.encode::<u32>().unwrap();
+/// let new_bytes = new.encode::<u32>().unwrap();
+///
+/// let patch = Patch::diff::<Vec<String>, u32>(&old_bytes, &new_bytes).unwrap();
+/// assert_eq!(patch.changed_entries(), 2);
+/// assert_eq!(patch.apply::<Vec<String>, u32>(&old_bytes).unwrap(), new_bytes);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    prefix: Vec<u8>,
+    len: u64,
+    changes: Vec<(u64, Vec<u8>)>,
+}
+
+impl Patch {
+    /// Returns the changes from the byte representation `old` of a value of type `T`
+    /// to the byte representation `new`
+    pub fn diff<T, Size>(old: &[u8], new: &[u8]) -> BVDecodeResult<Patch>
+        where T: ByteSchema + ?Sized,
+              Size: BVSize + ByteDecodable
+    {
+        let layout = Layout::of(&T::schema());
+        let (_, old_entries) = layout.split::<Size>(old)?;
+        let (prefix, new_entries) = layout.split::<Size>(new)?;
+        let changes = new_entries.iter()
+            .enumerate()
+            .filter(|&(index, entry)| old_entries.get(index) != Some(entry))
+            .map(|(index, entry)| (index as u64, entry.to_vec()))
+            .collect();
+        Ok(Patch {
+            prefix: prefix.to_vec(),
+            len: new_entries.len() as u64,
+            changes,
+        })
+    }
+
+    /// Returns the byte representation of a value of type `T` that results from applying
+    /// the changes to the byte representation `old`
+    pub fn apply<T, Size>(&self, old: &[u8]) -> BVDecodeResult<Vec<u8>>
+        where T: ByteSchema + ?Sized,
+              Size: BVSize + ByteEncodable + ByteDecodable
+    {
+        let layout = Layout::of(&T::schema());
+        let (_, old_entries) = layout.split::<Size>(old)?;
+        let len = self.len as usize;
+        if len > old_entries.len() + self.changes.len() {
+            return Err(ByteVecError::LengthMismatchError {
+                expected: len,
+                actual: old_entries.len() + self.changes.len(),
+            });
+        }
+        let mut entries = old_entries.iter()
+            .take(len)
+            .map(|entry| Some(*entry))
+            .collect::<Vec<_>>();
+        entries.resize(len, None);
+        for &(index, ref entry) in &self.changes {
+            match entries.get_mut(index as usize) {
+                Some(slot) => *slot = Some(entry),
+                None => {
+                    return Err(ByteVecError::LengthMismatchError {
+                        expected: len,
+                        actual: index as usize + 1,
+                    })
+                }
+            }
+        }
+        let entries = entries.into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ByteVecError::LengthMismatchError {
+                expected: len,
+                actual: old_entries.len(),
+            })?;
+        layout.join::<Size>(&self.prefix, &entries)
+    }
+
+    /// Returns the number of entries that the patch adds or replaces
+    pub fn changed_entries(&self) -> usize {
+        self.changes.len()
+    }
+}
+
+impl ByteEncodable for Patch {
+    fn get_size<Size>(&self) -> Option<Size>
+        where Size: BVSize + ByteEncodable
+    {
+        (&(&self.prefix, &self.len, &self.changes)).get_size::<Size>()
+    }
+
+    fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        (&(&self.prefix, &self.len, &self.changes)).encode::<Size>()
+    }
+}
+
+impl ByteDecodable for Patch {
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Patch>
+        where Size: BVSize + ByteDecodable
+    {
+        let (prefix, len, changes) = <(Vec<u8>, u64, Vec<(u64, Vec<u8>)>)>::decode::<Size>(bytes)?;
+        Ok(Patch {
+            prefix,
+            len,
+            changes,
+        })
+    }
+}
+
+/// Replaces the byte representation of the field `name` in the byte representation `bytes`
+/// of a `struct` of type `T`, without encoding the other fields again.
+///
+/// The value is serialized with the options of the field, such as its size type and its byte
+/// order, but not through the proxy given with `with`, if any, so a proxied field takes a
+/// value of its proxy type. The bytes that follow the field are shifted and its size is
+/// fixed up in the size table. The fields of a packed `struct` have no size table, so they
+/// can only be replaced by a value of the same length, and a `LengthMismatchError` is
+/// returned otherwise. A field that the `struct` doesn't have, or that is missing from an
+/// older buffer of an extensible `struct`, yields a `MissingFieldError`. The buffer is left
+/// unchanged if the field can't be replaced.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// bytevec_decl! {
+///     #[bytevec(schema)]
+///     #[derive(PartialEq, Eq, Debug)]
+///     struct Record {
+///         id: u64,
+///         status: String,
+///         history: Vec<u32>
+///     }
+/// }
+///
+/// fn main() {
+///     let record = Record {id: 7, status: "open".to_string(), history: vec![1; 1000]};
+///     let mut bytes = record.encode::<u32>().unwrap();
+///     bytevec::splice_field::<Record, _, u32>(&mut bytes, "status", "closed").unwrap();
+///     assert_eq!(Record::decode::<u32>(&bytes).unwrap(), Record {
+///         id: 7,
+///         status: "closed".to_string(),
+///         history: vec![1; 1000]
+///     });
+/// }
+/// ```
+pub fn splice_field<T, F, Size>(bytes: &mut Vec<u8>, name: &'static str, value: &F)
+    -> BVEncodeResult<()>
+    where T: ByteSchema + ?Sized,
+          F: ByteEncodable + ?Sized,
+          Size: BVSize + ByteEncodable + ByteDecodable
+{
+    let schema = T::schema();
+    let (index, field) = match schema {
+        Schema::Struct { ref fields, .. } => {
+            fields.iter()
+                .enumerate()
+                .find(|&(_, field)| field.name == name)
+                .ok_or(ByteVecError::MissingFieldError(name))?
+        }
+        _ => return Err(ByteVecError::MissingFieldError(name)),
+    };
+    let mut field_bytes = match field.size_width {
+        None => value.encode::<Size>()?,
+        Some(0) => value.encode::<VarSize>()?,
+        Some(1) => value.encode::<u8>()?,
+        Some(2) => value.encode::<u16>()?,
+        Some(4) => value.encode::<u32>()?,
+        Some(8) => value.encode::<u64>()?,
+        Some(16) => value.encode::<u128>()?,
+        Some(width) => return Err(ByteVecError::UnknownSizeWidthError(width)),
+    };
+    if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+        field_bytes.reverse();
+    }
+    let layout = Layout::of(&schema);
+    let (start, end, header_len, header) = {
+        let (prefix, mut entries) = layout.split::<Size>(bytes)?;
+        let old = match (&layout, entries.get(index)) {
+            (&Layout::Whole, _) | (_, None) => return Err(ByteVecError::MissingFieldError(name)),
+            (&Layout::Fixed(_), Some(old)) if old.len() != field_bytes.len() => {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: old.len(),
+                    actual: field_bytes.len(),
+                })
+            }
+            (_, Some(old)) => *old,
+        };
+        let start = old.as_ptr() as usize - bytes.as_ptr() as usize;
+        let header_len = bytes.len() - entries.iter().map(|entry| entry.len()).sum::<usize>();
+        entries[index] = &field_bytes;
+        (start, start + old.len(), header_len, layout.header::<Size>(prefix, &entries)?)
+    };
+    bytes.splice(start..end, field_bytes);
+    bytes.splice(..header_len, header);
+    Ok(())
+}
+
+/// How the entries of a byte representation are laid out
+enum Layout {
+    /// A table with the sizes of a known number of entries, as in structures and tuples,
+    /// after the version of the `struct`, if any
+    Table(bool, usize),
+    /// A table without the size of the last of a known number of entries, as in compact
+    /// structures, after the version of the `struct`, if any
+    CompactTable(bool, usize),
+    /// The number of entries followed by a table with their sizes, as in collections and
+    /// extensible structures, after the version of the `struct`, if any
+    CountedTable(bool),
+    /// Entries of known sizes, as in packed structures
+    Fixed(Vec<usize>),
+    /// The number of entries followed by entries of the same size, as in `PackedVec`
+    CountedFixed(usize),
+    /// A single entry
+    Whole,
+}
+
+impl Layout {
+    fn of(schema: &Schema) -> Layout {
+        match *schema {
+            Schema::Struct { packed: true, ref fields, .. } => {
+                fields.iter()
+                    .map(|field| field.schema.fixed_size())
+                    .collect::<Option<Vec<_>>>()
+                    .map_or(Layout::Whole, Layout::Fixed)
+            }
+            Schema::Struct { version, extensible: true, .. } => {
+                Layout::CountedTable(version.is_some())
+            }
+            Schema::Struct { version, compact: true, ref fields, .. } => {
+                Layout::CompactTable(version.is_some(), fields.len())
+            }
+            Schema::Struct { version, ref fields, .. } => {
+                Layout::Table(version.is_some(), fields.len())
+            }
+            Schema::Tuple(ref elems) => Layout::Table(false, elems.len()),
+            Schema::Seq(_) | Schema::Map(..) => Layout::CountedTable(false),
+            Schema::PackedSeq(ref elem) => {
+                elem.fixed_size().map_or(Layout::Whole, Layout::CountedFixed)
+            }
+            _ => Layout::Whole,
+        }
+    }
+
+    /// Splits a byte representation into its version prefix and its entries
+    fn split<'a, Size>(&self, bytes: &'a [u8]) -> BVDecodeResult<(&'a [u8], Vec<&'a [u8]>)>
+        where Size: BVSize + ByteDecodable
+    {
+        let mut index = 0;
+        let mut prefix_len = 0;
+        if let Layout::Table(true, _) | Layout::CompactTable(true, _) |
+               Layout::CountedTable(true) = *self {
+            prefix_len = read_size::<Size>(bytes)?.1;
+            index += prefix_len;
+        }
+        let sizes = match *self {
+            Layout::Table(_, entries) => {
+                let (sizes, table_len) = read_size_table::<Size>(&bytes[index..], entries)
+                    .map_err(|error| error.at(index))?;
+                index += table_len;
+                sizes
+            }
+            Layout::CompactTable(_, entries) => {
+                let (sizes, table_len) = compact_table::<Size>(&bytes[index..], entries)
+                    .map_err(|error| error.at(index))?;
+                index += table_len;
+                sizes
+            }
+            Layout::CountedTable(_) => {
+                let (entries, count_len) = read_size::<Size>(&bytes[index..])
+                    .map_err(|error| error.at(index))?;
+                index += count_len;
+                let (sizes, table_len) = read_size_table::<Size>(&bytes[index..], entries)
+                    .map_err(|error| error.at(index))?;
+                index += table_len;
+                sizes
+            }
+            Layout::Fixed(ref sizes) => sizes.clone(),
+            Layout::CountedFixed(size) => {
+                let (entries, count_len) = read_size::<Size>(bytes)?;
+                index += count_len;
+                let body_size = entries.checked_mul(size).ok_or(ByteVecError::SizeOverflowError)?;
+                if body_size != bytes[index..].len() {
+                    return Err(ByteVecError::BadSizeDecodeError {
+                        expected: BVExpectedSize::EqualTo(index.saturating_add(body_size)),
+                        actual: bytes.len(),
+                        offset: 0,
+                    });
+                }
+                vec![size; entries]
+            }
+            Layout::Whole => vec![bytes.len()],
+        };
+        let prefix = &bytes[..prefix_len];
+        let body_size = sizes.iter()
+            .try_fold(0usize, |acc, size| acc.checked_add(*size))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if body_size != bytes[index..].len() {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(index.saturating_add(body_size)),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let mut entries = Vec::with_capacity(sizes.len());
+        for size in sizes {
+            entries.push(&bytes[index..index + size]);
+            index += size;
+        }
+        Ok((prefix, entries))
+    }
+
+    /// Joins a version prefix and the entries into a byte representation
+    fn join<Size>(&self, prefix: &[u8], entries: &[&[u8]]) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = self.header::<Size>(prefix, entries)?;
+        for entry in entries {
+            bytes.extend_from_slice(entry);
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the bytes that precede the entries, which are the version prefix, the number
+    /// of entries and their sizes
+    fn header<Size>(&self, prefix: &[u8], entries: &[&[u8]]) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let mut bytes = prefix.to_vec();
+        let (counted, sized) = match *self {
+            Layout::Table(..) | Layout::CompactTable(..) => (false, true),
+            Layout::CountedTable(_) => (true, true),
+            Layout::CountedFixed(_) => (true, false),
+            Layout::Fixed(_) | Layout::Whole => (false, false),
+        };
+        // The size of the last entry of a compact structure is left out
+        let sized_entries = match *self {
+            Layout::CompactTable(..) => entries.len().saturating_sub(1),
+            _ => entries.len(),
+        };
+        let sizes = entries[..sized_entries].iter().map(|entry| entry.len());
+        let header = if counted { Some(entries.len()) } else { None };
+        for size in header.into_iter().chain(sizes.filter(|_| sized)) {
+            if size > Size::max_value().as_usize() {
+                return Err(ByteVecError::OverflowError);
+            }
+            Size::from_usize(size).encode_to::<Size, _>(&mut bytes)?;
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,198 @@
+//! Emission of the [protobuf wire format][1] for simple structures.
+//!
+//! `encode` serializes a `struct` with `u64` size indicators, reads it back through its
+//! [`Schema`](../enum.Schema.html) and writes it as a protobuf message. The fields have no
+//! tags of their own, so the field number of each field is its position in the `struct`,
+//! starting at 1, which the `.proto` definition of the consumer must follow. The unsigned
+//! integers and `char` are written as `uint32` or `uint64` varints, the signed integers as
+//! `int32` or `int64` varints, the `ZigZag` integers as `sint32` or `sint64` varints, `f32`
+//! and `f64` as `float` and `double`, and the strings and the nested structures as length
+//! delimited fields. The collections of numbers and the `BitVec` fields are written as
+//! packed repeated fields, the other collections as repeated fields, and the maps as
+//! repeated entries with the key in field 1 and the value in field 2. The skipped fields
+//! are left out of the message. The tuples, the enums and the `OptionVec` fields have no
+//! protobuf counterpart and yield an `InvalidValueError`. This module is only available
+//! with the `protobuf` feature.
+//!
+//! ```rust
+//! # #[macro_use] extern crate bytevec;
+//! # use bytevec::protobuf;
+//! #[derive(Debug, PartialEq, Default)]
+//! struct Point {
+//!     x: u32,
+//!     label: String
+//! }
+//!
+//! bytevec_impls! {
+//!     #[bytevec(schema)]
+//!     impl Point {
+//!         x: u32,
+//!         label: String
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let point = Point { x: 150, label: "a".to_string() };
+//! // message Point { uint32 x = 1; string label = 2; }
+//! assert_eq!(protobuf::encode(&point).unwrap(), vec![0x08, 0x96, 0x01, 0x12, 0x01, b'a']);
+//! # }
+//! ```
+//!
+//! [1]: https://protobuf.dev/programming-guides/encoding/
+
+use errors::ByteVecError;
+use schema::Schema;
+use value::Value;
+use {ByteEncodable, ByteSchema, BVEncodeResult};
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const FIXED32: u8 = 5;
+
+/// Returns the protobuf message of the `struct` `value`
+pub fn encode<T>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: ByteEncodable + ByteSchema + ?Sized
+{
+    let schema = T::schema();
+    let value = Value::from_bytes(&value.encode::<u64>()?, &schema)?;
+    let mut bytes = Vec::new();
+    write_message(&schema, &value, &mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_key(number: usize, wire_type: u8, bytes: &mut Vec<u8>) {
+    write_varint((number as u64) << 3 | wire_type as u64, bytes);
+}
+
+fn write_message(schema: &Schema, value: &Value, bytes: &mut Vec<u8>) -> BVEncodeResult<()> {
+    let (fields, pairs) = match (schema, value) {
+        (Schema::Struct { fields, .. }, Value::Map(pairs)) => (fields, pairs),
+        _ => return Err(ByteVecError::InvalidValueError("a struct")),
+    };
+    for (number, (field, (_, value))) in fields.iter().zip(pairs).enumerate() {
+        if *value != Value::Nil {
+            write_field(number + 1, &field.schema, value, bytes)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the wire type of the scalar values of `schema`, or `None` if they are
+/// length delimited
+fn scalar_wire_type(schema: &Schema) -> Option<u8> {
+    match *schema {
+        Schema::Primitive { name: "f16", .. } | Schema::Primitive { name: "f32", .. } => {
+            Some(FIXED32)
+        }
+        Schema::Primitive { name: "f64", .. } => Some(FIXED64),
+        Schema::Primitive { .. } | Schema::ZigZag(_) => Some(VARINT),
+        _ => None,
+    }
+}
+
+/// Writes a scalar value without its key
+fn write_scalar(schema: &Schema, value: &Value, bytes: &mut Vec<u8>) -> BVEncodeResult<()> {
+    match (schema, value) {
+        (_, Value::F32(value)) => bytes.extend_from_slice(&value.to_le_bytes()),
+        (_, Value::F64(value)) => bytes.extend_from_slice(&value.to_le_bytes()),
+        (_, Value::Bool(value)) => bytes.push(*value as u8),
+        (Schema::ZigZag(_), value) => {
+            let value = value.as_i64().ok_or(ByteVecError::InvalidValueError("an integer"))?;
+            write_varint(((value << 1) ^ (value >> 63)) as u64, bytes);
+        }
+        (_, Value::String(string)) => {
+            let value = string.chars().next().ok_or(ByteVecError::InvalidValueError("a char"))?;
+            write_varint(value as u64, bytes);
+        }
+        (_, Value::Int(value)) => write_varint(*value as u64, bytes),
+        (_, Value::UInt(value)) => write_varint(*value, bytes),
+        _ => return Err(ByteVecError::InvalidValueError("a protobuf scalar")),
+    }
+    Ok(())
+}
+
+/// Writes a length delimited value without its key
+fn write_len_delimited(schema: &Schema, value: &Value, bytes: &mut Vec<u8>)
+    -> BVEncodeResult<()>
+{
+    let mut payload = Vec::new();
+    match (schema, value) {
+        (Schema::String, Value::String(string)) |
+        (Schema::LossyString, Value::String(string)) => {
+            payload.extend_from_slice(string.as_bytes())
+        }
+        (Schema::Struct { .. }, _) => write_message(schema, value, &mut payload)?,
+        _ => return Err(ByteVecError::InvalidValueError("a protobuf message or string")),
+    }
+    write_varint(payload.len() as u64, bytes);
+    bytes.extend_from_slice(&payload);
+    Ok(())
+}
+
+fn write_field(number: usize, schema: &Schema, value: &Value, bytes: &mut Vec<u8>)
+    -> BVEncodeResult<()>
+{
+    let (elem, values) = match (schema, value) {
+        (Schema::Seq(elem), Value::Array(values)) |
+        (Schema::PackedSeq(elem), Value::Array(values)) |
+        (Schema::InternedSeq(elem), Value::Array(values)) => (&**elem, values),
+        (Schema::BitSeq, Value::Array(values)) => {
+            (&Schema::Primitive { name: "bool", size: 1 }, values)
+        }
+        (Schema::BitSet, Value::Array(values)) => {
+            (&Schema::Primitive { name: "u32", size: 4 }, values)
+        }
+        (Schema::Map(key, map_value), Value::Map(pairs)) => {
+            // The entries are messages with the key in field 1 and the value in field 2
+            for (pair_key, pair_value) in pairs {
+                let mut payload = Vec::new();
+                write_field(1, key, pair_key, &mut payload)?;
+                write_field(2, map_value, pair_value, &mut payload)?;
+                write_key(number, LEN, bytes);
+                write_varint(payload.len() as u64, bytes);
+                bytes.extend_from_slice(&payload);
+            }
+            return Ok(());
+        }
+        _ => {
+            match scalar_wire_type(schema) {
+                Some(wire_type) => {
+                    write_key(number, wire_type, bytes);
+                    write_scalar(schema, value, bytes)?;
+                }
+                None => {
+                    write_key(number, LEN, bytes);
+                    write_len_delimited(schema, value, bytes)?;
+                }
+            }
+            return Ok(());
+        }
+    };
+    if values.is_empty() {
+        return Ok(());
+    }
+    if scalar_wire_type(elem).is_some() {
+        let mut payload = Vec::new();
+        for value in values {
+            write_scalar(elem, value, &mut payload)?;
+        }
+        write_key(number, LEN, bytes);
+        write_varint(payload.len() as u64, bytes);
+        bytes.extend_from_slice(&payload);
+    } else {
+        for value in values {
+            write_key(number, LEN, bytes);
+            write_len_delimited(elem, value, bytes)?;
+        }
+    }
+    Ok(())
+}
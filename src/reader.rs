@@ -0,0 +1,96 @@
+use std::cmp;
+use std::io::{self, Read};
+use std::iter;
+use std::marker::PhantomData;
+
+use {ByteEncodable, BVSize};
+
+/// A reader of the byte representation of a value, or of a sequence of framed values.
+///
+/// The values are only serialized as the bytes are read, one value at a time, so the
+/// output can be handed to any API that takes an `io::Read`. A reader created with `new`
+/// produces the byte representation of a single value, while a reader created with
+/// `framed` produces the frames of every value of an iterator, as written by
+/// `ByteEncodable::encode_framed`. A serialization error is returned by `read` as an
+/// `io::Error` of kind `InvalidData`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::io::Read;
+/// # use bytevec::{ByteEncodable, EncodedReader};
+/// # fn main() {
+/// let mut reader = EncodedReader::<_, u32>::framed((1..4u16).map(|n| n * 100));
+/// let mut bytes = Vec::new();
+/// reader.read_to_end(&mut bytes).unwrap();
+/// assert_eq!(&bytes[..6], &100u16.encode_framed::<u32>().unwrap()[..]);
+/// # }
+/// ```
+pub struct EncodedReader<I, Size = u32> {
+    values: I,
+    framed: bool,
+    buffer: Vec<u8>,
+    position: usize,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<T, Size> EncodedReader<iter::Once<T>, Size>
+    where T: ByteEncodable,
+          Size: BVSize + ByteEncodable
+{
+    /// Returns a reader of the byte representation of `value`
+    pub fn new(value: T) -> EncodedReader<iter::Once<T>, Size> {
+        EncodedReader {
+            values: iter::once(value),
+            framed: false,
+            buffer: Vec::new(),
+            position: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<I, Size> EncodedReader<I, Size>
+    where I: Iterator,
+          I::Item: ByteEncodable,
+          Size: BVSize + ByteEncodable
+{
+    /// Returns a reader of the frames of the given values
+    pub fn framed<V>(values: V) -> EncodedReader<I, Size>
+        where V: IntoIterator<IntoIter = I, Item = I::Item>
+    {
+        EncodedReader {
+            values: values.into_iter(),
+            framed: true,
+            buffer: Vec::new(),
+            position: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<I, Size> Read for EncodedReader<I, Size>
+    where I: Iterator,
+          I::Item: ByteEncodable,
+          Size: BVSize + ByteEncodable
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            let value = match self.values.next() {
+                Some(value) => value,
+                None => return Ok(0),
+            };
+            self.buffer.clear();
+            self.position = 0;
+            if self.framed {
+                let frame = value.encode_framed::<Size>()?;
+                self.buffer.extend_from_slice(&frame);
+            } else {
+                value.encode_to::<Size, _>(&mut self.buffer)?;
+            }
+        }
+        let len = cmp::min(buf.len(), self.buffer.len() - self.position);
+        buf[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,117 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use errors::ByteVecError;
+use {ByteEncodable, ByteDecodable, BVDecodeResult, BVEncodeResult, BVSize, read_size,
+     write_size};
+
+type DecodeFn = Box<dyn Fn(&[u8]) -> BVDecodeResult<Box<dyn Any>> + Send + Sync>;
+
+/// A registry of the types of the values that are serialized along with a type tag.
+///
+/// Each type is registered with a tag, and with the function that deserializes its values.
+/// `encode_tagged` writes the tag of the type of the value as a size indicator, followed by
+/// the byte representation of the value, and `decode_tagged` reads the tag and hands the rest
+/// of the buffer over to the function registered for it, so the receiver of a message doesn't
+/// need to know its type beforehand. The value is returned as a `Box<dyn Any>`, to be
+/// downcast to the registered types. The sender and the receiver register the same tags, and
+/// an unknown tag yields an `UnknownVariantError`, as with the tags of the enums.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::TypeRegistry;
+/// # fn main() {
+/// let registry = TypeRegistry::<u32>::new()
+///     .register::<String>(1)
+///     .register::<Vec<u16>>(2);
+///
+/// let messages = vec![registry.encode_tagged(&"hello".to_string()).unwrap(),
+///                     registry.encode_tagged(&vec![1u16, 2]).unwrap()];
+/// for bytes in &messages {
+///     let value = registry.decode_tagged(bytes).unwrap();
+///     if let Some(text) = value.downcast_ref::<String>() {
+///         assert_eq!(text, "hello");
+///     } else {
+///         assert_eq!(value.downcast_ref::<Vec<u16>>().unwrap(), &[1, 2]);
+///     }
+/// }
+/// assert!(registry.decode_tagged(&[3, 0, 0, 0]).is_err());
+/// assert!(registry.encode_tagged(&7u8).is_err());
+/// # }
+/// ```
+pub struct TypeRegistry<Size = u32> {
+    decoders: HashMap<usize, DecodeFn>,
+    tags: HashMap<TypeId, usize>,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<Size> TypeRegistry<Size>
+    where Size: BVSize + ByteEncodable + ByteDecodable
+{
+    /// Returns an empty registry
+    pub fn new() -> TypeRegistry<Size> {
+        TypeRegistry {
+            decoders: HashMap::new(),
+            tags: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Registers the type `T` with `tag`, deserialized with its `ByteDecodable` implementation
+    pub fn register<T>(self, tag: usize) -> TypeRegistry<Size>
+        where T: ByteDecodable + Any
+    {
+        self.register_fn(tag, T::decode::<Size>)
+    }
+
+    /// Registers the type `T` with `tag`, deserialized with `decode`. A tag that was already
+    /// registered is replaced.
+    pub fn register_fn<T>(mut self, tag: usize, decode: fn(&[u8]) -> BVDecodeResult<T>)
+        -> TypeRegistry<Size>
+        where T: Any
+    {
+        self.tags.retain(|_, registered| *registered != tag);
+        self.tags.insert(TypeId::of::<T>(), tag);
+        self.decoders.insert(tag, Box::new(move |bytes| {
+            decode(bytes).map(|value| Box::new(value) as Box<dyn Any>)
+        }));
+        self
+    }
+
+    /// Returns the tag of the type `T`, if it's registered
+    pub fn tag_of<T>(&self) -> Option<usize>
+        where T: Any
+    {
+        self.tags.get(&TypeId::of::<T>()).cloned()
+    }
+
+    /// Returns the byte representation of `value`, preceded by the tag of its type. Fails
+    /// with an `InvalidValueError` if the type of `value` is not registered.
+    pub fn encode_tagged<T>(&self, value: &T) -> BVEncodeResult<Vec<u8>>
+        where T: ByteEncodable + Any
+    {
+        let tag = self.tag_of::<T>()
+            .ok_or(ByteVecError::InvalidValueError("a value of a registered type"))?;
+        let mut out = Vec::new();
+        write_size::<Size>(&mut out, tag)?;
+        value.encode_to_buf::<Size>(&mut out)?;
+        Ok(out)
+    }
+
+    /// Deserializes the value written by `encode_tagged` with the function registered for
+    /// its tag
+    pub fn decode_tagged(&self, bytes: &[u8]) -> BVDecodeResult<Box<dyn Any>> {
+        let (tag, len) = read_size::<Size>(bytes)?;
+        let decode = self.decoders.get(&tag).ok_or(ByteVecError::UnknownVariantError(tag))?;
+        decode(&bytes[len..])
+    }
+}
+
+impl<Size> Default for TypeRegistry<Size>
+    where Size: BVSize + ByteEncodable + ByteDecodable
+{
+    fn default() -> TypeRegistry<Size> {
+        TypeRegistry::new()
+    }
+}
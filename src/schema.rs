@@ -0,0 +1,440 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::mem::size_of;
+
+use header::FormatHeader;
+use {BVSize, ByteBuf, Bytes, PackedVec, OptionVec, InternedVec, BitVec, BitSet, ZigZag};
+
+/// A description of the byte representation of a type.
+///
+/// The schemas of the types are obtained through the [`ByteSchema`](trait.ByteSchema.html)
+/// trait, and can be exported as JSON with `to_json`, for decoders written in other
+/// languages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schema {
+    /// A primitive type, with its Rust name and the length of its byte representation
+    Primitive {
+        name: &'static str,
+        size: usize,
+    },
+    /// A UTF-8 string, which takes the whole byte buffer given by its container
+    String,
+    /// A string that may hold invalid UTF-8, as a `LossyUtf8` field or a `MaybeUtf8`, laid
+    /// out as a `String` but whose bytes aren't checked against UTF-8
+    LossyString,
+    /// The unit type, which takes a single size indicator of value 0
+    Unit,
+    /// A collection, made of the number of elements, the size of each element and
+    /// the elements
+    Seq(Box<Schema>),
+    /// A map, serialized as a `Seq` of key-value tuples
+    Map(Box<Schema>, Box<Schema>),
+    /// A `PackedVec`, made of the number of elements and the elements, without sizes
+    PackedSeq(Box<Schema>),
+    /// An `OptionVec`, made of the number of elements, the presence bitmap and the
+    /// present elements as a `Seq`
+    OptionSeq(Box<Schema>),
+    /// An `InternedVec`, made of the distinct elements as a `Seq`, the number of elements
+    /// and the index of each element among the distinct ones, with the fewest bytes that
+    /// fit every index
+    InternedSeq(Box<Schema>),
+    /// A `BitVec`, made of the number of elements and one bit per element
+    BitSeq,
+    /// A `BitSet`, made of its smallest element as a `u32`, the number of integers up to
+    /// its largest element and one bit per integer
+    BitSet,
+    /// A `ZigZag` integer, with the Rust name of the integer type, written in LEB128 after
+    /// the zig-zag encoding
+    ZigZag(&'static str),
+    /// A tuple, made of the size of each element and the elements
+    Tuple(Vec<Schema>),
+    /// A `struct`, made of its version, if any, the number of entries if it's extensible,
+    /// the size of each field and the fields, or only of its fields if it's packed. The
+    /// size of the last field is left out if it's compact.
+    Struct {
+        name: &'static str,
+        version: Option<usize>,
+        packed: bool,
+        extensible: bool,
+        compact: bool,
+        fields: Vec<FieldSchema>,
+    },
+    /// An `enum`, made of the tag of the variant and the fields of the variant as a `Tuple`
+    Enum {
+        name: &'static str,
+        variants: Vec<VariantSchema>,
+    },
+}
+
+/// A description of a field of a `struct`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSchema {
+    /// The name of the field, or its index in a tuple struct
+    pub name: &'static str,
+    /// The description of the value of the field
+    pub schema: Schema,
+    /// `true` if the field is serialized in big endian
+    pub big_endian: bool,
+    /// `true` if the field may be skipped, in which case its size is 0
+    pub skippable: bool,
+    /// The width of the size indicators of the field, if it's overridden with `size`
+    pub size_width: Option<u8>,
+}
+
+/// A description of a variant of an `enum`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantSchema {
+    /// The name of the variant
+    pub name: &'static str,
+    /// The tag of the variant
+    pub tag: usize,
+    /// The descriptions of the fields of the variant
+    pub fields: Vec<Schema>,
+}
+
+/// Provides the description of the byte representation of a type.
+///
+/// This trait is implemented for the types supported by the library, and for the
+/// structures and enums implemented with the `#[bytevec(schema)]` attribute of
+/// [`bytevec_impls`](macro.bytevec_impls.html).
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteSchema, Schema};
+/// # fn main() {
+/// assert_eq!(<Vec<u16>>::schema(),
+///            Schema::Seq(Box::new(Schema::Primitive {name: "u16", size: 2})));
+/// assert_eq!(<Vec<u16>>::schema().to_json::<u32>(),
+///            r#"{"format_version":1,"size_width":4,"schema":{"kind":"seq","element":{"kind":"primitive","type":"u16","size":2}}}"#);
+/// # }
+/// ```
+pub trait ByteSchema {
+    /// Returns the description of the byte representation of the type
+    fn schema() -> Schema;
+}
+
+impl Schema {
+    /// Returns the length of the byte representation if it's the same for every value,
+    /// as for the fields of a packed `struct`
+    pub(crate) fn fixed_size(&self) -> Option<usize> {
+        match *self {
+            Schema::Primitive { size, .. } => Some(size),
+            Schema::Struct { packed: true, ref fields, .. } => {
+                fields.iter().map(|field| field.schema.fixed_size()).sum()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the description as a JSON document, along with the format version and the
+    /// width of the size indicators of `Size`, as written in a `FormatHeader`
+    pub fn to_json<Size>(&self) -> String
+        where Size: BVSize
+    {
+        let header = FormatHeader::of::<Size>();
+        let mut json = String::new();
+        write!(json, r#"{{"format_version":{},"size_width":{},"schema":"#,
+               header.version, header.size_width).unwrap();
+        self.write_json(&mut json);
+        json.push('}');
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        match *self {
+            Schema::Primitive { name, size } => {
+                write!(json, r#"{{"kind":"primitive","type":"{}","size":{}}}"#, name, size)
+                    .unwrap();
+            }
+            Schema::String => json.push_str(r#"{"kind":"string"}"#),
+            Schema::LossyString => json.push_str(r#"{"kind":"lossy_string"}"#),
+            Schema::Unit => json.push_str(r#"{"kind":"unit"}"#),
+            Schema::Seq(ref elem) => {
+                json.push_str(r#"{"kind":"seq","element":"#);
+                elem.write_json(json);
+                json.push('}');
+            }
+            Schema::Map(ref key, ref value) => {
+                json.push_str(r#"{"kind":"map","key":"#);
+                key.write_json(json);
+                json.push_str(r#","value":"#);
+                value.write_json(json);
+                json.push('}');
+            }
+            Schema::PackedSeq(ref elem) => {
+                json.push_str(r#"{"kind":"packed_seq","element":"#);
+                elem.write_json(json);
+                json.push('}');
+            }
+            Schema::OptionSeq(ref elem) => {
+                json.push_str(r#"{"kind":"option_seq","element":"#);
+                elem.write_json(json);
+                json.push('}');
+            }
+            Schema::InternedSeq(ref elem) => {
+                json.push_str(r#"{"kind":"interned_seq","element":"#);
+                elem.write_json(json);
+                json.push('}');
+            }
+            Schema::BitSeq => json.push_str(r#"{"kind":"bit_seq"}"#),
+            Schema::ZigZag(name) => {
+                write!(json, r#"{{"kind":"zigzag","type":"{}"}}"#, name).unwrap()
+            }
+            Schema::BitSet => json.push_str(r#"{"kind":"bit_set"}"#),
+            Schema::Tuple(ref elems) => {
+                json.push_str(r#"{"kind":"tuple","elements":"#);
+                write_json_list(json, elems, Schema::write_json);
+                json.push('}');
+            }
+            Schema::Struct { name, version, packed, extensible, compact, ref fields } => {
+                write!(json, r#"{{"kind":"struct","name":"{}","version":"#, name).unwrap();
+                write_json_option(json, version);
+                write!(json, r#","packed":{},"extensible":{},"compact":{},"fields":"#,
+                       packed, extensible, compact)
+                    .unwrap();
+                write_json_list(json, fields, |field, json| {
+                    write!(json, r#"{{"name":"{}","big_endian":{},"skippable":{},"size_width":"#,
+                           field.name, field.big_endian, field.skippable).unwrap();
+                    write_json_option(json, field.size_width);
+                    json.push_str(r#","schema":"#);
+                    field.schema.write_json(json);
+                    json.push('}');
+                });
+                json.push('}');
+            }
+            Schema::Enum { name, ref variants } => {
+                write!(json, r#"{{"kind":"enum","name":"{}","variants":"#, name).unwrap();
+                write_json_list(json, variants, |variant, json| {
+                    write!(json, r#"{{"name":"{}","tag":{},"fields":"#, variant.name, variant.tag)
+                        .unwrap();
+                    write_json_list(json, &variant.fields, Schema::write_json);
+                    json.push('}');
+                });
+                json.push('}');
+            }
+        }
+    }
+}
+
+fn write_json_list<T, F>(json: &mut String, items: &[T], write_item: F)
+    where F: Fn(&T, &mut String)
+{
+    json.push('[');
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        write_item(item, json);
+    }
+    json.push(']');
+}
+
+fn write_json_option<T: ::std::fmt::Display>(json: &mut String, value: Option<T>) {
+    match value {
+        Some(value) => write!(json, "{}", value).unwrap(),
+        None => json.push_str("null"),
+    }
+}
+
+macro_rules! primitive_schema_impls {
+    ($($t:ty),*) => {
+        $(
+            impl ByteSchema for $t {
+                fn schema() -> Schema {
+                    Schema::Primitive {
+                        name: stringify!($t),
+                        size: size_of::<$t>(),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+primitive_schema_impls!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, char, usize);
+
+#[cfg(feature = "half")]
+impl ByteSchema for ::half::f16 {
+    fn schema() -> Schema {
+        Schema::Primitive {
+            name: "f16",
+            size: 2,
+        }
+    }
+}
+
+#[cfg(feature = "half")]
+impl ByteSchema for ::LossyF16 {
+    fn schema() -> Schema {
+        ::half::f16::schema()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: ByteSchema + ::zeroize::Zeroize> ByteSchema for ::zeroize::Zeroizing<T> {
+    fn schema() -> Schema {
+        T::schema()
+    }
+}
+
+macro_rules! zigzag_schema_impls {
+    ($($t:ty),*) => {
+        $(
+            impl ByteSchema for ZigZag<$t> {
+                fn schema() -> Schema {
+                    Schema::ZigZag(stringify!($t))
+                }
+            }
+        )*
+    }
+}
+
+zigzag_schema_impls!(i8, i16, i32, i64);
+
+impl ByteSchema for str {
+    fn schema() -> Schema {
+        Schema::String
+    }
+}
+
+impl ByteSchema for &str {
+    fn schema() -> Schema {
+        Schema::String
+    }
+}
+
+impl ByteSchema for String {
+    fn schema() -> Schema {
+        Schema::String
+    }
+}
+
+impl ByteSchema for ::LossyUtf8 {
+    fn schema() -> Schema {
+        Schema::LossyString
+    }
+}
+
+impl ByteSchema for ::MaybeUtf8 {
+    fn schema() -> Schema {
+        Schema::LossyString
+    }
+}
+
+impl ByteSchema for () {
+    fn schema() -> Schema {
+        Schema::Unit
+    }
+}
+
+impl<B: ByteSchema, C: ByteSchema> ByteSchema for ::std::ops::ControlFlow<B, C> {
+    fn schema() -> Schema {
+        Schema::Enum {
+            name: "ControlFlow",
+            variants: vec![
+                VariantSchema { name: "Continue", tag: 0, fields: vec![C::schema()] },
+                VariantSchema { name: "Break", tag: 1, fields: vec![B::schema()] },
+            ],
+        }
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for ::std::task::Poll<T> {
+    fn schema() -> Schema {
+        Schema::Enum {
+            name: "Poll",
+            variants: vec![
+                VariantSchema { name: "Ready", tag: 0, fields: vec![T::schema()] },
+                VariantSchema { name: "Pending", tag: 1, fields: vec![] },
+            ],
+        }
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for Vec<T> {
+    fn schema() -> Schema {
+        Schema::Seq(Box::new(T::schema()))
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for [T] {
+    fn schema() -> Schema {
+        Schema::Seq(Box::new(T::schema()))
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for HashSet<T> {
+    fn schema() -> Schema {
+        Schema::Seq(Box::new(T::schema()))
+    }
+}
+
+impl<K: ByteSchema, V: ByteSchema> ByteSchema for HashMap<K, V> {
+    fn schema() -> Schema {
+        Schema::Map(Box::new(K::schema()), Box::new(V::schema()))
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for PackedVec<T> {
+    fn schema() -> Schema {
+        Schema::PackedSeq(Box::new(T::schema()))
+    }
+}
+
+// A byte blob has the byte representation of a `PackedVec<u8>`
+impl ByteSchema for ByteBuf {
+    fn schema() -> Schema {
+        Schema::PackedSeq(Box::new(u8::schema()))
+    }
+}
+
+impl<'a> ByteSchema for Bytes<'a> {
+    fn schema() -> Schema {
+        ByteBuf::schema()
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for OptionVec<T> {
+    fn schema() -> Schema {
+        Schema::OptionSeq(Box::new(T::schema()))
+    }
+}
+
+impl<T: ByteSchema> ByteSchema for InternedVec<T> {
+    fn schema() -> Schema {
+        Schema::InternedSeq(Box::new(T::schema()))
+    }
+}
+
+impl ByteSchema for BitVec {
+    fn schema() -> Schema {
+        Schema::BitSeq
+    }
+}
+
+impl ByteSchema for BitSet {
+    fn schema() -> Schema {
+        Schema::BitSet
+    }
+}
+
+macro_rules! tuple_schema_impls {
+    ($t:ident) => {
+        impl<$t: ByteSchema> ByteSchema for ($t,) {
+            fn schema() -> Schema {
+                Schema::Tuple(vec![$t::schema()])
+            }
+        }
+    };
+    ($t:ident, $($ts:ident),+) => {
+        impl<$t: ByteSchema, $($ts: ByteSchema),+> ByteSchema for ($t, $($ts),+) {
+            fn schema() -> Schema {
+                Schema::Tuple(vec![$t::schema(), $($ts::schema()),+])
+            }
+        }
+
+        tuple_schema_impls!($($ts),+);
+    };
+}
+
+tuple_schema_impls!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -0,0 +1,452 @@
+use std::io::{Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+
+use errors::{ByteVecError, BVExpectedSize};
+use {ByteEncodable, ByteDecodable, ByteDecodeBorrowed, BVEncodeResult, BVDecodeResult, BVSize,
+     check_encoded_len, read_size, read_size_table, write_size};
+
+/// A push-based encoder of collections, for elements that are not held in a collection.
+///
+/// The elements pushed to the encoder are written to `W` one at a time, and the result is
+/// the same byte representation as the one of a `Vec` of those elements. Since the sizes
+/// of the elements are written before the elements themselves, the encoder takes the
+/// number of elements beforehand and writes a placeholder for the sizes, which is patched
+/// by `finish` once every element has been written. A `LengthMismatchError` is returned if
+/// the number of pushed elements differs from the declared one.
+///
+/// Patching the sizes in place requires them to have a fixed length, so `Size` must be one
+/// of the fixed size integral types. An encoder with a `VarSize` is rejected by `new` with
+/// an `OverflowError`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use std::io::Cursor;
+/// # use bytevec::{ByteEncodable, SeqEncoder};
+/// # fn main() {
+/// let mut encoder = SeqEncoder::<u32, _>::new(Cursor::new(Vec::new()), 3).unwrap();
+/// for row in 0..3u64 {
+///     encoder.push(&format!("row {}", row)).unwrap();
+/// }
+/// let bytes = encoder.finish().unwrap().into_inner();
+/// assert_eq!(bytes, vec!["row 0", "row 1", "row 2"].encode::<u32>().unwrap());
+/// # }
+/// ```
+pub struct SeqEncoder<Size, W> {
+    writer: W,
+    start: u64,
+    len: usize,
+    sizes: Vec<usize>,
+    total_size: usize,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<Size, W> SeqEncoder<Size, W>
+    where Size: BVSize + ByteEncodable,
+          W: Write + Seek
+{
+    /// Returns an encoder of `len` elements, after writing the length and the placeholder
+    /// for the sizes of the elements to `writer`
+    pub fn new(mut writer: W, len: usize) -> BVEncodeResult<SeqEncoder<Size, W>> {
+        if Size::max_value().encoded_len() != Size::from_usize(0).encoded_len() {
+            return Err(ByteVecError::OverflowError);
+        }
+        let total_size = len.checked_add(1)
+            .and_then(|sizes_len| sizes_len.checked_mul(Size::get_size_of().as_usize()))
+            .ok_or(ByteVecError::OverflowError)?;
+        if total_size > Size::max_value().as_usize() {
+            return Err(ByteVecError::OverflowError);
+        }
+        let start = writer.stream_position()?;
+        Size::from_usize(len).encode_to::<Size, W>(&mut writer)?;
+        for _ in 0..len {
+            Size::from_usize(0).encode_to::<Size, W>(&mut writer)?;
+        }
+        Ok(SeqEncoder {
+            writer,
+            start,
+            len,
+            sizes: Vec::with_capacity(len),
+            total_size,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Writes the byte representation of `elem` to the underlying writer
+    pub fn push<T>(&mut self, elem: &T) -> BVEncodeResult<()>
+        where T: ByteEncodable + ?Sized
+    {
+        if self.sizes.len() == self.len {
+            return Err(ByteVecError::LengthMismatchError {
+                expected: self.len,
+                actual: self.len + 1,
+            });
+        }
+        let size = elem.get_size::<Size>().ok_or(ByteVecError::OverflowError)?.as_usize();
+        let total_size = self.total_size
+            .checked_add(size)
+            .and_then(|total_size| {
+                if total_size <= Size::max_value().as_usize() {
+                    Some(total_size)
+                } else {
+                    None
+                }
+            })
+            .ok_or(ByteVecError::OverflowError)?;
+        elem.encode_to::<Size, W>(&mut self.writer)?;
+        self.sizes.push(size);
+        self.total_size = total_size;
+        Ok(())
+    }
+
+    /// Returns the number of elements pushed so far
+    pub fn pushed(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Patches the sizes of the elements and returns the underlying writer, positioned
+    /// at the end of the byte representation
+    pub fn finish(mut self) -> BVEncodeResult<W> {
+        if self.sizes.len() != self.len {
+            return Err(ByteVecError::LengthMismatchError {
+                expected: self.len,
+                actual: self.sizes.len(),
+            });
+        }
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.start + Size::get_size_of().as_usize() as u64))?;
+        for size in &self.sizes {
+            Size::from_usize(*size).encode_to::<Size, W>(&mut self.writer)?;
+        }
+        self.writer.seek(SeekFrom::Start(end))?;
+        Ok(self.writer)
+    }
+}
+
+/// A lazy decoder of collections, which yields the elements one at a time.
+///
+/// The byte representation is read as the one of a `Vec<T>`, but the elements are only
+/// deserialized as the iterator advances, so the consumer can filter them or stop early
+/// without allocating the whole collection. The size indicators are validated when the
+/// decoder is created, and each element is yielded as a `BVDecodeResult<T>`.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, SeqDecoder};
+/// # fn main() {
+/// let bytes = (1..1000u32).collect::<Vec<_>>().encode::<u32>().unwrap();
+/// let first_even = SeqDecoder::<u32, u32>::new(&bytes).unwrap()
+///     .map(Result::unwrap)
+///     .find(|n| n % 2 == 0);
+/// assert_eq!(first_even, Some(2));
+/// # }
+/// ```
+pub struct SeqDecoder<'a, T, Size = u32> {
+    bytes: &'a [u8],
+    sizes_index: usize,
+    body_index: usize,
+    remaining: usize,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<'a, T, Size> SeqDecoder<'a, T, Size>
+    where T: ByteDecodeBorrowed<'a>,
+          Size: BVSize + ByteDecodable
+{
+    /// Returns a decoder of the elements of the collection serialized in `bytes`
+    pub fn new(bytes: &'a [u8]) -> BVDecodeResult<SeqDecoder<'a, T, Size>> {
+        let (len, sizes_index, body_index) = split_collection::<Size>(bytes)?;
+        Ok(SeqDecoder {
+            bytes,
+            sizes_index,
+            body_index,
+            remaining: len,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T, Size> Iterator for SeqDecoder<'a, T, Size>
+    where T: ByteDecodeBorrowed<'a>,
+          Size: BVSize + ByteDecodable
+{
+    type Item = BVDecodeResult<T>;
+
+    fn next(&mut self) -> Option<BVDecodeResult<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (size, size_len) = match read_size::<Size>(&self.bytes[self.sizes_index..]) {
+            Ok(size) => size,
+            Err(error) => return Some(Err(error)),
+        };
+        let elem = T::decode_borrowed::<Size>(&self.bytes[self.body_index..self.body_index + size]);
+        self.sizes_index += size_len;
+        self.body_index += size;
+        self.remaining -= 1;
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, Size> ExactSizeIterator for SeqDecoder<'a, T, Size>
+    where T: ByteDecodeBorrowed<'a>,
+          Size: BVSize + ByteDecodable
+{
+}
+
+/// Returns the byte representation of the elements yielded by `iter`, the same as the one of
+/// a `Vec` of those elements, without collecting them first.
+///
+/// The elements are written to the buffer as they're yielded, and their sizes are inserted
+/// in front of them at the end, so the number of elements doesn't need to be known
+/// beforehand. `len_hint` is the expected number of elements, which reserves the room of
+/// their sizes, and may differ from the actual one.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::ByteEncodable;
+/// # fn main() {
+/// let rows = (0..3).map(|row| format!("row {}", row));
+/// let bytes = bytevec::encode_iter::<u32, _>(rows, 3).unwrap();
+/// assert_eq!(bytes, vec!["row 0", "row 1", "row 2"].encode::<u32>().unwrap());
+/// # }
+/// ```
+pub fn encode_iter<Size, I>(iter: I, len_hint: usize) -> BVEncodeResult<Vec<u8>>
+    where Size: BVSize + ByteEncodable,
+          I: IntoIterator,
+          I::Item: ByteEncodable
+{
+    let mut sizes = Vec::new();
+    if !Size::bincode() {
+        sizes.reserve(len_hint.saturating_mul(Size::get_size_of().as_usize()));
+    }
+    let mut out = Vec::new();
+    let mut len = 0usize;
+    for elem in iter {
+        let elem_start = out.len();
+        elem.encode_to_buf::<Size>(&mut out)?;
+        let size = out.len() - elem_start;
+        if size > Size::max_value().as_usize() {
+            return Err(ByteVecError::OverflowError);
+        }
+        if !Size::bincode() {
+            Size::from_usize(size).encode_to::<Size, _>(&mut sizes)?;
+        }
+        len += 1;
+    }
+    let mut header = Vec::with_capacity(Size::get_size_of().as_usize() + sizes.len());
+    write_size::<Size>(&mut header, len)?;
+    header.extend_from_slice(&sizes);
+    out.splice(0..0, header);
+    check_encoded_len::<Size>(&out, 0)?;
+    Ok(out)
+}
+
+/// Returns the `n` values deserialized from the start of `bytes`, whose byte representations
+/// are written back to back, along with the bytes that follow them.
+///
+/// Each value is read as with `ByteDecodable::decode_lenient`, so the remaining bytes can be
+/// handed over to the next call to page through a long sequence of values, such as a file of
+/// records, or the elements of a collection in the layout of bincode, which follow its
+/// length. The values must delimit themselves, as the structures, the tuples and the
+/// collections do, while a string of its own takes the rest of the buffer outside of the
+/// layout of bincode. The elements of a collection in the layout of this crate are preceded
+/// by their sizes and are read lazily by `SeqDecoder` instead. A buffer that ends before the
+/// `n`-th value yields the error of the value that is cut short.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::ByteEncodable;
+/// # fn main() {
+/// let mut bytes = Vec::new();
+/// for id in 0..5u16 {
+///     (id, format!("record {}", id)).encode_to_buf::<u16>(&mut bytes).unwrap();
+/// }
+/// let (page, rest) = bytevec::decode_n::<(u16, String), u16>(&bytes, 2).unwrap();
+/// assert_eq!(page[1], (1, "record 1".to_string()));
+/// let (page, rest) = bytevec::decode_n::<(u16, String), u16>(rest, 3).unwrap();
+/// assert_eq!(page[2].0, 4);
+/// assert!(rest.is_empty());
+/// # }
+/// ```
+pub fn decode_n<T, Size>(bytes: &[u8], n: usize) -> BVDecodeResult<(Vec<T>, &[u8])>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    let mut values = Vec::with_capacity(n.min(bytes.len()));
+    let mut index = 0;
+    for position in 0..n {
+        let (value, len) = T::decode_with_len::<Size>(&bytes[index..])
+            .map_err(|error| error.at(index).in_element(position))?;
+        values.push(value);
+        index += len;
+    }
+    Ok((values, &bytes[index..]))
+}
+
+/// Returns the element at `index` of the collection serialized in `bytes`, as a `Vec<T>`,
+/// without deserializing the other elements.
+///
+/// The size table of the collection is read to find the bytes of the element, and to check
+/// that the sizes add up to the length of the buffer, as with `SeqDecoder`. An `index` past
+/// the end of the collection yields an `IndexOutOfBoundsError`. In the layout of bincode,
+/// which has no size table, the elements before `index` are skipped through their
+/// `decode_with_len` implementation.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::ByteEncodable;
+/// # fn main() {
+/// let records = (0..100_000u32).map(|id| format!("record {}", id)).collect::<Vec<_>>();
+/// let bytes = records.encode::<u32>().unwrap();
+/// let record = bytevec::collection_index::<String, u32>(&bytes, 99_999).unwrap();
+/// assert_eq!(record, "record 99999");
+/// assert!(bytevec::collection_index::<String, u32>(&bytes, 100_000).is_err());
+/// # }
+/// ```
+pub fn collection_index<T, Size>(bytes: &[u8], index: usize) -> BVDecodeResult<T>
+    where T: ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    let (len, mut body_index) = read_size::<Size>(bytes)?;
+    if index >= len {
+        return Err(ByteVecError::IndexOutOfBoundsError { index, len });
+    }
+    if Size::bincode() {
+        for position in 0..index {
+            let (_, size) = T::decode_with_len::<Size>(&bytes[body_index..])
+                .map_err(|error| error.at(body_index).in_element(position))?;
+            body_index += size;
+        }
+        return T::decode_with_len::<Size>(&bytes[body_index..])
+            .map(|(elem, _)| elem)
+            .map_err(|error| error.at(body_index).in_element(index));
+    }
+    let mut elem_start = 0usize;
+    let mut elem_size = 0;
+    let mut body_size = 0usize;
+    for position in 0..len {
+        let (size, size_len) = read_size::<Size>(&bytes[body_index..])
+            .map_err(|error| error.at(body_index))?;
+        if position == index {
+            elem_start = body_size;
+            elem_size = size;
+        }
+        body_index += size_len;
+        body_size = body_size.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+    }
+    if body_index.checked_add(body_size) != Some(bytes.len()) {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(body_index.saturating_add(body_size)),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    let start = body_index + elem_start;
+    T::decode::<Size>(&bytes[start..start + elem_size])
+        .map_err(|error| error.at(start).in_element(index))
+}
+
+/// Reads the length and the size table of the collection serialized in `bytes`, and checks
+/// that the sizes add up to the length of the buffer. Returns the length, the start of the
+/// size table and the start of the elements.
+fn split_collection<Size>(bytes: &[u8]) -> BVDecodeResult<(usize, usize, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let (len, sizes_index) = read_size::<Size>(bytes)?;
+    let mut body_index = sizes_index;
+    let mut body_size = 0usize;
+    for _ in 0..len {
+        let (size, size_len) = read_size::<Size>(&bytes[body_index..])
+            .map_err(|error| error.at(body_index))?;
+        body_index += size_len;
+        body_size = body_size.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+    }
+    if body_index.checked_add(body_size) != Some(bytes.len()) {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(body_index.saturating_add(body_size)),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    Ok((len, sizes_index, body_index))
+}
+
+/// Returns the byte representation of a `Vec` with the elements of the `Vec` serialized in
+/// `a` followed by the ones of the `Vec` serialized in `b`, without deserializing them.
+///
+/// The size tables and the elements of both collections are copied as they are, and only
+/// the length is written again. The sizes of the tables are checked as with `SeqDecoder`,
+/// while the elements themselves are not, and the layout of bincode, which has no size
+/// table, is merged through the lengths alone. The result overflows the `Size` type with an
+/// `OverflowError`. Merging the byte representations of sets or maps may yield duplicate
+/// elements, which are rejected on deserialization with a `Strict` size type.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, ByteDecodable};
+/// # fn main() {
+/// let first = vec!["GET /".to_string()].encode::<u32>().unwrap();
+/// let second = vec!["POST /login".to_string(), "GET /home".to_string()].encode::<u32>().unwrap();
+/// let batch = bytevec::merge_encoded_vecs::<u32>(&first, &second).unwrap();
+/// assert_eq!(Vec::<String>::decode::<u32>(&batch).unwrap(),
+///            vec!["GET /", "POST /login", "GET /home"]);
+/// # }
+/// ```
+pub fn merge_encoded_vecs<Size>(a: &[u8], b: &[u8]) -> BVEncodeResult<Vec<u8>>
+    where Size: BVSize + ByteEncodable + ByteDecodable
+{
+    let ((a_len, a_sizes, a_body), (b_len, b_sizes, b_body)) = if Size::bincode() {
+        let (a_len, a_body) = read_size::<Size>(a)?;
+        let (b_len, b_body) = read_size::<Size>(b)?;
+        ((a_len, a_body, a_body), (b_len, b_body, b_body))
+    } else {
+        (split_collection::<Size>(a)?, split_collection::<Size>(b)?)
+    };
+    let len = a_len.checked_add(b_len).ok_or(ByteVecError::OverflowError)?;
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    write_size::<Size>(&mut out, len)?;
+    out.extend_from_slice(&a[a_sizes..a_body]);
+    out.extend_from_slice(&b[b_sizes..b_body]);
+    out.extend_from_slice(&a[a_body..]);
+    out.extend_from_slice(&b[b_body..]);
+    check_encoded_len::<Size>(&out, 0)?;
+    Ok(out)
+}
+
+/// Returns the number of elements declared by the collection serialized in `bytes`, without
+/// reading the rest of the buffer.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::ByteEncodable;
+/// # fn main() {
+/// let bytes = vec!["a", "bc", "def"].encode::<u16>().unwrap();
+/// assert_eq!(bytevec::peek_len::<u16>(&bytes).unwrap(), 3);
+/// assert_eq!(bytevec::peek_sizes::<u16>(&bytes).unwrap(), vec![1, 2, 3]);
+/// # }
+/// ```
+pub fn peek_len<Size>(bytes: &[u8]) -> BVDecodeResult<usize>
+    where Size: BVSize + ByteDecodable
+{
+    read_size::<Size>(bytes).map(|(len, _)| len)
+}
+
+/// Returns the sizes of the elements of the collection serialized in `bytes`, without
+/// deserializing them.
+///
+/// The sizes are checked to add up to the length of the buffer, as with `SeqDecoder`. The
+/// layout of bincode has no size table, so the sizes of its elements can't be read without
+/// deserializing them, and an `InvalidValueError` is returned.
+pub fn peek_sizes<Size>(bytes: &[u8]) -> BVDecodeResult<Vec<usize>>
+    where Size: BVSize + ByteDecodable
+{
+    if Size::bincode() {
+        return Err(ByteVecError::InvalidValueError("a size table"));
+    }
+    let (len, sizes_index, _) = split_collection::<Size>(bytes)?;
+    read_size_table::<Size>(&bytes[sizes_index..], len).map(|(sizes, _)| sizes)
+}
@@ -0,0 +1,1083 @@
+//! A [serde][1] `Serializer` and `Deserializer` for the byte representations of bytevec.
+//!
+//! [`ByteVecSerializer`](struct.ByteVecSerializer.html) writes any `Serialize` value in the
+//! layout that the bytevec implementations of the equivalent types write, and
+//! [`ByteVecDeserializer`](struct.ByteVecDeserializer.html) reads it back into any
+//! `Deserialize` type, so a type that derives the serde traits can exchange its values with
+//! the types that implement `ByteEncodable` and `ByteDecodable`. The `Size` type parameter
+//! selects the size indicators and the options of the layout, as with the `encode` and
+//! `decode` methods.
+//!
+//! - A `struct`, a tuple and a tuple `struct` are serialized as the structures of
+//!   `bytevec_impls`, with a size table followed by the fields, and so is a newtype
+//!   `struct` with its single field. A unit `struct` takes no bytes.
+//! - A sequence and a map are serialized as a `Vec` and a `HashMap`, with their number of
+//!   elements followed by a size table, where each entry of a map is a tuple of its key and
+//!   its value. In the deterministic mode, the entries of a map are sorted by the byte
+//!   representation of their keys, but the elements of a set are written in their
+//!   iteration order, as serde doesn't tell the sets apart from the sequences.
+//! - An `enum` is serialized as the enums of `bytevec_impls`, with the index of its variant
+//!   followed by its fields as a tuple, and an `Option` as an `enum` with the variants
+//!   `None` and `Some`, in this order.
+//! - A `bool` is serialized as a `u8` of value 0 or 1, and a byte array as a `Vec<u8>`.
+//!
+//! The byte representations are not self-describing, so the types that are deserialized
+//! through `deserialize_any`, such as the untagged enums or the flattened fields, are not
+//! supported, and neither are the fields skipped with `skip_serializing_if`. This module
+//! is only available with the `serde` feature.
+//!
+//! ```rust
+//! # #[macro_use] extern crate bytevec;
+//! # #[macro_use] extern crate serde;
+//! # use bytevec::serde_bridge;
+//! # use bytevec::{ByteEncodable, ByteDecodable};
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Point {
+//!     x: u8,
+//!     y: i8
+//! }
+//!
+//! bytevec_decl! {
+//!     #[derive(Debug, PartialEq)]
+//!     struct BvPoint {
+//!         x: u8,
+//!         y: i8
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let bytes = serde_bridge::encode::<_, u8>(&Point { x: 1, y: -1 }).unwrap();
+//! assert_eq!(bytes, [1, 1, 1, 0xff]);
+//! assert_eq!(BvPoint::decode::<u8>(&bytes).unwrap(), BvPoint { x: 1, y: -1 });
+//! let bytes = BvPoint { x: 2, y: -2 }.encode::<u8>().unwrap();
+//! assert_eq!(serde_bridge::decode::<Point, u8>(&bytes).unwrap(), Point { x: 2, y: -2 });
+//! # }
+//! ```
+//!
+//! [1]: https://serde.rs
+
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::mem;
+use std::str;
+
+use serde::{de, ser, Serialize, Deserialize};
+use serde::de::{DeserializeSeed, IntoDeserializer, Visitor};
+
+use errors::{ByteVecError, BVExpectedSize};
+use impls::{read_bincode_bytes, read_tag, write_tag};
+use limits::{check_elements, enter_nested};
+use {ByteEncodable, ByteDecodable, ByteDecodeBorrowed, BVEncodeResult, BVDecodeResult, BVSize,
+     SizeTable, check_encoded_len, read_size, read_size_table, write_size};
+
+impl ser::Error for ByteVecError {
+    fn custom<T: Display>(message: T) -> ByteVecError {
+        ByteVecError::CustomError(message.to_string())
+    }
+}
+
+impl de::Error for ByteVecError {
+    fn custom<T: Display>(message: T) -> ByteVecError {
+        ByteVecError::CustomError(message.to_string())
+    }
+}
+
+/// Returns the byte representation of `value` written through its `Serialize`
+/// implementation
+pub fn encode<T, Size>(value: &T) -> BVEncodeResult<Vec<u8>>
+    where T: Serialize + ?Sized,
+          Size: BVSize + ByteEncodable
+{
+    let mut out = Vec::new();
+    value.serialize(&mut ByteVecSerializer::<Size>::new(&mut out))?;
+    check_encoded_len::<Size>(&out, 0)?;
+    Ok(out)
+}
+
+/// Reads a value of type `T` through its `Deserialize` implementation from its byte
+/// representation, which must take the whole buffer
+pub fn decode<'de, T, Size>(bytes: &'de [u8]) -> BVDecodeResult<T>
+    where T: Deserialize<'de>,
+          Size: BVSize + ByteDecodable
+{
+    let mut deserializer = ByteVecDeserializer::<Size>::new(bytes);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A serde `Serializer` that appends the byte representations of the values to a buffer
+pub struct ByteVecSerializer<'a, Size = u32> {
+    out: &'a mut Vec<u8>,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<'a, Size> ByteVecSerializer<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    /// Returns a serializer that appends the byte representations to `out`
+    pub fn new(out: &'a mut Vec<u8>) -> ByteVecSerializer<'a, Size> {
+        ByteVecSerializer {
+            out,
+            _marker: PhantomData,
+        }
+    }
+
+    fn primitive<T: ByteEncodable>(&mut self, value: T) -> BVEncodeResult<()> {
+        value.encode_to::<Size, _>(self.out)
+    }
+
+    /// Starts a structure of `len` fields, preceded by the index of its variant if any
+    fn fields(&mut self, tag: Option<u32>, len: usize) -> BVEncodeResult<Compound<'_, Size>> {
+        let start = self.out.len();
+        if let Some(tag) = tag {
+            write_tag::<Size>(self.out, tag as usize)?;
+        }
+        let table = Table::Fixed(SizeTable::new::<Size>(self.out, len));
+        Ok(Compound::new(self.out, start, table, Some(len)))
+    }
+
+    /// Starts a collection, whose number of elements is written along with the size table
+    /// at the end if it's not known beforehand
+    fn elements(&mut self, len: Option<usize>, sorted: bool)
+        -> BVEncodeResult<Compound<'_, Size>>
+    {
+        let start = self.out.len();
+        let table = match len {
+            Some(len) => {
+                write_size::<Size>(self.out, len)?;
+                Table::Fixed(SizeTable::new::<Size>(self.out, len))
+            }
+            None => Table::Deferred(Vec::new()),
+        };
+        let mut compound = Compound::new(self.out, start, table, len);
+        if sorted {
+            compound.sorted = Some(Vec::new());
+        }
+        Ok(compound)
+    }
+
+    /// Writes `value` as the single field of a structure
+    fn wrapped<T>(&mut self, tag: Option<u32>, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        let mut compound = self.fields(tag, 1)?;
+        compound.element(value)?;
+        compound.finish()
+    }
+}
+
+impl<'a, 'b, Size> ser::Serializer for &'b mut ByteVecSerializer<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    type Ok = ();
+    type Error = ByteVecError;
+    type SerializeSeq = Compound<'b, Size>;
+    type SerializeTuple = Compound<'b, Size>;
+    type SerializeTupleStruct = Compound<'b, Size>;
+    type SerializeTupleVariant = Compound<'b, Size>;
+    type SerializeMap = Compound<'b, Size>;
+    type SerializeStruct = Compound<'b, Size>;
+    type SerializeStructVariant = Compound<'b, Size>;
+
+    fn serialize_bool(self, value: bool) -> BVEncodeResult<()> {
+        self.primitive(value as u8)
+    }
+
+    fn serialize_i8(self, value: i8) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_i16(self, value: i16) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_i32(self, value: i32) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_i64(self, value: i64) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_i128(self, value: i128) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_u16(self, value: u16) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_u32(self, value: u32) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_u64(self, value: u64) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_u128(self, value: u128) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_f64(self, value: f64) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_char(self, value: char) -> BVEncodeResult<()> {
+        self.primitive(value)
+    }
+
+    fn serialize_str(self, value: &str) -> BVEncodeResult<()> {
+        value.encode_to::<Size, _>(self.out)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> BVEncodeResult<()> {
+        value.encode_to_buf::<Size>(self.out)
+    }
+
+    fn serialize_none(self) -> BVEncodeResult<()> {
+        write_tag::<Size>(self.out, 0)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.wrapped(Some(1), value)
+    }
+
+    fn serialize_unit(self) -> BVEncodeResult<()> {
+        ().encode_to_buf::<Size>(self.out)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> BVEncodeResult<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, index: u32, _variant: &'static str)
+        -> BVEncodeResult<()>
+    {
+        write_tag::<Size>(self.out, index as usize)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.wrapped(None, value)
+    }
+
+    fn serialize_newtype_variant<T>(self,
+                                    _name: &'static str,
+                                    index: u32,
+                                    _variant: &'static str,
+                                    value: &T)
+                                    -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.wrapped(Some(index), value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> BVEncodeResult<Compound<'b, Size>> {
+        self.elements(len, false)
+    }
+
+    fn serialize_tuple(self, len: usize) -> BVEncodeResult<Compound<'b, Size>> {
+        self.fields(None, len)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize)
+        -> BVEncodeResult<Compound<'b, Size>>
+    {
+        self.fields(None, len)
+    }
+
+    fn serialize_tuple_variant(self,
+                               _name: &'static str,
+                               index: u32,
+                               _variant: &'static str,
+                               len: usize)
+                               -> BVEncodeResult<Compound<'b, Size>> {
+        self.fields(Some(index), len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> BVEncodeResult<Compound<'b, Size>> {
+        self.elements(len, Size::deterministic())
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize)
+        -> BVEncodeResult<Compound<'b, Size>>
+    {
+        self.fields(None, len)
+    }
+
+    fn serialize_struct_variant(self,
+                                _name: &'static str,
+                                index: u32,
+                                _variant: &'static str,
+                                len: usize)
+                                -> BVEncodeResult<Compound<'b, Size>> {
+        self.fields(Some(index), len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The size table of a `Compound`, filled in as its entries are written, or written at the
+/// end along with the number of elements if the number of elements is not known beforehand
+enum Table {
+    Fixed(SizeTable),
+    Deferred(Vec<usize>),
+}
+
+/// The state of a sequence, a map, a tuple or a structure written by a `ByteVecSerializer`
+pub struct Compound<'a, Size> {
+    out: &'a mut Vec<u8>,
+    start: usize,
+    table: Table,
+    len: Option<usize>,
+    entries: usize,
+    // The size table of the key and the value of the map entry being written, along with
+    // the start of the entry
+    pair: Option<(usize, SizeTable)>,
+    // In the deterministic mode, the entries of a map are written to `entry` and held in
+    // `sorted` along with the byte representation of their keys until the map is finished
+    entry: Vec<u8>,
+    key: Vec<u8>,
+    sorted: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+impl<'a, Size> Compound<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    fn new(out: &'a mut Vec<u8>, start: usize, table: Table, len: Option<usize>)
+        -> Compound<'a, Size>
+    {
+        Compound {
+            out,
+            start,
+            table,
+            len,
+            entries: 0,
+            pair: None,
+            entry: Vec::new(),
+            key: Vec::new(),
+            sorted: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Writes `value` as the next entry
+    fn element<T>(&mut self, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        let entry_start = self.out.len();
+        value.serialize(&mut ByteVecSerializer::<Size>::new(self.out))?;
+        self.entry(entry_start)
+    }
+
+    /// Records the size of the entry written at the end of the buffer from `entry_start`
+    fn entry(&mut self, entry_start: usize) -> BVEncodeResult<()> {
+        self.entries += 1;
+        match self.table {
+            Table::Fixed(ref mut table) => table.entry::<Size>(self.out, entry_start),
+            Table::Deferred(ref mut sizes) => {
+                let size = self.out.len() - entry_start;
+                if size > Size::max_value().as_usize() {
+                    return Err(ByteVecError::OverflowError);
+                }
+                sizes.push(size);
+                Ok(())
+            }
+        }
+    }
+
+    fn key<T>(&mut self, key: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        let out = if self.sorted.is_some() { &mut self.entry } else { &mut *self.out };
+        let entry_start = out.len();
+        let mut pair = SizeTable::new::<Size>(out, 2);
+        let key_start = out.len();
+        key.serialize(&mut ByteVecSerializer::<Size>::new(out))?;
+        if self.sorted.is_some() {
+            self.key = out[key_start..].to_vec();
+        }
+        pair.entry::<Size>(out, key_start)?;
+        self.pair = Some((entry_start, pair));
+        Ok(())
+    }
+
+    fn value<T>(&mut self, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        let (entry_start, mut pair) = self.pair
+            .take()
+            .ok_or(ByteVecError::InvalidValueError("a key before the value of a map entry"))?;
+        let out = if self.sorted.is_some() { &mut self.entry } else { &mut *self.out };
+        let value_start = out.len();
+        value.serialize(&mut ByteVecSerializer::<Size>::new(out))?;
+        pair.entry::<Size>(out, value_start)?;
+        pair.finish(out);
+        match self.sorted {
+            Some(ref mut sorted) => {
+                sorted.push((mem::take(&mut self.key), mem::take(&mut self.entry)));
+                Ok(())
+            }
+            None => self.entry(entry_start),
+        }
+    }
+
+    fn finish(mut self) -> BVEncodeResult<()> {
+        if let Some(mut sorted) = self.sorted.take() {
+            sorted.sort_by(|a, b| a.0.cmp(&b.0));
+            for (_, entry) in sorted {
+                let entry_start = self.out.len();
+                self.out.extend_from_slice(&entry);
+                self.entry(entry_start)?;
+            }
+        }
+        if let Some(len) = self.len {
+            if len != self.entries {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: len,
+                    actual: self.entries,
+                });
+            }
+        }
+        match self.table {
+            Table::Fixed(table) => table.finish(self.out),
+            Table::Deferred(sizes) => {
+                let mut header = Vec::new();
+                write_size::<Size>(&mut header, sizes.len())?;
+                if !Size::bincode() {
+                    for size in sizes {
+                        write_size::<Size>(&mut header, size)?;
+                    }
+                }
+                self.out.splice(self.start..self.start, header);
+            }
+        }
+        check_encoded_len::<Size>(self.out, self.start)
+    }
+}
+
+macro_rules! compound_impls {
+    ($($t:ident $method:ident),*) => {
+        $(
+            impl<'a, Size> ser::$t for Compound<'a, Size>
+                where Size: BVSize + ByteEncodable
+            {
+                type Ok = ();
+                type Error = ByteVecError;
+
+                fn $method<T>(&mut self, value: &T) -> BVEncodeResult<()>
+                    where T: Serialize + ?Sized
+                {
+                    self.element(value)
+                }
+
+                fn end(self) -> BVEncodeResult<()> {
+                    self.finish()
+                }
+            }
+        )*
+    }
+}
+
+compound_impls!(SerializeSeq serialize_element,
+                SerializeTuple serialize_element,
+                SerializeTupleStruct serialize_field,
+                SerializeTupleVariant serialize_field);
+
+impl<'a, Size> ser::SerializeMap for Compound<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    type Ok = ();
+    type Error = ByteVecError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.value(value)
+    }
+
+    fn end(self) -> BVEncodeResult<()> {
+        self.finish()
+    }
+}
+
+impl<'a, Size> ser::SerializeStruct for Compound<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    type Ok = ();
+    type Error = ByteVecError;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> BVEncodeResult<()> {
+        self.finish()
+    }
+}
+
+impl<'a, Size> ser::SerializeStructVariant for Compound<'a, Size>
+    where Size: BVSize + ByteEncodable
+{
+    type Ok = ();
+    type Error = ByteVecError;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> BVEncodeResult<()>
+        where T: Serialize + ?Sized
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> BVEncodeResult<()> {
+        self.finish()
+    }
+}
+
+/// A serde `Deserializer` that reads the values from their byte representations
+pub struct ByteVecDeserializer<'de, Size = u32> {
+    bytes: &'de [u8],
+    index: usize,
+    _marker: PhantomData<fn() -> Size>,
+}
+
+/// How the errors of the entries of a structure or a collection are located in their path
+#[derive(Clone, Copy)]
+enum Path {
+    Elements,
+    Fields(&'static [&'static str]),
+    Tuple,
+}
+
+impl Path {
+    fn locate(self, error: ByteVecError, position: usize) -> ByteVecError {
+        match self {
+            Path::Elements => error.in_element(position),
+            Path::Fields(names) => {
+                match names.get(position) {
+                    Some(name) => error.in_field(name),
+                    None => error,
+                }
+            }
+            Path::Tuple => error,
+        }
+    }
+}
+
+impl<'de, Size> ByteVecDeserializer<'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    /// Returns a deserializer that reads a value from `bytes`
+    pub fn new(bytes: &'de [u8]) -> ByteVecDeserializer<'de, Size> {
+        ByteVecDeserializer {
+            bytes,
+            index: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fails if the bytes were not all read by the deserialization
+    pub fn end(&self) -> BVDecodeResult<()> {
+        if self.index == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(self.index),
+                actual: self.bytes.len(),
+                offset: 0,
+            })
+        }
+    }
+
+    fn primitive<T: ByteDecodable>(&mut self) -> BVDecodeResult<T> {
+        let (value, len) = T::decode_with_len::<Size>(&self.bytes[self.index..])
+            .map_err(|error| error.at(self.index))?;
+        self.index += len;
+        Ok(value)
+    }
+
+    fn size(&mut self) -> BVDecodeResult<usize> {
+        let (size, len) = read_size::<Size>(&self.bytes[self.index..])
+            .map_err(|error| error.at(self.index))?;
+        self.index += len;
+        Ok(size)
+    }
+
+    fn tag(&mut self) -> BVDecodeResult<usize> {
+        let (tag, len) = read_tag::<Size>(&self.bytes[self.index..])
+            .map_err(|error| error.at(self.index))?;
+        self.index += len;
+        Ok(tag)
+    }
+
+    /// Returns the bytes of a string, which take the rest of the buffer, or follow their
+    /// length in the layout of bincode
+    fn string_bytes(&mut self) -> BVDecodeResult<&'de [u8]> {
+        let bytes = self.bytes;
+        if Size::bincode() {
+            let (string, len) = read_bincode_bytes::<Size>(&bytes[self.index..])
+                .map_err(|error| error.at(self.index))?;
+            self.index += len;
+            Ok(string)
+        } else {
+            let string = &bytes[self.index..];
+            self.index = bytes.len();
+            Ok(string)
+        }
+    }
+
+    fn str(&mut self) -> BVDecodeResult<&'de str> {
+        let string = self.string_bytes()?;
+        let start = self.index - string.len();
+        str::from_utf8(string).map_err(|error| ByteVecError::from(error).at(start))
+    }
+
+    /// Reads a size table of `entries` entries, whose entries must take the rest of the bytes
+    /// up to `end`
+    fn table(&mut self, entries: usize, end: usize) -> BVDecodeResult<Vec<usize>> {
+        let start = self.index;
+        let (sizes, table_len) = read_size_table::<Size>(&self.bytes[start..end], entries)
+            .map_err(|error| error.at(start))?;
+        let len = sizes.iter()
+            .try_fold(table_len, |len, size| len.checked_add(*size))
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if len != end - start {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::EqualTo(len),
+                actual: end - start,
+                offset: start,
+            });
+        }
+        self.index += table_len;
+        Ok(sizes)
+    }
+
+    /// Reads the value that takes the next `size` bytes with `read`, through a deserializer of
+    /// its own
+    fn entry<T, F>(&mut self, size: usize, read: F) -> BVDecodeResult<T>
+        where F: FnOnce(&mut ByteVecDeserializer<'de, Size>) -> BVDecodeResult<T>
+    {
+        let start = self.index;
+        let mut deserializer = ByteVecDeserializer::new(&self.bytes[start..start + size]);
+        let value = read(&mut deserializer)
+            .and_then(|value| deserializer.end().map(|_| value))
+            .map_err(|error| error.at(start))?;
+        self.index += size;
+        Ok(value)
+    }
+
+    /// Reads the single field of a structure with `read`
+    fn wrapped<T, F>(&mut self, read: F) -> BVDecodeResult<T>
+        where F: FnOnce(&mut ByteVecDeserializer<'de, Size>) -> BVDecodeResult<T>
+    {
+        if Size::bincode() {
+            return read(self);
+        }
+        let end = self.bytes.len();
+        let sizes = self.table(1, end)?;
+        self.entry(sizes[0], read)
+    }
+
+    /// Returns the access to the next `len` entries, which take the rest of the bytes
+    fn entries(&mut self, len: usize, path: Path) -> BVDecodeResult<Entries<'_, 'de, Size>> {
+        let sizes = if Size::bincode() {
+            Vec::new()
+        } else {
+            let end = self.bytes.len();
+            self.table(len, end)?
+        };
+        Ok(Entries {
+            deserializer: self,
+            sizes,
+            len,
+            position: 0,
+            path,
+            value: None,
+        })
+    }
+
+    /// Returns the number of elements of a collection
+    fn elements(&mut self) -> BVDecodeResult<usize> {
+        let len = self.size()?;
+        check_elements(len)?;
+        Ok(len)
+    }
+
+    fn not_self_describing<T>(&self) -> BVDecodeResult<T> {
+        Err(de::Error::custom("the byte representations of bytevec are not self-describing, so \
+                               the type of each value must be known"))
+    }
+}
+
+macro_rules! deserialize_primitives {
+    ($($method:ident $visit:ident $t:ty),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+                where V: Visitor<'de>
+            {
+                visitor.$visit(self.primitive::<$t>()?)
+            }
+        )*
+    }
+}
+
+impl<'de, Size> de::Deserializer<'de> for &mut ByteVecDeserializer<'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    type Error = ByteVecError;
+
+    deserialize_primitives!(deserialize_i8 visit_i8 i8,
+                            deserialize_i16 visit_i16 i16,
+                            deserialize_i32 visit_i32 i32,
+                            deserialize_i64 visit_i64 i64,
+                            deserialize_i128 visit_i128 i128,
+                            deserialize_u8 visit_u8 u8,
+                            deserialize_u16 visit_u16 u16,
+                            deserialize_u32 visit_u32 u32,
+                            deserialize_u64 visit_u64 u64,
+                            deserialize_u128 visit_u128 u128,
+                            deserialize_f32 visit_f32 f32,
+                            deserialize_f64 visit_f64 f64,
+                            deserialize_char visit_char char);
+
+    fn deserialize_any<V>(self, _visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        self.not_self_describing()
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        match self.primitive::<u8>()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(ByteVecError::InvalidValueError("a boolean of value 0 or 1")),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        visitor.visit_borrowed_str(self.str()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let bytes = if Size::bincode() {
+            self.string_bytes()?
+        } else {
+            let start = self.index;
+            let bytes = <&[u8]>::decode_borrowed::<Size>(&self.bytes[start..])
+                .map_err(|error| error.at(start))?;
+            self.index = self.bytes.len();
+            bytes
+        };
+        visitor.visit_borrowed_bytes(bytes)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        match self.tag()? {
+            0 => visitor.visit_none(),
+            1 => self.wrapped(|deserializer| visitor.visit_some(deserializer)),
+            tag => Err(ByteVecError::UnknownVariantError(tag)),
+        }
+    }
+
+    // A `()` takes the rest of the buffer, as with its `ByteDecodable` implementation
+    fn deserialize_unit<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        if !Size::bincode() {
+            self.index = self.bytes.len();
+        }
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V)
+        -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V)
+        -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        self.wrapped(|deserializer| visitor.visit_newtype_struct(deserializer))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        let len = self.elements()?;
+        visitor.visit_seq(self.entries(len, Path::Elements)?)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        visitor.visit_seq(self.entries(len, Path::Tuple)?)
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, len: usize, visitor: V)
+        -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        let len = self.elements()?;
+        visitor.visit_map(self.entries(len, Path::Elements)?)
+    }
+
+    fn deserialize_struct<V>(self,
+                             _name: &'static str,
+                             fields: &'static [&'static str],
+                             visitor: V)
+                             -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        visitor.visit_seq(self.entries(fields.len(), Path::Fields(fields))?)
+    }
+
+    fn deserialize_enum<V>(self,
+                           _name: &'static str,
+                           variants: &'static [&'static str],
+                           visitor: V)
+                           -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let _nested = enter_nested()?;
+        let tag = self.tag()?;
+        let name = *variants.get(tag).ok_or(ByteVecError::UnknownVariantError(tag))?;
+        visitor.visit_enum(Variant {
+            deserializer: self,
+            tag,
+            name,
+        })
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        self.not_self_describing()
+    }
+
+    // Every value takes the rest of its buffer, except in the layout of bincode
+    fn deserialize_ignored_any<V>(self, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        if Size::bincode() {
+            return self.not_self_describing();
+        }
+        self.index = self.bytes.len();
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The access to the entries of a structure or a collection read by a `ByteVecDeserializer`
+struct Entries<'a, 'de: 'a, Size: 'a> {
+    deserializer: &'a mut ByteVecDeserializer<'de, Size>,
+    sizes: Vec<usize>,
+    len: usize,
+    position: usize,
+    path: Path,
+    // The size of the value of the map entry whose key was read
+    value: Option<usize>,
+}
+
+impl<'a, 'de, Size> Entries<'a, 'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    fn next<T, F>(&mut self, size: usize, read: F) -> BVDecodeResult<T>
+        where F: FnOnce(&mut ByteVecDeserializer<'de, Size>) -> BVDecodeResult<T>
+    {
+        let (path, position) = (self.path, self.position);
+        if Size::bincode() {
+            read(self.deserializer)
+        } else {
+            self.deserializer.entry(size, read)
+        }
+        .map_err(|error| path.locate(error, position))
+    }
+}
+
+impl<'a, 'de, Size> de::SeqAccess<'de> for Entries<'a, 'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    type Error = ByteVecError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> BVDecodeResult<Option<T::Value>>
+        where T: DeserializeSeed<'de>
+    {
+        if self.position == self.len {
+            return Ok(None);
+        }
+        let size = self.sizes.get(self.position).cloned().unwrap_or(0);
+        let value = self.next(size, |deserializer| seed.deserialize(deserializer))?;
+        self.position += 1;
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.position)
+    }
+}
+
+// Each entry of a map is a tuple of its key and its value, with a size table of its own
+impl<'a, 'de, Size> de::MapAccess<'de> for Entries<'a, 'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    type Error = ByteVecError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> BVDecodeResult<Option<K::Value>>
+        where K: DeserializeSeed<'de>
+    {
+        if self.position == self.len {
+            return Ok(None);
+        }
+        let (key_size, value_size) = if Size::bincode() {
+            (0, 0)
+        } else {
+            let end = self.deserializer.index + self.sizes[self.position];
+            let sizes = self.deserializer
+                .table(2, end)
+                .map_err(|error| error.in_element(self.position))?;
+            (sizes[0], sizes[1])
+        };
+        let key = self.next(key_size, |deserializer| seed.deserialize(deserializer))?;
+        self.value = Some(value_size);
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> BVDecodeResult<V::Value>
+        where V: DeserializeSeed<'de>
+    {
+        let size = self.value
+            .take()
+            .ok_or(ByteVecError::InvalidValueError("a key before the value of a map entry"))?;
+        let value = self.next(size, |deserializer| seed.deserialize(deserializer))?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.position)
+    }
+}
+
+/// The access to the variant of an `enum` read by a `ByteVecDeserializer`
+struct Variant<'a, 'de: 'a, Size: 'a> {
+    deserializer: &'a mut ByteVecDeserializer<'de, Size>,
+    tag: usize,
+    name: &'static str,
+}
+
+impl<'a, 'de, Size> de::EnumAccess<'de> for Variant<'a, 'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    type Error = ByteVecError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> BVDecodeResult<(V::Value, Self)>
+        where V: DeserializeSeed<'de>
+    {
+        let deserializer: de::value::U64Deserializer<ByteVecError> =
+            (self.tag as u64).into_deserializer();
+        Ok((seed.deserialize(deserializer)?, self))
+    }
+}
+
+// The fields of a variant are read as a tuple
+impl<'a, 'de, Size> de::VariantAccess<'de> for Variant<'a, 'de, Size>
+    where Size: BVSize + ByteDecodable
+{
+    type Error = ByteVecError;
+
+    fn unit_variant(self) -> BVDecodeResult<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> BVDecodeResult<T::Value>
+        where T: DeserializeSeed<'de>
+    {
+        let name = self.name;
+        self.deserializer
+            .wrapped(|deserializer| seed.deserialize(deserializer))
+            .map_err(|error| error.in_field(name))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let name = self.name;
+        self.deserializer
+            .entries(len, Path::Tuple)
+            .and_then(|entries| visitor.visit_seq(entries))
+            .map_err(|error| error.in_field(name))
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V)
+        -> BVDecodeResult<V::Value>
+        where V: Visitor<'de>
+    {
+        let name = self.name;
+        self.deserializer
+            .entries(fields.len(), Path::Fields(fields))
+            .and_then(|entries| visitor.visit_seq(entries))
+            .map_err(|error| error.in_field(name))
+    }
+}
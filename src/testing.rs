@@ -0,0 +1,146 @@
+//! Property testing of the byte representations, through [`arbitrary`][1].
+//!
+//! `roundtrip_property` checks that the values of a type survive a serialization and
+//! deserialization cycle, with values built from pseudorandom bytes by the `Arbitrary`
+//! implementation of the type, and `decode_any` reads a byte buffer as a type with every
+//! fixed size indicator, for the fuzz targets that feed it arbitrary buffers. The wrapper
+//! types of this crate, such as `RunVec` or `BitVec`, implement `Arbitrary` with this
+//! feature, so the structures that hold them can derive it. This module is only available
+//! with the `testing` feature.
+//!
+//! ```rust
+//! # extern crate bytevec;
+//! # use bytevec::{testing, RunVec, ZigZag};
+//! # fn main() {
+//! testing::roundtrip_property::<(String, RunVec<u16>, Vec<ZigZag<i32>>), u32>();
+//! testing::decode_any::<Vec<String>>(&[2, 1, 1, b'a', 0xff]);
+//! # }
+//! ```
+//!
+//! [1]: https://docs.rs/arbitrary
+
+use std::fmt::Debug;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use errors::ByteVecError;
+use limits::LimitsGuard;
+use {ByteEncodable, ByteDecodable, BVSize, DecodeLimits, PackedVec, OptionVec, InternedVec,
+     BitVec, BitSet, UnknownFields, ColumnVec, RunVec, MaybeUtf8, VarSize, ZigZag};
+
+/// The number of values checked by `roundtrip_property`
+pub const ROUNDTRIP_CASES: usize = 256;
+
+/// The limits of the decodings of `decode_any`
+pub const FUZZ_LIMITS: DecodeLimits = DecodeLimits {
+    max_elements: 1 << 20,
+    max_bytes: usize::MAX,
+    max_depth: 128,
+    max_steps: 1 << 22,
+};
+
+macro_rules! newtype_arbitrary_impls {
+    ($($name:ident $(<$t:ident>)?),*) => {
+        $(
+            impl<'a $(, $t: Arbitrary<'a>)?> Arbitrary<'a> for $name $(<$t>)? {
+                fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+                    Arbitrary::arbitrary(u).map($name)
+                }
+            }
+        )*
+    }
+}
+
+newtype_arbitrary_impls!(PackedVec<T>, OptionVec<T>, InternedVec<T>, ColumnVec<T>, RunVec<T>,
+                         ZigZag<T>, BitVec, UnknownFields, VarSize);
+
+/// The sets are made of the integers of a range of 256 integers from an arbitrary start, as
+/// the bitmap of a sparse set can take up to 512 MiB.
+impl<'a> Arbitrary<'a> for BitSet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<BitSet> {
+        let start = u32::arbitrary(u)?;
+        let offsets = Vec::<u8>::arbitrary(u)?;
+        Ok(BitSet(offsets.into_iter().map(|offset| start.saturating_add(offset.into())).collect()))
+    }
+}
+
+impl<'a> Arbitrary<'a> for MaybeUtf8 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<MaybeUtf8> {
+        Ok(MaybeUtf8::from(Vec::<u8>::arbitrary(u)?))
+    }
+}
+
+/// Returns the pseudorandom bytes of the case `case` of `roundtrip_property`, from a
+/// xorshift generator, so the checked values are the same on every run
+fn case_bytes(case: usize) -> Vec<u8> {
+    let mut state = (case as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    (0..16 + case * 8)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 32) as u8
+        })
+        .collect()
+}
+
+/// Asserts that `value` is deserialized back from its byte representation, through both
+/// `decode` and `decode_with_len`. The values too large for the `Size` type are skipped.
+pub fn check_roundtrip<T, Size>(value: &T)
+    where T: ByteEncodable + ByteDecodable + PartialEq + Debug,
+          Size: BVSize + ByteEncodable + ByteDecodable
+{
+    let bytes = match value.encode::<Size>() {
+        Ok(bytes) => bytes,
+        Err(ByteVecError::OverflowError) => return,
+        Err(error) => panic!("failed to encode {:?}: {}", value, error),
+    };
+    match T::decode::<Size>(&bytes) {
+        Ok(decoded) => assert_eq!(&decoded, value, "the value decoded from {:?}", bytes),
+        Err(error) => panic!("failed to decode {:?} from {:?}: {}", value, bytes, error),
+    }
+    match T::decode_with_len::<Size>(&bytes) {
+        Ok((decoded, len)) => {
+            assert_eq!(&decoded, value, "the value decoded from {:?}", bytes);
+            assert_eq!(len, bytes.len(), "the length read from {:?}", bytes);
+        }
+        Err(error) => panic!("failed to decode {:?} from {:?}: {}", value, bytes, error),
+    }
+}
+
+/// Asserts that `ROUNDTRIP_CASES` values of `T` built by its `Arbitrary` implementation
+/// are deserialized back from their byte representation, as with `check_roundtrip`.
+///
+/// The values are built from the same pseudorandom bytes on every run. A `NaN` is never
+/// equal to itself, so the types that hold floats may fail the check for such values.
+pub fn roundtrip_property<T, Size>()
+    where T: for<'a> Arbitrary<'a> + ByteEncodable + ByteDecodable + PartialEq + Debug,
+          Size: BVSize + ByteEncodable + ByteDecodable
+{
+    for case in 0..ROUNDTRIP_CASES {
+        let bytes = case_bytes(case);
+        if let Ok(value) = T::arbitrary(&mut Unstructured::new(&bytes)) {
+            check_roundtrip::<T, Size>(&value);
+        }
+    }
+}
+
+/// Reads `bytes` as a `T` value through `decode` and `decode_with_len`, with each of the
+/// `u8`, `u16`, `u32` and `u64` size indicators, and discards the results. A panic is a
+/// bug of the `ByteDecodable` implementation of `T`, as any buffer must be either read or
+/// rejected with an error. The values are read with the `FUZZ_LIMITS`, so the buffers
+/// that declare billions of elements, such as the runs of a `RunVec`, are rejected rather
+/// than exhausting the memory.
+pub fn decode_any<T>(bytes: &[u8])
+    where T: ByteDecodable
+{
+    fn decode_with<T: ByteDecodable, Size: BVSize + ByteDecodable>(bytes: &[u8]) {
+        let _limits = LimitsGuard::new(&FUZZ_LIMITS);
+        let _ = T::decode::<Size>(bytes);
+        let _ = T::decode_with_len::<Size>(bytes);
+    }
+    decode_with::<T, u8>(bytes);
+    decode_with::<T, u16>(bytes);
+    decode_with::<T, u32>(bytes);
+    decode_with::<T, u64>(bytes);
+}
@@ -1,20 +1,263 @@
-use {BVEncodeResult, BVDecodeResult, BVSize};
+use std::io::{self, Read, Write};
+use std::mem::MaybeUninit;
+
+use {BVEncodeResult, BVDecodeResult, BVSize, read_size};
 use errors::{ByteVecError, BVExpectedSize};
+use header::FormatHeader;
+use frames::frame_header;
+use checksum::{crc32, CHECKSUM_LEN};
+use limits::{check_bytes, DecodeLimits, LimitsGuard};
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+use compress::{self, Compression};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, Zeroizing};
+#[cfg(feature = "zeroize")]
+use impls::read_to_end_zeroizing;
 
 /// Provides serialization functionality for the implementing types.
 pub trait ByteEncodable {
     /// Returns the total length of the byte buffer 
     /// than can be obtained through the `encode` method  
     fn get_size<Size>(&self) -> Option<Size> where Size: BVSize + ByteEncodable;
+    /// Returns the length in bytes of the byte representation of the data object, as
+    /// measured by `get_size`, without encoding it
+    ///
+    /// The length can be used to preallocate a file, to reserve a buffer or to enforce a
+    /// quota before the value is encoded. Fails with an `OverflowError` if the length
+    /// overflows the `Size` type or, as with `u64` on the 32 bit targets, an `usize`.
+    ///
+    /// ```rust
+    /// # extern crate bytevec;
+    /// # use bytevec::ByteEncodable;
+    /// # fn main() {
+    /// let value = ("name", vec![1u32, 2, 3]);
+    /// assert_eq!(value.encoded_size::<u32>().unwrap(), value.encode::<u32>().unwrap().len());
+    /// assert!(vec![0u8; 300].encoded_size::<u8>().is_err());
+    /// # }
+    /// ```
+    fn encoded_size<Size>(&self) -> BVEncodeResult<usize> where Size: BVSize + ByteEncodable {
+        self.get_size::<Size>()
+            .and_then(|size| size.checked_as_usize())
+            .ok_or(ByteVecError::OverflowError)
+    }
     /// Returs a byte representation of the original data object
     fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable;
+    /// Returns the byte representation of the data object with `u32` size indicators, the
+    /// same as `encode::<u32>()`, for the call sites that don't need another `Size` type
+    fn to_bytes(&self) -> BVEncodeResult<Vec<u8>> {
+        self.encode::<u32>()
+    }
+    /// Writes the byte representation of the data object to `writer`
+    ///
+    /// The default implementation writes the result of `encode`. The collections and the
+    /// structures implemented through the macros of this crate override it to write their
+    /// elements one at a time, so their whole byte representation is never held in memory.
+    /// Their sizes are written first, so the elements are measured by `get_size` before they
+    /// are written, whereas `encode` encodes each element once and fills in their sizes
+    /// afterwards, through `encode_to_buf`.
+    fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        writer.write_all(&self.encode::<Size>()?)?;
+        Ok(())
+    }
+    /// Appends the byte representation of the data object to `out`
+    ///
+    /// The default implementation calls `encode_to`. The collections, tuples, structures
+    /// and enums implemented through the macros of this crate override it to write their
+    /// elements straight into `out` and fill in their size tables afterwards, so the nested
+    /// values are encoded without buffers of their own, and `encode` makes a single
+    /// allocation. The implementations that hold other values should override it as well,
+    /// calling the `encode_to_buf` method of these values.
+    fn encode_to_buf<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable
+    {
+        self.encode_to::<Size, _>(out)
+    }
+    /// Returns the byte representation of the data object, preceded by its length
+    /// encoded as a `Size` value
+    ///
+    /// Such a frame can be read back with `ByteDecodable::decode_framed` from a buffer that
+    /// holds more data after it, like a stream of concatenated records. The frame delimits
+    /// the values whose byte representation doesn't record its own length, such as a
+    /// standalone `String` or `str`.
+    fn encode_framed<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?;
+        let mut bytes = Vec::with_capacity(size.encoded_len() + size.as_usize());
+        bytes.extend_from_slice(&size.encode::<Size>()?);
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Writes the frame of the data object to `writer`, as returned by `encode_framed`
+    fn encode_framed_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+        where Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?;
+        size.encode_to::<Size, W>(writer)?;
+        self.encode_to::<Size, W>(writer)
+    }
+    /// Returns the byte representation of the data object, preceded by a `FormatHeader`
+    ///
+    /// The header records the format version and the width of `Size`, so the bytes can be
+    /// stored and checked by `ByteDecodable::decode_with_header` when they are read back.
+    fn encode_with_header<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?;
+        let mut bytes = Vec::with_capacity(FormatHeader::LEN + size.as_usize());
+        bytes.extend_from_slice(&FormatHeader::of::<Size>().to_bytes());
+        self.encode_to::<Size, _>(&mut bytes)?;
+        Ok(bytes)
+    }
+    /// Returns the byte representation of the data object with the narrowest of `u8`, `u16`,
+    /// `u32` and `u64` that fits its sizes, preceded by a marker byte with the width of
+    /// that type in bytes
+    ///
+    /// Small values then take as few bytes as with `u8` sizes, while large ones don't
+    /// overflow. The bytes can be read back with `ByteDecodable::decode_auto`.
+    fn encode_auto(&self) -> BVEncodeResult<Vec<u8>> {
+        let mut bytes = vec![0];
+        if self.reserve_encoded::<u8>(&mut bytes).is_ok() {
+            bytes[0] = 1;
+            self.encode_to::<u8, _>(&mut bytes)?;
+        } else if self.reserve_encoded::<u16>(&mut bytes).is_ok() {
+            bytes[0] = 2;
+            self.encode_to::<u16, _>(&mut bytes)?;
+        } else if self.reserve_encoded::<u32>(&mut bytes).is_ok() {
+            bytes[0] = 4;
+            self.encode_to::<u32, _>(&mut bytes)?;
+        } else {
+            bytes[0] = 8;
+            self.encode_to::<u64, _>(&mut bytes)?;
+        }
+        Ok(bytes)
+    }
+    /// Returns the byte representation of the data object, followed by its CRC-32
+    /// checksum as a little endian `u32`
+    ///
+    /// The checksum is verified by `ByteDecodable::decode_with_checksum`, so corrupted or
+    /// truncated buffers are detected before they are deserialized.
+    fn encode_with_checksum<Size>(&self) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?;
+        let mut bytes = Vec::with_capacity(size.as_usize() + CHECKSUM_LEN);
+        self.encode_to::<Size, _>(&mut bytes)?;
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        Ok(bytes)
+    }
+    /// Returns the byte representation of the data object compressed with `compression`,
+    /// preceded by the algorithm and the length of the uncompressed bytes
+    ///
+    /// This method is only available with the `flate2` or the `zstd` features.
+    #[cfg(any(feature = "flate2", feature = "zstd"))]
+    fn encode_compressed<Size>(&self, compression: Compression) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable
+    {
+        let bytes = self.encode::<Size>()?;
+        let mut compressed = vec![compression.id()];
+        Size::from_usize(bytes.len()).encode_to::<Size, _>(&mut compressed)?;
+        compressed.extend_from_slice(&compress::compress(compression, &bytes)?);
+        Ok(compressed)
+    }
+    /// Returns the byte representation of the data object in a buffer that is wiped when
+    /// it's dropped
+    ///
+    /// The buffer is allocated once with the length given by `get_size`, so no copy of the
+    /// bytes is left behind by a reallocation, and it's wiped as well if the serialization
+    /// fails. The fields with a `with` proxy or the `big_endian` attribute are still
+    /// encoded into intermediate buffers. This method is only available with the `zeroize`
+    /// feature.
+    #[cfg(feature = "zeroize")]
+    fn encode_zeroizing<Size>(&self) -> BVEncodeResult<Zeroizing<Vec<u8>>>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?.as_usize();
+        let mut bytes = Zeroizing::new(Vec::with_capacity(size));
+        self.encode_to::<Size, _>(&mut *bytes)?;
+        Ok(bytes)
+    }
+    /// Returns the byte representation of the data object passed through `transform`
+    ///
+    /// The transformed bytes can be read back with `ByteDecodable::decode_transformed`
+    /// and the same transform.
+    fn encode_transformed<Size, T>(&self, transform: &T) -> BVEncodeResult<Vec<u8>>
+        where Size: BVSize + ByteEncodable,
+              T: PayloadTransform + ?Sized
+    {
+        transform.apply(self.encode::<Size>()?)
+    }
+    /// Writes the byte representation of the data object at the start of `buf`, and returns
+    /// the number of bytes written
+    ///
+    /// The primitive types, the strings, the collections and the structures implemented
+    /// through the macros of this crate are written without any allocation, except for the
+    /// fields with a `with` proxy or the `big_endian` attribute. If `buf` is
+    /// shorter than the byte representation, a `BufferTooSmallError` is returned and
+    /// nothing is written.
+    fn encode_into_slice<Size>(&self, buf: &mut [u8]) -> BVEncodeResult<usize>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = self.get_size::<Size>().ok_or(ByteVecError::OverflowError)?.as_usize();
+        if buf.len() < size {
+            return Err(ByteVecError::BufferTooSmallError {
+                required: size,
+                actual: buf.len(),
+            });
+        }
+        self.encode_to::<Size, _>(&mut &mut buf[..size])?;
+        Ok(size)
+    }
+    /// Reserves room in `out` for the byte representation of the data object, as measured
+    /// by `get_size`, and returns its length
+    ///
+    /// The implementations that write the byte representations of their parts into a single
+    /// buffer can call it before writing them, so the buffer is grown once rather than at
+    /// each part. Fails with an `OverflowError` if the length overflows the `Size` type.
+    fn reserve_encoded<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<usize>
+        where Size: BVSize + ByteEncodable
+    {
+        let size = self.encoded_size::<Size>()?;
+        out.reserve(size);
+        Ok(size)
+    }
+    /// Appends the byte representation of the data object to `out`, and returns the number
+    /// of bytes appended
+    ///
+    /// This allows serializing many values into a single reused buffer. If the serialization
+    /// fails, `out` is left as it was.
+    fn encode_append<Size>(&self, out: &mut Vec<u8>) -> BVEncodeResult<usize>
+        where Size: BVSize + ByteEncodable
+    {
+        let start = out.len();
+        self.reserve_encoded::<Size>(out)?;
+        match self.encode_to::<Size, _>(out) {
+            Ok(()) => Ok(out.len() - start),
+            Err(error) => {
+                out.truncate(start);
+                Err(error)
+            }
+        }
+    }
 }
 
 /// Provides deserialization functionality for the implementing types.
 pub trait ByteDecodable: Sized {
     /// Returns an instance of `Self` obtained from the deserialization of the provided byte buffer.
     fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<Self> where Size: BVSize + ByteDecodable;
+    /// Returns the instance of `Self` deserialized from a byte buffer with `u32` size
+    /// indicators, the same as `decode::<u32>(bytes)`
+    fn from_bytes(bytes: &[u8]) -> BVDecodeResult<Self> {
+        Self::decode::<u32>(bytes)
+    }
     /// Returns the result of `decode` if `bytes.len()` is less or equal than `limit`
+    ///
+    /// Only the length of the buffer is bounded, so the runs of a `RunVec` or the elements
+    /// of a collection that take no bytes can still add up to any number of elements.
+    /// `decode_limited` bounds them across the nested collections as well.
     fn decode_max<Size>(bytes: &[u8], limit: Size) -> BVDecodeResult<Self>
         where Size: BVSize + ByteDecodable
     {
@@ -24,7 +267,527 @@ pub trait ByteDecodable: Sized {
             Err(ByteVecError::BadSizeDecodeError {
                 expected: BVExpectedSize::LessOrEqualThan(limit.as_usize()),
                 actual: bytes.len(),
+                offset: 0,
             })
         }
     }
-}
\ No newline at end of file
+    /// Returns the result of `decode` if `bytes` doesn't exceed the given limits, which
+    /// are enforced on every collection and nested value read from the buffer
+    fn decode_limited<Size>(bytes: &[u8], limits: &DecodeLimits) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable
+    {
+        check_bytes(limits, bytes.len())?;
+        let _limits = LimitsGuard::new(limits);
+        Self::decode::<Size>(bytes)
+    }
+    /// Returns an instance of `Self` obtained from the deserialization of the start of the
+    /// provided byte buffer, along with the number of bytes that were read.
+    ///
+    /// The byte representations of the primitive types, the collections, the tuples and the
+    /// structures implemented through the macros of this crate record their own length, so
+    /// the bytes after them are left unread. The default implementation, which is used by
+    /// the strings and by versioned structures, reads the whole buffer.
+    fn decode_with_len<Size>(bytes: &[u8]) -> BVDecodeResult<(Self, usize)>
+        where Size: BVSize + ByteDecodable
+    {
+        Self::decode::<Size>(bytes).map(|value| (value, bytes.len()))
+    }
+    /// Returns an instance of `Self` obtained from the deserialization of the start of the
+    /// provided byte buffer, along with the trailing bytes that were left unread.
+    ///
+    /// Unlike `decode`, which requires the byte representation to take the whole buffer,
+    /// this method reads values embedded in larger buffers. The bytes that are read are
+    /// the ones given by `decode_with_len`.
+    fn decode_lenient<Size>(bytes: &[u8]) -> BVDecodeResult<(Self, &[u8])>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = Self::decode_with_len::<Size>(bytes)?;
+        Ok((value, &bytes[len..]))
+    }
+    /// Returns the instance of `Self` deserialized from the start of the provided byte
+    /// buffer, and fails with a `TrailingBytesError` if bytes are left after it.
+    ///
+    /// The bytes that are read are the ones given by `decode_with_len`, so a buffer with a
+    /// well formed value followed by other data, such as concatenated frames, yields a
+    /// `TrailingBytesError` rather than the `BadSizeDecodeError` of a corrupted buffer.
+    fn decode_strict<Size>(bytes: &[u8]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable
+    {
+        let (value, len) = Self::decode_with_len::<Size>(bytes)?;
+        if len < bytes.len() {
+            Err(ByteVecError::TrailingBytesError {
+                consumed: len,
+                remaining: bytes.len() - len,
+            })
+        } else {
+            Ok(value)
+        }
+    }
+    /// Deserializes the provided byte buffer into `self`, replacing its previous value.
+    ///
+    /// The strings and the collections are cleared and refilled, so they keep their
+    /// allocations from one call to the next, and the elements already in a `Vec` are
+    /// decoded into as well, outside of the layout of bincode. The default implementation
+    /// assigns the result of `decode`. If the deserialization fails, `self` is left with a
+    /// valid but unspecified value.
+    fn decode_into<Size>(&mut self, bytes: &[u8]) -> BVDecodeResult<()>
+        where Size: BVSize + ByteDecodable
+    {
+        *self = Self::decode::<Size>(bytes)?;
+        Ok(())
+    }
+    /// Returns an instance of `Self` obtained from the deserialization of the bytes read
+    /// from `reader` until the end of the stream.
+    fn decode_from<Size, R>(reader: &mut R) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable,
+              R: Read + ?Sized
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::decode::<Size>(&bytes)
+    }
+    /// Returns an instance of `Self` obtained from the deserialization of the provided byte
+    /// buffer, which is wiped from the memory when it's dropped.
+    ///
+    /// Only the returned value is wiped. The byte buffer belongs to the caller, who wipes it
+    /// if needed, and the copies made while decoding are dropped without being wiped: the
+    /// memory left behind when a collection or a string grows, the intermediate buffers of
+    /// the fields with a `with` proxy or the `big_endian` attribute, and the values already
+    /// decoded when the deserialization fails. This method is only available with the
+    /// `zeroize` feature.
+    #[cfg(feature = "zeroize")]
+    fn decode_zeroizing<Size>(bytes: &[u8]) -> BVDecodeResult<Zeroizing<Self>>
+        where Size: BVSize + ByteDecodable,
+              Self: Zeroize
+    {
+        Self::decode::<Size>(bytes).map(Zeroizing::new)
+    }
+    /// Returns an instance of `Self` obtained from the deserialization of the bytes read
+    /// from `reader` until the end of the stream, which is wiped from the memory when it's
+    /// dropped, along with the buffers that held the bytes.
+    ///
+    /// The copies made while decoding the value are not wiped, as with `decode_zeroizing`.
+    /// This method is only available with the `zeroize` feature.
+    #[cfg(feature = "zeroize")]
+    fn decode_from_zeroizing<Size, R>(reader: &mut R) -> BVDecodeResult<Zeroizing<Self>>
+        where Size: BVSize + ByteDecodable,
+              R: Read + ?Sized,
+              Self: Zeroize
+    {
+        let bytes = read_to_end_zeroizing(reader)?;
+        Self::decode_zeroizing::<Size>(&bytes)
+    }
+    /// Returns the instance of `Self` deserialized from the frame at the start of `bytes`,
+    /// as written by `ByteEncodable::encode_framed`, along with the bytes that follow it.
+    fn decode_framed<Size>(bytes: &[u8]) -> BVDecodeResult<(Self, &[u8])>
+        where Size: BVSize + ByteDecodable
+    {
+        let (payload_size, header_size) = read_size::<Size>(bytes)?;
+        let frame_size = header_size.checked_add(payload_size)
+            .ok_or(ByteVecError::SizeOverflowError)?;
+        if bytes.len() < frame_size {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(frame_size),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let value = Self::decode::<Size>(&bytes[header_size..frame_size])
+            .map_err(|error| error.at(header_size))?;
+        Ok((value, &bytes[frame_size..]))
+    }
+    /// Returns the instance of `Self` deserialized from the next frame read from `reader`,
+    /// as written by `ByteEncodable::encode_framed`.
+    ///
+    /// Unlike `decode_from`, only the bytes of the frame are read, so the stream can hold
+    /// other values after it. The header is read a byte at a time, so `reader` should be
+    /// buffered, and the payload is read as it arrives rather than being allocated upfront
+    /// from the length in the header. A stream that ends before the end of the frame yields
+    /// an `IoError` of the kind `UnexpectedEof`.
+    fn decode_framed_from<Size, R>(reader: &mut R) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable,
+              R: Read + ?Sized
+    {
+        let mut header = Vec::with_capacity(Size::get_size_of().as_usize());
+        let (payload_size, header_size) = loop {
+            if let Some(frame_header) = frame_header::<Size>(&header)? {
+                break frame_header;
+            }
+            let mut byte = [0u8];
+            reader.read_exact(&mut byte)?;
+            header.push(byte[0]);
+        };
+        let mut payload = Vec::new();
+        reader.take(payload_size as u64).read_to_end(&mut payload)?;
+        if payload.len() < payload_size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Self::decode::<Size>(&payload).map_err(|error| error.at(header_size))
+    }
+    /// Returns the instance of `Self` deserialized from a buffer written by
+    /// `ByteEncodable::encode_with_header`
+    ///
+    /// A buffer without a format header yields a `BadMagicError`, and a buffer written with
+    /// another format version or size width yields a `HeaderMismatchError`.
+    fn decode_with_header<Size>(bytes: &[u8]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable
+    {
+        let (header, body) = FormatHeader::read(bytes)?;
+        let expected = FormatHeader::of::<Size>();
+        if header != expected {
+            return Err(ByteVecError::HeaderMismatchError {
+                expected,
+                actual: header,
+            });
+        }
+        Self::decode::<Size>(body)
+    }
+    /// Returns the instance of `Self` deserialized from a buffer written by
+    /// `ByteEncodable::encode_auto`
+    ///
+    /// A marker byte other than 1, 2, 4 or 8 yields an `UnknownSizeWidthError`.
+    fn decode_auto(bytes: &[u8]) -> BVDecodeResult<Self> {
+        let (&width, body) = bytes.split_first().ok_or(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(1),
+            actual: 0,
+            offset: 0,
+        })?;
+        match width {
+            1 => Self::decode::<u8>(body),
+            2 => Self::decode::<u16>(body),
+            4 => Self::decode::<u32>(body),
+            8 => Self::decode::<u64>(body),
+            _ => Err(ByteVecError::UnknownSizeWidthError(width)),
+        }
+    }
+    /// Returns the instance of `Self` deserialized from a buffer written by
+    /// `ByteEncodable::encode_with_checksum`
+    ///
+    /// A checksum that doesn't match the contents of the buffer yields a
+    /// `ChecksumMismatchError`.
+    fn decode_with_checksum<Size>(bytes: &[u8]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable
+    {
+        if bytes.len() < CHECKSUM_LEN {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(CHECKSUM_LEN),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        let (body, trailer) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        let expected = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let actual = crc32(body);
+        if expected != actual {
+            return Err(ByteVecError::ChecksumMismatchError { expected, actual });
+        }
+        Self::decode::<Size>(body)
+    }
+    /// Returns the instance of `Self` deserialized from a buffer written by
+    /// `ByteEncodable::encode_compressed`
+    ///
+    /// A buffer compressed with an algorithm that isn't enabled yields an
+    /// `UnknownCompressionError`. This method is only available with the `flate2` or the
+    /// `zstd` features.
+    #[cfg(any(feature = "flate2", feature = "zstd"))]
+    fn decode_compressed<Size>(bytes: &[u8]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable
+    {
+        let id = *bytes.first().ok_or(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(1),
+            actual: 0,
+            offset: 0,
+        })?;
+        let compression = Compression::from_id(id)
+            .ok_or(ByteVecError::UnknownCompressionError(id))?;
+        let (len, header_len) = read_size::<Size>(&bytes[1..]).map_err(|error| error.at(1))?;
+        let decompressed = compress::decompress(compression, &bytes[1 + header_len..], len)?;
+        Self::decode::<Size>(&decompressed)
+    }
+    /// Returns the instance of `Self` deserialized from the bytes restored by `transform`
+    /// from a buffer written by `ByteEncodable::encode_transformed`
+    fn decode_transformed<Size, T>(bytes: &[u8], transform: &T) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable,
+              T: PayloadTransform + ?Sized
+    {
+        Self::decode::<Size>(&transform.reverse(bytes)?)
+    }
+}
+
+/// Deserializes byte buffers at the end of a call chain, as `bytes.decode_as::<T>()`.
+///
+/// This trait is implemented for `[u8]`, so it's available on `&[u8]`, `Vec<u8>` and the
+/// other buffers that dereference to a byte slice, and it takes `u32` as the `Size` type, as
+/// `ByteDecodable::from_bytes` does. Values are encoded in the same fashion by
+/// [`EncodeExt::to_bytevec`](trait.EncodeExt.html).
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{ByteEncodable, BytesDecodeExt};
+/// # fn main() {
+/// let buffer = ("ping".to_string(), 7u64).to_bytes().unwrap();
+/// let (message, id) = buffer.decode_as::<(String, u64)>().unwrap();
+/// assert_eq!((message.as_str(), id), ("ping", 7));
+/// # }
+/// ```
+pub trait BytesDecodeExt {
+    /// Returns the instance of `T` deserialized from the buffer with `u32` size indicators
+    fn decode_as<T>(&self) -> BVDecodeResult<T> where T: ByteDecodable;
+}
+
+impl BytesDecodeExt for [u8] {
+    fn decode_as<T>(&self) -> BVDecodeResult<T>
+        where T: ByteDecodable
+    {
+        T::from_bytes(self)
+    }
+}
+
+/// Serializes values at the end of a call chain, as `value.to_bytevec()`.
+///
+/// This trait is implemented for every `ByteEncodable` type, and takes `u32` as the `Size`
+/// type, as `ByteEncodable::to_bytes` does, to which it forwards. The buffers are read back
+/// by [`BytesDecodeExt::decode_as`](trait.BytesDecodeExt.html).
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::{BytesDecodeExt, EncodeExt};
+/// # fn main() {
+/// let buffer = ("ping".to_string(), 7u64).to_bytevec().unwrap();
+/// assert_eq!(buffer.decode_as::<(String, u64)>().unwrap(), ("ping".to_string(), 7));
+/// # }
+/// ```
+pub trait EncodeExt {
+    /// Returns the byte representation of the value with `u32` size indicators
+    fn to_bytevec(&self) -> BVEncodeResult<Vec<u8>>;
+}
+
+impl<T> EncodeExt for T
+    where T: ByteEncodable + ?Sized
+{
+    fn to_bytevec(&self) -> BVEncodeResult<Vec<u8>> {
+        self.to_bytes()
+    }
+}
+
+/// Provides deserialization functionality for types that borrow from the byte buffer.
+///
+/// Besides every `ByteDecodable` type, this trait is implemented for `&'a str` and
+/// `&'a [u8]`, which are views into the provided buffer, and for vectors of them, so
+/// large read-only buffers can be read without allocating a `String` or a `Vec<u8>`
+/// for every element.
+pub trait ByteDecodeBorrowed<'a>: Sized {
+    /// Returns an instance of `Self` obtained from the deserialization of the provided
+    /// byte buffer, which may borrow from `bytes`.
+    fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable;
+}
+
+impl<'a, T> ByteDecodeBorrowed<'a> for T
+    where T: ByteDecodable
+{
+    fn decode_borrowed<Size>(bytes: &'a [u8]) -> BVDecodeResult<T>
+        where Size: BVSize + ByteDecodable
+    {
+        T::decode::<Size>(bytes)
+    }
+}
+
+/// An arena that holds the strings and slices deserialized by
+/// [`ByteDecodeIn`](trait.ByteDecodeIn.html).
+///
+/// The values are allocated next to each other in the arena, and freed all at once when the
+/// arena is dropped or reset. This trait is implemented for `bumpalo::Bump` with the
+/// `bumpalo` feature. Only `Copy` values are moved into the arena, as it may not drop them.
+pub trait DecodeArena {
+    /// Returns a copy of `string` allocated in the arena
+    fn alloc_str(&self, string: &str) -> &str;
+    /// Returns a copy of `values` allocated in the arena
+    fn alloc_slice_copy<T>(&self, values: &[T]) -> &[T] where T: Copy;
+}
+
+/// Provides deserialization functionality for types allocated in a
+/// [`DecodeArena`](trait.DecodeArena.html).
+///
+/// Besides every `ByteDecodable` type, which ignores the arena, this trait is implemented for
+/// `&'a str`, whose bytes are copied into the arena, and for `&'a [T]`, whose elements are
+/// deserialized in the arena themselves, so a large collection of strings takes a single
+/// arena instead of a `String` for every element.
+pub trait ByteDecodeIn<'a>: Sized {
+    /// Returns an instance of `Self` obtained from the deserialization of the provided
+    /// byte buffer, which may be allocated in `arena`.
+    fn decode_in<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena;
+    /// Returns the instance of `Self` deserialized from the start of `bytes` along with
+    /// the length of its byte representation, as `ByteDecodable::decode_with_len`
+    fn decode_in_with_len<Size, A>(bytes: &[u8], arena: &'a A) -> BVDecodeResult<(Self, usize)>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena;
+}
+
+impl<'a, T> ByteDecodeIn<'a> for T
+    where T: ByteDecodable
+{
+    fn decode_in<Size, A>(bytes: &[u8], _arena: &'a A) -> BVDecodeResult<T>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        T::decode::<Size>(bytes)
+    }
+
+    fn decode_in_with_len<Size, A>(bytes: &[u8], _arena: &'a A) -> BVDecodeResult<(T, usize)>
+        where Size: BVSize + ByteDecodable,
+              A: DecodeArena
+    {
+        T::decode_with_len::<Size>(bytes)
+    }
+}
+
+/// Provides serialization functionality on behalf of a foreign type `T`.
+///
+/// This trait is usually implemented through the [`bytevec_remote`](macro.bytevec_remote.html)
+/// macro for a local proxy type, so types from other crates can be serialized as the fields of
+/// a local `struct` using the `#[bytevec(with = Proxy)]` field attribute.
+pub trait ByteRemote<T> {
+    /// Returns the total length of the byte buffer that can be obtained from `value`
+    fn get_size<Size>(value: &T) -> Option<Size> where Size: BVSize + ByteEncodable;
+    /// Returns a byte representation of `value`
+    fn encode<Size>(value: &T) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable;
+    /// Returns an instance of `T` obtained from the deserialization of the provided byte buffer
+    fn decode<Size>(bytes: &[u8]) -> BVDecodeResult<T> where Size: BVSize + ByteDecodable;
+}
+
+/// A reversible transformation of whole byte representations, like encryption or signing.
+///
+/// A transform is given the complete byte representation of a value by
+/// `ByteEncodable::encode_transformed`, and the transformed bytes by
+/// `ByteDecodable::decode_transformed`. Errors of the transform can be returned as an
+/// `IoError` through the `From<io::Error>` conversion. A pair of transforms applies the
+/// first one and then the second one, and reverses them in the opposite order.
+pub trait PayloadTransform {
+    /// Returns the transformed bytes of a byte representation
+    fn apply(&self, bytes: Vec<u8>) -> BVEncodeResult<Vec<u8>>;
+    /// Returns the byte representation restored from the transformed bytes
+    fn reverse(&self, bytes: &[u8]) -> BVDecodeResult<Vec<u8>>;
+}
+
+impl<A, B> PayloadTransform for (A, B)
+    where A: PayloadTransform,
+          B: PayloadTransform
+{
+    fn apply(&self, bytes: Vec<u8>) -> BVEncodeResult<Vec<u8>> {
+        self.1.apply(self.0.apply(bytes)?)
+    }
+
+    fn reverse(&self, bytes: &[u8]) -> BVDecodeResult<Vec<u8>> {
+        self.0.reverse(&self.1.reverse(bytes)?)
+    }
+}
+
+/// Implemented by the types whose byte representation always has the same length.
+///
+/// This trait is implemented for the integral types, the floating point types and `char`, for
+/// the `struct`s implemented with the `#[bytevec(packed)]` attribute of
+/// [`bytevec_impls`](macro.bytevec_impls.html), and for the
+/// [`PackedArray`](struct.PackedArray.html)s of these types, whose `SIZE` is the sum of the
+/// sizes of their elements.
+pub trait FixedByteSize: ByteEncodable + ByteDecodable {
+    /// The length of the byte representation of any value of the type
+    const SIZE: usize;
+    /// The alignment of the type in a `#[bytevec(packed, aligned)]` structure, which is
+    /// the natural alignment of the primitive types
+    const ALIGN: usize = 1;
+    /// Writes the byte representations of `values` back to back, as in a `PackedVec`
+    ///
+    /// The default implementation writes the values one at a time, while the primitive types
+    /// convert them in bulk, and the `u8` values are written at once.
+    fn encode_slice_to<Size, W>(values: &[Self], writer: &mut W) -> BVEncodeResult<()>
+        where Self: Sized,
+              Size: BVSize + ByteEncodable,
+              W: Write + ?Sized
+    {
+        for value in values {
+            value.encode_to::<Size, W>(writer)?;
+        }
+        Ok(())
+    }
+    /// Returns the `len` values whose byte representations fill `bytes` back to back, as in
+    /// a `PackedVec`
+    ///
+    /// The default implementation reads the values one at a time, while the primitive types
+    /// convert them in bulk, and the `u8` values are copied at once.
+    fn decode_slice<Size>(bytes: &[u8], len: usize) -> BVDecodeResult<Vec<Self>>
+        where Self: Sized,
+              Size: BVSize + ByteDecodable
+    {
+        let mut values = Vec::with_capacity(len);
+        for index in 0..len {
+            let start = index * Self::SIZE;
+            values.push(Self::decode::<Size>(&bytes[start..start + Self::SIZE])
+                .map_err(|error| error.at(start).in_element(index))?);
+        }
+        Ok(values)
+    }
+    /// Deserializes the value whose byte representation is `bytes` straight into `slot`,
+    /// which is left uninitialized if it fails
+    ///
+    /// The default implementation writes the value returned by `decode`, while the
+    /// `PackedArray`s and the packed structures decode their elements and fields in place, so
+    /// the large ones aren't built on the stack and then moved. A `PackedArray` drops the
+    /// elements it already decoded when one of them fails.
+    fn decode_in_place<Size>(slot: &mut MaybeUninit<Self>, bytes: &[u8]) -> BVDecodeResult<()>
+        where Self: Sized,
+              Size: BVSize + ByteDecodable
+    {
+        slot.write(Self::decode::<Size>(bytes)?);
+        Ok(())
+    }
+    /// Returns the byte representation of the value in an array of `N` bytes on the stack,
+    /// without allocating, where `N` is checked at compile time to be `SIZE`
+    ///
+    /// ```rust
+    /// # extern crate bytevec;
+    /// # use bytevec::FixedByteSize;
+    /// # fn main() {
+    /// let bytes: [u8; 4] = 0x0102_0304u32.encode_array::<u32, 4>().unwrap();
+    /// assert_eq!(bytes, [4, 3, 2, 1]);
+    /// # }
+    /// ```
+    ///
+    /// An array of another length doesn't compile:
+    ///
+    /// ```rust,compile_fail
+    /// # extern crate bytevec;
+    /// # use bytevec::FixedByteSize;
+    /// # fn main() {
+    /// let bytes = 7u32.encode_array::<u32, 8>();
+    /// # }
+    /// ```
+    fn encode_array<Size, const N: usize>(&self) -> BVEncodeResult<[u8; N]>
+        where Self: Sized,
+              Size: BVSize + ByteEncodable
+    {
+        const { assert!(N == Self::SIZE, "the length of the array is not the size of the type") };
+        let mut bytes = [0; N];
+        self.encode_to::<Size, _>(&mut &mut bytes[..])?;
+        Ok(bytes)
+    }
+}
+
+/// Splits the values of a `struct` into one entry per field, to serialize collections of
+/// them column by column in a [`ColumnVec`](struct.ColumnVec.html).
+///
+/// This trait is implemented for the `struct`s implemented with the `#[bytevec(columnar)]`
+/// attribute of [`bytevec_impls`](macro.bytevec_impls.html). The entries are the byte
+/// representations of the fields as they're written in the `struct`, with their options.
+pub trait ByteColumns: Sized {
+    /// The number of fields with an entry
+    const COLUMNS: usize;
+    /// Returns the byte representation of each field with an entry
+    fn encode_columns<Size>(&self) -> BVEncodeResult<Vec<Vec<u8>>>
+        where Size: BVSize + ByteEncodable;
+    /// Returns the value built from the byte representation of each field with an entry
+    fn decode_columns<Size>(entries: &[&[u8]]) -> BVDecodeResult<Self>
+        where Size: BVSize + ByteDecodable;
+}
@@ -0,0 +1,348 @@
+use errors::{ByteVecError, BVExpectedSize};
+use impls::{index_width, string_bytes};
+use limits::{check_bytes, check_elements, enter_nested, DecodeLimits, LimitsGuard};
+use schema::{FieldSchema, Schema};
+use {ByteDecodable, ByteDecodeBorrowed, ByteSchema, BVDecodeResult, BVSize, BitSet, BitVec,
+     VarSize, ZigZag, compact_table, read_size};
+
+/// Checks that `bytes` is a well formed byte representation of a value of type `T`, without
+/// deserializing it.
+///
+/// The buffer is walked along the [`Schema`](enum.Schema.html) of `T`: the size tables are
+/// checked against the length of the buffer, the strings against UTF-8, the primitive values
+/// against their length and the `char` values against the Unicode scalar values, and the
+/// versions and the tags of the enums against the known ones. Neither the values nor the
+/// strings are allocated, so a buffer can be admitted or rejected before it's decoded. The
+/// errors and their paths are the ones of `decode`. The layout of bincode has no size
+/// tables to check, so its buffers are validated by `decode` instead.
+///
+/// Only the current version of a versioned `struct` is admitted: the schema describes the
+/// fields of that version alone, and the older versions are read by the migration functions
+/// given to `migrate`, which `validate` can't follow. A buffer of an older version that
+/// `decode` upgrades yields an `UnknownVersionError`, and is checked by decoding it.
+///
+/// ```rust
+/// # extern crate bytevec;
+/// # use bytevec::ByteEncodable;
+/// # fn main() {
+/// let bytes = vec![(1u8, "one".to_string()), (2, "two".to_string())].encode::<u16>().unwrap();
+/// assert!(bytevec::validate::<Vec<(u8, String)>, u16>(&bytes).is_ok());
+/// assert!(bytevec::validate::<Vec<(u8, String)>, u16>(&bytes[..bytes.len() - 1]).is_err());
+/// assert!(bytevec::validate::<Vec<(u8, u32)>, u16>(&bytes).is_err());
+/// # }
+/// ```
+pub fn validate<T, Size>(bytes: &[u8]) -> BVDecodeResult<()>
+    where T: ByteSchema + ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    if Size::bincode() {
+        return T::decode::<Size>(bytes).map(|_| ());
+    }
+    validate_schema::<Size>(&T::schema(), bytes)
+}
+
+/// Checks that `bytes` is a well formed byte representation described by `schema`, as
+/// `validate`
+pub fn validate_schema<Size>(schema: &Schema, bytes: &[u8]) -> BVDecodeResult<()>
+    where Size: BVSize + ByteDecodable
+{
+    check::<Size>(schema, bytes)
+}
+
+/// Checks that `bytes` is a well formed byte representation of a value of type `T` that
+/// doesn't exceed the given limits, as `ByteDecodable::decode_limited`
+pub fn validate_limited<T, Size>(bytes: &[u8], limits: &DecodeLimits) -> BVDecodeResult<()>
+    where T: ByteSchema + ByteDecodable,
+          Size: BVSize + ByteDecodable
+{
+    check_bytes(limits, bytes.len())?;
+    let _limits = LimitsGuard::new(limits);
+    validate::<T, Size>(bytes)
+}
+
+fn expect_len(bytes: &[u8], len: usize) -> BVDecodeResult<()> {
+    if bytes.len() == len {
+        Ok(())
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+/// Reads the table of `entries` sizes at `index`, or of as many sizes as the count at
+/// `index` if `entries` is `None`, and checks that the entries it describes end at the end
+/// of `bytes`. Returns the number of entries, the start of the table and the start of the
+/// entries, without collecting the sizes.
+fn read_table<Size>(bytes: &[u8], mut index: usize, entries: Option<usize>)
+    -> BVDecodeResult<(usize, usize, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let entries = match entries {
+        Some(entries) => entries,
+        None => {
+            let (entries, count_len) = read_size::<Size>(&bytes[index..])
+                .map_err(|error| error.at(index))?;
+            check_elements(entries)?;
+            index += count_len;
+            entries
+        }
+    };
+    let table_start = index;
+    let mut body_size = 0usize;
+    for _ in 0..entries {
+        let (size, size_len) = read_size::<Size>(&bytes[index..])
+            .map_err(|error| error.at(index))?;
+        body_size = body_size.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+        index += size_len;
+    }
+    expect_len(bytes, index.saturating_add(body_size))?;
+    Ok((entries, table_start, index))
+}
+
+/// Checks the entries described by the size table at `table` whose first entry starts at
+/// `index`, with the schema given for each position. The errors of the elements of a
+/// collection are given their position in the path, while the ones of a tuple are not.
+fn check_entries<'a, Size, F>(bytes: &[u8], table: usize, index: usize, entries: usize,
+                              elements: bool, schema_of: F)
+    -> BVDecodeResult<()>
+    where Size: BVSize + ByteDecodable,
+          F: Fn(usize) -> &'a Schema
+{
+    let mut table = table;
+    let mut index = index;
+    for position in 0..entries {
+        let (size, size_len) = read_size::<Size>(&bytes[table..])?;
+        table += size_len;
+        check::<Size>(schema_of(position), &bytes[index..index + size])
+            .map_err(|error| {
+                let error = error.at(index);
+                if elements { error.in_element(position) } else { error }
+            })?;
+        index += size;
+    }
+    Ok(())
+}
+
+fn check<Size>(schema: &Schema, bytes: &[u8]) -> BVDecodeResult<()>
+    where Size: BVSize + ByteDecodable
+{
+    match *schema {
+        Schema::Primitive { name, size } => {
+            match name {
+                "char" => char::decode::<Size>(bytes).map(|_| ()),
+                "usize" => usize::decode::<Size>(bytes).map(|_| ()),
+                _ => expect_len(bytes, size),
+            }
+        }
+        Schema::String => <&str>::decode_borrowed::<Size>(bytes).map(|_| ()),
+        Schema::LossyString => string_bytes::<Size>(bytes).map(|_| ()),
+        Schema::Unit => <()>::decode::<Size>(bytes),
+        Schema::Seq(ref elem) => {
+            let (entries, table, index) = read_table::<Size>(bytes, 0, None)?;
+            let _nested = enter_nested()?;
+            check_entries::<Size, _>(bytes, table, index, entries, true, |_| &**elem)
+        }
+        Schema::Map(ref key, ref value) => {
+            let (entries, table, index) = read_table::<Size>(bytes, 0, None)?;
+            let _nested = enter_nested()?;
+            let entry = Schema::Tuple(vec![(**key).clone(), (**value).clone()]);
+            check_entries::<Size, _>(bytes, table, index, entries, true, |_| &entry)
+        }
+        Schema::PackedSeq(ref elem) => {
+            let (count, mut index) = read_size::<Size>(bytes)?;
+            check_elements(count)?;
+            let size = elem.fixed_size().unwrap_or(0);
+            let body_size = count.checked_mul(size).ok_or(ByteVecError::SizeOverflowError)?;
+            expect_len(bytes, index.saturating_add(body_size))?;
+            for position in 0..count {
+                check::<Size>(elem, &bytes[index..index + size])
+                    .map_err(|error| error.at(index).in_element(position))?;
+                index += size;
+            }
+            Ok(())
+        }
+        Schema::OptionSeq(ref elem) => {
+            let (count, count_len) = read_size::<Size>(bytes)?;
+            check_elements(count)?;
+            let index = count_len.saturating_add(count.div_ceil(8));
+            if bytes.len() < index {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::MoreThan(index),
+                    actual: bytes.len(),
+                    offset: 0,
+                });
+            }
+            let present = (0..count)
+                .filter(|&position| bytes[count_len + position / 8] & (1 << (position % 8)) != 0)
+                .count();
+            let (entries, table, body) = read_table::<Size>(bytes, index, None)?;
+            if entries != present {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: present,
+                    actual: entries,
+                });
+            }
+            let _nested = enter_nested()?;
+            check_entries::<Size, _>(bytes, table, body, entries, true, |_| &**elem)
+        }
+        Schema::InternedSeq(ref elem) => {
+            let (entries, mut index) = read_size::<Size>(bytes)?;
+            check_elements(entries)?;
+            let table = index;
+            let mut body_size = 0usize;
+            for _ in 0..entries {
+                let (size, size_len) = read_size::<Size>(&bytes[index..])
+                    .map_err(|error| error.at(index))?;
+                body_size = body_size.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+                index += size_len;
+            }
+            let body = index;
+            index = index.checked_add(body_size).ok_or(ByteVecError::SizeOverflowError)?;
+            if bytes.len() < index {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::MoreThan(index),
+                    actual: bytes.len(),
+                    offset: 0,
+                });
+            }
+            {
+                let _nested = enter_nested()?;
+                check_entries::<Size, _>(&bytes[..index], table, body, entries, true, |_| &**elem)?;
+            }
+            let (count, count_len) = read_size::<Size>(&bytes[index..])
+                .map_err(|error| error.at(index))?;
+            check_elements(count)?;
+            index += count_len;
+            let width = index_width(entries);
+            let end = count.checked_mul(width)
+                .and_then(|indexes_len| indexes_len.checked_add(index))
+                .ok_or(ByteVecError::SizeOverflowError)?;
+            expect_len(bytes, end)?;
+            for (position, entry) in bytes[index..end].chunks(width).enumerate() {
+                let mut le_bytes = [0u8; 8];
+                le_bytes[..width].copy_from_slice(entry);
+                if u64::from_le_bytes(le_bytes) >= entries as u64 {
+                    return Err(ByteVecError::InvalidValueError("an index of a distinct element")
+                        .at(index + position * width)
+                        .in_element(position));
+                }
+            }
+            Ok(())
+        }
+        Schema::BitSeq => BitVec::decode::<Size>(bytes).map(|_| ()),
+        Schema::BitSet => BitSet::decode::<Size>(bytes).map(|_| ()),
+        Schema::ZigZag(name) => {
+            match name {
+                "i8" => ZigZag::<i8>::decode::<Size>(bytes).map(|_| ()),
+                "i16" => ZigZag::<i16>::decode::<Size>(bytes).map(|_| ()),
+                "i32" => ZigZag::<i32>::decode::<Size>(bytes).map(|_| ()),
+                _ => ZigZag::<i64>::decode::<Size>(bytes).map(|_| ()),
+            }
+        }
+        Schema::Tuple(ref elems) => {
+            let (entries, table, index) = read_table::<Size>(bytes, 0, Some(elems.len()))?;
+            let _nested = enter_nested()?;
+            check_entries::<Size, _>(bytes, table, index, entries, false, |position| {
+                &elems[position]
+            })
+        }
+        Schema::Struct { version, packed, extensible, compact, ref fields, .. } => {
+            let mut index = 0;
+            // The older versions are upgraded by migration functions, which the schema
+            // doesn't describe, so only the current one is admitted
+            if let Some(version) = version {
+                let (actual, version_len) = read_size::<Size>(bytes)?;
+                if actual != version {
+                    return Err(ByteVecError::UnknownVersionError(actual));
+                }
+                index += version_len;
+            }
+            let _nested = enter_nested()?;
+            if packed || compact {
+                let sizes = if packed {
+                    fields.iter()
+                        .map(|field| {
+                            field.schema
+                                .fixed_size()
+                                .ok_or(ByteVecError::MissingFieldError(field.name))
+                        })
+                        .collect::<BVDecodeResult<Vec<_>>>()?
+                } else {
+                    let (sizes, table_len) = compact_table::<Size>(&bytes[index..], fields.len())
+                        .map_err(|error| error.at(index))?;
+                    index += table_len;
+                    sizes
+                };
+                let body_size = sizes.iter()
+                    .try_fold(0usize, |acc, size| acc.checked_add(*size))
+                    .ok_or(ByteVecError::SizeOverflowError)?;
+                expect_len(bytes, index.saturating_add(body_size))?;
+                for (field, size) in fields.iter().zip(sizes) {
+                    check_field::<Size>(field, &bytes[index..index + size])
+                        .map_err(|error| error.at(index).in_field(field.name))?;
+                    index += size;
+                }
+                return Ok(());
+            }
+            let count = if extensible { None } else { Some(fields.len()) };
+            let (entries, mut table, mut index) = read_table::<Size>(bytes, index, count)?;
+            for position in 0..entries {
+                let (size, size_len) = read_size::<Size>(&bytes[table..])?;
+                table += size_len;
+                if let Some(field) = fields.get(position) {
+                    check_field::<Size>(field, &bytes[index..index + size])
+                        .map_err(|error| error.at(index).in_field(field.name))?;
+                }
+                index += size;
+            }
+            Ok(())
+        }
+        Schema::Enum { ref variants, .. } => {
+            let (tag, tag_len) = read_size::<Size>(bytes)?;
+            let variant = variants.iter()
+                .find(|variant| variant.tag == tag)
+                .ok_or(ByteVecError::UnknownVariantError(tag))?;
+            let _nested = enter_nested()?;
+            if variant.fields.is_empty() {
+                return expect_len(bytes, tag_len);
+            }
+            let fields = &bytes[tag_len..];
+            read_table::<Size>(fields, 0, Some(variant.fields.len()))
+                .and_then(|(entries, table, index)| {
+                    check_entries::<Size, _>(fields, table, index, entries, false, |position| {
+                        &variant.fields[position]
+                    })
+                })
+                .map_err(|error| error.at(tag_len).in_field(variant.name))
+        }
+    }
+}
+
+/// Checks a field with its byte order and the width of its size indicators
+fn check_field<Size>(field: &FieldSchema, bytes: &[u8]) -> BVDecodeResult<()>
+    where Size: BVSize + ByteDecodable
+{
+    if field.skippable && bytes.is_empty() {
+        return Ok(());
+    }
+    let reversed;
+    let bytes = if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+        reversed = bytes.iter().rev().cloned().collect::<Vec<_>>();
+        &reversed[..]
+    } else {
+        bytes
+    };
+    match field.size_width {
+        None => check::<Size>(&field.schema, bytes),
+        Some(0) => check::<VarSize>(&field.schema, bytes),
+        Some(1) => check::<u8>(&field.schema, bytes),
+        Some(2) => check::<u16>(&field.schema, bytes),
+        Some(4) => check::<u32>(&field.schema, bytes),
+        Some(8) => check::<u64>(&field.schema, bytes),
+        Some(16) => check::<u128>(&field.schema, bytes),
+        Some(width) => Err(ByteVecError::UnknownSizeWidthError(width)),
+    }
+}
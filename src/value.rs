@@ -0,0 +1,682 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+use errors::{ByteVecError, BVExpectedSize};
+use impls::{index_width, string_bytes};
+use schema::{FieldSchema, Schema};
+use {ByteEncodable, ByteDecodable, BVDecodeResult, BVEncodeResult, BVSize, BitSet, BitVec,
+     VarSize, ZigZag, compact_table, read_size};
+
+/// A value read from a byte representation through the `Schema` of its type, which the
+/// adapters to other formats write in their own encoding.
+///
+/// A `struct` is a `Map` from the names of its fields, an `enum` is the name of its
+/// variant if the variant has no fields, or a `Map` from the name of the variant to the
+/// `Array` of its fields otherwise, and a `char` is a one-character `String`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Nil,
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// Reads the value serialized in `bytes` with `u64` size indicators
+    pub(crate) fn from_bytes(bytes: &[u8], schema: &Schema) -> BVDecodeResult<Value> {
+        read::<u64>(schema, bytes)
+    }
+
+    /// Returns the byte representation of the value with `u64` size indicators
+    pub(crate) fn to_bytes(&self, schema: &Schema) -> BVEncodeResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        write::<u64>(schema, self, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub(crate) fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::UInt(value) => Some(value),
+            Value::Int(value) if value >= 0 => Some(value as u64),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(value) => Some(value),
+            Value::UInt(value) if value <= i64::MAX as u64 => Some(value as i64),
+            _ => None,
+        }
+    }
+}
+
+/// The deepest nesting of arrays and maps accepted by the parsers of the other formats
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// Returns the next `len` bytes after `index`, and moves `index` past them
+pub(crate) fn take<'a>(bytes: &'a [u8], index: &mut usize, len: usize)
+    -> BVDecodeResult<&'a [u8]>
+{
+    let end = index.checked_add(len).ok_or(ByteVecError::SizeOverflowError)?;
+    if bytes.len() < end {
+        return Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::MoreThan(end),
+            actual: bytes.len(),
+            offset: 0,
+        });
+    }
+    let taken = &bytes[*index..end];
+    *index = end;
+    Ok(taken)
+}
+
+/// Reads a big endian unsigned integer of `len` bytes
+pub(crate) fn take_uint(bytes: &[u8], index: &mut usize, len: usize) -> BVDecodeResult<u64> {
+    Ok(take(bytes, index, len)?.iter().fold(0, |value, &byte| value << 8 | byte as u64))
+}
+
+fn expect_len(bytes: &[u8], len: usize) -> BVDecodeResult<()> {
+    if bytes.len() == len {
+        Ok(())
+    } else {
+        Err(ByteVecError::BadSizeDecodeError {
+            expected: BVExpectedSize::EqualTo(len),
+            actual: bytes.len(),
+            offset: 0,
+        })
+    }
+}
+
+/// Reads a table of `entries` sizes, or of as many sizes as the count at the start of
+/// `bytes` if `entries` is `None`, and returns the entries it measures along with the
+/// length of the whole representation
+fn read_entries<Size>(bytes: &[u8], entries: Option<usize>) -> BVDecodeResult<(Vec<&[u8]>, usize)>
+    where Size: BVSize + ByteDecodable
+{
+    let mut index = 0;
+    let entries = match entries {
+        Some(entries) => entries,
+        None => {
+            let (entries, count_len) = read_size::<Size>(bytes)?;
+            index += count_len;
+            entries
+        }
+    };
+    let mut sizes = Vec::new();
+    for _ in 0..entries {
+        let (size, size_len) = read_size::<Size>(&bytes[index..])?;
+        sizes.push(size);
+        index += size_len;
+    }
+    let mut values = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        let end = index.checked_add(size).ok_or(ByteVecError::SizeOverflowError)?;
+        if bytes.len() < end {
+            return Err(ByteVecError::BadSizeDecodeError {
+                expected: BVExpectedSize::MoreThan(end),
+                actual: bytes.len(),
+                offset: 0,
+            });
+        }
+        values.push(&bytes[index..end]);
+        index = end;
+    }
+    Ok((values, index))
+}
+
+/// Reads the value described by `schema` from its whole byte representation
+fn read<Size>(schema: &Schema, bytes: &[u8]) -> BVDecodeResult<Value>
+    where Size: BVSize + ByteDecodable
+{
+    Ok(match *schema {
+        Schema::Primitive { name, .. } => read_primitive::<Size>(name, bytes)?,
+        Schema::String => Value::String(String::decode::<Size>(bytes)?),
+        Schema::LossyString => {
+            Value::String(String::from_utf8_lossy(string_bytes::<Size>(bytes)?).into_owned())
+        }
+        Schema::Unit => Value::Nil,
+        Schema::Seq(ref elem) => {
+            let (entries, len) = read_entries::<Size>(bytes, None)?;
+            expect_len(bytes, len)?;
+            Value::Array(entries.into_iter()
+                .map(|entry| read::<Size>(elem, entry))
+                .collect::<BVDecodeResult<_>>()?)
+        }
+        Schema::Map(ref key, ref value) => {
+            let (entries, len) = read_entries::<Size>(bytes, None)?;
+            expect_len(bytes, len)?;
+            let mut map = Vec::with_capacity(entries.len());
+            for entry in entries {
+                let (pair, pair_len) = read_entries::<Size>(entry, Some(2))?;
+                expect_len(entry, pair_len)?;
+                map.push((read::<Size>(key, pair[0])?, read::<Size>(value, pair[1])?));
+            }
+            Value::Map(map)
+        }
+        Schema::PackedSeq(ref elem) => {
+            let size = elem.fixed_size().ok_or(ByteVecError::InvalidValueError("a fixed size"))?;
+            let (count, count_len) = read_size::<Size>(bytes)?;
+            let len = count.checked_mul(size)
+                .and_then(|body_len| body_len.checked_add(count_len))
+                .ok_or(ByteVecError::SizeOverflowError)?;
+            expect_len(bytes, len)?;
+            Value::Array((0..count)
+                .map(|index| {
+                    let start = count_len + index * size;
+                    read::<Size>(elem, &bytes[start..start + size])
+                })
+                .collect::<BVDecodeResult<_>>()?)
+        }
+        Schema::OptionSeq(ref elem) => {
+            let (count, count_len) = read_size::<Size>(bytes)?;
+            let values_index = count_len + count.div_ceil(8);
+            if bytes.len() < values_index {
+                return Err(ByteVecError::BadSizeDecodeError {
+                    expected: BVExpectedSize::MoreThan(values_index),
+                    actual: bytes.len(),
+                    offset: 0,
+                });
+            }
+            let bitmap = &bytes[count_len..values_index];
+            let (entries, len) = read_entries::<Size>(&bytes[values_index..], None)?;
+            expect_len(bytes, values_index + len)?;
+            let mut entries = entries.into_iter();
+            let mut values = Vec::with_capacity(count);
+            for index in 0..count {
+                values.push(if bitmap[index / 8] & (1 << (index % 8)) != 0 {
+                    let entry = entries.next().ok_or(ByteVecError::LengthMismatchError {
+                        expected: index + 1,
+                        actual: index,
+                    })?;
+                    read::<Size>(elem, entry)?
+                } else {
+                    Value::Nil
+                });
+            }
+            Value::Array(values)
+        }
+        Schema::InternedSeq(ref elem) => {
+            let (table, mut index) = read_entries::<Size>(bytes, None)?;
+            let table = table.into_iter()
+                .map(|entry| read::<Size>(elem, entry))
+                .collect::<BVDecodeResult<Vec<_>>>()?;
+            let (count, count_len) = read_size::<Size>(&bytes[index..])?;
+            index += count_len;
+            let width = index_width(table.len());
+            let len = count.checked_mul(width)
+                .and_then(|indexes_len| indexes_len.checked_add(index))
+                .ok_or(ByteVecError::SizeOverflowError)?;
+            expect_len(bytes, len)?;
+            Value::Array(bytes[index..].chunks(width)
+                .map(|entry| {
+                    let mut le_bytes = [0u8; 8];
+                    le_bytes[..width].copy_from_slice(entry);
+                    let entry = u64::from_le_bytes(le_bytes);
+                    table.get(entry as usize).cloned().ok_or(ByteVecError::LengthMismatchError {
+                        expected: table.len(),
+                        actual: entry.saturating_add(1) as usize,
+                    })
+                })
+                .collect::<BVDecodeResult<_>>()?)
+        }
+        Schema::BitSeq => {
+            Value::Array(BitVec::decode::<Size>(bytes)?.0.into_iter().map(Value::Bool).collect())
+        }
+        Schema::BitSet => {
+            let mut elems = BitSet::decode::<Size>(bytes)?.0.into_iter().collect::<Vec<_>>();
+            elems.sort();
+            Value::Array(elems.into_iter().map(|elem| Value::UInt(elem as u64)).collect())
+        }
+        Schema::ZigZag(name) => {
+            Value::Int(match name {
+                "i8" => ZigZag::<i8>::decode::<Size>(bytes)?.0 as i64,
+                "i16" => ZigZag::<i16>::decode::<Size>(bytes)?.0 as i64,
+                "i32" => ZigZag::<i32>::decode::<Size>(bytes)?.0 as i64,
+                "i64" => ZigZag::<i64>::decode::<Size>(bytes)?.0,
+                _ => return Err(ByteVecError::InvalidValueError("a signed integer type")),
+            })
+        }
+        Schema::Tuple(ref elems) => {
+            let (entries, len) = read_entries::<Size>(bytes, Some(elems.len()))?;
+            expect_len(bytes, len)?;
+            Value::Array(elems.iter()
+                .zip(entries)
+                .map(|(elem, entry)| read::<Size>(elem, entry))
+                .collect::<BVDecodeResult<_>>()?)
+        }
+        Schema::Struct { version, packed, extensible, compact, ref fields, .. } => {
+            let mut index = 0;
+            if let Some(version) = version {
+                let (actual, version_len) = read_size::<Size>(bytes)?;
+                if actual != version {
+                    return Err(ByteVecError::UnknownVersionError(actual));
+                }
+                index += version_len;
+            }
+            let entries = if packed {
+                let mut entries = Vec::with_capacity(fields.len());
+                for field in fields {
+                    let size = field.schema
+                        .fixed_size()
+                        .ok_or(ByteVecError::InvalidValueError("a fixed size"))?;
+                    let end = index + size;
+                    if bytes.len() < end {
+                        return Err(ByteVecError::BadSizeDecodeError {
+                            expected: BVExpectedSize::MoreThan(end),
+                            actual: bytes.len(),
+                            offset: 0,
+                        });
+                    }
+                    entries.push(&bytes[index..end]);
+                    index = end;
+                }
+                entries
+            } else if compact {
+                let (sizes, table_len) = compact_table::<Size>(&bytes[index..], fields.len())?;
+                index += table_len;
+                sizes.into_iter()
+                    .map(|size| {
+                        index += size;
+                        &bytes[index - size..index]
+                    })
+                    .collect()
+            } else {
+                let count = if extensible { None } else { Some(fields.len()) };
+                let (entries, len) = read_entries::<Size>(&bytes[index..], count)?;
+                index += len;
+                entries
+            };
+            expect_len(bytes, index)?;
+            Value::Map(fields.iter()
+                .zip(entries)
+                .map(|(field, entry)| {
+                    Ok((Value::String(field.name.to_string()), read_field::<Size>(field, entry)?))
+                })
+                .collect::<BVDecodeResult<_>>()?)
+        }
+        Schema::Enum { ref variants, .. } => {
+            let (tag, tag_len) = read_size::<Size>(bytes)?;
+            let variant = variants.iter()
+                .find(|variant| variant.tag == tag)
+                .ok_or(ByteVecError::UnknownVariantError(tag))?;
+            if variant.fields.is_empty() {
+                expect_len(bytes, tag_len)?;
+                Value::String(variant.name.to_string())
+            } else {
+                let payload = Schema::Tuple(variant.fields.clone());
+                Value::Map(vec![(Value::String(variant.name.to_string()),
+                                 read::<Size>(&payload, &bytes[tag_len..])?)])
+            }
+        }
+    })
+}
+
+fn read_primitive<Size>(name: &str, bytes: &[u8]) -> BVDecodeResult<Value>
+    where Size: BVSize + ByteDecodable
+{
+    Ok(match name {
+        "u8" => Value::UInt(u8::decode::<Size>(bytes)? as u64),
+        "u16" => Value::UInt(u16::decode::<Size>(bytes)? as u64),
+        "u32" => Value::UInt(u32::decode::<Size>(bytes)? as u64),
+        "u64" => Value::UInt(u64::decode::<Size>(bytes)?),
+        "usize" => Value::UInt(usize::decode::<Size>(bytes)? as u64),
+        "i8" => Value::Int(i8::decode::<Size>(bytes)? as i64),
+        "i16" => Value::Int(i16::decode::<Size>(bytes)? as i64),
+        "i32" => Value::Int(i32::decode::<Size>(bytes)? as i64),
+        "i64" => Value::Int(i64::decode::<Size>(bytes)?),
+        #[cfg(feature = "half")]
+        "f16" => Value::F32(::half::f16::decode::<Size>(bytes)?.to_f32()),
+        "f32" => Value::F32(f32::decode::<Size>(bytes)?),
+        "f64" => Value::F64(f64::decode::<Size>(bytes)?),
+        "char" => {
+            let value = ::std::char::from_u32(u32::decode::<Size>(bytes)?)
+                .ok_or(ByteVecError::InvalidValueError("a Unicode scalar value"))?;
+            Value::String(value.to_string())
+        }
+        _ => return Err(ByteVecError::InvalidValueError("a primitive type")),
+    })
+}
+
+/// Reads a field with its byte order and the width of its size indicators
+fn read_field<Size>(field: &FieldSchema, bytes: &[u8]) -> BVDecodeResult<Value>
+    where Size: BVSize + ByteDecodable
+{
+    if field.skippable && bytes.is_empty() {
+        return Ok(Value::Nil);
+    }
+    let mut reversed = Vec::new();
+    let bytes = if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+        reversed.extend(bytes.iter().rev());
+        &reversed[..]
+    } else {
+        bytes
+    };
+    match field.size_width {
+        None => read::<Size>(&field.schema, bytes),
+        Some(0) => read::<VarSize>(&field.schema, bytes),
+        Some(1) => read::<u8>(&field.schema, bytes),
+        Some(2) => read::<u16>(&field.schema, bytes),
+        Some(4) => read::<u32>(&field.schema, bytes),
+        Some(8) => read::<u64>(&field.schema, bytes),
+        Some(16) => read::<u128>(&field.schema, bytes),
+        Some(_) => Err(ByteVecError::InvalidValueError("a known size width")),
+    }
+}
+
+fn write_size<Size>(size: usize, out: &mut Vec<u8>) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    if size > Size::max_value().as_usize() {
+        return Err(ByteVecError::OverflowError);
+    }
+    Size::from_usize(size).encode_to::<Size, _>(out)
+}
+
+/// Writes the size table of the entries, preceded by their number if `counted` is `true`,
+/// followed by the entries
+fn write_entries<Size>(entries: &[Vec<u8>], counted: bool, out: &mut Vec<u8>)
+    -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    if counted {
+        write_size::<Size>(entries.len(), out)?;
+    }
+    for entry in entries {
+        write_size::<Size>(entry.len(), out)?;
+    }
+    for entry in entries {
+        out.extend_from_slice(entry);
+    }
+    Ok(())
+}
+
+fn write_all<Size>(schema: &Schema, values: &[Value]) -> BVEncodeResult<Vec<Vec<u8>>>
+    where Size: BVSize + ByteEncodable
+{
+    values.iter()
+        .map(|value| {
+            let mut bytes = Vec::new();
+            write::<Size>(schema, value, &mut bytes)?;
+            Ok(bytes)
+        })
+        .collect()
+}
+
+fn as_array(value: &Value) -> BVEncodeResult<&[Value]> {
+    match *value {
+        Value::Array(ref values) => Ok(values),
+        _ => Err(ByteVecError::InvalidValueError("an array")),
+    }
+}
+
+/// Writes the byte representation of `value` as described by `schema`
+fn write<Size>(schema: &Schema, value: &Value, out: &mut Vec<u8>) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    match *schema {
+        Schema::Primitive { name, .. } => write_primitive::<Size>(name, value, out),
+        Schema::String | Schema::LossyString => {
+            match *value {
+                Value::String(ref string) => string.encode_to::<Size, _>(out),
+                _ => Err(ByteVecError::InvalidValueError("a string")),
+            }
+        }
+        Schema::Unit => Ok(()),
+        Schema::Seq(ref elem) => {
+            write_entries::<Size>(&write_all::<Size>(elem, as_array(value)?)?, true, out)
+        }
+        Schema::Map(ref key, ref map_value) => {
+            let pairs = match *value {
+                Value::Map(ref pairs) => pairs,
+                _ => return Err(ByteVecError::InvalidValueError("a map")),
+            };
+            let mut entries = Vec::with_capacity(pairs.len());
+            for (pair_key, pair_value) in pairs {
+                let mut key_bytes = Vec::new();
+                write::<Size>(key, pair_key, &mut key_bytes)?;
+                let mut value_bytes = Vec::new();
+                write::<Size>(map_value, pair_value, &mut value_bytes)?;
+                let mut entry = Vec::new();
+                write_entries::<Size>(&[key_bytes, value_bytes], false, &mut entry)?;
+                entries.push(entry);
+            }
+            write_entries::<Size>(&entries, true, out)
+        }
+        Schema::PackedSeq(ref elem) => {
+            let values = as_array(value)?;
+            write_size::<Size>(values.len(), out)?;
+            for value in values {
+                write::<Size>(elem, value, out)?;
+            }
+            Ok(())
+        }
+        Schema::OptionSeq(ref elem) => {
+            let values = as_array(value)?;
+            let mut bitmap = vec![0u8; values.len().div_ceil(8)];
+            let mut present = Vec::new();
+            for (index, value) in values.iter().enumerate() {
+                if *value != Value::Nil {
+                    bitmap[index / 8] |= 1 << (index % 8);
+                    present.push(value.clone());
+                }
+            }
+            write_size::<Size>(values.len(), out)?;
+            out.extend_from_slice(&bitmap);
+            write_entries::<Size>(&write_all::<Size>(elem, &present)?, true, out)
+        }
+        Schema::InternedSeq(ref elem) => {
+            let mut table = Vec::new();
+            let mut positions = HashMap::new();
+            let mut indexes = Vec::new();
+            for entry in write_all::<Size>(elem, as_array(value)?)? {
+                let next = table.len();
+                let index = *positions.entry(entry.clone()).or_insert(next);
+                if index == next {
+                    table.push(entry);
+                }
+                indexes.push(index);
+            }
+            write_entries::<Size>(&table, true, out)?;
+            write_size::<Size>(indexes.len(), out)?;
+            let width = index_width(table.len());
+            for index in indexes {
+                out.extend_from_slice(&(index as u64).to_le_bytes()[..width]);
+            }
+            Ok(())
+        }
+        Schema::BitSeq => {
+            let bits = as_array(value)?
+                .iter()
+                .map(|value| match *value {
+                    Value::Bool(bit) => Ok(bit),
+                    _ => Err(ByteVecError::InvalidValueError("a boolean")),
+                })
+                .collect::<BVEncodeResult<Vec<_>>>()?;
+            BitVec(bits).encode_to::<Size, _>(out)
+        }
+        Schema::BitSet => {
+            let elems = as_array(value)?
+                .iter()
+                .map(|value| {
+                    value.as_u64()
+                        .filter(|&elem| elem <= u32::MAX as u64)
+                        .map(|elem| elem as u32)
+                        .ok_or(ByteVecError::InvalidValueError("a u32 integer"))
+                })
+                .collect::<BVEncodeResult<HashSet<_>>>()?;
+            BitSet(elems).encode_to::<Size, _>(out)
+        }
+        Schema::ZigZag(name) => {
+            let value = value.as_i64().ok_or(ByteVecError::InvalidValueError("an integer"))?;
+            let out_of_range = ByteVecError::InvalidValueError("an integer in the range");
+            match name {
+                "i8" => {
+                    let value = i8::try_from(value).map_err(|_| out_of_range)?;
+                    ZigZag(value).encode_to::<Size, _>(out)
+                }
+                "i16" => {
+                    let value = i16::try_from(value).map_err(|_| out_of_range)?;
+                    ZigZag(value).encode_to::<Size, _>(out)
+                }
+                "i32" => {
+                    let value = i32::try_from(value).map_err(|_| out_of_range)?;
+                    ZigZag(value).encode_to::<Size, _>(out)
+                }
+                "i64" => ZigZag(value).encode_to::<Size, _>(out),
+                _ => Err(ByteVecError::InvalidValueError("a signed integer type")),
+            }
+        }
+        Schema::Tuple(ref elems) => {
+            let values = as_array(value)?;
+            if values.len() != elems.len() {
+                return Err(ByteVecError::LengthMismatchError {
+                    expected: elems.len(),
+                    actual: values.len(),
+                });
+            }
+            let entries = elems.iter()
+                .zip(values)
+                .map(|(elem, value)| {
+                    let mut bytes = Vec::new();
+                    write::<Size>(elem, value, &mut bytes)?;
+                    Ok(bytes)
+                })
+                .collect::<BVEncodeResult<Vec<_>>>()?;
+            write_entries::<Size>(&entries, false, out)
+        }
+        Schema::Struct { version, packed, extensible, compact, ref fields, .. } => {
+            let pairs = match *value {
+                Value::Map(ref pairs) => pairs,
+                _ => return Err(ByteVecError::InvalidValueError("a map")),
+            };
+            let mut entries = Vec::with_capacity(fields.len());
+            for field in fields {
+                let value = pairs.iter()
+                    .find(|(key, _)| *key == Value::String(field.name.to_string()))
+                    .map(|(_, value)| value);
+                entries.push(match value {
+                    Some(&Value::Nil) | None if field.skippable => Vec::new(),
+                    Some(value) => write_field::<Size>(field, value)?,
+                    None => return Err(ByteVecError::MissingFieldError(field.name)),
+                });
+            }
+            if let Some(version) = version {
+                write_size::<Size>(version, out)?;
+            }
+            if packed {
+                for entry in entries {
+                    out.extend_from_slice(&entry);
+                }
+                Ok(())
+            } else if compact {
+                let elided = entries.len().saturating_sub(1);
+                for entry in &entries[..elided] {
+                    write_size::<Size>(entry.len(), out)?;
+                }
+                for entry in entries {
+                    out.extend_from_slice(&entry);
+                }
+                Ok(())
+            } else {
+                write_entries::<Size>(&entries, extensible, out)
+            }
+        }
+        Schema::Enum { ref variants, .. } => {
+            let (name, fields) = match *value {
+                Value::String(ref name) => (name, None),
+                Value::Map(ref pairs) if pairs.len() == 1 => {
+                    match pairs[0] {
+                        (Value::String(ref name), ref fields) => (name, Some(fields)),
+                        _ => return Err(ByteVecError::InvalidValueError("a variant name")),
+                    }
+                }
+                _ => return Err(ByteVecError::InvalidValueError("a variant")),
+            };
+            let variant = variants.iter()
+                .find(|variant| variant.name == name)
+                .ok_or(ByteVecError::InvalidValueError("a variant of the enum"))?;
+            write_size::<Size>(variant.tag, out)?;
+            match fields {
+                None if variant.fields.is_empty() => Ok(()),
+                Some(fields) => write::<Size>(&Schema::Tuple(variant.fields.clone()), fields, out),
+                None => Err(ByteVecError::InvalidValueError("the fields of the variant")),
+            }
+        }
+    }
+}
+
+fn write_primitive<Size>(name: &str, value: &Value, out: &mut Vec<u8>) -> BVEncodeResult<()>
+    where Size: BVSize + ByteEncodable
+{
+    let out_of_range = ByteVecError::InvalidValueError("an integer in the range");
+    macro_rules! write_integer {
+        ($t:ty, $as_int:ident) => {{
+            let value = value.$as_int()
+                .ok_or(ByteVecError::InvalidValueError("an integer"))?;
+            <$t>::try_from(value).map_err(|_| out_of_range)?.encode_to::<Size, _>(out)
+        }}
+    }
+    match name {
+        "u8" => write_integer!(u8, as_u64),
+        "u16" => write_integer!(u16, as_u64),
+        "u32" => write_integer!(u32, as_u64),
+        "u64" => write_integer!(u64, as_u64),
+        "usize" => write_integer!(usize, as_u64),
+        "i8" => write_integer!(i8, as_i64),
+        "i16" => write_integer!(i16, as_i64),
+        "i32" => write_integer!(i32, as_i64),
+        "i64" => write_integer!(i64, as_i64),
+        "f16" | "f32" | "f64" => {
+            let float = match *value {
+                Value::F32(float) => float as f64,
+                Value::F64(float) => float,
+                _ => return Err(ByteVecError::InvalidValueError("a floating point number")),
+            };
+            match name {
+                #[cfg(feature = "half")]
+                "f16" => ::half::f16::from_f64(float).encode_to::<Size, _>(out),
+                "f32" => (float as f32).encode_to::<Size, _>(out),
+                "f64" => float.encode_to::<Size, _>(out),
+                _ => Err(ByteVecError::InvalidValueError("a primitive type")),
+            }
+        }
+        "char" => {
+            let mut chars = match *value {
+                Value::String(ref string) => string.chars(),
+                _ => return Err(ByteVecError::InvalidValueError("a character")),
+            };
+            match (chars.next(), chars.next()) {
+                (Some(value), None) => value.encode_to::<Size, _>(out),
+                _ => Err(ByteVecError::InvalidValueError("a character")),
+            }
+        }
+        _ => Err(ByteVecError::InvalidValueError("a primitive type")),
+    }
+}
+
+/// Writes a field with its byte order and the width of its size indicators
+fn write_field<Size>(field: &FieldSchema, value: &Value) -> BVEncodeResult<Vec<u8>>
+    where Size: BVSize + ByteEncodable
+{
+    let mut bytes = Vec::new();
+    match field.size_width {
+        None => write::<Size>(&field.schema, value, &mut bytes)?,
+        Some(0) => write::<VarSize>(&field.schema, value, &mut bytes)?,
+        Some(1) => write::<u8>(&field.schema, value, &mut bytes)?,
+        Some(2) => write::<u16>(&field.schema, value, &mut bytes)?,
+        Some(4) => write::<u32>(&field.schema, value, &mut bytes)?,
+        Some(8) => write::<u64>(&field.schema, value, &mut bytes)?,
+        Some(16) => write::<u128>(&field.schema, value, &mut bytes)?,
+        Some(_) => return Err(ByteVecError::InvalidValueError("a known size width")),
+    }
+    if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+        bytes.reverse();
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,112 @@
+use std::marker::PhantomData;
+
+use errors::ByteVecError;
+use migrate::Layout;
+use {ByteDecodable, ByteSchema, BVDecodeResult, BVSize, VarSize};
+
+/// A view over the byte representation of a `struct`, whose fields are decoded on demand.
+///
+/// The view reads the size table of the `struct` when it's created, as given by the
+/// `ByteSchema` implementation of `T`, and then decodes each field only when it's asked
+/// for, without deserializing the rest of the value. A field is found by its name, and
+/// decoded with its options of big endian and of the width of its size indicators, but
+/// not through the proxy given with `with`, if any, whose bytes are available through
+/// `field_bytes`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate bytevec;
+/// #
+/// # use bytevec::{ByteEncodable, LazyStruct};
+/// bytevec_decl! {
+///     #[bytevec(schema)]
+///     struct Record {
+///         id: u64,
+///         name: String,
+///         history: Vec<u32>
+///     }
+/// }
+///
+/// fn main() {
+///     let record = Record {id: 7, name: "Ada".to_string(), history: vec![1; 1000]};
+///     let bytes = record.encode::<u32>().unwrap();
+///     let view = LazyStruct::<Record, u32>::new(&bytes).unwrap();
+///     assert_eq!(view.field::<String>("name").unwrap(), Some("Ada".to_string()));
+///     assert_eq!(view.field_bytes("id"), Some(&[7, 0, 0, 0, 0, 0, 0, 0][..]));
+/// }
+/// ```
+pub struct LazyStruct<'a, T, Size = u32> {
+    layout: Layout,
+    bytes: &'a [u8],
+    fields: Vec<&'a [u8]>,
+    _marker: PhantomData<fn() -> (T, Size)>,
+}
+
+impl<'a, T, Size> LazyStruct<'a, T, Size>
+    where T: ByteSchema,
+          Size: BVSize + ByteDecodable
+{
+    /// Returns a view over the byte representation of a `T` in `bytes`, after checking its
+    /// version, if any, and its size table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the schema of `T` is not a `Schema::Struct`.
+    pub fn new(bytes: &'a [u8]) -> BVDecodeResult<LazyStruct<'a, T, Size>> {
+        let layout = Layout::of(T::schema());
+        let fields = layout.read_fields::<Size>(bytes)?;
+        Ok(LazyStruct {
+            layout,
+            bytes,
+            fields,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the byte representation of the field `name`, or `None` if the `struct`
+    /// has no such field or if it's missing from an older buffer of an extensible `struct`
+    pub fn field_bytes(&self, name: &str) -> Option<&'a [u8]> {
+        self.layout
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .and_then(|index| self.fields.get(index).cloned())
+    }
+
+    /// Decodes the field `name` as a value of type `F`. Returns `None` if the field has no
+    /// value in the buffer, as with `field_bytes`, or if it was skipped.
+    pub fn field<F>(&self, name: &str) -> BVDecodeResult<Option<F>>
+        where F: ByteDecodable
+    {
+        let (field, bytes) = match self.layout
+            .fields
+            .iter()
+            .zip(&self.fields)
+            .find(|&(field, _)| field.name == name) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        if field.skippable && bytes.is_empty() {
+            return Ok(None);
+        }
+        let offset = bytes.as_ptr() as usize - self.bytes.as_ptr() as usize;
+        let reversed;
+        let bytes = if field.big_endian && !(field.size_width.is_none() && Size::big_endian()) {
+            reversed = bytes.iter().rev().cloned().collect::<Vec<_>>();
+            &reversed[..]
+        } else {
+            *bytes
+        };
+        let value = match field.size_width {
+            None => F::decode::<Size>(bytes),
+            Some(0) => F::decode::<VarSize>(bytes),
+            Some(1) => F::decode::<u8>(bytes),
+            Some(2) => F::decode::<u16>(bytes),
+            Some(4) => F::decode::<u32>(bytes),
+            Some(8) => F::decode::<u64>(bytes),
+            Some(16) => F::decode::<u128>(bytes),
+            Some(width) => Err(ByteVecError::UnknownSizeWidthError(width)),
+        };
+        value.map(Some).map_err(|error| error.at(offset).in_field(field.name))
+    }
+}
@@ -1,5 +1,20 @@
 #[macro_use]
 extern crate bytevec;
+#[cfg(feature = "codec")]
+extern crate bytes;
+#[cfg(feature = "codec")]
+extern crate tokio_util;
+#[cfg(feature = "half")]
+extern crate half;
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+#[cfg(feature = "bumpalo")]
+extern crate bumpalo;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_test;
 
 use bytevec::{ByteEncodable, ByteDecodable};
 
@@ -134,4 +149,3604 @@ fn test_decode_limit_err() {
     let vec = vec![0u32; 100];
     let bytes = vec.encode::<u32>().unwrap();
     assert!(<Vec<u32>>::decode_max::<u32>(&bytes, 100).is_err());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_field_default_expr() {
+    use std::num::NonZeroU32;
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Job {
+        id: u32,
+        name: String,
+        retries: NonZeroU32,
+    }
+
+    bytevec_impls! {
+        impl Job {
+            id: u32,
+            #[bytevec(default = "unnamed".to_string())]
+            name: String,
+            #[bytevec(default = NonZeroU32::new(3).unwrap())]
+            retries: NonZeroU32
+        }
+    }
+
+    let job_1 = Job { id: 7, name: "backup".to_string(), retries: NonZeroU32::new(9).unwrap() };
+    let bytes = job_1.encode::<u32>().unwrap();
+    assert_eq!(bytes.len(), 8);
+    let job_2 = Job::decode::<u32>(&bytes).unwrap();
+    assert_eq!(job_2, Job { id: 7, name: "unnamed".to_string(), retries: NonZeroU32::new(3).unwrap() });
+}
+
+#[test]
+fn test_versioned_migration() {
+    use bytevec::{BVDecodeResult, BVSize};
+
+    #[derive(PartialEq, Eq, Debug, Default)]
+    struct SettingsV1 {
+        volume: u8,
+    }
+
+    #[derive(PartialEq, Eq, Debug, Default)]
+    struct Settings {
+        volume: u8,
+        theme: String,
+    }
+
+    impl Settings {
+        fn from_v1<Size>(bytes: &[u8]) -> BVDecodeResult<Settings>
+            where Size: BVSize + ByteDecodable
+        {
+            let old = SettingsV1::decode::<Size>(bytes)?;
+            Ok(Settings { volume: old.volume, theme: "light".to_string() })
+        }
+    }
+
+    bytevec_impls! {
+        impl SettingsV1 {
+            volume: u8
+        }
+
+        #[bytevec(version = 2, migrate(1 => from_v1))]
+        impl Settings {
+            volume: u8,
+            theme: String
+        }
+    }
+
+    #[derive(PartialEq, Eq, Debug, Default)]
+    struct SettingsV1Written {
+        volume: u8,
+    }
+
+    bytevec_impls! {
+        #[bytevec(version = 1)]
+        impl SettingsV1Written {
+            volume: u8
+        }
+    }
+
+    let current = Settings { volume: 3, theme: "dark".to_string() };
+    let bytes = current.encode::<u16>().unwrap();
+    assert_eq!(&bytes[..2], &[2, 0]);
+    assert_eq!(Settings::decode::<u16>(&bytes).unwrap(), current);
+
+    let bytes = SettingsV1Written { volume: 8 }.encode::<u16>().unwrap();
+    assert_eq!(Settings::decode::<u16>(&bytes).unwrap(),
+               Settings { volume: 8, theme: "light".to_string() });
+
+    let mut bytes = current.encode::<u16>().unwrap();
+    bytes[0] = 7;
+    match Settings::decode::<u16>(&bytes) {
+        Err(bytevec::errors::ByteVecError::UnknownVersionError(7)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+mod inventory {
+    bytevec_decl! {
+        /// An item stored in the inventory
+        #[derive(PartialEq, Eq, Debug, Clone)]
+        pub struct Item {
+            /// Unique identifier of the item
+            pub id: u32,
+            pub(crate) name: String,
+            #[allow(dead_code)]
+            #[bytevec(default = 1)]
+            quantity: u16
+        }
+
+        #[derive(PartialEq, Eq, Debug)]
+        #[bytevec(version = 3)]
+        pub(crate) struct Shelf {
+            pub(crate) label: String,
+            pub items: Vec<Item>,
+        }
+    }
+
+    pub fn item(id: u32, name: &str) -> Item {
+        Item { id, name: name.to_string(), quantity: 5 }
+    }
+}
+
+#[test]
+fn test_decl_mixed_visibility_and_attributes() {
+    let shelf_1 = inventory::Shelf {
+        label: "A1".to_string(),
+        items: vec![inventory::item(1, "bolt"), inventory::item(2, "nut")],
+    };
+    let bytes = shelf_1.encode::<u32>().unwrap();
+    assert_eq!(&bytes[..4], &[3, 0, 0, 0]);
+    let shelf_2 = inventory::Shelf::decode::<u32>(&bytes).unwrap();
+    assert_eq!(shelf_2.label, "A1");
+    assert_eq!(shelf_2.items.len(), 2);
+    assert_eq!(shelf_2.items[1].id, 2);
+    assert_eq!(shelf_2.items[1].name, "nut");
+    assert!(shelf_2.items[0] != shelf_1.items[0]);
+}
+
+#[test]
+fn test_tuple_structs() {
+    #[derive(PartialEq, Debug, Default)]
+    struct Meters(f64);
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Labeled(u32, String, #[allow(dead_code)] u8);
+
+    bytevec_impls! {
+        impl Meters(f64)
+
+        impl Labeled(u32, String, #[bytevec(default = 9)] u8)
+    }
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        pub struct Pair(pub u32, String);
+
+        #[derive(PartialEq, Eq, Debug)]
+        struct UserId(u64);
+    }
+
+    let meters = Meters(12.5);
+    let bytes = meters.encode::<u8>().unwrap();
+    assert_eq!(Meters::decode::<u8>(&bytes).unwrap(), meters);
+
+    let labeled = Labeled(4, "four".to_string(), 4);
+    let bytes = labeled.encode::<u8>().unwrap();
+    assert_eq!(Labeled::decode::<u8>(&bytes).unwrap(), Labeled(4, "four".to_string(), 9));
+
+    let pair = Pair(1, "one".to_string());
+    let bytes = pair.encode::<u16>().unwrap();
+    assert_eq!(Pair::decode::<u16>(&bytes).unwrap(), pair);
+    assert_eq!(bytes, (1u32, "one").encode::<u16>().unwrap());
+
+    let id = UserId(42);
+    let bytes = id.encode::<u32>().unwrap();
+    assert_eq!(UserId::decode::<u32>(&bytes).unwrap(), id);
+}
+
+#[test]
+fn test_transparent_newtypes() {
+    #[derive(PartialEq, Eq, Debug)]
+    struct Name {
+        inner: String,
+    }
+
+    bytevec_impls! {
+        #[bytevec(transparent)]
+        impl Name {
+            inner: String
+        }
+    }
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+        #[bytevec(transparent)]
+        struct UserId(u64);
+    }
+
+    let id = UserId(0xdead_beef);
+    let bytes = id.encode::<u32>().unwrap();
+    assert_eq!(bytes, 0xdead_beefu64.encode::<u32>().unwrap());
+    assert_eq!(UserId::decode::<u32>(&bytes).unwrap(), id);
+    assert_eq!(u64::decode::<u32>(&bytes).unwrap(), 0xdead_beef);
+
+    let names = vec![Name { inner: "ann".to_string() }, Name { inner: "bo".to_string() }];
+    let bytes = names.encode::<u8>().unwrap();
+    assert_eq!(bytes, vec!["ann", "bo"].encode::<u8>().unwrap());
+    assert_eq!(<Vec<Name>>::decode::<u8>(&bytes).unwrap(), names);
+}
+
+#[test]
+fn test_skip_if_predicate() {
+    #[derive(PartialEq, Eq, Debug, Default)]
+    struct Snapshot {
+        id: u32,
+        persist_cache: bool,
+        cache: Vec<u32>,
+        note: String,
+    }
+
+    bytevec_impls! {
+        impl Snapshot {
+            id: u32,
+            #[bytevec(skip_if = |s: &Snapshot| !s.persist_cache)]
+            cache: Vec<u32>,
+            #[bytevec(skip_if = |s: &Snapshot| s.note.is_empty(), default = "none".to_string())]
+            note: String
+        }
+    }
+
+    let full = Snapshot { id: 1, persist_cache: true, cache: vec![1, 2, 3], note: "n".to_string() };
+    let bytes = full.encode::<u32>().unwrap();
+    assert_eq!(Snapshot::decode::<u32>(&bytes).unwrap(),
+               Snapshot { persist_cache: false, ..full });
+
+    let bare = Snapshot { id: 2, persist_cache: false, cache: vec![1, 2, 3], note: String::new() };
+    let bytes = bare.encode::<u32>().unwrap();
+    assert_eq!(bytes.len(), 3 * 4 + 4);
+    assert_eq!(Snapshot::decode::<u32>(&bytes).unwrap(),
+               Snapshot { id: 2, persist_cache: false, cache: vec![], note: "none".to_string() });
+}
+
+mod remote {
+    #[derive(PartialEq, Eq, Debug, Clone, Default)]
+    pub struct Rgb<T> {
+        pub r: T,
+        pub g: T,
+        pub b: T,
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Default)]
+    pub struct Span {
+        start: u32,
+        len: u32,
+    }
+
+    impl Span {
+        pub fn new(start: u32, len: u32) -> Span {
+            Span { start, len }
+        }
+
+        pub fn start(&self) -> u32 {
+            self.start
+        }
+
+        pub fn len(&self) -> u32 {
+            self.len
+        }
+    }
+}
+
+bytevec_remote! {
+    struct RgbDef for remote::Rgb<u8> {
+        r: u8,
+        g: u8,
+        b: u8
+    }
+
+    pub struct SpanDef for remote::Span {
+        start(): u32,
+        len(): u32
+    } => remote::Span::new;
+}
+
+#[test]
+fn test_remote_impls() {
+    use bytevec::ByteRemote;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        struct Highlight {
+            #[bytevec(with = RgbDef)]
+            color: remote::Rgb<u8>,
+            #[bytevec(with = SpanDef)]
+            span: remote::Span
+        }
+    }
+
+    let highlight = Highlight {
+        color: remote::Rgb { r: 255, g: 128, b: 0 },
+        span: remote::Span::new(10, 4),
+    };
+    let bytes = highlight.encode::<u16>().unwrap();
+    assert_eq!(Highlight::decode::<u16>(&bytes).unwrap(), highlight);
+
+    let bytes = SpanDef::encode::<u8>(&highlight.span).unwrap();
+    assert_eq!(bytes, (10u32, 4u32).encode::<u8>().unwrap());
+    assert_eq!(SpanDef::decode::<u8>(&bytes).unwrap(), highlight.span);
+}
+
+#[test]
+fn test_field_size_and_endianness() {
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        struct Packet {
+            #[bytevec(big_endian)]
+            magic: u32,
+            #[bytevec(size = u8)]
+            tags: Vec<String>,
+            blob: Vec<u8>
+        }
+    }
+
+    let packet = Packet {
+        magic: 0x0102_0304,
+        tags: vec!["a".to_string(), "bc".to_string()],
+        blob: vec![7; 3],
+    };
+    let bytes = packet.encode::<u64>().unwrap();
+    assert_eq!(&bytes[24..28], &[1, 2, 3, 4]);
+    assert_eq!(&bytes[28..34], &[2, 1, 2, b'a', b'b', b'c']);
+    assert_eq!(bytes.len(), 24 + 4 + 6 + 8 + 3 * 8 + 3);
+    assert_eq!(Packet::decode::<u64>(&bytes).unwrap(), packet);
+
+    let packet = Packet { tags: vec!["x".repeat(300)], ..packet };
+    assert!(packet.encode::<u64>().is_err());
+}
+
+#[test]
+fn test_packed_structs() {
+    use bytevec::FixedByteSize;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Clone, Copy)]
+        #[bytevec(packed)]
+        struct Reading {
+            x: f32,
+            y: f32,
+            z: f32
+        }
+
+        #[derive(PartialEq, Debug)]
+        #[bytevec(packed)]
+        struct SensorFrame {
+            #[bytevec(big_endian)]
+            sensor: u16,
+            timestamp: u64,
+            accel: Reading,
+            flag: char
+        }
+    }
+
+    assert_eq!(Reading::ENCODED_SIZE, 12);
+    assert_eq!(SensorFrame::ENCODED_SIZE, 26);
+    assert_eq!(<SensorFrame as FixedByteSize>::SIZE, 26);
+
+    let frame = SensorFrame {
+        sensor: 0x0a0b,
+        timestamp: 1_000_000,
+        accel: Reading { x: 0.5, y: -1.0, z: 9.8 },
+        flag: 'k',
+    };
+    let bytes = frame.encode::<u8>().unwrap();
+    assert_eq!(bytes.len(), SensorFrame::ENCODED_SIZE);
+    assert_eq!(&bytes[..2], &[0x0a, 0x0b]);
+    assert_eq!(SensorFrame::decode::<u8>(&bytes).unwrap(), frame);
+    assert!(SensorFrame::decode::<u8>(&bytes[1..]).is_err());
+
+    let frames = vec![Reading { x: 1.0, y: 2.0, z: 3.0 }; 2];
+    let bytes = frames.encode::<u32>().unwrap();
+    assert_eq!(<Vec<Reading>>::decode::<u32>(&bytes).unwrap(), frames);
+}
+
+#[test]
+fn test_complete_field_list() {
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        struct UserId(u32);
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Account {
+        id: UserId,
+        name: String,
+    }
+
+    bytevec_impls! {
+        #[bytevec(complete)]
+        impl Account {
+            id: UserId,
+            name: String
+        }
+    }
+
+    let account = Account { id: UserId(7), name: "root".to_owned() };
+    let bytes = account.encode::<u8>().unwrap();
+    assert_eq!(Account::decode::<u8>(&bytes).unwrap(), account);
+}
+
+#[test]
+fn test_enums() {
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        pub enum Shape {
+            /// An empty shape
+            Empty,
+            Circle(f64),
+            Rect(u32, u32),
+            Polygon {
+                name: String,
+                points: Vec<(i32, i32)>,
+            },
+        }
+
+        #[derive(PartialEq, Debug)]
+        struct Drawing {
+            shapes: Vec<Shape>
+        }
+    }
+
+    let drawing = Drawing {
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(2.5),
+            Shape::Rect(4, 8),
+            Shape::Polygon {
+                name: "triangle".to_owned(),
+                points: vec![(0, 0), (3, 0), (0, -4)],
+            },
+        ],
+    };
+    assert_eq!(Shape::Empty.encode::<u8>().unwrap(), vec![0]);
+    let bytes = drawing.encode::<u16>().unwrap();
+    assert_eq!(Drawing::decode::<u16>(&bytes).unwrap(), drawing);
+
+    match Shape::decode::<u8>(&[7]) {
+        Err(bytevec::errors::ByteVecError::UnknownVariantError(7)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(Shape::decode::<u8>(&[0, 1]).is_err());
+}
+
+#[test]
+fn test_writer_and_reader() {
+    use std::io::Cursor;
+    use bytevec::errors::ByteVecError;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        struct Snapshot {
+            id: u32,
+            #[bytevec(big_endian)]
+            epoch: u16,
+            entries: Vec<String>
+        }
+    }
+
+    let snapshot = Snapshot {
+        id: 4,
+        epoch: 12,
+        entries: vec!["alpha".to_owned(), "beta".to_owned()],
+    };
+    let mut written = Vec::new();
+    snapshot.encode_to::<u32, _>(&mut written).unwrap();
+    assert_eq!(written, snapshot.encode::<u32>().unwrap());
+
+    let mut reader = Cursor::new(written);
+    assert_eq!(Snapshot::decode_from::<u32, _>(&mut reader).unwrap(), snapshot);
+
+    let mut buffer = [0u8; 8];
+    match snapshot.encode_to::<u32, _>(&mut &mut buffer[..]) {
+        Err(ByteVecError::IoError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_codec() {
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+    use bytevec::codec::BytevecCodec;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Clone)]
+        struct Message {
+            id: u16,
+            body: String
+        }
+    }
+
+    let hello = Message { id: 1, body: "hello".to_owned() };
+    let bye = Message { id: 2, body: "bye".to_owned() };
+    let mut codec = BytevecCodec::<Message, u16>::new();
+    let mut frames = BytesMut::new();
+    codec.encode(hello.clone(), &mut frames).unwrap();
+    codec.encode(bye.clone(), &mut frames).unwrap();
+
+    // Feed the frames one byte at a time to go through partial reads
+    let mut buffer = BytesMut::new();
+    let mut decoded = Vec::new();
+    for byte in frames.iter() {
+        buffer.extend_from_slice(&[*byte]);
+        if let Some(message) = codec.decode(&mut buffer).unwrap() {
+            decoded.push(message);
+        }
+    }
+    assert_eq!(decoded, vec![hello.clone(), bye]);
+    assert!(buffer.is_empty());
+
+    let mut small_codec = BytevecCodec::<Message, u16>::with_max_frame_size(4);
+    assert!(small_codec.encode(hello.clone(), &mut BytesMut::new()).is_err());
+    let mut frame = BytesMut::new();
+    codec.encode(hello, &mut frame).unwrap();
+    assert!(small_codec.decode(&mut frame).is_err());
+}
+
+#[test]
+fn test_framed_records() {
+    let mut stream = 42u32.encode_framed::<u8>().unwrap();
+    stream.extend(vec!["first", "second"].encode_framed::<u8>().unwrap());
+    stream.extend("tail".to_owned().encode_framed::<u8>().unwrap());
+
+    let (number, rest) = u32::decode_framed::<u8>(&stream).unwrap();
+    let (words, rest) = <Vec<String>>::decode_framed::<u8>(rest).unwrap();
+    let (tail, rest) = String::decode_framed::<u8>(rest).unwrap();
+    assert_eq!(number, 42);
+    assert_eq!(words, vec!["first".to_owned(), "second".to_owned()]);
+    assert_eq!(tail, "tail");
+    assert!(rest.is_empty());
+    assert!(String::decode_framed::<u8>(&[4, b't', b'a']).is_err());
+}
+
+#[test]
+fn test_frame_iter() {
+    use bytevec::FrameIter;
+
+    let mut stream = Vec::new();
+    for word in &["one", "two", "three"] {
+        stream.extend(word.encode_framed::<u16>().unwrap());
+    }
+    // A frame whose payload is not valid UTF-8, followed by a truncated frame
+    stream.extend(&[1, 0, 0xff]);
+    stream.extend(&[5, 0, b'f', b'o']);
+
+    let mut frames = FrameIter::<String, u16>::new(&stream);
+    assert_eq!(frames.next().unwrap().unwrap(), "one");
+    assert_eq!(frames.next().unwrap().unwrap(), "two");
+    assert_eq!(frames.next().unwrap().unwrap(), "three");
+    assert!(!frames.is_incomplete());
+    assert!(frames.next().unwrap().is_err());
+    assert!(frames.next().is_none());
+    assert!(frames.is_incomplete());
+    assert_eq!(frames.remaining(), &[5, 0, b'f', b'o']);
+
+    let mut frames = FrameIter::<String, u16>::new(&stream[..10]);
+    assert_eq!(frames.by_ref().count(), 2);
+    assert!(!frames.is_incomplete());
+}
+
+#[test]
+fn test_encode_into_slice() {
+    use bytevec::errors::ByteVecError;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        struct Reading {
+            sensor: String,
+            values: Vec<f32>
+        }
+    }
+
+    let reading = Reading { sensor: "temp".to_owned(), values: vec![20.5, 21.0] };
+    let mut buffer = [0u8; 64];
+    let written = reading.encode_into_slice::<u8>(&mut buffer).unwrap();
+    assert_eq!(&buffer[..written], &reading.encode::<u8>().unwrap()[..]);
+    assert_eq!(Reading::decode::<u8>(&buffer[..written]).unwrap(), reading);
+
+    match reading.encode_into_slice::<u8>(&mut buffer[..written - 1]) {
+        Err(ByteVecError::BufferTooSmallError { required, actual }) => {
+            assert_eq!((required, actual), (written, written - 1));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_encode_append() {
+    let mut out = vec![0xaa];
+    let first = 7u16.encode_append::<u8>(&mut out).unwrap();
+    let second = vec!["a", "bc"].encode_append::<u8>(&mut out).unwrap();
+    assert_eq!((first, second), (2, 6));
+    assert_eq!(out, vec![0xaa, 7, 0, 2, 1, 2, b'a', b'b', b'c']);
+
+    let long = vec![0u8; 300];
+    assert!(long.encode_append::<u8>(&mut out).is_err());
+    assert_eq!(out.len(), 9);
+}
+
+#[test]
+fn test_borrowed_decoding() {
+    use bytevec::ByteDecodeBorrowed;
+
+    let names = vec!["alpha", "beta", "gamma"];
+    let bytes = names.encode::<u16>().unwrap();
+    let decoded = <Vec<&str>>::decode_borrowed::<u16>(&bytes).unwrap();
+    assert_eq!(decoded, names);
+    assert_eq!(decoded[1].as_ptr(), bytes[bytes.len() - 9..].as_ptr());
+
+    let blob: &[u8] = &[1, 2, 3, 250];
+    let bytes = blob.encode::<u8>().unwrap();
+    let view = <&[u8]>::decode_borrowed::<u8>(&bytes).unwrap();
+    assert_eq!(view, blob);
+    assert!(<&[u8]>::decode_borrowed::<u8>(&bytes[..bytes.len() - 1]).is_err());
+    assert!(<&[u8]>::decode_borrowed::<u8>(&[2, 0, 2, 7, 7]).is_err());
+
+    let number = u32::decode_borrowed::<u8>(&7u32.encode::<u8>().unwrap()).unwrap();
+    assert_eq!(number, 7);
+}
+
+#[test]
+fn test_seq_encoder() {
+    use std::io::Cursor;
+    use bytevec::SeqEncoder;
+    use bytevec::errors::ByteVecError;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Clone)]
+        struct Row {
+            id: u64,
+            name: String
+        }
+    }
+
+    let rows = (0..4).map(|id| Row { id, name: format!("row-{}", id) }).collect::<Vec<_>>();
+    let mut header = Cursor::new(vec![0xff]);
+    header.set_position(1);
+    let mut encoder = SeqEncoder::<u16, _>::new(header, rows.len()).unwrap();
+    for row in &rows {
+        encoder.push(row).unwrap();
+    }
+    assert_eq!(encoder.pushed(), 4);
+    let bytes = encoder.finish().unwrap().into_inner();
+    assert_eq!(bytes[0], 0xff);
+    assert_eq!(&bytes[1..], &rows.encode::<u16>().unwrap()[..]);
+    assert_eq!(<Vec<Row>>::decode::<u16>(&bytes[1..]).unwrap(), rows);
+
+    let mut encoder = SeqEncoder::<u16, _>::new(Cursor::new(Vec::new()), 1).unwrap();
+    encoder.push(&rows[0]).unwrap();
+    match encoder.push(&rows[1]) {
+        Err(ByteVecError::LengthMismatchError { expected: 1, actual: 2 }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let encoder = SeqEncoder::<u16, _>::new(Cursor::new(Vec::new()), 2).unwrap();
+    assert!(encoder.finish().is_err());
+}
+
+#[test]
+fn test_seq_decoder() {
+    use bytevec::SeqDecoder;
+
+    let words = vec!["lazy", "decoding", "of", "words"];
+    let bytes = words.encode::<u8>().unwrap();
+    let mut decoder = SeqDecoder::<&str, u8>::new(&bytes).unwrap();
+    assert_eq!(decoder.len(), 4);
+    assert_eq!(decoder.next().unwrap().unwrap(), "lazy");
+    let rest = decoder.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(rest, &words[1..]);
+
+    let mut bad = bytes.clone();
+    let last = bad.len() - 1;
+    bad[last] = 0xff;
+    let results = SeqDecoder::<String, u8>::new(&bad).unwrap().collect::<Vec<_>>();
+    assert!(results[..3].iter().all(Result::is_ok));
+    assert!(results[3].is_err());
+    assert!(SeqDecoder::<String, u8>::new(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_streaming_decoder() {
+    use bytevec::{StreamingDecoder, DecodeProgress};
+
+    let mut stream = (1u16, "first".to_owned()).encode_framed::<u16>().unwrap();
+    stream.extend((2u16, "second".to_owned()).encode_framed::<u16>().unwrap());
+
+    let mut decoder = StreamingDecoder::<(u16, String), u16>::new();
+    let mut values = Vec::new();
+    for chunk in stream.chunks(3) {
+        let mut progress = decoder.feed(chunk).unwrap();
+        while let DecodeProgress::Done(value) = progress {
+            values.push(value);
+            progress = decoder.feed(&[]).unwrap();
+        }
+    }
+    assert_eq!(values, vec![(1, "first".to_owned()), (2, "second".to_owned())]);
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.feed(&[]).unwrap(), DecodeProgress::NeedMore(2));
+
+    let mut decoder = StreamingDecoder::<String, u16>::with_max_frame_size(4);
+    assert!(decoder.feed(&[5, 0]).is_err());
+    assert_eq!(decoder.buffered(), 0);
+}
+
+#[test]
+fn test_decode_with_len() {
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        struct Header {
+            kind: u8,
+            tags: Vec<String>
+        }
+
+        #[derive(PartialEq, Debug)]
+        enum Op {
+            Nop,
+            Push(u32),
+            Label { name: String }
+        }
+    }
+
+    let header = Header { kind: 3, tags: vec!["a".to_owned(), "bc".to_owned()] };
+    let mut bytes = header.encode::<u16>().unwrap();
+    let header_len = bytes.len();
+    bytes.extend(Op::Label { name: "start".to_owned() }.encode::<u16>().unwrap());
+    bytes.extend(Op::Nop.encode::<u16>().unwrap());
+    bytes.extend(Op::Push(9).encode::<u16>().unwrap());
+    bytes.extend((1u8, 2u16).encode::<u16>().unwrap());
+    bytes.extend(vec![5u32, 6].encode::<u16>().unwrap());
+    bytes.extend(7.5f64.encode::<u16>().unwrap());
+
+    let (decoded, len) = Header::decode_with_len::<u16>(&bytes).unwrap();
+    assert_eq!((decoded, len), (header, header_len));
+    let mut rest = &bytes[len..];
+    let mut ops = Vec::new();
+    for _ in 0..3 {
+        let (op, len) = Op::decode_with_len::<u16>(rest).unwrap();
+        ops.push(op);
+        rest = &rest[len..];
+    }
+    assert_eq!(ops, vec![Op::Label { name: "start".to_owned() }, Op::Nop, Op::Push(9)]);
+    let (pair, len) = <(u8, u16)>::decode_with_len::<u16>(rest).unwrap();
+    assert_eq!(pair, (1, 2));
+    rest = &rest[len..];
+    let (numbers, len) = <Vec<u32>>::decode_with_len::<u16>(rest).unwrap();
+    assert_eq!(numbers, vec![5, 6]);
+    rest = &rest[len..];
+    assert_eq!(f64::decode_with_len::<u16>(rest).unwrap(), (7.5, 8));
+    assert!(<Vec<u32>>::decode_with_len::<u16>(&[2, 0, 4, 0]).is_err());
+}
+
+#[test]
+fn test_encoded_reader() {
+    use std::io::Read;
+    use bytevec::{EncodedReader, FrameIter};
+
+    let names = vec!["reader".to_owned(), "adapter".to_owned()];
+    let mut reader = EncodedReader::<_, u8>::new(names.clone());
+    let mut bytes = Vec::new();
+    let mut chunk = [0u8; 3];
+    loop {
+        let len = reader.read(&mut chunk).unwrap();
+        if len == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..len]);
+    }
+    assert_eq!(bytes, names.encode::<u8>().unwrap());
+
+    let mut reader = EncodedReader::<_, u16>::framed(names.iter().map(|name| name.len() as u32));
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).unwrap();
+    let lens = FrameIter::<u32, u16>::new(&bytes).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(lens, vec![6, 7]);
+
+    let mut reader = EncodedReader::<_, u8>::new(vec![0u8; 300]);
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn test_io_error_interop() {
+    use std::error::Error;
+    use std::io;
+    use bytevec::errors::ByteVecError;
+
+    fn read_name(bytes: &[u8]) -> io::Result<String> {
+        Ok(String::decode::<u8>(bytes)?)
+    }
+
+    let error = read_name(&[0xff, 0xfe]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let inner = error.get_ref().unwrap();
+    assert!(inner.source().is_some());
+
+    let io_error = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated");
+    let error = ByteVecError::from(io_error);
+    assert!(error.source().is_some());
+    assert_eq!(io::Error::from(error).kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_varint_sizes() {
+    use bytevec::{VarSize, FrameIter};
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        struct Record {
+            id: u32,
+            tags: Vec<String>,
+            blob: Vec<u8>
+        }
+    }
+
+    let record = Record {
+        id: 7,
+        tags: vec!["a".to_string(), "bc".to_string()],
+        blob: vec![0xab; 200],
+    };
+    let bytes = record.encode::<VarSize>().unwrap();
+    assert_eq!(bytes.len(), record.get_size::<VarSize>().unwrap().0 as usize);
+    assert!(bytes.len() < record.encode::<u32>().unwrap().len());
+    assert_eq!(Record::decode::<VarSize>(&bytes).unwrap(), record);
+
+    assert_eq!(VarSize(127).encode::<VarSize>().unwrap(), vec![0x7f]);
+    assert_eq!(VarSize(300).encode::<VarSize>().unwrap(), vec![0xac, 0x02]);
+    assert_eq!(VarSize::decode_with_len::<VarSize>(&[0xac, 0x02, 0xff]).unwrap(),
+               (VarSize(300), 2));
+    assert!(VarSize::decode::<VarSize>(&[0x80]).is_err());
+    assert!(VarSize::decode::<VarSize>(&[0xff; 11]).is_err());
+
+    let mut frames = (1u64, "one".to_string()).encode_framed::<VarSize>().unwrap();
+    frames.extend((2u64, "two".to_string()).encode_framed::<VarSize>().unwrap());
+    let values = FrameIter::<(u64, String), VarSize>::new(&frames[..frames.len() - 1])
+        .collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(values, vec![(1, "one".to_string())]);
+}
+
+#[test]
+fn test_packed_vec() {
+    use bytevec::PackedVec;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        struct Series {
+            name: String,
+            points: PackedVec<f64>
+        }
+    }
+
+    let series = Series {
+        name: "temperature".to_string(),
+        points: PackedVec(vec![20.5, 21.0, 19.75]),
+    };
+    let bytes = series.encode::<u16>().unwrap();
+    assert_eq!(bytes.len(), 2 * 2 + 11 + 2 + 3 * 8);
+    assert_eq!(Series::decode::<u16>(&bytes).unwrap(), series);
+
+    let chars = PackedVec::from(vec!['a', 'b']);
+    let bytes = chars.encode::<u8>().unwrap();
+    assert_eq!(PackedVec::<char>::decode_with_len::<u8>(&bytes).unwrap(), (chars, 9));
+    assert!(PackedVec::<char>::decode::<u8>(&bytes[..8]).is_err());
+    assert!(PackedVec(vec![0u8; 256]).encode::<u8>().is_err());
+}
+
+#[test]
+fn test_option_vec() {
+    use bytevec::OptionVec;
+
+    let column = OptionVec(vec![None, Some("x".to_string()), None, None, None, None, None, None,
+                                None, Some("yz".to_string())]);
+    let bytes = column.encode::<u8>().unwrap();
+    assert_eq!(bytes.len(), column.get_size::<u8>().unwrap() as usize);
+    assert_eq!(&bytes[..6], &[10, 0b10, 0b10, 2, 1, 2]);
+    assert_eq!(OptionVec::<String>::decode::<u8>(&bytes).unwrap(), column);
+
+    let mut corrupted = bytes.clone();
+    corrupted[1] = 0b11;
+    assert!(OptionVec::<String>::decode::<u8>(&corrupted).is_err());
+    assert_eq!(OptionVec::<u32>::decode::<u8>(&[0, 0]).unwrap(), OptionVec(vec![]));
+}
+
+#[test]
+fn test_format_header() {
+    use bytevec::{FormatHeader, VarSize};
+    use bytevec::errors::ByteVecError;
+
+    let value = vec![(1u16, "a".to_string()), (2, "b".to_string())];
+    let bytes = value.encode_with_header::<u32>().unwrap();
+    assert_eq!(&bytes[..6], b"BVEC\x01\x04");
+    assert_eq!(Vec::<(u16, String)>::decode_with_header::<u32>(&bytes).unwrap(), value);
+
+    match Vec::<(u16, String)>::decode_with_header::<u64>(&bytes) {
+        Err(ByteVecError::HeaderMismatchError { expected, actual }) => {
+            assert_eq!(expected.size_width, 8);
+            assert_eq!(actual.size_width, 4);
+        }
+        _ => panic!("expected a HeaderMismatchError"),
+    }
+    match Vec::<(u16, String)>::decode_with_header::<u32>(&value.encode::<u32>().unwrap()) {
+        Err(ByteVecError::BadMagicError) => {}
+        _ => panic!("expected a BadMagicError"),
+    }
+    assert_eq!(FormatHeader::of::<VarSize>().size_width, 0);
+}
+
+#[test]
+fn test_checksum_trailer() {
+    use bytevec::errors::ByteVecError;
+
+    let bytes = "123456789".encode_with_checksum::<u32>().unwrap();
+    assert_eq!(&bytes[9..], &0xcbf4_3926u32.to_le_bytes());
+    assert_eq!(String::decode_with_checksum::<u32>(&bytes).unwrap(), "123456789");
+
+    let mut corrupted = bytes.clone();
+    corrupted[4] ^= 0x10;
+    match String::decode_with_checksum::<u32>(&corrupted) {
+        Err(ByteVecError::ChecksumMismatchError { expected, .. }) => {
+            assert_eq!(expected, 0xcbf4_3926)
+        }
+        _ => panic!("expected a ChecksumMismatchError"),
+    }
+    assert!(String::decode_with_checksum::<u32>(&bytes[1..]).is_err());
+}
+
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+#[test]
+fn test_compression() {
+    use std::collections::HashMap;
+    use bytevec::compress::Compression;
+    use bytevec::errors::ByteVecError;
+
+    let snapshot = (0..500u32).map(|key| (key, "value".to_string())).collect::<HashMap<_, _>>();
+    let algorithms = [
+        #[cfg(feature = "flate2")]
+        Compression::Deflate,
+        #[cfg(feature = "zstd")]
+        Compression::Zstd,
+    ];
+
+    for &compression in &algorithms {
+        let bytes = snapshot.encode_compressed::<u32>(compression).unwrap();
+        assert_eq!(bytes[0], compression.id());
+        assert!(bytes.len() < snapshot.encode::<u32>().unwrap().len() / 4);
+        assert_eq!(HashMap::<u32, String>::decode_compressed::<u32>(&bytes).unwrap(), snapshot);
+
+        let mut truncated = bytes.clone();
+        truncated[1] += 1;
+        assert!(HashMap::<u32, String>::decode_compressed::<u32>(&truncated).is_err());
+    }
+    match String::decode_compressed::<u32>(&[0xff, 0, 0, 0, 0]) {
+        Err(ByteVecError::UnknownCompressionError(0xff)) => {}
+        _ => panic!("expected an UnknownCompressionError"),
+    }
+}
+
+#[test]
+fn test_payload_transform() {
+    use std::io;
+    use bytevec::{BVEncodeResult, BVDecodeResult, PayloadTransform};
+
+    struct Xor(u8);
+
+    impl PayloadTransform for Xor {
+        fn apply(&self, bytes: Vec<u8>) -> BVEncodeResult<Vec<u8>> {
+            Ok(bytes.into_iter().map(|byte| byte ^ self.0).collect())
+        }
+
+        fn reverse(&self, bytes: &[u8]) -> BVDecodeResult<Vec<u8>> {
+            Ok(bytes.iter().map(|byte| byte ^ self.0).collect())
+        }
+    }
+
+    struct Signed;
+
+    impl PayloadTransform for Signed {
+        fn apply(&self, mut bytes: Vec<u8>) -> BVEncodeResult<Vec<u8>> {
+            bytes.push(bytes.iter().fold(0, |acc: u8, byte| acc.wrapping_add(*byte)));
+            Ok(bytes)
+        }
+
+        fn reverse(&self, bytes: &[u8]) -> BVDecodeResult<Vec<u8>> {
+            match bytes.split_last() {
+                Some((&sign, body)) if body.iter()
+                    .fold(0, |acc: u8, byte| acc.wrapping_add(*byte)) == sign => Ok(body.to_vec()),
+                _ => Err(io::Error::new(io::ErrorKind::InvalidData, "bad signature").into()),
+            }
+        }
+    }
+
+    let value = ("secret".to_string(), 42u32);
+    let bytes = value.encode_transformed::<u16, _>(&Xor(0x5a)).unwrap();
+    assert_ne!(bytes, value.encode::<u16>().unwrap());
+    assert_eq!(<(String, u32)>::decode_transformed::<u16, _>(&bytes, &Xor(0x5a)).unwrap(), value);
+
+    let transform = (Xor(0x5a), Signed);
+    let mut bytes = value.encode_transformed::<u16, _>(&transform).unwrap();
+    assert_eq!(<(String, u32)>::decode_transformed::<u16, _>(&bytes, &transform).unwrap(), value);
+    bytes[0] ^= 1;
+    assert!(<(String, u32)>::decode_transformed::<u16, _>(&bytes, &transform).is_err());
+}
+
+#[test]
+fn test_deterministic_encoding() {
+    use std::collections::{HashMap, HashSet};
+    use bytevec::Deterministic;
+
+    let mut a = HashMap::new();
+    let mut b = HashMap::with_capacity(1000);
+    for index in 0..50u32 {
+        a.insert(index.to_string(), (0..index).collect::<HashSet<_>>());
+    }
+    for index in (0..50u32).rev() {
+        b.insert(index.to_string(), (0..index).rev().collect::<HashSet<_>>());
+    }
+    let bytes = a.encode::<Deterministic<u32>>().unwrap();
+    assert_eq!(bytes, b.encode::<Deterministic<u32>>().unwrap());
+    assert_eq!(bytes.len(), a.encode::<u32>().unwrap().len());
+    assert_eq!(HashMap::<String, HashSet<u32>>::decode::<u32>(&bytes).unwrap(), a);
+    assert_eq!(HashMap::<String, HashSet<u32>>::decode::<Deterministic<u32>>(&bytes).unwrap(), b);
+}
+
+#[test]
+fn test_schema() {
+    use bytevec::{ByteSchema, Schema, FieldSchema, VariantSchema};
+
+    #[derive(Default)]
+    #[allow(dead_code)]
+    struct Sample {
+        id: u32,
+        tags: Vec<String>,
+        hidden: u8
+    }
+
+    #[allow(dead_code)]
+    enum Shape {
+        Empty,
+        Circle(u16),
+        Rect {w: u16, h: u16}
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema, version = 2)]
+        impl Sample {
+            #[bytevec(big_endian)]
+            id: u32,
+            #[bytevec(size = u8)]
+            tags: Vec<String>,
+            #[bytevec(default = 7)]
+            hidden: u8
+        }
+
+        #[bytevec(schema)]
+        impl enum Shape {
+            Empty,
+            Circle(u16),
+            Rect {w: u16, h: u16}
+        }
+    }
+
+    let u32_schema = Schema::Primitive {name: "u32", size: 4};
+    let u16_schema = Schema::Primitive {name: "u16", size: 2};
+    assert_eq!(Sample::schema(), Schema::Struct {
+        name: "Sample",
+        version: Some(2),
+        packed: false,
+        extensible: false,
+        compact: false,
+        fields: vec![
+            FieldSchema {
+                name: "id",
+                schema: u32_schema.clone(),
+                big_endian: true,
+                skippable: false,
+                size_width: None
+            },
+            FieldSchema {
+                name: "tags",
+                schema: Schema::Seq(Box::new(Schema::String)),
+                big_endian: false,
+                skippable: false,
+                size_width: Some(1)
+            }
+        ]
+    });
+    assert_eq!(Shape::schema(), Schema::Enum {
+        name: "Shape",
+        variants: vec![
+            VariantSchema {name: "Empty", tag: 0, fields: vec![]},
+            VariantSchema {name: "Circle", tag: 1, fields: vec![u16_schema.clone()]},
+            VariantSchema {name: "Rect", tag: 2, fields: vec![u16_schema.clone(), u16_schema]}
+        ]
+    });
+    assert_eq!(<(u32, String)>::schema().to_json::<u16>(),
+        r#"{"format_version":1,"size_width":2,"schema":{"kind":"tuple","elements":[{"kind":"primitive","type":"u32","size":4},{"kind":"string"}]}}"#);
+}
+
+#[test]
+fn test_schema_migration() {
+    use bytevec::{ByteSchema, Migrator};
+    use bytevec::errors::ByteVecError;
+
+    #[derive(Default)]
+    struct RecordV1 {
+        id: u32,
+        title: String,
+        legacy: u64,
+        score: u16
+    }
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Record {
+        id: u32,
+        name: String,
+        score: u32,
+        tags: Vec<String>,
+        cache: Vec<u8>
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema, version = 1)]
+        impl RecordV1 {
+            id: u32,
+            title: String,
+            legacy: u64,
+            score: u16
+        }
+
+        #[bytevec(schema, version = 2)]
+        impl Record {
+            id: u32,
+            name: String,
+            score: u32,
+            tags: Vec<String>,
+            #[bytevec(skip_if = |r: &Record| r.cache.is_empty())]
+            cache: Vec<u8>
+        }
+    }
+
+    let old = RecordV1 {id: 7, title: "draft".to_string(), legacy: 99, score: 3};
+    let bytes = old.encode::<u16>().unwrap();
+    let migrator = Migrator::<Record, u16>::new(RecordV1::schema())
+        .rename("title", "name")
+        .with_default("score", &0u32).unwrap()
+        .with_default("tags", &vec!["migrated".to_string()]).unwrap();
+    assert_eq!(migrator.migrate(&bytes).unwrap(), Record {
+        id: 7,
+        name: "draft".to_string(),
+        score: 0,
+        tags: vec!["migrated".to_string()],
+        cache: vec![]
+    });
+
+    let incomplete = Migrator::<Record, u16>::new(RecordV1::schema())
+        .with_default("score", &0u32).unwrap();
+    match incomplete.migrate(&bytes) {
+        Err(ByteVecError::MissingFieldError("name")) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+    match migrator.migrate(&Record::default().encode::<u16>().unwrap()) {
+        Err(ByteVecError::UnknownVersionError(2)) => (),
+        other => panic!("unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn test_patch() {
+    use bytevec::{Patch, PackedVec};
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Document {
+        id: u32,
+        title: String,
+        lines: Vec<String>
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema, version = 3)]
+        impl Document {
+            id: u32,
+            title: String,
+            lines: Vec<String>
+        }
+    }
+
+    let old = Document {
+        id: 1,
+        title: "notes".to_string(),
+        lines: vec!["first".to_string(), "second".to_string()]
+    };
+    let mut new = old.clone();
+    new.title = "renamed notes".to_string();
+    let old_bytes = old.encode::<u16>().unwrap();
+    let new_bytes = new.encode::<u16>().unwrap();
+
+    let patch = Patch::diff::<Document, u16>(&old_bytes, &new_bytes).unwrap();
+    assert_eq!(patch.changed_entries(), 1);
+    let shipped = Patch::decode::<u32>(&patch.encode::<u32>().unwrap()).unwrap();
+    assert_eq!(shipped, patch);
+    let patched = shipped.apply::<Document, u16>(&old_bytes).unwrap();
+    assert_eq!(Document::decode::<u16>(&patched).unwrap(), new);
+
+    let old_points = PackedVec(vec![1u32, 2, 3, 4]).encode::<u8>().unwrap();
+    let new_points = PackedVec(vec![1u32, 5, 3]).encode::<u8>().unwrap();
+    let patch = Patch::diff::<PackedVec<u32>, u8>(&old_points, &new_points).unwrap();
+    assert_eq!(patch.changed_entries(), 1);
+    assert_eq!(patch.apply::<PackedVec<u32>, u8>(&old_points).unwrap(), new_points);
+    assert!(patch.apply::<PackedVec<u32>, u8>(&new_points[..3]).is_err());
+}
+
+#[test]
+fn test_interned_vec() {
+    use bytevec::{ByteSchema, InternedVec, Schema, VarSize};
+
+    let keys = ["host", "level", "message", "host", "level", "host"];
+    let column = InternedVec(keys.iter().map(|key| key.to_string()).collect::<Vec<_>>());
+    let bytes = column.encode::<u16>().unwrap();
+    assert_eq!(bytes.len(), column.get_size::<u16>().unwrap() as usize);
+    assert_eq!(&bytes[bytes.len() - 8..], &[6, 0, 0, 1, 2, 0, 1, 0]);
+    assert_eq!(InternedVec::<String>::decode::<u16>(&bytes).unwrap(), column);
+
+    let bytes = column.encode::<VarSize>().unwrap();
+    assert_eq!(InternedVec::<String>::decode::<VarSize>(&bytes).unwrap(), column);
+    let mut pair = (column.clone(), 7u8).encode::<VarSize>().unwrap();
+    let pair_len = pair.len();
+    pair.push(0);
+    let (decoded, len) = <(InternedVec<String>, u8)>::decode_with_len::<VarSize>(&pair).unwrap();
+    assert_eq!(decoded, (column, 7));
+    assert_eq!(len, pair_len);
+
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() = 3;
+    assert!(InternedVec::<String>::decode::<VarSize>(&corrupted).is_err());
+
+    let wide = InternedVec((0..300u16).chain(0..300).collect::<Vec<_>>());
+    let bytes = wide.encode::<u32>().unwrap();
+    assert_eq!(bytes.len(), 4 + 300 * (4 + 2) + 4 + 600 * 2);
+    assert_eq!(InternedVec::<u16>::decode::<u32>(&bytes).unwrap(), wide);
+
+    bytevec_decl! {
+        #[bytevec(schema)]
+        #[derive(PartialEq, Debug)]
+        struct Export {
+            id: u32,
+            #[bytevec(dictionary)]
+            levels: Vec<String>
+        }
+    }
+
+    let export = Export {id: 9, levels: keys.iter().map(|key| key.to_string()).collect()};
+    let bytes = export.encode::<u16>().unwrap();
+    let expected = (9u32, InternedVec(export.levels.clone())).encode::<u16>().unwrap();
+    assert_eq!(bytes, expected);
+    assert_eq!(Export::decode::<u16>(&bytes).unwrap(), export);
+    match Export::schema() {
+        Schema::Struct { fields, .. } => {
+            assert_eq!(fields[1].schema, Schema::InternedSeq(Box::new(Schema::String)));
+        }
+        schema => panic!("unexpected schema {:?}", schema),
+    }
+}
+
+#[test]
+fn test_bitsets() {
+    use std::collections::HashSet;
+    use bytevec::{BitVec, BitSet, VarSize};
+
+    let flags = BitVec((0..20).map(|index| index % 3 == 0).collect());
+    let bytes = flags.encode::<u16>().unwrap();
+    assert_eq!(bytes, vec![20, 0, 0b01001001, 0b10010010, 0b0100]);
+    assert_eq!(BitVec::decode::<u16>(&bytes).unwrap(), flags);
+    assert_eq!(BitVec::decode::<u16>(&[0, 0]).unwrap(), BitVec(vec![]));
+    assert!(BitVec::decode::<u16>(&bytes[..4]).is_err());
+
+    let ids = BitSet([7u32, 9, 10, 22].iter().cloned().collect::<HashSet<_>>());
+    let bytes = ids.encode::<VarSize>().unwrap();
+    assert_eq!(bytes, vec![7, 0, 0, 0, 16, 0b00001101, 0b10000000]);
+    assert_eq!(BitSet::decode::<VarSize>(&bytes).unwrap(), ids);
+    assert_eq!(BitSet::default().encode::<u8>().unwrap(), vec![0, 0, 0, 0, 0]);
+    assert_eq!(BitSet::decode::<u8>(&[0, 0, 0, 0, 0]).unwrap(), BitSet::default());
+
+    let wide = BitSet([0u32, u32::MAX].iter().cloned().collect::<HashSet<_>>());
+    assert!(wide.encode::<u16>().is_err());
+    assert!(BitSet::decode::<u8>(&[255, 255, 255, 255, 2, 0b11]).is_err());
+}
+
+#[test]
+fn test_zigzag_integers() {
+    use bytevec::{ZigZag, VarSize};
+
+    let cases: [(i64, &[u8]); 6] = [
+        (0, &[0]),
+        (-1, &[1]),
+        (1, &[2]),
+        (-64, &[127]),
+        (64, &[128, 1]),
+        (i64::MIN, &[255, 255, 255, 255, 255, 255, 255, 255, 255, 1]),
+    ];
+    for &(value, bytes) in cases.iter() {
+        assert_eq!(ZigZag(value).encode::<u8>().unwrap(), bytes);
+        assert_eq!(ZigZag::<i64>::decode::<u8>(bytes).unwrap(), ZigZag(value));
+    }
+    assert_eq!(ZigZag(i8::MIN).encode::<u8>().unwrap(), vec![255, 1]);
+    assert_eq!(ZigZag::<i8>::decode::<u8>(&[255, 1]).unwrap(), ZigZag(i8::MIN));
+    assert!(ZigZag::<i8>::decode::<u8>(&[128, 2]).is_err());
+
+    let deltas = vec![ZigZag(-3i32), ZigZag(5), ZigZag(-100000)];
+    let bytes = deltas.encode::<VarSize>().unwrap();
+    assert_eq!(Vec::<ZigZag<i32>>::decode::<VarSize>(&bytes).unwrap(), deltas);
+}
+
+#[test]
+fn test_aligned_layout() {
+    use bytevec::FixedByteSize;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Default, Clone, Copy)]
+        #[repr(C)]
+        #[bytevec(packed, aligned)]
+        struct Entry {
+            tag: u8,
+            len: u32,
+            flags: u16,
+            offset: u64
+        }
+
+        #[derive(PartialEq, Debug, Default)]
+        #[repr(C)]
+        #[bytevec(packed, aligned)]
+        struct Table {
+            count: u16,
+            entry: Entry
+        }
+    }
+
+    assert_eq!(Entry::ENCODED_SIZE, std::mem::size_of::<Entry>());
+    assert_eq!(<Entry as FixedByteSize>::ALIGN, 8);
+    let entry = Entry {tag: 1, len: 0x0302, flags: 0xffff, offset: 9};
+    let bytes = entry.encode::<u8>().unwrap();
+    assert_eq!(bytes, vec![1, 0, 0, 0, 2, 3, 0, 0, 255, 255, 0, 0, 0, 0, 0, 0,
+                           9, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Entry::decode::<u8>(&bytes).unwrap(), entry);
+
+    let table = Table {count: 1, entry};
+    let bytes = table.encode::<u8>().unwrap();
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(&bytes[8..], &entry.encode::<u8>().unwrap()[..]);
+    assert_eq!(Table::decode::<u8>(&bytes).unwrap(), table);
+}
+
+#[test]
+fn test_decode_lenient() {
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug)]
+        struct Record {
+            id: u16,
+            tags: Vec<String>
+        }
+    }
+
+    let record = Record {id: 3, tags: vec!["a".to_string(), "bc".to_string()]};
+    let mut buffer = record.encode::<u8>().unwrap();
+    buffer.extend_from_slice(&[0xde, 0xad]);
+    assert!(Record::decode::<u8>(&buffer).is_err());
+    let (decoded, rest) = Record::decode_lenient::<u8>(&buffer).unwrap();
+    assert_eq!(decoded, record);
+    assert_eq!(rest, &[0xde, 0xad]);
+
+    let (number, rest) = u16::decode_lenient::<u8>(rest).unwrap();
+    assert_eq!((number, rest), (0xadde, &[][..]));
+    assert!(u32::decode_lenient::<u8>(&[1, 2]).is_err());
+}
+
+#[test]
+fn test_extensible_structs() {
+    use bytevec::UnknownFields;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug, Default)]
+        #[bytevec(extensible)]
+        struct EventV1 {
+            id: u32,
+            #[bytevec(unknown)]
+            unknown: UnknownFields
+        }
+
+        #[derive(PartialEq, Eq, Debug, Default)]
+        #[bytevec(extensible)]
+        struct EventV2 {
+            id: u32,
+            #[bytevec(skip_if = |e: &EventV2| e.source.is_empty(), default = "none".to_string())]
+            source: String,
+            tags: Vec<String>
+        }
+
+        #[derive(PartialEq, Eq, Debug, Default)]
+        #[bytevec(extensible)]
+        struct EventV3 {
+            id: u32,
+            source: String,
+            tags: Vec<String>
+        }
+    }
+
+    let event = EventV3 {id: 7, source: "api".to_string(), tags: vec!["x".to_string()]};
+    let bytes = event.encode::<u8>().unwrap();
+    assert_eq!(&bytes[..4], &[3, 4, 3, 3]);
+    let old = EventV1::decode::<u8>(&bytes).unwrap();
+    assert_eq!(old.id, 7);
+    assert_eq!(old.unknown.len(), 2);
+    assert_eq!(old.encode::<u8>().unwrap(), bytes);
+
+    let old = EventV1 {id: 9, unknown: UnknownFields::default()};
+    let bytes = old.encode::<u8>().unwrap();
+    assert_eq!(EventV2::decode::<u8>(&bytes).unwrap(), EventV2 {
+        id: 9,
+        source: "none".to_string(),
+        tags: vec![]
+    });
+    assert_eq!(EventV3::decode::<u8>(&bytes).unwrap(), EventV3 {id: 9, ..Default::default()});
+
+    let events = vec![event, EventV3::default()];
+    let bytes = events.encode::<u8>().unwrap();
+    assert_eq!(Vec::<EventV1>::decode::<u8>(&bytes).unwrap().encode::<u8>().unwrap(), bytes);
+}
+
+#[test]
+fn test_bincode_layout() {
+    use std::collections::HashMap;
+    use bytevec::Bincode;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug, Default)]
+        struct Account {
+            id: u32,
+            name: String,
+            balances: Vec<(String, i64)>
+        }
+
+        #[derive(PartialEq, Eq, Debug)]
+        enum Event {
+            Created(Account),
+            Renamed {id: u32, name: String},
+            Closed
+        }
+    }
+
+    let account = Account {id: 1, name: "ab".to_string(), balances: vec![("eu".to_string(), -2)]};
+    let bytes = account.encode::<Bincode>().unwrap();
+    assert_eq!(bytes, vec![1, 0, 0, 0,
+                           2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b',
+                           1, 0, 0, 0, 0, 0, 0, 0,
+                           2, 0, 0, 0, 0, 0, 0, 0, b'e', b'u',
+                           254, 255, 255, 255, 255, 255, 255, 255]);
+    assert_eq!(account.get_size::<Bincode>(), Some(Bincode(bytes.len() as u64)));
+    assert_eq!(Account::decode::<Bincode>(&bytes).unwrap(), account);
+    assert!(Account::decode::<Bincode>(&bytes[..bytes.len() - 1]).is_err());
+
+    let events = vec![Event::Renamed {id: 1, name: "c".to_string()}, Event::Closed,
+                      Event::Created(account)];
+    let bytes = events.encode::<Bincode>().unwrap();
+    assert_eq!(&bytes[..25], &[3, 0, 0, 0, 0, 0, 0, 0,
+                               1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'c']);
+    assert_eq!(&bytes[25..33], &[2, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(Vec::<Event>::decode::<Bincode>(&bytes).unwrap(), events);
+
+    let map = (0..10u8).map(|i| (i, i.to_string())).collect::<HashMap<_, _>>();
+    let bytes = map.encode::<Bincode>().unwrap();
+    assert_eq!(bytes.len(), 8 + 10 * (1 + 8 + 1));
+    assert_eq!(HashMap::<u8, String>::decode::<Bincode>(&bytes).unwrap(), map);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn test_msgpack() {
+    use bytevec::msgpack;
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Reading {
+        sensor: String,
+        value: i32,
+        samples: Vec<u16>,
+        unit: char
+    }
+
+    #[derive(PartialEq, Debug)]
+    enum Status {
+        Idle,
+        Busy(u8),
+        Done {readings: Vec<Reading>}
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl Reading {
+            sensor: String,
+            #[bytevec(big_endian)]
+            value: i32,
+            samples: Vec<u16>,
+            unit: char
+        }
+
+        #[bytevec(schema)]
+        impl enum Status {
+            Idle,
+            Busy(u8),
+            Done {readings: Vec<Reading>}
+        }
+    }
+
+    let reading = Reading {sensor: "t1".to_string(), value: -300, samples: vec![1, 300],
+                           unit: 'C'};
+    let bytes = msgpack::encode(&reading).unwrap();
+    assert_eq!(bytes, vec![0x84,
+                           0xa6, b's', b'e', b'n', b's', b'o', b'r', 0xa2, b't', b'1',
+                           0xa5, b'v', b'a', b'l', b'u', b'e', 0xd1, 0xfe, 0xd4,
+                           0xa7, b's', b'a', b'm', b'p', b'l', b'e', b's',
+                           0x92, 0x01, 0xcd, 0x01, 0x2c,
+                           0xa4, b'u', b'n', b'i', b't', 0xa1, b'C']);
+    assert_eq!(msgpack::decode::<Reading>(&bytes).unwrap(), reading);
+
+    assert_eq!(msgpack::encode(&Status::Idle).unwrap(), vec![0xa4, b'I', b'd', b'l', b'e']);
+    assert_eq!(msgpack::encode(&Status::Busy(200)).unwrap(),
+               vec![0x81, 0xa4, b'B', b'u', b's', b'y', 0x91, 0xcc, 200]);
+    let done = Status::Done {readings: vec![reading]};
+    let bytes = msgpack::encode(&done).unwrap();
+    assert_eq!(msgpack::decode::<Status>(&bytes).unwrap(), done);
+
+    // Documents written by other implementations may use wider integer forms
+    assert_eq!(msgpack::decode::<Status>(&[0x81, 0xa4, b'B', b'u', b's', b'y',
+                                           0x91, 0xd3, 0, 0, 0, 0, 0, 0, 0, 7]).unwrap(),
+               Status::Busy(7));
+    match msgpack::decode::<Status>(&[0x81, 0xa4, b'B', b'u', b's', b'y', 0x91, 0xcd, 1, 0]) {
+        Err(ByteVecError::InvalidValueError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match msgpack::decode::<Reading>(&[0x81, 0xa4, b'u', b'n', b'i', b't', 0xa1, b'C']) {
+        Err(ByteVecError::MissingFieldError("sensor")) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(msgpack::decode::<Vec<u8>>(&[0x91, 0xc4, 0x01, 0x00]).is_err());
+    assert!(msgpack::decode::<Vec<u8>>(&[0x92, 0x01]).is_err());
+    assert!(msgpack::decode::<Vec<Vec<u8>>>(&[0x91; 200]).is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn test_cbor() {
+    use bytevec::{cbor, BitVec};
+    use bytevec::errors::ByteVecError;
+    use std::collections::HashMap;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Measurement {
+        device: String,
+        offset: i64,
+        flags: BitVec,
+        ratio: f32
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl Measurement {
+            device: String,
+            offset: i64,
+            flags: BitVec,
+            ratio: f32
+        }
+    }
+
+    let measurement = Measurement {device: "d".to_string(), offset: -500, flags: BitVec(vec![true]),
+                                   ratio: 0.5};
+    let bytes = cbor::encode(&measurement).unwrap();
+    assert_eq!(bytes, vec![0xa4,
+                           0x66, b'd', b'e', b'v', b'i', b'c', b'e', 0x61, b'd',
+                           0x66, b'o', b'f', b'f', b's', b'e', b't', 0x39, 0x01, 0xf3,
+                           0x65, b'f', b'l', b'a', b'g', b's', 0x81, 0xf5,
+                           0x65, b'r', b'a', b't', b'i', b'o', 0xfa, 0x3f, 0x00, 0x00, 0x00]);
+    assert_eq!(cbor::decode::<Measurement>(&bytes).unwrap(), measurement);
+
+    let map = (0..30u32).map(|i| (i * 1000, vec![i as i8 - 10])).collect::<HashMap<_, _>>();
+    let bytes = cbor::encode(&map).unwrap();
+    assert_eq!(&bytes[..2], &[0xb8, 30]);
+    assert_eq!(cbor::decode::<HashMap<u32, Vec<i8>>>(&bytes).unwrap(), map);
+
+    // Half precision floats are widened, while byte strings and tags are rejected
+    assert_eq!(cbor::decode::<Vec<f32>>(&[0x82, 0xf9, 0x3e, 0x00, 0xf9, 0xc4, 0x00]).unwrap(),
+               vec![1.5, -4.0]);
+    match cbor::decode::<Vec<u8>>(&[0x41, 0x00]) {
+        Err(ByteVecError::InvalidValueError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(cbor::decode::<u8>(&[0xc1, 0x00]).is_err());
+    assert!(cbor::decode::<Vec<u8>>(&[0x9f, 0x01, 0xff]).is_err());
+    assert!(cbor::decode::<u8>(&[0x38, 0x00]).is_err());
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn test_protobuf() {
+    use bytevec::{protobuf, ZigZag};
+    use bytevec::errors::ByteVecError;
+    use std::collections::HashMap;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Item {
+        name: String
+    }
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Order {
+        id: u64,
+        qty: i32,
+        delta: ZigZag<i64>,
+        price: f64,
+        tags: Vec<String>,
+        lines: Vec<u32>,
+        item: Item,
+        note: String
+    }
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Counts {
+        counts: HashMap<String, u32>,
+        pair: (u8, u8)
+    }
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Tally {
+        counts: HashMap<String, u32>
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl Item {
+            name: String
+        }
+
+        #[bytevec(schema)]
+        impl Order {
+            id: u64,
+            qty: i32,
+            delta: ZigZag<i64>,
+            price: f64,
+            tags: Vec<String>,
+            lines: Vec<u32>,
+            item: Item,
+            #[bytevec(skip_if = |o: &Order| o.note.is_empty())]
+            note: String
+        }
+
+        #[bytevec(schema)]
+        impl Counts {
+            counts: HashMap<String, u32>,
+            pair: (u8, u8)
+        }
+
+        #[bytevec(schema)]
+        impl Tally {
+            counts: HashMap<String, u32>
+        }
+    }
+
+    let order = Order {
+        id: 1,
+        qty: -1,
+        delta: ZigZag(-2),
+        price: 1.0,
+        tags: vec!["a".to_string(), "b".to_string()],
+        lines: vec![1, 300],
+        item: Item {name: "x".to_string()},
+        note: String::new()
+    };
+    // message Order { uint64 id = 1; int32 qty = 2; sint64 delta = 3; double price = 4;
+    //                 repeated string tags = 5; repeated uint32 lines = 6; Item item = 7;
+    //                 string note = 8; }
+    assert_eq!(protobuf::encode(&order).unwrap(),
+               vec![0x08, 0x01,
+                    0x10, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01,
+                    0x18, 0x03,
+                    0x21, 0, 0, 0, 0, 0, 0, 0xf0, 0x3f,
+                    0x2a, 0x01, b'a', 0x2a, 0x01, b'b',
+                    0x32, 0x03, 0x01, 0xac, 0x02,
+                    0x3a, 0x03, 0x0a, 0x01, b'x']);
+
+    // Maps are repeated entries, while tuples have no protobuf counterpart
+    let mut counts = Counts::default();
+    counts.counts.insert("a".to_string(), 5);
+    match protobuf::encode(&counts) {
+        Err(ByteVecError::InvalidValueError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(protobuf::encode(&Tally {counts: counts.counts}).unwrap(),
+               vec![0x0a, 0x05, 0x0a, 0x01, b'a', 0x10, 0x05]);
+}
+
+#[test]
+fn test_debug_json() {
+    use bytevec::debug;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Entry {
+        id: u16,
+        names: Vec<String>
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl Entry {
+            #[bytevec(big_endian)]
+            id: u16,
+            names: Vec<String>
+        }
+    }
+
+    let entry = Entry {id: 258, names: vec!["a\"b".to_string()]};
+    let bytes = entry.encode::<u8>().unwrap();
+    assert_eq!(debug::to_json::<Entry, u8>(&bytes),
+               concat!(r#"{"kind":"struct","type":"Entry","offset":0,"size":9,"children":["#,
+                       r#"{"name":"id","kind":"primitive","type":"u16","offset":2,"size":2,"#,
+                       r#""value":258},"#,
+                       r#"{"name":"names","kind":"seq","offset":4,"size":5,"count":1,"#,
+                       r#""children":[{"name":"0","kind":"string","offset":6,"size":3,"#,
+                       r#""value":"a\"b"}]}]}"#));
+
+    // The fields read before the truncated one are still rendered
+    let json = debug::to_json::<Entry, u8>(&bytes[..8]);
+    assert!(json.starts_with(r#"{"kind":"struct","type":"Entry","offset":0,"size":8,"#));
+    assert!(json.contains(r#""value":258}],"error":"The size expected for the structure at the offset 0 is "#));
+    assert!(json.ends_with(r#"more than 9, but the size of the given buffer is 8"}"#));
+    assert!(debug::to_json::<Entry, u8>(&bytes[..1]).contains(r#""error":"#));
+}
+
+#[test]
+fn test_column_vec() {
+    use bytevec::ColumnVec;
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Reading {
+        sensor: String,
+        value: u32,
+        note: String,
+        cache: u8
+    }
+
+    bytevec_impls! {
+        #[bytevec(columnar)]
+        impl Reading {
+            sensor: String,
+            #[bytevec(big_endian)]
+            value: u32,
+            #[bytevec(skip_if = |r: &Reading| r.note.is_empty())]
+            note: String,
+            #[bytevec(default = 7)]
+            cache: u8
+        }
+    }
+
+    let readings = ColumnVec((0..100u32).map(|i| Reading {
+        sensor: "temp".to_string(),
+        value: i,
+        note: if i == 3 { "reset".to_string() } else { String::new() },
+        cache: 7
+    }).collect::<Vec<_>>());
+    let bytes = readings.encode::<u16>().unwrap();
+    assert_eq!(readings.get_size::<u16>(), Some(bytes.len() as u16));
+    assert_eq!(&bytes[..8], &[100, 0, 88, 2, 88, 2, 205, 0]);
+    // The `sensor` column holds every size, then every string
+    assert_eq!(&bytes[8..10], &[4, 0]);
+    assert_eq!(&bytes[208..212], b"temp");
+    assert_eq!(ColumnVec::<Reading>::decode::<u16>(&bytes).unwrap(), readings);
+    assert_eq!(ColumnVec::<Reading>::decode_column::<String, u16>(&bytes, 2).unwrap()[3],
+               "reset");
+    assert!(ColumnVec::<Reading>::decode_column::<u8, u16>(&bytes, 3).is_err());
+
+    let nested = vec![readings.clone(), ColumnVec(vec![])];
+    let bytes = nested.encode::<u32>().unwrap();
+    assert_eq!(Vec::<ColumnVec<Reading>>::decode::<u32>(&bytes).unwrap(), nested);
+    match ColumnVec::<Reading>::decode::<u16>(&[1, 0, 2, 0, 0, 0, 0, 0]) {
+        Err(ByteVecError::BadSizeDecodeError {..}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_run_vec() {
+    use bytevec::{DecodeLimits, RunVec, VarSize, RUN_VEC_MAX_LEN};
+    use bytevec::errors::ByteVecError;
+
+    let mut samples = vec![0i16; 300];
+    samples.extend(vec![-4i16; 2]);
+    samples.push(0);
+    let samples = RunVec(samples);
+    // A run of 300 elements does not fit in a `u8` size
+    assert_eq!(samples.get_size::<u8>(), None);
+    assert!(samples.encode::<u8>().is_err());
+    let bytes = samples.encode::<u16>().unwrap();
+    assert_eq!(samples.get_size::<u16>(), Some(bytes.len() as u16));
+    assert_eq!(&bytes[bytes.len() - 6..], &[44, 1, 2, 0, 1, 0]);
+    assert_eq!(RunVec::<i16>::decode::<u16>(&bytes).unwrap(), samples);
+
+    let bytes = samples.encode::<VarSize>().unwrap();
+    assert_eq!(RunVec::<i16>::decode::<VarSize>(&bytes).unwrap(), samples);
+    let nested = (RunVec(vec!["on".to_string(); 3]), RunVec::<u8>(vec![]));
+    let bytes = nested.encode::<u8>().unwrap();
+    assert_eq!(<(RunVec<String>, RunVec<u8>)>::decode::<u8>(&bytes).unwrap(), nested);
+    match RunVec::<u8>::decode::<u8>(&[1, 1, 7, 0]) {
+        Err(ByteVecError::InvalidValueError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut bomb = vec![1, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0];
+    bomb.extend_from_slice(&7u64.to_le_bytes());
+    bomb.extend_from_slice(&(1u64 << 40).to_le_bytes());
+    match RunVec::<u64>::decode::<u64>(&bomb) {
+        Err(ByteVecError::LimitExceededError { max, .. }) => assert_eq!(max, RUN_VEC_MAX_LEN),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let limits = DecodeLimits {max_elements: 1000, ..DecodeLimits::default()};
+    assert!(RunVec::<u64>::decode_limited::<u64>(&bomb, &limits).is_err());
+    let long = RunVec(vec![3u8; RUN_VEC_MAX_LEN + 1]);
+    let bytes = long.encode::<u32>().unwrap();
+    assert!(RunVec::<u8>::decode::<u32>(&bytes).is_err());
+    let limits = DecodeLimits::default();
+    assert_eq!(RunVec::<u8>::decode_limited::<u32>(&bytes, &limits).unwrap(), long);
+}
+
+#[test]
+fn test_compact_structs() {
+    use bytevec::{Patch, Schema, ByteSchema, debug};
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Frame {
+        seq: u32,
+        payload: Vec<u8>
+    }
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Packet {
+        frame: Frame,
+        tag: String
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema, compact, version = 2)]
+        impl Frame {
+            seq: u32,
+            payload: Vec<u8>
+        }
+
+        #[bytevec(schema, compact)]
+        impl Packet {
+            frame: Frame,
+            tag: String
+        }
+    }
+
+    let frame = Frame {seq: 9, payload: vec![1, 2, 3]};
+    let bytes = frame.encode::<u8>().unwrap();
+    assert_eq!(bytes, vec![2, 4, 9, 0, 0, 0, 3, 1, 1, 1, 1, 2, 3]);
+    assert_eq!(frame.get_size::<u8>(), Some(bytes.len() as u8));
+    assert_eq!(Frame::decode::<u8>(&bytes).unwrap(), frame);
+    assert_eq!(Frame::decode_with_len::<u8>(&bytes).unwrap(), (frame.clone(), bytes.len()));
+
+    let packets = vec![Packet {frame: frame.clone(), tag: "a".to_string()},
+                       Packet {frame: Frame::default(), tag: String::new()}];
+    let bytes = packets.encode::<u16>().unwrap();
+    assert_eq!(Vec::<Packet>::decode::<u16>(&bytes).unwrap(), packets);
+    assert!(Vec::<Packet>::decode::<u16>(&bytes[..bytes.len() - 1]).is_err());
+
+    match Packet::schema() {
+        Schema::Struct {compact, ..} => assert!(compact),
+        schema => panic!("unexpected schema: {:?}", schema),
+    }
+    let bytes = packets[0].encode::<u8>().unwrap();
+    assert!(debug::to_json::<Packet, u8>(&bytes).contains(r#""name":"tag","kind":"string""#));
+    let new_bytes = Packet {tag: "b".to_string(), ..packets[0].clone()}.encode::<u8>().unwrap();
+    let patch = Patch::diff::<Packet, u8>(&bytes, &new_bytes).unwrap();
+    assert_eq!(patch.changed_entries(), 1);
+    assert_eq!(patch.apply::<Packet, u8>(&bytes).unwrap(), new_bytes);
+}
+
+#[test]
+fn test_auto_size_width() {
+    use bytevec::errors::ByteVecError;
+
+    let small = vec!["a".to_string(), "bc".to_string()];
+    let bytes = small.encode_auto().unwrap();
+    assert_eq!(bytes, vec![1, 2, 1, 2, b'a', b'b', b'c']);
+    assert_eq!(Vec::<String>::decode_auto(&bytes).unwrap(), small);
+
+    let large = vec![0u8; 300];
+    let bytes = large.encode_auto().unwrap();
+    assert_eq!(bytes[0], 2);
+    assert_eq!(&bytes[1..], &large.encode::<u16>().unwrap()[..]);
+    assert_eq!(Vec::<u8>::decode_auto(&bytes).unwrap(), large);
+
+    let huge = vec![0u8; 70_000];
+    assert_eq!(huge.encode_auto().unwrap()[0], 4);
+    match u8::decode_auto(&[3, 0]) {
+        Err(ByteVecError::UnknownSizeWidthError(3)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(u8::decode_auto(&[]).is_err());
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn test_f16() {
+    use bytevec::{LossyF16, Schema, ByteSchema};
+    use half::f16;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Embedding {
+        id: u32,
+        scale: f16,
+        weights: Vec<f16>,
+        bias: f32
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl Embedding {
+            id: u32,
+            scale: f16,
+            weights: Vec<f16>,
+            #[bytevec(with = LossyF16)]
+            bias: f32
+        }
+    }
+
+    let value = f16::from_f32(1.5);
+    assert_eq!(value.encode::<u8>().unwrap(), vec![0x00, 0x3e]);
+    assert_eq!(f16::decode::<u8>(&[0x00, 0x3e]).unwrap(), value);
+    assert_eq!(f16::schema(), Schema::Primitive {name: "f16", size: 2});
+
+    let embedding = Embedding {
+        id: 7,
+        scale: f16::from_f32(0.5),
+        weights: vec![f16::from_f32(-1.0), f16::from_f32(0.25)],
+        bias: 0.3
+    };
+    let bytes = embedding.encode::<u8>().unwrap();
+    assert_eq!(&bytes[..4], &[4, 2, 7, 2]);
+    let decoded = Embedding::decode::<u8>(&bytes).unwrap();
+    assert_eq!(decoded.weights, embedding.weights);
+    assert_eq!(decoded.bias, f16::from_f32(0.3).to_f32());
+    assert!(decoded.bias != 0.3);
+    assert!(bytevec::debug::to_json::<Embedding, u8>(&bytes).contains(r#""value":0.5"#));
+}
+
+#[test]
+fn test_error_offsets() {
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Record {
+        id: u16,
+        tags: Vec<String>
+    }
+
+    bytevec_impls! {
+        impl Record {
+            id: u16,
+            tags: Vec<String>
+        }
+    }
+
+    let records = vec![Record {id: 1, tags: vec!["a".to_string()]},
+                       Record {id: 2, tags: vec!["b".to_string(), "xyz".to_string()]}];
+    let mut bytes = records.encode::<u8>().unwrap();
+
+    // The offset of an invalid string is that of its first invalid byte in the whole buffer
+    let position = bytes.iter().position(|&byte| byte == b'y').unwrap();
+    bytes[position] = 0xff;
+    let error = Vec::<Record>::decode::<u8>(&bytes).unwrap_err();
+    match *error.inner() {
+        ByteVecError::StringDecodeUtf8Error { .. } => assert_eq!(error.offset(), Some(position)),
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+
+    // The offset of a size mismatch is the start of the structure with the wrong size
+    bytes[position] = b'y';
+    let second = bytes.len() - records[1].get_size::<u8>().unwrap() as usize;
+    bytes[second] += 1;
+    let error = Vec::<Record>::decode::<u8>(&bytes).unwrap_err();
+    match *error.inner() {
+        ByteVecError::BadSizeDecodeError { .. } => assert_eq!(error.offset(), Some(second)),
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(ByteVecError::OverflowError.offset(), None);
+}
+
+#[test]
+fn test_error_paths() {
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Profile {
+        age: u8,
+        name: String
+    }
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Company {
+        id: u32,
+        employees: Vec<Profile>
+    }
+
+    bytevec_impls! {
+        impl Profile {
+            age: u8,
+            name: String
+        }
+
+        impl Company {
+            id: u32,
+            employees: Vec<Profile>
+        }
+    }
+
+    let employee = |name: &str| Profile {age: 30, name: name.to_string()};
+    let company = Company {
+        id: 1,
+        employees: vec![employee("ann"), employee("bob"), employee("eve"), employee("joe")]
+    };
+    let mut bytes = company.encode::<u8>().unwrap();
+    let position = bytes.iter().position(|&byte| byte == b'j').unwrap();
+    bytes[position] = 0xff;
+
+    let error = Company::decode::<u8>(&bytes).unwrap_err();
+    assert_eq!(error.path(), Some("employees[3].name"));
+    assert_eq!(error.offset(), Some(position));
+    match *error.inner() {
+        ByteVecError::StringDecodeUtf8Error { .. } => {}
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().starts_with("PathError: Failed to decode employees[3].name: "));
+
+    // The errors of the outermost structure have no path
+    let error = Company::decode::<u8>(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(error.path(), None);
+}
+
+#[test]
+fn test_malformed_input() {
+    use std::collections::HashMap;
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default, Clone)]
+    struct Entry {
+        key: char,
+        values: HashMap<String, (u8, Vec<u16>)>
+    }
+
+    bytevec_impls! {
+        impl Entry {
+            key: char,
+            values: HashMap<String, (u8, Vec<u16>)>
+        }
+    }
+
+    let mut values = HashMap::new();
+    values.insert("a".to_string(), (1, vec![2, 3]));
+    let entries = vec![Entry {key: 'k', values}, Entry::default()];
+
+    // Every truncated or corrupted buffer yields a value or an error, without panicking
+    let bytes = entries.encode::<u64>().unwrap();
+    for index in 0..bytes.len() {
+        assert!(Vec::<Entry>::decode::<u64>(&bytes[..index]).is_err());
+        for &byte in &[0x00, 0x7f, 0x80, 0xff] {
+            let mut corrupted = bytes.clone();
+            for byte_ref in corrupted.iter_mut().skip(index).take(8) {
+                *byte_ref = byte;
+            }
+            let _ = Vec::<Entry>::decode::<u64>(&corrupted);
+            let _ = Vec::<Entry>::decode_with_len::<u64>(&corrupted);
+        }
+    }
+
+    match char::decode::<u8>(&[0x00, 0xd8, 0x00, 0x00]) {
+        Err(ByteVecError::InvalidValueError(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_limits() {
+    use bytevec::{DecodeLimits, RunVec};
+    use bytevec::errors::ByteVecError;
+
+    let runs = RunVec(vec![7u8; 1000]);
+    let bytes = runs.encode::<u16>().unwrap();
+    let limits = DecodeLimits {max_elements: 100, ..DecodeLimits::default()};
+    match RunVec::<u8>::decode_limited::<u16>(&bytes, &limits) {
+        // The run values count along with the expanded runs
+        Err(ByteVecError::LimitExceededError {limit: "max_elements", max: 100, actual: 1001}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(RunVec::<u8>::decode::<u16>(&bytes).unwrap(), runs);
+
+    let nested = vec![vec![vec![1u8]]];
+    let bytes = nested.encode::<u8>().unwrap();
+    let limits = DecodeLimits {max_depth: 2, ..DecodeLimits::default()};
+    let error = Vec::<Vec<Vec<u8>>>::decode_limited::<u8>(&bytes, &limits).unwrap_err();
+    assert_eq!(error.path(), Some("[0][0]"));
+    match *error.inner() {
+        ByteVecError::LimitExceededError {limit: "max_depth", max: 2, actual: 3} => {}
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    let limits = DecodeLimits {max_depth: 3, ..DecodeLimits::default()};
+    assert_eq!(Vec::<Vec<Vec<u8>>>::decode_limited::<u8>(&bytes, &limits).unwrap(), nested);
+
+    // The elements of the nested collections share the budget
+    let lists = vec![vec![1u16; 6]; 3];
+    let list_bytes = lists.encode::<u8>().unwrap();
+    let limits = DecodeLimits {max_elements: 21, ..DecodeLimits::default()};
+    assert_eq!(Vec::<Vec<u16>>::decode_limited::<u8>(&list_bytes, &limits).unwrap(), lists);
+    let lists = vec![vec![1u16; 6]; 4];
+    let list_bytes = lists.encode::<u8>().unwrap();
+    match Vec::<Vec<u16>>::decode_limited::<u8>(&list_bytes, &limits).unwrap_err().inner() {
+        &ByteVecError::LimitExceededError {limit: "max_elements", max: 21, actual: 22} => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let limits = DecodeLimits {max_bytes: bytes.len() - 1, ..DecodeLimits::default()};
+    match Vec::<Vec<Vec<u8>>>::decode_limited::<u8>(&bytes, &limits) {
+        Err(ByteVecError::LimitExceededError {limit: "max_bytes", ..}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_lossy_strings() {
+    use bytevec::{debug, Bincode, LossyUtf8, MaybeUtf8};
+    use bytevec::errors::ByteVecError;
+
+    #[derive(PartialEq, Debug, Default)]
+    struct LogLine {
+        level: u8,
+        message: String,
+        source: MaybeUtf8
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema)]
+        impl LogLine {
+            level: u8,
+            #[bytevec(with = LossyUtf8)]
+            message: String,
+            source: MaybeUtf8
+        }
+    }
+
+    let line = LogLine {
+        level: 2,
+        message: "disk full".to_string(),
+        source: MaybeUtf8::Utf8("sda".to_string())
+    };
+    let bytes = line.encode::<u8>().unwrap();
+    assert_eq!(&bytes[..4], &[1, 9, 3, 2]);
+    assert_eq!(LogLine::decode::<u8>(&bytes).unwrap(), line);
+
+    let mut bytes = vec![1, 4, 2, 1];
+    bytes.extend_from_slice(&[b'f', 0xc3, b'!', 0xe2, 0x82, 0xff]);
+    let decoded = LogLine::decode::<u8>(&bytes).unwrap();
+    assert_eq!(decoded.message, "f\u{fffd}!\u{fffd}");
+    assert_eq!(decoded.source, MaybeUtf8::Raw(vec![0x82, 0xff]));
+    assert_eq!(decoded.source.to_string_lossy(), "\u{fffd}\u{fffd}");
+    bytevec::validate::<LogLine, u8>(&bytes).unwrap();
+    bytevec::validate::<MaybeUtf8, u8>(&[255, 98, 255]).unwrap();
+    assert!(bytevec::validate::<String, u8>(&[255, 98, 255]).is_err());
+    let json = debug::to_json::<LogLine, u8>(&bytes);
+    assert!(json.contains(r#""name":"message","kind":"lossy_string""#));
+    assert!(json.contains("\"value\":\"f\u{fffd}!\u{fffd}\""));
+    assert!(!json.contains("error"));
+    match String::decode::<u8>(&bytes[4..8]) {
+        Err(ByteVecError::StringDecodeUtf8Error {..}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let sources = vec![MaybeUtf8::Utf8("sda".to_string()), MaybeUtf8::Raw(vec![0x80])];
+    let bytes = sources.encode::<Bincode>().unwrap();
+    assert_eq!(Vec::<MaybeUtf8>::decode::<Bincode>(&bytes).unwrap(), sources);
+}
+
+#[test]
+fn test_strict_collections() {
+    use std::collections::{HashMap, HashSet};
+    use bytevec::{Bincode, Deterministic, Strict};
+    use bytevec::errors::ByteVecError;
+
+    let bytes = vec![1u16, 2, 1].encode::<u8>().unwrap();
+    assert_eq!(HashSet::<u16>::decode::<u8>(&bytes).unwrap().len(), 2);
+    match HashSet::<u16>::decode::<Strict<u8>>(&bytes) {
+        Err(ByteVecError::LengthMismatchError {expected: 3, actual: 2}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let bytes = vec![(1u8, 'a'), (1, 'b')].encode::<Bincode>().unwrap();
+    match HashMap::<u8, char>::decode::<Strict<Bincode>>(&bytes) {
+        Err(ByteVecError::LengthMismatchError {expected: 2, actual: 1}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let set = (0..10u32).collect::<HashSet<_>>();
+    let bytes = set.encode::<Strict<Deterministic<u32>>>().unwrap();
+    assert_eq!(bytes, set.encode::<Deterministic<u32>>().unwrap());
+    assert_eq!(HashSet::<u32>::decode::<Strict<Deterministic<u32>>>(&bytes).unwrap(), set);
+}
+
+#[test]
+fn test_trailing_bytes() {
+    use bytevec::errors::ByteVecError;
+
+    let mut bytes = vec![1u16, 2, 3].encode::<u8>().unwrap();
+    assert_eq!(Vec::<u16>::decode_strict::<u8>(&bytes).unwrap(), vec![1, 2, 3]);
+    let len = bytes.len();
+    bytes.extend_from_slice(&[1, 2, 3, 4, 5]);
+    match Vec::<u16>::decode::<u8>(&bytes) {
+        Err(ByteVecError::BadSizeDecodeError {..}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let error = Vec::<u16>::decode_strict::<u8>(&bytes).unwrap_err();
+    assert_eq!(error.offset(), Some(len));
+    match error {
+        ByteVecError::TrailingBytesError {consumed, remaining: 5} if consumed == len => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // A corrupted buffer is still reported as such
+    bytes[1] = 0xff;
+    match Vec::<u16>::decode_strict::<u8>(&bytes) {
+        Err(ByteVecError::BadSizeDecodeError {..}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_accessors() {
+    use std::error::Error;
+    use bytevec::errors::BVExpectedSize;
+
+    let bytes = vec![vec![1u16, 2], vec![3]].encode::<u8>().unwrap();
+    let error = Vec::<Vec<u16>>::decode::<u8>(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(error.expected(), Some(BVExpectedSize::EqualTo(bytes.len())));
+    assert_eq!(error.actual(), Some(bytes.len() - 1));
+
+    // The accessors and the source look through the path of the error
+    let mut bytes = vec!["ab".to_string(), "cd".to_string()].encode::<u8>().unwrap();
+    let len = bytes.len();
+    bytes[len - 1] = 0xff;
+    let error = Vec::<String>::decode::<u8>(&bytes).unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+    assert_eq!(error.expected(), None);
+    let source = error.source().unwrap();
+    assert!(source.to_string().starts_with("StringDecodeUtf8Error"));
+    assert!(source.source().is_some());
+
+    let bytes = (1u8, 2u8).encode::<u8>().unwrap();
+    let mut long_bytes = bytes.clone();
+    long_bytes.push(0);
+    let error = <(u8, u8)>::decode_strict::<u8>(&long_bytes).unwrap_err();
+    assert_eq!(error.expected(), Some(BVExpectedSize::EqualTo(bytes.len())));
+    assert_eq!(error.actual(), Some(long_bytes.len()));
+}
+
+#[test]
+fn test_size_overflow() {
+    use bytevec::errors::ByteVecError;
+
+    // 2 elements of 2^63 bytes
+    let mut bytes = 2u64.encode::<u64>().unwrap();
+    bytes.extend((1u64 << 63).encode::<u64>().unwrap());
+    bytes.extend((1u64 << 63).encode::<u64>().unwrap());
+    match Vec::<Vec<u8>>::decode::<u64>(&bytes) {
+        Err(ByteVecError::SizeOverflowError) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match Vec::<Vec<u8>>::decode_with_len::<u64>(&bytes) {
+        Err(ByteVecError::SizeOverflowError) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_budget() {
+    use bytevec::{DecodeLimits, RunVec};
+    use bytevec::errors::ByteVecError;
+
+    // A few bytes expand to many elements that take no bytes
+    let strings = RunVec(vec![String::new(); 60000]);
+    let bytes = strings.encode::<u16>().unwrap();
+    assert!(bytes.len() < 10);
+    let limits = DecodeLimits {max_steps: 1000, ..DecodeLimits::default()};
+    match RunVec::<String>::decode_limited::<u16>(&bytes, &limits) {
+        Err(ByteVecError::BudgetExhaustedError {budget: 1000}) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    // The outer list, the 3 inner lists and their 6 elements take 13 steps
+    let lists = vec![vec![1u8, 2], vec![3, 4], vec![5, 6]];
+    let bytes = lists.encode::<u8>().unwrap();
+    let limits = DecodeLimits {max_steps: 13, ..DecodeLimits::default()};
+    assert_eq!(Vec::<Vec<u8>>::decode_limited::<u8>(&bytes, &limits).unwrap(), lists);
+    let limits = DecodeLimits {max_steps: 12, ..DecodeLimits::default()};
+    match Vec::<Vec<u8>>::decode_limited::<u8>(&bytes, &limits).unwrap_err().inner() {
+        &ByteVecError::BudgetExhaustedError {budget: 12} => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_roundtrip_property() {
+    use std::collections::{HashMap, HashSet};
+    use bytevec::{testing, BitSet, BitVec, Deterministic, InternedVec, MaybeUtf8, OptionVec,
+                  RunVec, VarSize, ZigZag};
+
+    testing::roundtrip_property::<(u8, char, String), u8>();
+    testing::roundtrip_property::<Vec<(Vec<u16>, String, i8)>, u32>();
+    testing::roundtrip_property::<HashMap<String, HashSet<u64>>, Deterministic<u16>>();
+    testing::roundtrip_property::<(RunVec<u8>, BitVec, BitSet), u32>();
+    testing::roundtrip_property::<(InternedVec<String>, OptionVec<u16>), u32>();
+    testing::roundtrip_property::<(Vec<ZigZag<i64>>, VarSize, MaybeUtf8), VarSize>();
+    testing::check_roundtrip::<String, u8>(&"a".repeat(300));
+
+    for case in 0..64u8 {
+        let bytes = (0..case).map(|byte| byte.wrapping_mul(case)).collect::<Vec<_>>();
+        testing::decode_any::<Vec<(String, HashSet<u32>)>>(&bytes);
+        testing::decode_any::<(RunVec<u16>, BitSet, MaybeUtf8)>(&bytes);
+    }
+}
+
+#[test]
+fn test_redacted_fields() {
+    bytevec_decl! {
+        #[derive(PartialEq, Eq, Debug, Default, Clone)]
+        pub struct Account {
+            pub id: u32,
+            #[bytevec(redact)]
+            pub token: String,
+            #[bytevec(redact, default = 3)]
+            pub retries: u8,
+            pub name: String
+        }
+    }
+
+    let account = Account {
+        id: 7,
+        token: "secret-token".to_string(),
+        retries: 0,
+        name: "ann".to_string()
+    };
+    let bytes = account.encode::<u16>().unwrap();
+    assert!(!bytes.windows(6).any(|window| window == b"secret"));
+    assert_eq!(Account::decode::<u16>(&bytes).unwrap(), Account {
+        token: String::new(),
+        retries: 3,
+        ..account.clone()
+    });
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_zeroizing() {
+    use std::io::Cursor;
+    use zeroize::Zeroizing;
+
+    #[derive(PartialEq, Eq, Debug, Default)]
+    struct KeyPair {
+        public: Vec<u8>,
+        secret: Zeroizing<Vec<u8>>
+    }
+
+    bytevec_impls! {
+        impl KeyPair {
+            public: Vec<u8>,
+            secret: Zeroizing<Vec<u8>>
+        }
+    }
+
+    let pair = KeyPair {public: vec![1, 2, 3], secret: Zeroizing::new(vec![4, 5, 6, 7])};
+    let bytes = pair.encode_zeroizing::<u16>().unwrap();
+    assert_eq!(&bytes[..], &pair.encode::<u16>().unwrap()[..]);
+    assert_eq!(bytes.capacity(), bytes.len());
+    assert_eq!(KeyPair::decode::<u16>(&bytes).unwrap(), pair);
+
+    let secret = Zeroizing::new("passphrase".to_string());
+    let bytes = secret.encode_zeroizing::<u8>().unwrap();
+    assert_eq!(*String::decode_zeroizing::<u8>(&bytes).unwrap(), "passphrase");
+    let large = Zeroizing::new(vec![7u32; 5000]);
+    let bytes = large.encode_zeroizing::<u32>().unwrap();
+    let decoded = Vec::<u32>::decode_from_zeroizing::<u32, _>(&mut Cursor::new(&bytes[..]));
+    assert_eq!(decoded.unwrap(), large);
+}
+
+#[test]
+fn test_single_pass_encoding() {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use bytevec::{BVSize, BVEncodeResult, Deterministic, Bincode};
+
+    thread_local! {
+        static CALLS: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+    }
+
+    // Counts the calls of `get_size` and `encode`
+    #[derive(PartialEq, Debug)]
+    struct Probe(u32);
+
+    impl ByteEncodable for Probe {
+        fn get_size<Size>(&self) -> Option<Size> where Size: BVSize + ByteEncodable {
+            CALLS.with(|calls| calls.set((calls.get().0 + 1, calls.get().1)));
+            self.0.get_size::<Size>()
+        }
+
+        fn encode<Size>(&self) -> BVEncodeResult<Vec<u8>> where Size: BVSize + ByteEncodable {
+            CALLS.with(|calls| calls.set((calls.get().0, calls.get().1 + 1)));
+            self.0.encode::<Size>()
+        }
+
+        fn encode_to<Size, W>(&self, writer: &mut W) -> BVEncodeResult<()>
+            where Size: BVSize + ByteEncodable,
+                  W: Write + ?Sized
+        {
+            writer.write_all(&self.encode::<Size>()?)?;
+            Ok(())
+        }
+    }
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        #[bytevec(version = 2, compact)]
+        struct Level {
+            name: String,
+            #[bytevec(skip_if = |level: &Level| level.tags.is_empty())]
+            tags: HashMap<String, u16>,
+            points: Vec<(i32, u8)>
+        }
+
+        #[derive(PartialEq, Debug)]
+        enum Node {
+            Leaf(u64),
+            Branch {level: Level, children: Vec<Vec<u32>>}
+        }
+    }
+
+    let probes = vec![vec![(Probe(1), Probe(2))], vec![(Probe(3), Probe(4)), (Probe(5), Probe(6))]];
+    let bytes = probes.encode::<u32>().unwrap();
+    assert_eq!(CALLS.with(|calls| calls.get()), (6, 6));
+    let mut written = Vec::new();
+    probes.encode_to::<u32, _>(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    let tags = (0..100u16).map(|index| (index.to_string(), index)).collect();
+    let node = Node::Branch {
+        level: Level {name: "root".to_string(), tags, points: vec![(-1, 2), (3, 4)]},
+        children: vec![vec![], vec![1, 2, 3]],
+    };
+    let nodes = vec![node, Node::Leaf(9)];
+    fn check<Size: BVSize + ByteEncodable>(nodes: &Vec<Node>) {
+        let mut written = Vec::new();
+        nodes.encode_to::<Size, _>(&mut written).unwrap();
+        assert_eq!(nodes.encode::<Size>().unwrap(), written);
+    }
+    check::<u16>(&nodes);
+    check::<Deterministic<u32>>(&nodes);
+    check::<Bincode>(&nodes);
+    assert!(nodes.encode::<u8>().is_err());
+    assert!(nodes.encode_to::<u8, _>(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn test_preallocated_buffers() {
+    use bytevec::{PackedVec, Bincode};
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        enum Entry {
+            Empty,
+            Named {name: String, values: PackedVec<u16>}
+        }
+    }
+
+    let entries = vec![
+        Entry::Named {name: "first".to_string(), values: PackedVec(vec![1, 2, 3])},
+        Entry::Empty,
+    ];
+    let bytes = entries.encode::<u16>().unwrap();
+    assert_eq!(bytes.capacity(), bytes.len());
+    let bytes = entries.encode::<Bincode>().unwrap();
+    assert_eq!(bytes.capacity(), bytes.len());
+    let bytes = "text".encode::<u8>().unwrap();
+    assert_eq!(bytes.capacity(), bytes.len());
+
+    let mut out = vec![0xff];
+    let size = entries.reserve_encoded::<u16>(&mut out).unwrap();
+    assert!(out.capacity() > size);
+    entries.encode_to::<u16, _>(&mut out).unwrap();
+    assert_eq!(out.len(), size + 1);
+    assert!(vec![0u32; 100].reserve_encoded::<u8>(&mut out).is_err());
+}
+
+// Counts the allocations of the current thread, for `test_shared_output_buffer`
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { std::alloc::System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        unsafe { std::alloc::System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_shared_output_buffer() {
+    use bytevec::{VarSize, Bincode};
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Clone)]
+        struct Record {
+            id: u32,
+            name: String,
+            scores: Vec<(u8, i64)>
+        }
+    }
+
+    let records = (0..1000u32)
+        .map(|id| Record {id, name: id.to_string(), scores: vec![(1, -2), (3, 4)]})
+        .collect::<Vec<_>>();
+    let before = ALLOCATIONS.with(|count| count.get());
+    let bytes = records.encode::<u32>().unwrap();
+    assert_eq!(ALLOCATIONS.with(|count| count.get()) - before, 1);
+    assert_eq!(Vec::<Record>::decode::<u32>(&bytes).unwrap(), records);
+
+    let mut out = vec![0xff; 3];
+    records.encode_to_buf::<u32>(&mut out).unwrap();
+    assert_eq!(&out[3..], &bytes[..]);
+    let mut written = Vec::new();
+    records.encode_to::<VarSize, _>(&mut written).unwrap();
+    assert_eq!(records.encode::<VarSize>().unwrap(), written);
+    let mut written = Vec::new();
+    records.encode_to::<Bincode, _>(&mut written).unwrap();
+    assert_eq!(records.encode::<Bincode>().unwrap(), written);
+    assert!(records.encode_to_buf::<u8>(&mut out).is_err());
+}
+
+#[test]
+fn test_byte_blobs() {
+    use bytevec::{ByteBuf, ByteDecodeBorrowed, Bytes, PackedVec};
+
+    let blob = PackedVec((0..1 << 20).map(|index| index as u8).collect::<Vec<_>>());
+    let bytes = blob.encode::<u32>().unwrap();
+    assert_eq!(bytes.len(), 4 + (1 << 20));
+    assert_eq!(&bytes[4..], &blob[..]);
+    assert_eq!(PackedVec::<u8>::decode::<u32>(&bytes).unwrap(), blob);
+    assert_eq!(blob.0.encode::<u32>().unwrap().len(), 4 + 5 * (1 << 20));
+
+    let blob = (0..10 << 20).map(|index| (index * 7) as u8).collect::<Vec<u8>>();
+    let buf = ByteBuf::from(blob.clone());
+    let bytes = buf.encode::<u32>().unwrap();
+    assert_eq!(bytes.len(), 4 + (10 << 20));
+    assert_eq!(bytes, PackedVec(blob.clone()).encode::<u32>().unwrap());
+    assert_eq!(Bytes(&blob).encode::<u32>().unwrap(), bytes);
+    assert_eq!(ByteBuf::decode::<u32>(&bytes).unwrap().into_inner(), blob);
+    let borrowed = Bytes::decode_borrowed::<u32>(&bytes).unwrap();
+    assert_eq!(borrowed.0.as_ptr(), bytes[4..].as_ptr());
+    assert!(ByteBuf::decode::<u32>(&bytes[..bytes.len() - 1]).is_err());
+    let (pair, len) = <(ByteBuf, u8)>::decode_with_len::<u8>(&[3, 1, 2, 9, 9, 7]).unwrap();
+    assert_eq!((pair, len), ((ByteBuf(vec![9, 9]), 7), 6));
+
+    let error = PackedVec::<char>::decode::<u8>(&[2, 97, 0, 0, 0, 0, 0xd8, 0, 0]).unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+}
+
+#[test]
+fn test_reusable_encoder() {
+    use bytevec::Encoder;
+
+    let messages = (0..100u32).map(|id| (id, format!("message {}", id))).collect::<Vec<_>>();
+    let mut encoder = Encoder::<u16>::new();
+    encoder.encode(&messages[99]).unwrap();
+    let expected = messages.encode::<u16>().unwrap().len() - 2 - 2 * 100;
+    let before = ALLOCATIONS.with(|count| count.get());
+    let lens = messages.iter().map(|message| encoder.encode(message).unwrap().len());
+    assert_eq!(lens.sum::<usize>(), expected);
+    assert_eq!(ALLOCATIONS.with(|count| count.get()) - before, 0);
+    let bytes = encoder.encode(&messages[42]).unwrap();
+    assert_eq!(<(u32, String)>::decode::<u16>(bytes).unwrap(), messages[42]);
+
+    let frame = encoder.encode_framed(&messages[7]).unwrap().to_vec();
+    assert_eq!(frame, messages[7].encode_framed::<u16>().unwrap());
+    let mut encoder = Encoder::<u8>::default();
+    assert!(encoder.encode(&vec![0u32; 100]).is_err());
+    assert_eq!(encoder.encode(&"bytes").unwrap(), b"bytes");
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_encoding() {
+    use std::collections::{HashMap, HashSet};
+    use bytevec::{ParallelEncode, Deterministic, VarSize, Bincode};
+
+    let rows = (0..20_000u32).map(|row| (row, format!("row {}", row))).collect::<Vec<_>>();
+    let bytes = rows.par_encode::<u32>().unwrap();
+    assert_eq!(bytes, rows.encode::<u32>().unwrap());
+    assert_eq!(Vec::<(u32, String)>::decode::<u32>(&bytes).unwrap(), rows);
+    assert_eq!(rows.par_encode::<VarSize>().unwrap(), rows.encode::<VarSize>().unwrap());
+    assert_eq!(rows.par_encode::<Bincode>().unwrap(), rows.encode::<Bincode>().unwrap());
+    assert!(rows.par_encode::<u16>().is_err());
+
+    let map = rows.iter().cloned().collect::<HashMap<_, _>>();
+    assert_eq!(map.par_encode::<Deterministic<u32>>().unwrap(),
+               map.encode::<Deterministic<u32>>().unwrap());
+    let bytes = map.par_encode::<u32>().unwrap();
+    assert_eq!(HashMap::<u32, String>::decode::<u32>(&bytes).unwrap(), map);
+    let set = (0..10_000u64).collect::<HashSet<_>>();
+    assert_eq!(set.par_encode::<Deterministic<u32>>().unwrap(),
+               set.encode::<Deterministic<u32>>().unwrap());
+}
+
+#[test]
+fn test_bulk_primitive_slices() {
+    use bytevec::PackedVec;
+
+    let values = PackedVec((0..5000u32).map(|n| n.wrapping_mul(0x9e37_79b9)).collect::<Vec<_>>());
+    let bytes = values.encode::<u32>().unwrap();
+    let expected = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>();
+    assert_eq!(&bytes[4..], &expected[..]);
+    assert_eq!(PackedVec::<u32>::decode::<u32>(&bytes).unwrap(), values);
+
+    let samples = PackedVec(vec![-0.0f64, 1.5, f64::MAX, f64::from_bits(0x7ff8_0000_0000_0001)]);
+    let bytes = samples.encode::<u8>().unwrap();
+    let decoded = PackedVec::<f64>::decode::<u8>(&bytes).unwrap();
+    assert_eq!(decoded.iter().map(|sample| sample.to_bits()).collect::<Vec<_>>(),
+               samples.iter().map(|sample| sample.to_bits()).collect::<Vec<_>>());
+    let deltas = PackedVec((-3000..3000i16).collect::<Vec<_>>());
+    let mut written = Vec::new();
+    deltas.encode_to::<u16, _>(&mut written).unwrap();
+    assert_eq!(PackedVec::<i16>::decode::<u16>(&written).unwrap(), deltas);
+}
+
+#[test]
+fn test_allocation_free_decoding() {
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Clone, Copy)]
+        struct Sample {
+            sensor: u16,
+            reading: f64,
+            position: (i32, i32, u8)
+        }
+
+        #[derive(PartialEq, Debug, Default)]
+        #[bytevec(extensible)]
+        struct Tick {
+            sequence: u64,
+            weight: u32
+        }
+    }
+
+    let sample = Sample {sensor: 3, reading: -0.25, position: (-7, 12, 1)};
+    let bytes = sample.encode::<u16>().unwrap();
+    let before = ALLOCATIONS.with(|count| count.get());
+    let decoded = Sample::decode::<u16>(&bytes).unwrap();
+    assert_eq!(ALLOCATIONS.with(|count| count.get()) - before, 0);
+    assert_eq!(decoded, sample);
+
+    let tick = Tick {sequence: 42, weight: 5};
+    let bytes = tick.encode::<u8>().unwrap();
+    let old_bytes = [1, 8, 42, 0, 0, 0, 0, 0, 0, 0];
+    let before = ALLOCATIONS.with(|count| count.get());
+    let decoded = Tick::decode::<u8>(&bytes).unwrap();
+    let old = Tick::decode::<u8>(&old_bytes).unwrap();
+    assert_eq!(ALLOCATIONS.with(|count| count.get()) - before, 0);
+    assert_eq!(decoded, tick);
+    assert_eq!(old, Tick {sequence: 42, weight: 0});
+}
+
+#[test]
+fn test_lazy_struct() {
+    use bytevec::LazyStruct;
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug, Default)]
+        #[bytevec(schema, version = 2)]
+        struct Entry {
+            #[bytevec(big_endian)]
+            id: u32,
+            #[bytevec(size = u8)]
+            tags: Vec<String>,
+            #[bytevec(skip_if = |entry: &Entry| entry.note.is_empty())]
+            note: String,
+            payload: Vec<u64>
+        }
+
+        #[derive(PartialEq, Debug, Default)]
+        #[bytevec(schema, extensible)]
+        struct Event {
+            kind: u8,
+            entry: Entry
+        }
+    }
+
+    let entry = Entry {
+        id: 0x0102_0304,
+        tags: vec!["a".to_string(), "b".to_string()],
+        note: String::new(),
+        payload: (0..10_000).collect(),
+    };
+    let bytes = entry.encode::<u32>().unwrap();
+    let view = LazyStruct::<Entry, u32>::new(&bytes).unwrap();
+    assert_eq!(view.field::<u32>("id").unwrap(), Some(0x0102_0304));
+    assert_eq!(view.field::<Vec<String>>("tags").unwrap(), Some(entry.tags.clone()));
+    assert_eq!(view.field::<String>("note").unwrap(), None);
+    assert_eq!(view.field::<u32>("missing").unwrap(), None);
+    assert_eq!(view.field_bytes("payload").map(|bytes| bytes.len()), Some(4 + 12 * 10_000));
+    let error = view.field::<String>("payload").unwrap_err();
+    assert_eq!(error.path(), Some("payload"));
+
+    let mut old = bytes.clone();
+    old[0] = 1;
+    assert!(LazyStruct::<Entry, u32>::new(&old).is_err());
+
+    let event = Event {kind: 3, entry};
+    let bytes = event.encode::<u32>().unwrap();
+    let view = LazyStruct::<Event, u32>::new(&bytes).unwrap();
+    assert_eq!(view.field::<u8>("kind").unwrap(), Some(3));
+    let inner = LazyStruct::<Entry, u32>::new(view.field_bytes("entry").unwrap()).unwrap();
+    assert_eq!(inner.field::<u32>("id").unwrap(), Some(0x0102_0304));
+    let view = LazyStruct::<Event, u32>::new(&[1, 0, 0, 0, 1, 0, 0, 0, 3]).unwrap();
+    assert_eq!(view.field::<u8>("kind").unwrap(), Some(3));
+    assert_eq!(view.field_bytes("entry"), None);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_arena_decoding() {
+    use bumpalo::Bump;
+    use bytevec::{ByteDecodeIn, Bincode};
+
+    let names = (0..1000).map(|n| format!("name {}", n)).collect::<Vec<_>>();
+    let groups = vec![vec!["a", "bc"], vec![], vec!["def"]];
+    let mut arena = Bump::new();
+    {
+        let bytes = names.encode::<u32>().unwrap();
+        let decoded = <&[&str]>::decode_in::<u32, _>(&bytes, &arena).unwrap();
+        assert_eq!(decoded, &names[..]);
+        let bytes = names.encode::<Bincode>().unwrap();
+        assert_eq!(<&[&str]>::decode_in::<Bincode, _>(&bytes, &arena).unwrap(), &names[..]);
+
+        let bytes = groups.encode::<u16>().unwrap();
+        let decoded = <&[&[&str]]>::decode_in::<u16, _>(&bytes, &arena).unwrap();
+        assert_eq!(decoded, &groups[..]);
+        let bytes = vec![1u32, 2, 3].encode::<u8>().unwrap();
+        assert_eq!(<&[u32]>::decode_in::<u8, _>(&bytes, &arena).unwrap(), &[1, 2, 3]);
+        let error = <&[&str]>::decode_in::<u8, _>(&[2, 1, 1, b'a', 0xff], &arena).unwrap_err();
+        assert_eq!(error.path(), Some("[1]"));
+        for &count in &[1u64 << 40, 1 << 61] {
+            let bytes = count.to_le_bytes();
+            assert!(<&[u64]>::decode_in::<Bincode, _>(&bytes, &arena).is_err());
+        }
+    }
+    assert!(arena.allocated_bytes() > names.iter().map(|name| name.len()).sum::<usize>());
+    arena.reset();
+}
+
+#[test]
+fn test_decode_in_place() {
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use bytevec::{FixedByteSize, PackedArray, PackedVec};
+
+    bytevec_decl! {
+        #[bytevec(packed)]
+        struct Spectrum {
+            #[bytevec(big_endian)]
+            channel: u16,
+            bins: PackedArray<f32, 1024>,
+            peak: char
+        }
+    }
+
+    let mut spectrum = Box::new(MaybeUninit::<Spectrum>::uninit());
+    let mut bins = [0.0f32; 1024];
+    for (index, bin) in bins.iter_mut().enumerate() {
+        *bin = index as f32 / 4.0;
+    }
+    let bytes = Spectrum {channel: 0x0102, bins: PackedArray(bins), peak: 'z'}
+        .encode::<u32>()
+        .unwrap();
+    assert_eq!(bytes.len(), 2 + 4096 + 4);
+    assert_eq!(&bytes[..2], &[1, 2]);
+    Spectrum::decode_in_place::<u32>(&mut spectrum, &bytes).unwrap();
+    let spectrum = unsafe { spectrum.assume_init() };
+    assert_eq!(spectrum.channel, 0x0102);
+    assert_eq!(&spectrum.bins[..], &bins[..]);
+    assert_eq!(spectrum.peak, 'z');
+
+    let mut bad = bytes.clone();
+    bad[2 + 4096..].copy_from_slice(&0xd800u32.to_le_bytes());
+    let mut slot = Box::new(MaybeUninit::<Spectrum>::uninit());
+    let error = Spectrum::decode_in_place::<u32>(&mut slot, &bad).unwrap_err();
+    assert_eq!(error.path(), Some("peak"));
+    assert!(Spectrum::decode_in_place::<u32>(&mut slot, &bytes[1..]).is_err());
+
+    let pairs = PackedArray([PackedArray([1u16, 2, 3]), PackedArray([4, 5, 6])]);
+    let bytes = pairs.encode::<u8>().unwrap();
+    assert_eq!(bytes, vec![1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 6, 0]);
+    assert_eq!(PackedArray::<PackedArray<u16, 3>, 2>::decode::<u8>(&bytes).unwrap(), pairs);
+    assert_eq!(<PackedArray<PackedArray<u16, 3>, 2> as FixedByteSize>::SIZE, 12);
+    let error = PackedArray::<char, 2>::decode::<u8>(&[97, 0, 0, 0, 0, 0xd8, 0, 0]).unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+    assert_eq!(&[1u16, 2, 3].encode::<u8>().unwrap(), &[3, 2, 2, 2, 1, 0, 2, 0, 3, 0]);
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    bytevec_decl! {
+        #[bytevec(packed)]
+        struct Tracked {
+            letter: char
+        }
+    }
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    let mut slot = MaybeUninit::<PackedArray<Tracked, 3>>::uninit();
+    let bytes = [97, 0, 0, 0, 98, 0, 0, 0, 0, 0xd8, 0, 0];
+    assert!(PackedArray::<Tracked, 3>::decode_in_place::<u8>(&mut slot, &bytes).is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+
+    let rows = PackedVec(vec![PackedArray([1u16, 2, 3]), PackedArray([4, 5, 6])]);
+    let bytes = rows.encode::<u32>().unwrap();
+    assert_eq!(PackedVec::<PackedArray<u16, 3>>::decode::<u32>(&bytes).unwrap().0, rows.0);
+}
+
+#[test]
+fn test_decode_into() {
+    use std::collections::{HashMap, HashSet};
+    use bytevec::Bincode;
+
+    let mut names = Vec::with_capacity(16);
+    names.push(String::with_capacity(64));
+    let first = vec!["Ada".to_string(), "Grace".to_string()];
+    names.decode_into::<u32>(&first.encode::<u32>().unwrap()).unwrap();
+    assert_eq!(names, first);
+    let (buffer, name_buffer) = (names.as_ptr(), names[0].as_ptr());
+    assert_eq!(names[0].capacity(), 64);
+
+    let second = vec!["Barbara".to_string()];
+    names.decode_into::<u32>(&second.encode::<u32>().unwrap()).unwrap();
+    assert_eq!(names, second);
+    assert_eq!((names.as_ptr(), names[0].as_ptr()), (buffer, name_buffer));
+    names.decode_into::<Bincode>(&first.encode::<Bincode>().unwrap()).unwrap();
+    assert_eq!(names, first);
+    assert_eq!(names.as_ptr(), buffer);
+
+    let mut map = HashMap::with_capacity(32);
+    let capacity = map.capacity();
+    for round in 0..3u32 {
+        let source = (0..10).map(|key| (key, round)).collect::<HashMap<u32, u32>>();
+        map.decode_into::<u16>(&source.encode::<u16>().unwrap()).unwrap();
+        assert_eq!(map, source);
+    }
+    assert_eq!(map.capacity(), capacity);
+    let mut set = HashSet::new();
+    set.insert(9u8);
+    set.decode_into::<Bincode>(&vec![1u8, 2].encode::<Bincode>().unwrap()).unwrap();
+    assert_eq!(set, [1, 2].iter().cloned().collect());
+
+    let mut text = String::with_capacity(32);
+    text.decode_into::<u32>(b"hello").unwrap();
+    text.decode_into::<Bincode>(&"world".encode::<Bincode>().unwrap()).unwrap();
+    assert_eq!((text.as_str(), text.capacity()), ("world", 32));
+    let error = names.decode_into::<u8>(&[2, 1, 1, b'a', 0xff]).unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+    let mut id = 0u32;
+    id.decode_into::<u32>(&7u32.encode::<u32>().unwrap()).unwrap();
+    assert_eq!(id, 7);
+}
+
+#[cfg(feature = "bench-support")]
+#[test]
+fn test_bench_support() {
+    use bytevec::bench_support::{self, Generator, Order, SEED};
+    use bytevec::PackedVec;
+
+    let mut generator = Generator::new(0);
+    assert_eq!(generator.next_u64(), Generator::new(1).next_u64());
+    assert!((0..100).all(|_| generator.below(10) < 10));
+    assert_eq!(generator.below(0), 0);
+
+    assert_eq!(bench_support::integers(64, SEED), bench_support::integers(64, SEED));
+    assert_ne!(bench_support::integers(64, SEED), bench_support::integers(64, SEED + 1));
+    assert!(bench_support::floats(64, SEED).iter().all(|&float| (0.0..1.0).contains(&float)));
+    assert!(bench_support::strings(64, SEED).iter().all(|string| string.len() <= 64));
+    assert_eq!(bench_support::index(500, SEED).len(), 500);
+
+    let orders = bench_support::orders(200, SEED);
+    assert!(orders.iter().all(|order| order.lines.len() <= 8));
+    let bytes = orders.encode::<u32>().unwrap();
+    assert_eq!(Vec::<Order>::decode::<u32>(&bytes).unwrap(), orders);
+    let floats = PackedVec(bench_support::floats(100, SEED));
+    let bytes = floats.encode::<u32>().unwrap();
+    assert_eq!(PackedVec::<f64>::decode::<u32>(&bytes).unwrap().0, floats.0);
+}
+
+#[test]
+fn test_default_size_functions() {
+    let slice: &[&str] = &["Rust", "Is", "Awesome!"];
+    let bytes = bytevec::encode(slice).unwrap();
+    assert_eq!(bytes, slice.encode::<u32>().unwrap());
+    let vec: Vec<String> = bytevec::decode(&bytes).unwrap();
+    assert_eq!(vec, slice);
+    assert_eq!(bytevec::encode("text").unwrap(), b"text");
+    assert!(bytevec::decode::<(u8, u16)>(&bytes).is_err());
+}
+
+#[test]
+fn test_default_size_methods() {
+    let point = (3u16, "three".to_string());
+    let bytes = point.to_bytes().unwrap();
+    assert_eq!(bytes, point.encode::<u32>().unwrap());
+    assert_eq!(<(u16, String)>::from_bytes(&bytes).unwrap(), point);
+    let slice: &[u64] = &[1, 2, 3];
+    assert_eq!(slice.to_bytes().unwrap(), bytevec::encode(slice).unwrap());
+    assert_eq!(Vec::<u64>::from_bytes(&slice.to_bytes().unwrap()).unwrap(), slice);
+    assert!(u32::from_bytes(&[1, 2]).is_err());
+}
+
+#[test]
+fn test_options() {
+    use std::collections::HashSet;
+    use bytevec::{BigEndian, DecodeLimits, Deterministic, PackedVec, Strict, VarSize};
+
+    bytevec_decl! {
+        #[derive(PartialEq, Debug)]
+        struct Packet {
+            #[bytevec(big_endian)]
+            port: u16,
+            #[bytevec(big_endian, size = u8)]
+            flags: u16,
+            payload: Vec<u32>
+        }
+    }
+
+    let packet = Packet {port: 0x0102, flags: 0x0304, payload: vec![0x0a0b0c0d]};
+    let options = bytevec::options().with_size::<u8>().big_endian();
+    let bytes = options.encode(&packet).unwrap();
+    assert_eq!(bytes, vec![2, 2, 6, 1, 2, 3, 4, 1, 4, 0x0a, 0x0b, 0x0c, 0x0d]);
+    assert_eq!(options.decode::<Packet>(&bytes).unwrap(), packet);
+    assert_eq!(Packet::decode::<BigEndian<u8>>(&bytes).unwrap(), packet);
+    let bytes = packet.encode::<u8>().unwrap();
+    assert_eq!(&bytes[3..7], &[1, 2, 3, 4]);
+
+    let floats = PackedVec(vec![1.5f32, -2.0]);
+    let bytes = floats.encode::<BigEndian<u8>>().unwrap();
+    assert_eq!(&bytes[1..5], &1.5f32.to_bits().to_be_bytes());
+    assert_eq!(PackedVec::<f32>::decode::<BigEndian<u8>>(&bytes).unwrap().0, floats.0);
+
+    let set = (0..50u32).collect::<HashSet<_>>();
+    let options = bytevec::options().varint_sizes().strict().deterministic().big_endian();
+    let bytes = options.encode(&set).unwrap();
+    assert_ne!(bytes, set.encode::<Deterministic<VarSize>>().unwrap());
+    assert_eq!(HashSet::<u32>::decode::<BigEndian<VarSize>>(&bytes).unwrap(), set);
+    assert_eq!(options.decode::<HashSet<u32>>(&bytes).unwrap(), set);
+    assert_eq!(options.encode(&set).unwrap(), bytes);
+    let duplicates = vec![7u32, 7].encode::<BigEndian<VarSize>>().unwrap();
+    assert!(options.decode::<HashSet<u32>>(&duplicates).is_err());
+    assert!(HashSet::<u32>::decode::<Deterministic<Strict<u8>>>(&vec![1u32, 1].encode::<u8>()
+        .unwrap()).is_err());
+
+    let limited = bytevec::options().limits(DecodeLimits {max_elements: 10,
+                                                           ..DecodeLimits::default()});
+    let bytes = limited.encode(&vec![0u8; 20]).unwrap();
+    assert!(limited.decode::<Vec<u8>>(&bytes).is_err());
+    assert_eq!(bytevec::options().decode::<Vec<u8>>(&bytes).unwrap(), vec![0u8; 20]);
+    let lenient = bytevec::options().allow_trailing_bytes();
+    assert_eq!(lenient.decode::<(u8, u16)>(&[1, 0, 0, 0, 2, 0, 0, 0, 1, 2, 0, 9]).unwrap(),
+               (1, 2));
+    assert!(bytevec::options().decode::<u16>(&[2, 0, 9]).is_err());
+
+    let mut encoder = bytevec::options().with_size::<u16>().big_endian().encoder();
+    assert_eq!(encoder.encode(&vec![1u16]).unwrap(), &[0, 1, 0, 2, 0, 1]);
+
+    bytevec_decl! {
+        #[bytevec(schema)]
+        struct Header {
+            #[bytevec(big_endian)]
+            port: u16,
+            len: u32
+        }
+    }
+
+    let bytes = Header {port: 0x0102, len: 7}.encode::<BigEndian<u16>>().unwrap();
+    let view = bytevec::LazyStruct::<Header, BigEndian<u16>>::new(&bytes).unwrap();
+    assert_eq!(view.field::<u16>("port").unwrap(), Some(0x0102));
+    assert_eq!(view.field::<u32>("len").unwrap(), Some(7));
+}
+
+#[test]
+fn test_framed_streams() {
+    use std::io::{Cursor, ErrorKind};
+    use bytevec::errors::ByteVecError;
+    use bytevec::VarSize;
+
+    let mut stream = Vec::new();
+    "first".encode_framed_to::<u16, _>(&mut stream).unwrap();
+    "second".to_string().encode_framed_to::<u16, _>(&mut stream).unwrap();
+    (7u8, vec![1u32, 2]).encode_framed_to::<u16, _>(&mut stream).unwrap();
+    assert_eq!(&stream[..7], &"first".encode_framed::<u16>().unwrap()[..]);
+
+    let mut reader = Cursor::new(&stream);
+    assert_eq!(String::decode_framed_from::<u16, _>(&mut reader).unwrap(), "first");
+    assert_eq!(reader.position(), 7);
+    assert_eq!(String::decode_framed_from::<u16, _>(&mut reader).unwrap(), "second");
+    assert_eq!(<(u8, Vec<u32>)>::decode_framed_from::<u16, _>(&mut reader).unwrap(),
+               (7, vec![1, 2]));
+    match String::decode_framed_from::<u16, _>(&mut reader) {
+        Err(ByteVecError::IoError(error)) => assert_eq!(error.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let long = "x".repeat(300);
+    let bytes = long.encode_framed::<VarSize>().unwrap();
+    let mut reader = Cursor::new(&bytes[..bytes.len() - 1]);
+    assert!(String::decode_framed_from::<VarSize, _>(&mut reader).is_err());
+    let mut reader = Cursor::new(&bytes);
+    assert_eq!(String::decode_framed_from::<VarSize, _>(&mut reader).unwrap(), long);
+    let mut reader = Cursor::new(&[0xff, 0xff, 0xff, 0x7f][..]);
+    assert!(String::decode_framed_from::<u32, _>(&mut reader).is_err());
+}
+
+#[test]
+fn test_encoded_size() {
+    use std::collections::HashMap;
+    use bytevec::{Bincode, VarSize};
+
+    let map = (0..20u32).map(|key| (key, key.to_string())).collect::<HashMap<_, _>>();
+    let nested = (map.clone(), vec!["a".to_string(); 100], 'x');
+    assert_eq!(map.encoded_size::<u32>().unwrap(), map.encode::<u32>().unwrap().len());
+    assert_eq!(nested.encoded_size::<u16>().unwrap(), nested.encode::<u16>().unwrap().len());
+    assert_eq!(nested.encoded_size::<VarSize>().unwrap(),
+               nested.encode::<VarSize>().unwrap().len());
+    assert_eq!(nested.encoded_size::<Bincode>().unwrap(),
+               nested.encode::<Bincode>().unwrap().len());
+    assert_eq!("text".encoded_size::<u8>().unwrap(), 4);
+    assert!(vec!["a"; 200].encoded_size::<u8>().is_err());
+}
+
+#[test]
+fn test_bytes_decode_ext() {
+    use bytevec::{BytesDecodeExt, EncodeExt};
+
+    let buffer = vec!["a".to_string(), "bc".to_string()].to_bytevec().unwrap();
+    assert_eq!(buffer, vec!["a".to_string(), "bc".to_string()].to_bytes().unwrap());
+    assert_eq!(["a", "bc"][..].to_bytevec().unwrap(), buffer);
+    assert_eq!(buffer.decode_as::<Vec<String>>().unwrap(), vec!["a", "bc"]);
+    let slice: &[u8] = &buffer;
+    assert_eq!(slice.decode_as::<Vec<String>>().unwrap().len(), 2);
+    assert_eq!(buffer[..4].decode_as::<u32>().unwrap(), 2);
+    assert!(buffer.decode_as::<u8>().is_err());
+}
+
+#[test]
+fn test_dyn_encoding() {
+    use std::io::Cursor;
+    use bytevec::{DynByteEncodable, VarSize};
+
+    let names: Vec<Box<dyn DynByteEncodable>> = vec![Box::new("one"), Box::new("two".to_string())];
+    assert_eq!(names.encode::<u32>().unwrap(), vec!["one", "two"].encode::<u32>().unwrap());
+
+    let queue: Vec<Box<dyn DynByteEncodable>> =
+        vec![Box::new(1u8), Box::new("two".to_string()), Box::new(vec![3u16, 4])];
+    let bytes = queue.encode::<u32>().unwrap();
+    assert_eq!(queue.encoded_size::<u32>().unwrap(), bytes.len());
+    assert_eq!((*queue[0]).dyn_encode().unwrap(), 1u8.encode::<u32>().unwrap());
+    let mut cursor = Cursor::new(Vec::new());
+    (*queue[2]).dyn_encode_to(&mut cursor).unwrap();
+    assert_eq!(cursor.into_inner(), vec![3u16, 4].encode::<u32>().unwrap());
+    assert!(queue.encode::<VarSize>().is_err());
+
+    let varint: Vec<Box<dyn DynByteEncodable<VarSize>>> = vec![Box::new(1u32), Box::new(2u32)];
+    assert_eq!(varint.encode::<VarSize>().unwrap(),
+               vec![1u32, 2].encode::<VarSize>().unwrap());
+    assert_eq!(Box::<u32>::decode::<u32>(&[5, 0, 0, 0]).unwrap(), Box::new(5));
+}
+
+#[test]
+fn test_type_registry() {
+    use bytevec::errors::ByteVecError;
+    use bytevec::{TypeRegistry, VarSize};
+
+    let registry = TypeRegistry::<VarSize>::new()
+        .register::<u32>(1)
+        .register::<String>(300)
+        .register_fn(2, |bytes| u8::decode::<VarSize>(bytes).map(|value| value != 0));
+    let bytes = registry.encode_tagged(&"text".to_string()).unwrap();
+    assert_eq!(&bytes[..2], &[0xac, 0x02]);
+    let value = registry.decode_tagged(&bytes).unwrap();
+    assert_eq!(value.downcast_ref::<String>().map(|text| &text[..]), Some("text"));
+    let value = registry.decode_tagged(&registry.encode_tagged(&9u32).unwrap()).unwrap();
+    assert_eq!(value.downcast_ref::<u32>(), Some(&9));
+    assert_eq!(registry.tag_of::<bool>(), Some(2));
+    assert_eq!(registry.decode_tagged(&[2, 1]).unwrap().downcast_ref::<bool>(), Some(&true));
+
+    match registry.decode_tagged(&[3, 0]) {
+        Err(ByteVecError::UnknownVariantError(3)) => {}
+        other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+    assert!(registry.encode_tagged(&1u64).is_err());
+    let registry = registry.register::<u64>(1);
+    assert_eq!(registry.tag_of::<u32>(), None);
+    assert_eq!(registry.tag_of::<u64>(), Some(1));
+}
+
+#[test]
+fn test_encode_iter() {
+    use std::collections::BTreeMap;
+    use bytevec::{Bincode, VarSize};
+
+    let words = vec!["alpha", "beta", "", "gamma"];
+    for &hint in &[0, 4, 100] {
+        assert_eq!(bytevec::encode_iter::<u32, _>(words.iter().cloned(), hint).unwrap(),
+                   words.encode::<u32>().unwrap());
+    }
+    let map = (0..5u16).map(|key| (key, key.to_string())).collect::<BTreeMap<_, _>>();
+    let pairs = map.iter().map(|(&key, value)| (key, value.clone()));
+    assert_eq!(bytevec::encode_iter::<VarSize, _>(pairs, 5).unwrap(),
+               map.clone().into_iter().collect::<Vec<_>>().encode::<VarSize>().unwrap());
+    let squares = (0..50u64).map(|n| n * n);
+    let bytes = bytevec::encode_iter::<Bincode, _>(squares.clone(), 0).unwrap();
+    assert_eq!(Vec::<u64>::decode::<Bincode>(&bytes).unwrap(), squares.collect::<Vec<_>>());
+    assert_eq!(bytevec::encode_iter::<u8, _>(Vec::<u8>::new(), 0).unwrap(), vec![0]);
+    assert!(bytevec::encode_iter::<u8, _>((0..200).map(|_| "x"), 200).is_err());
+}
+
+#[test]
+fn test_decode_n() {
+    use bytevec::{Bincode, VarSize};
+
+    let mut bytes = Vec::new();
+    for (id, word) in vec!["one", "two", "three"].into_iter().enumerate() {
+        (id as u8, word).encode_to_buf::<VarSize>(&mut bytes).unwrap();
+    }
+    let (words, rest) = bytevec::decode_n::<(u8, String), VarSize>(&bytes, 2).unwrap();
+    assert_eq!(words, vec![(0, "one".to_string()), (1, "two".to_string())]);
+    assert_eq!(rest, &(2u8, "three").encode::<VarSize>().unwrap()[..]);
+    let (words, rest) = bytevec::decode_n::<(u8, String), VarSize>(&bytes, 0).unwrap();
+    assert!(words.is_empty());
+    assert_eq!(rest, &bytes[..]);
+    assert!(bytevec::decode_n::<(u8, String), VarSize>(&bytes, 4).is_err());
+
+    let bytes = vec!["a", "bc", "def"].encode::<Bincode>().unwrap();
+    let (words, rest) = bytevec::decode_n::<String, Bincode>(&bytes[8..], 2).unwrap();
+    assert_eq!(words, vec!["a", "bc"]);
+    assert_eq!(bytevec::decode_n::<String, Bincode>(rest, 1).unwrap().0, vec!["def"]);
+
+    let bytes = (0..10u32).collect::<Vec<_>>().encode::<Bincode>().unwrap();
+    let (first, rest) = bytevec::decode_n::<u32, Bincode>(&bytes[8..], 4).unwrap();
+    assert_eq!(first, vec![0, 1, 2, 3]);
+    assert_eq!(bytevec::decode_n::<u32, Bincode>(rest, 6).unwrap().0, (4..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_collection_index() {
+    use bytevec::errors::ByteVecError;
+    use bytevec::{Bincode, VarSize};
+
+    let names = vec!["zero".to_string(), "one".to_string(), String::new(), "three".to_string()];
+    for index in 0..names.len() {
+        let bytes = names.encode::<VarSize>().unwrap();
+        assert_eq!(bytevec::collection_index::<String, VarSize>(&bytes, index).unwrap(),
+                   names[index]);
+        let bytes = names.encode::<Bincode>().unwrap();
+        assert_eq!(bytevec::collection_index::<String, Bincode>(&bytes, index).unwrap(),
+                   names[index]);
+    }
+    let pairs = vec![(1u8, vec![2u16]), (3, vec![4, 5])];
+    let bytes = pairs.encode::<u16>().unwrap();
+    assert_eq!(bytevec::collection_index::<(u8, Vec<u16>), u16>(&bytes, 1).unwrap(),
+               (3, vec![4, 5]));
+    match bytevec::collection_index::<(u8, Vec<u16>), u16>(&bytes, 2) {
+        Err(ByteVecError::IndexOutOfBoundsError { index: 2, len: 2 }) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(bytevec::collection_index::<(u8, Vec<u16>), u16>(&bytes[..bytes.len() - 1], 0)
+        .is_err());
+    let error = bytevec::collection_index::<u32, u16>(&bytes, 1).unwrap_err();
+    assert_eq!(error.path(), Some("[1]"));
+}
+
+#[test]
+fn test_splice_field() {
+    use bytevec::errors::ByteVecError;
+    use bytevec::VarSize;
+
+    bytevec_decl! {
+        #[bytevec(schema, version = 3)]
+        #[derive(PartialEq, Debug, Clone)]
+        struct Account {
+            #[bytevec(big_endian)]
+            id: u32,
+            #[bytevec(size = u8)]
+            tags: Vec<String>,
+            balance: u64
+        }
+
+        #[bytevec(schema, packed)]
+        #[derive(PartialEq, Debug)]
+        struct Point {
+            x: u16,
+            y: u16
+        }
+    }
+
+    let account = Account {id: 9, tags: vec!["a".to_string()], balance: 10};
+    let mut expected = account.clone();
+    let mut bytes = account.encode::<VarSize>().unwrap();
+    let tags = vec!["x".repeat(200), "y".to_string()];
+    bytevec::splice_field::<Account, _, VarSize>(&mut bytes, "tags", &tags).unwrap();
+    expected.tags = tags;
+    assert_eq!(bytes, expected.encode::<VarSize>().unwrap());
+    bytevec::splice_field::<Account, _, VarSize>(&mut bytes, "id", &0x0102u32).unwrap();
+    bytevec::splice_field::<Account, _, VarSize>(&mut bytes, "balance", &5u64).unwrap();
+    expected.id = 0x0102;
+    expected.balance = 5;
+    assert_eq!(Account::decode::<VarSize>(&bytes).unwrap(), expected);
+
+    let before = bytes.clone();
+    match bytevec::splice_field::<Account, _, VarSize>(&mut bytes, "owner", "Ada") {
+        Err(ByteVecError::MissingFieldError("owner")) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+    let long_tags = vec!["z".to_string(); 300];
+    assert!(bytevec::splice_field::<Account, _, VarSize>(&mut bytes, "tags", &long_tags).is_err());
+    assert_eq!(bytes, before);
+
+    let mut bytes = Point {x: 1, y: 2}.encode::<u32>().unwrap();
+    bytevec::splice_field::<Point, _, u32>(&mut bytes, "y", &7u16).unwrap();
+    assert_eq!(Point::decode::<u32>(&bytes).unwrap(), Point {x: 1, y: 7});
+    match bytevec::splice_field::<Point, _, u32>(&mut bytes, "x", &7u32) {
+        Err(ByteVecError::LengthMismatchError {expected: 2, actual: 4}) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_merge_encoded_vecs() {
+    use bytevec::{Bincode, VarSize};
+
+    let a = vec![(1u8, "a".to_string()), (2, "b".repeat(300))];
+    let b = vec![(3u8, String::new())];
+    let merged = a.iter().chain(&b).cloned().collect::<Vec<_>>();
+    assert_eq!(bytevec::merge_encoded_vecs::<VarSize>(&a.encode::<VarSize>().unwrap(),
+                                                      &b.encode::<VarSize>().unwrap())
+                   .unwrap(),
+               merged.encode::<VarSize>().unwrap());
+    assert_eq!(bytevec::merge_encoded_vecs::<Bincode>(&a.encode::<Bincode>().unwrap(),
+                                                      &b.encode::<Bincode>().unwrap())
+                   .unwrap(),
+               merged.encode::<Bincode>().unwrap());
+    let empty = Vec::<(u8, String)>::new().encode::<u32>().unwrap();
+    let a_bytes = a.encode::<u32>().unwrap();
+    assert_eq!(bytevec::merge_encoded_vecs::<u32>(&empty, &a_bytes).unwrap(), a_bytes);
+    assert!(bytevec::merge_encoded_vecs::<u32>(&a_bytes[1..], &a_bytes).is_err());
+
+    let half = vec!["x"; 100].encode::<u8>().unwrap();
+    assert!(bytevec::merge_encoded_vecs::<u8>(&half, &half).is_err());
+}
+
+#[test]
+fn test_validate() {
+    use std::collections::HashMap;
+    use bytevec::errors::ByteVecError;
+    use bytevec::{BVDecodeResult, BVSize, Bincode, DecodeLimits, VarSize};
+
+    bytevec_decl! {
+        #[bytevec(schema, version = 2)]
+        #[derive(PartialEq, Debug)]
+        struct Event {
+            #[bytevec(big_endian)]
+            id: u32,
+            code: char,
+            #[bytevec(size = u8)]
+            labels: HashMap<String, u16>,
+            kind: Kind,
+            payload: Vec<Vec<u32>>
+        }
+
+        #[bytevec(schema)]
+        #[derive(PartialEq, Debug)]
+        enum Kind {
+            Ping,
+            Data(String, u8)
+        }
+    }
+
+    let event = Event {
+        id: 0x0102_0304,
+        code: 'é',
+        labels: vec![("a".to_string(), 1u16)].into_iter().collect(),
+        kind: Kind::Data("hello".to_string(), 3),
+        payload: vec![vec![1, 2], vec![]],
+    };
+    let bytes = event.encode::<VarSize>().unwrap();
+    bytevec::validate::<Event, VarSize>(&bytes).unwrap();
+    bytevec::validate::<Event, Bincode>(&event.encode::<Bincode>().unwrap()).unwrap();
+    for len in 0..bytes.len() {
+        assert!(bytevec::validate::<Event, VarSize>(&bytes[..len]).is_err());
+    }
+
+    let position = bytes.windows(5).position(|window| window == b"hello").unwrap();
+    let mut corrupted = bytes.clone();
+    corrupted[position] = 0xff;
+    let error = bytevec::validate::<Event, VarSize>(&corrupted).unwrap_err();
+    assert_eq!(error.to_string(),
+               Event::decode::<VarSize>(&corrupted).unwrap_err().to_string());
+    match error.inner() {
+        &ByteVecError::StringDecodeUtf8Error { .. } => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+    let mut corrupted = bytes.clone();
+    corrupted[0] = 1;
+    match bytevec::validate::<Event, VarSize>(&corrupted) {
+        Err(ByteVecError::UnknownVersionError(1)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    #[derive(PartialEq, Debug, Default)]
+    struct Point {
+        x: u8,
+        y: u8
+    }
+
+    impl Point {
+        fn from_v1<Size>(bytes: &[u8]) -> BVDecodeResult<Point>
+            where Size: BVSize + ByteDecodable
+        {
+            Ok(Point {x: u8::decode::<Size>(bytes)?, y: 0})
+        }
+    }
+
+    bytevec_impls! {
+        #[bytevec(schema, version = 2, migrate(1 => from_v1))]
+        impl Point {
+            x: u8,
+            y: u8
+        }
+    }
+
+    let old = [1, 0, 7];
+    assert_eq!(Point::decode::<u16>(&old).unwrap(), Point {x: 7, y: 0});
+    match bytevec::validate::<Point, u16>(&old) {
+        Err(ByteVecError::UnknownVersionError(1)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    bytevec::validate::<Point, u16>(&Point {x: 7, y: 1}.encode::<u16>().unwrap()).unwrap();
+
+    let limits = DecodeLimits {max_depth: 2, ..DecodeLimits::default()};
+    assert!(bytevec::validate_limited::<Event, VarSize>(&bytes, &limits).is_err());
+    let limits = DecodeLimits {max_depth: 3, ..DecodeLimits::default()};
+    bytevec::validate_limited::<Event, VarSize>(&bytes, &limits).unwrap();
+    assert!(Event::decode_limited::<VarSize>(&bytes, &limits).is_ok());
+}
+
+#[test]
+fn test_peek_collection() {
+    use bytevec::{Bincode, VarSize};
+
+    let records = vec!["x".repeat(200), String::new(), "abc".to_string()];
+    let bytes = records.encode::<VarSize>().unwrap();
+    assert_eq!(bytevec::peek_len::<VarSize>(&bytes).unwrap(), 3);
+    assert_eq!(bytevec::peek_sizes::<VarSize>(&bytes).unwrap(), vec![200, 0, 3]);
+    assert!(bytevec::peek_sizes::<VarSize>(&bytes[..bytes.len() - 1]).is_err());
+
+    let pairs = (0..4u8).map(|key| (key, vec![key; key as usize])).collect::<Vec<_>>();
+    let bytes = pairs.encode::<u8>().unwrap();
+    assert_eq!(bytevec::peek_len::<u8>(&bytes).unwrap(), 4);
+    assert_eq!(bytevec::peek_sizes::<u8>(&bytes).unwrap(), vec![4, 6, 8, 10]);
+
+    let bytes = records.encode::<Bincode>().unwrap();
+    assert_eq!(bytevec::peek_len::<Bincode>(&bytes).unwrap(), 3);
+    assert!(bytevec::peek_sizes::<Bincode>(&bytes).is_err());
+    assert!(bytevec::peek_len::<u32>(&[1, 0]).is_err());
+}
+
+#[test]
+fn test_encode_array() {
+    use bytevec::{BigEndian, FixedByteSize};
+
+    bytevec_decl! {
+        #[bytevec(packed)]
+        #[derive(PartialEq, Debug)]
+        struct Header {
+            kind: u8,
+            #[bytevec(big_endian)]
+            len: u16,
+            flag: char
+        }
+    }
+
+    let header = Header {kind: 1, len: 0x0203, flag: 'a'};
+    let bytes = header.encode_array::<u32, { Header::SIZE }>().unwrap();
+    assert_eq!(&bytes[..], &header.encode::<u32>().unwrap()[..]);
+    assert_eq!(Header::decode::<u32>(&bytes).unwrap(), header);
+    assert_eq!(0x0102u16.encode_array::<BigEndian<u32>, 2>().unwrap(), [1, 2]);
+    assert_eq!(1.5f64.encode_array::<u8, 8>().unwrap(), 1.5f64.to_le_bytes());
+}
+
+#[test]
+fn test_platform_and_wide_sizes() {
+    use std::mem::size_of;
+    let vec = vec!["a".to_string(), "bc".to_string()];
+    let bytes = vec.encode::<usize>().unwrap();
+    assert_eq!(bytes.len(), 3 * size_of::<usize>() + 3);
+    assert_eq!(Vec::<String>::decode::<usize>(&bytes).unwrap(), vec);
+
+    let bytes = vec.encode::<u128>().unwrap();
+    assert_eq!(bytes.len(), 3 * 16 + 3);
+    assert_eq!(Vec::<String>::decode::<u128>(&bytes).unwrap(), vec);
+
+    let wide = (u128::MAX - 1, i128::MIN);
+    let bytes = wide.encode::<u8>().unwrap();
+    assert_eq!(bytes.len(), 2 + 32);
+    assert_eq!(<(u128, i128)>::decode::<u8>(&bytes).unwrap(), wide);
+}
+
+#[test]
+fn test_encoded() {
+    use bytevec::Encoded;
+    let pair = ("left".to_string(), 7u32);
+    let encoded = Encoded::<(String, u32), u8>::new(&pair).unwrap();
+    assert_eq!(encoded.as_bytes(), &pair.encode::<u8>().unwrap()[..]);
+    assert_eq!(encoded.len(), 2 + 4 + 4);
+    assert_eq!(encoded.decode().unwrap(), pair);
+    assert_eq!(encoded.clone(), encoded);
+    assert_eq!(encoded.to_string(), "04046c65667407000000");
+
+    let raw: Vec<u8> = encoded.into();
+    let typed = Encoded::<u16>::from_bytes(raw);
+    assert!(typed.decode().is_err());
+    assert!(Encoded::<String>::new(&String::new()).unwrap().is_empty());
+
+    #[cfg(feature = "serde")]
+    {
+        use serde_test::{Token, assert_tokens, assert_de_tokens};
+        let encoded = Encoded::<u16>::new(&0x0102).unwrap();
+        assert_tokens(&encoded, &[Token::Bytes(&[2, 1])]);
+        assert_de_tokens(&encoded, &[Token::Seq { len: Some(2) }, Token::U8(2), Token::U8(1),
+                                     Token::SeqEnd]);
+    }
+}
+
+#[test]
+fn test_content_hash() {
+    use std::collections::HashSet;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+    let a = vec![("x".to_string(), (0..50u16).collect::<HashSet<_>>())];
+    let b = vec![("x".to_string(), (0..50u16).rev().collect::<HashSet<_>>())];
+    let hash = bytevec::hash::<_, DefaultHasher>(&a).unwrap();
+    assert_eq!(hash, bytevec::hash::<_, DefaultHasher>(&b).unwrap());
+    assert!(hash != bytevec::hash::<_, DefaultHasher>(&vec![("y".to_string(), HashSet::<u16>::new())])
+        .unwrap());
+
+    let mut hasher = DefaultHasher::new();
+    bytevec::hash_into(&a[..], &mut hasher).unwrap();
+    assert_eq!(hasher.finish(), hash);
+}
+
+#[test]
+fn test_control_flow_and_poll() {
+    use std::ops::ControlFlow;
+    use std::task::Poll;
+    use bytevec::{Bincode, VarSize};
+    let flows: Vec<ControlFlow<String, u16>> =
+        vec![ControlFlow::Continue(3), ControlFlow::Break("done".to_string())];
+    let bytes = flows[1].encode::<u8>().unwrap();
+    assert_eq!(bytes, b"\x01\x04done");
+    for flow in &flows {
+        assert_eq!(&ControlFlow::decode::<u8>(&flow.encode::<u8>().unwrap()).unwrap(), flow);
+    }
+
+    let polls = vec![Poll::Ready(vec![1u32, 2]), Poll::Pending, Poll::Ready(vec![])];
+    assert_eq!(Poll::<u32>::Pending.encode::<u8>().unwrap(), [1]);
+    let snapshot = (polls.clone(), flows.clone());
+    let bytes = snapshot.encode::<VarSize>().unwrap();
+    assert_eq!(<(Vec<Poll<Vec<u32>>>, Vec<ControlFlow<String, u16>>)>::decode::<VarSize>(&bytes)
+        .unwrap(), snapshot);
+    let bytes = snapshot.encode::<Bincode>().unwrap();
+    assert_eq!(&bytes[8..12], [0, 0, 0, 0]);
+    assert_eq!(<(Vec<Poll<Vec<u32>>>, Vec<ControlFlow<String, u16>>)>::decode::<Bincode>(&bytes)
+        .unwrap(), snapshot);
+    assert!(bytevec::validate::<Vec<Poll<Vec<u32>>>, u32>(&polls.encode::<u32>().unwrap())
+        .is_ok());
+    assert!(Poll::<u8>::decode::<u8>(&[2]).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_bridge() {
+    use std::collections::HashMap;
+    use bytevec::{serde_bridge, BVSize, Bincode, BigEndian, Deterministic, VarSize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Empty,
+        Circle(u32),
+        Rect(u16, u16),
+        Named { name: String },
+    }
+
+    bytevec_impls! {
+        impl enum Shape {
+            Empty,
+            Circle(u32),
+            Rect(u16, u16),
+            Named { name: String }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Drawing {
+        title: String,
+        layers: Vec<(u8, char)>,
+        shapes: Vec<Shape>,
+        tags: HashMap<String, i64>,
+        scale: f64,
+    }
+
+    bytevec_impls! {
+        #[bytevec(complete)]
+        impl Drawing {
+            title: String,
+            layers: Vec<(u8, char)>,
+            shapes: Vec<Shape>,
+            tags: HashMap<String, i64>,
+            scale: f64
+        }
+    }
+
+    fn round_trip<Size>(drawing: &Drawing)
+        where Size: BVSize + ByteEncodable + ByteDecodable
+    {
+        let bytes = serde_bridge::encode::<_, Size>(drawing).unwrap();
+        assert_eq!(bytes, drawing.encode::<Size>().unwrap());
+        assert_eq!(&Drawing::decode::<Size>(&bytes).unwrap(), drawing);
+        assert_eq!(&serde_bridge::decode::<Drawing, Size>(&bytes).unwrap(), drawing);
+    }
+
+    let drawing = Drawing {
+        title: "plan".to_string(),
+        layers: vec![(1, 'a'), (2, '\u{e9}')],
+        shapes: vec![Shape::Empty, Shape::Circle(5), Shape::Rect(2, 3),
+                     Shape::Named { name: "door".to_string() }],
+        tags: (0..20).map(|i| (i.to_string(), -i)).collect(),
+        scale: 1.5,
+    };
+    round_trip::<u16>(&drawing);
+    round_trip::<VarSize>(&drawing);
+    round_trip::<Bincode>(&drawing);
+    round_trip::<BigEndian<u32>>(&drawing);
+    round_trip::<Deterministic<u32>>(&drawing);
+
+    let value = (Some(7u8), None::<u8>, true, vec![vec![1u16], vec![]]);
+    let bytes = serde_bridge::encode::<_, u8>(&value).unwrap();
+    assert_eq!(&bytes[..8], [3, 1, 1, 8, 1, 1, 7, 0]);
+    assert_eq!(serde_bridge::decode::<(Option<u8>, Option<u8>, bool, Vec<Vec<u16>>), u8>(&bytes)
+        .unwrap(), value);
+    let bytes = serde_bridge::encode::<_, Bincode>(&value).unwrap();
+    assert_eq!(serde_bridge::decode::<(Option<u8>, Option<u8>, bool, Vec<Vec<u16>>), Bincode>(
+        &bytes).unwrap(), value);
+
+    let bytes = serde_bridge::encode::<_, u8>(&Shape::Named { name: "x".to_string() }).unwrap();
+    let error = serde_bridge::decode::<Shape, u8>(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(error.path(), Some("Named"));
+    assert!(serde_bridge::decode::<Shape, u8>(&[4]).is_err());
+    assert!(serde_bridge::decode::<bool, u8>(&[2]).is_err());
+}